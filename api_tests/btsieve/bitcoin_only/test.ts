@@ -103,6 +103,21 @@ setTimeout(async function() {
                     );
                 });
 
+                it("btsieve should list the bitcoin transaction query with its match count", async function() {
+                    let res = await request(btsieve.url()).get(
+                        "/queries/bitcoin/regtest?type=transactions"
+                    );
+
+                    expect(res).to.have.status(200);
+                    let listed = res.body.find(
+                        (query: any) => query.location === location
+                    );
+                    expect(listed.type).to.equal("transactions");
+                    expect(listed.query.to_address).to.equal(to_address);
+                    expect(listed.match_count).to.equal(1);
+                    expect(listed.created_at).to.be.a("string");
+                });
+
                 it("btsieve should respond with no content when deleting an existing bitcoin transaction query", async function() {
                     let res = await request(
                         btsieve.absoluteLocation(location)
@@ -1,7 +1,12 @@
 import { Wallet } from "../../lib/wallet";
 import { expect, request } from "chai";
 import { HarnessGlobal, sleep } from "../../lib/util";
-import { Btsieve, EthereumMatch, IdMatch } from "../../lib/btsieve";
+import {
+    Btsieve,
+    DecodedLogsMatch,
+    EthereumMatch,
+    IdMatch,
+} from "../../lib/btsieve";
 import "../../lib/setupChai";
 
 declare var global: HarnessGlobal;
@@ -303,6 +308,19 @@ setTimeout(async function() {
                     expect(body.matches[0].receipt).to.be.a("object");
                 });
 
+                it("btsieve should return decoded transfer logs if `return_as=decoded_logs` is given", async function() {
+                    let body = await btsieve.pollUntilMatches<DecodedLogsMatch>(
+                        btsieve.absoluteLocation(location) +
+                            "?return_as=decoded_logs"
+                    );
+
+                    expect(body.matches).to.have.length(1);
+                    expect(body.matches[0].transaction).to.be.a("object");
+                    expect(body.matches[0].logs).to.have.length(1);
+                    expect(body.matches[0].logs[0].event).to.equal("Transfer");
+                    expect(body.matches[0].logs[0].value).to.equal("0x1");
+                });
+
                 it("btsieve should respond with no content when deleting an existing ethereum transaction receipt query", async function() {
                     let res = await request(
                         btsieve.absoluteLocation(location)
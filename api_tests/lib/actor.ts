@@ -182,9 +182,19 @@ export class Actor {
     }
 
     async doLedgerAction(action: LedgerAction) {
-        let network = action.payload.network;
-        if (network != "regtest") {
-            throw Error("Expected network regtest, found " + network);
+        if (
+            action.type === "ethereum-deploy-contract" ||
+            action.type === "ethereum-call-contract"
+        ) {
+            let chain_id = action.payload.chain_id;
+            if (chain_id != 17) {
+                throw Error("Expected chain id 17, found " + chain_id);
+            }
+        } else {
+            let network = action.payload.network;
+            if (network != "regtest") {
+                throw Error("Expected network regtest, found " + network);
+            }
         }
         switch (action.type) {
             case "bitcoin-send-amount-to-address": {
@@ -243,6 +253,7 @@ export class Actor {
                 action.payload.should.include.all.keys(
                     "contract_address",
                     "data",
+                    "amount",
                     "gas_limit",
                     "min_block_timestamp"
                 );
@@ -250,6 +261,7 @@ export class Actor {
                 let {
                     contract_address,
                     data,
+                    amount,
                     gas_limit,
                     min_block_timestamp,
                 } = action.payload;
@@ -270,7 +282,12 @@ export class Actor {
 
                 return this.wallet
                     .eth()
-                    .sendEthTransactionTo(contract_address, data, 0, gas_limit);
+                    .sendEthTransactionTo(
+                        contract_address,
+                        data,
+                        amount,
+                        gas_limit
+                    );
             }
             default:
                 throw Error(`Action ${action} is not unsupported`);
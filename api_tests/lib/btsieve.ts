@@ -23,6 +23,17 @@ export interface EthereumMatch {
     receipt: TransactionReceipt;
 }
 
+export interface DecodedLog {
+    address: string;
+    event: string;
+    [param: string]: string;
+}
+
+export interface DecodedLogsMatch {
+    transaction: Transaction;
+    logs: DecodedLog[];
+}
+
 export interface MetaBtsieveConfig {
     host: string;
     config_file: string;
@@ -15,13 +15,25 @@ export type LedgerAction =
               min_median_block_time?: number;
           };
       }
+    | {
+          type: "litecoin-send-amount-to-address";
+          payload: { to: string; amount: string; network: string };
+      }
+    | {
+          type: "litecoin-broadcast-signed-transaction";
+          payload: {
+              hex: string;
+              network: string;
+              min_median_block_time?: number;
+          };
+      }
     | {
           type: "ethereum-deploy-contract";
           payload: {
               data: string;
               amount: string;
               gas_limit: string;
-              network: string;
+              chain_id: number;
           };
       }
     | {
@@ -30,9 +42,32 @@ export type LedgerAction =
               contract_address: string;
               data: string;
               gas_limit: string;
-              network: string;
+              chain_id: number;
               min_block_timestamp?: number;
           };
+      }
+    | {
+          type: "lightning-send-payment";
+          payload: {
+              to: string;
+              amount: string;
+              secret_hash: string;
+              final_cltv_delta: number;
+              network: string;
+          };
+      }
+    | {
+          type: "lightning-add-hold-invoice";
+          payload: {
+              amount: string;
+              secret_hash: string;
+              cltv_expiry: number;
+              network: string;
+          };
+      }
+    | {
+          type: "lightning-settle-invoice";
+          payload: { secret: string; network: string };
       };
 
 export interface Asset {
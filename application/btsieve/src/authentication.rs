@@ -0,0 +1,377 @@
+use crate::{
+    routes::{Error as RouteError, HttpApiProblemStdError},
+    settings::Authentication,
+};
+use btsieve_auth::{content_hash, hmac_signature, CONTENT_SHA256_HEADER};
+use bytes::Buf;
+use chrono::Utc;
+use crypto::util::fixed_time_eq;
+use serde::de::DeserializeOwned;
+use warp::{self, body::FullBody, filters::BoxedFilter, http::Method, path::FullPath, Filter};
+
+/// HMAC signed requests are only accepted if their timestamp is at most this
+/// many seconds away from our clock. This limits how long a captured request
+/// can be replayed.
+pub const MAX_CLOCK_SKEW_SECS: i64 = 300;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    MissingCredential,
+    MalformedCredential,
+    InvalidCredential,
+    ExpiredTimestamp,
+    UnsignedBody,
+}
+
+/// What of a request its signature covers
+#[derive(Debug)]
+pub struct SignedParts<'a> {
+    pub method: &'a Method,
+    pub path: &'a str,
+    pub query: Option<&'a str>,
+    /// The content hash the client claims for the body, if any
+    pub content_hash: Option<&'a str>,
+    pub has_body: bool,
+}
+
+/// Rejects every request that does not carry the configured credential.
+/// Without any configured authentication, all requests are let through.
+pub fn authenticate(authentication: Option<Authentication>) -> BoxedFilter<()> {
+    let query = warp::query::raw()
+        .map(Some)
+        .or(warp::any().map(|| None))
+        .unify();
+    let has_body = warp::header::optional::<u64>("content-length")
+        .and(warp::header::optional::<String>("transfer-encoding"))
+        .map(
+            |content_length: Option<u64>, transfer_encoding: Option<String>| {
+                content_length.map_or(false, |length| length > 0) || transfer_encoding.is_some()
+            },
+        );
+
+    warp::method()
+        .and(warp::path::full())
+        .and(query)
+        .and(warp::header::optional::<String>(CONTENT_SHA256_HEADER))
+        .and(has_body)
+        .and(warp::header::optional::<String>("authorization"))
+        .and_then(
+            move |method: Method,
+                  path: FullPath,
+                  query: Option<String>,
+                  claimed_hash: Option<String>,
+                  has_body: bool,
+                  authorization: Option<String>| {
+                let authentication = match &authentication {
+                    Some(authentication) => authentication,
+                    None => return Ok(()),
+                };
+
+                verify(
+                    authentication,
+                    &SignedParts {
+                        method: &method,
+                        path: path.as_str(),
+                        query: query.as_ref().map(String::as_str),
+                        content_hash: claimed_hash.as_ref().map(String::as_str),
+                        has_body,
+                    },
+                    authorization.as_ref().map(String::as_str),
+                    Utc::now().timestamp(),
+                )
+                .map_err(|e| {
+                    log::warn!(
+                        "Rejecting unauthenticated request to {}: {:?}",
+                        path.as_str(),
+                        e
+                    );
+                    warp::reject::custom(HttpApiProblemStdError {
+                        http_api_problem: RouteError::Unauthorized.into(),
+                    })
+                })
+            },
+        )
+        .untuple_one()
+        .boxed()
+}
+
+/// Deserializes the JSON body of a request after checking that it is the
+/// body the client signed, if it claims to have signed one.
+pub fn json_body<T: DeserializeOwned + Send + 'static>() -> BoxedFilter<(T,)> {
+    warp::header::optional::<String>(CONTENT_SHA256_HEADER)
+        .and(warp::body::concat())
+        .and_then(|claimed_hash: Option<String>, body: FullBody| {
+            let body = body.bytes();
+
+            if let Some(claimed_hash) = claimed_hash {
+                if !fixed_time_eq(claimed_hash.as_bytes(), content_hash(body).as_bytes()) {
+                    log::warn!("Rejecting request whose body does not match its signature");
+                    return Err(warp::reject::custom(HttpApiProblemStdError {
+                        http_api_problem: RouteError::Unauthorized.into(),
+                    }));
+                }
+            }
+
+            serde_json::from_slice(body).map_err(|e| {
+                warp::reject::custom(HttpApiProblemStdError {
+                    http_api_problem: RouteError::MalformedBody(e.to_string()).into(),
+                })
+            })
+        })
+        .boxed()
+}
+
+pub fn verify(
+    authentication: &Authentication,
+    request: &SignedParts<'_>,
+    authorization: Option<&str>,
+    now: i64,
+) -> Result<(), Error> {
+    let authorization = authorization.ok_or(Error::MissingCredential)?;
+
+    match authentication {
+        Authentication::Bearer { token } => {
+            let presented = strip_scheme(authorization, "Bearer")?;
+
+            if fixed_time_eq(presented.as_bytes(), token.as_bytes()) {
+                Ok(())
+            } else {
+                Err(Error::InvalidCredential)
+            }
+        }
+        Authentication::Hmac { secret } => {
+            let credential = strip_scheme(authorization, "HMAC")?;
+
+            let mut parts = credential.splitn(2, ':');
+            let (timestamp, signature) = match (parts.next(), parts.next()) {
+                (Some(timestamp), Some(signature)) => (timestamp, signature),
+                _ => return Err(Error::MalformedCredential),
+            };
+            let timestamp = timestamp
+                .parse::<i64>()
+                .map_err(|_| Error::MalformedCredential)?;
+            let signature = hex::decode(signature).map_err(|_| Error::MalformedCredential)?;
+
+            if (now - timestamp).abs() > MAX_CLOCK_SKEW_SECS {
+                return Err(Error::ExpiredTimestamp);
+            }
+
+            // Without a content hash only requests without a body are
+            // covered completely by the signature.
+            let content_hash = match request.content_hash {
+                Some(claimed_hash) => claimed_hash.to_string(),
+                None if request.has_body => return Err(Error::UnsignedBody),
+                None => content_hash(&[]),
+            };
+
+            let expected = hmac_signature(
+                secret,
+                request.method.as_str(),
+                request.path,
+                request.query,
+                &content_hash,
+                timestamp,
+            );
+
+            if fixed_time_eq(&signature, &expected) {
+                Ok(())
+            } else {
+                Err(Error::InvalidCredential)
+            }
+        }
+    }
+}
+
+fn strip_scheme<'a>(authorization: &'a str, scheme: &str) -> Result<&'a str, Error> {
+    let mut parts = authorization.splitn(2, ' ');
+
+    match (parts.next(), parts.next()) {
+        (Some(presented_scheme), Some(credential))
+            if presented_scheme.eq_ignore_ascii_case(scheme) =>
+        {
+            Ok(credential.trim())
+        }
+        _ => Err(Error::MalformedCredential),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use btsieve_auth::hmac_authorization;
+    use spectral::prelude::*;
+
+    const PATH: &str = "/queries/bitcoin/regtest/transactions";
+    const NOW: i64 = 1_556_000_000;
+
+    fn bearer() -> Authentication {
+        Authentication::Bearer {
+            token: "s3cr3t".to_string(),
+        }
+    }
+
+    fn hmac() -> Authentication {
+        Authentication::Hmac {
+            secret: "s3cr3t".to_string(),
+        }
+    }
+
+    fn request<'a>(method: &'a Method, path: &'a str) -> SignedParts<'a> {
+        SignedParts {
+            method,
+            path,
+            query: None,
+            content_hash: None,
+            has_body: false,
+        }
+    }
+
+    fn hmac_header(request: &SignedParts<'_>, timestamp: i64) -> String {
+        hmac_authorization(
+            "s3cr3t",
+            request.method.as_str(),
+            request.path,
+            request.query,
+            request.content_hash.unwrap_or(&content_hash(&[])),
+            timestamp,
+        )
+    }
+
+    #[test]
+    fn given_correct_bearer_token_request_is_authenticated() {
+        let result = verify(
+            &bearer(),
+            &request(&Method::POST, PATH),
+            Some("Bearer s3cr3t"),
+            NOW,
+        );
+
+        assert_that(&result).is_ok();
+    }
+
+    #[test]
+    fn given_wrong_bearer_token_request_is_rejected() {
+        let result = verify(
+            &bearer(),
+            &request(&Method::POST, PATH),
+            Some("Bearer guess"),
+            NOW,
+        );
+
+        assert_that(&result).is_err_containing(Error::InvalidCredential);
+    }
+
+    #[test]
+    fn given_no_credential_request_is_rejected() {
+        let result = verify(&bearer(), &request(&Method::POST, PATH), None, NOW);
+
+        assert_that(&result).is_err_containing(Error::MissingCredential);
+    }
+
+    #[test]
+    fn given_correct_hmac_signature_request_is_authenticated() {
+        let request = request(&Method::GET, PATH);
+        let header = hmac_header(&request, NOW - 10);
+
+        let result = verify(&hmac(), &request, Some(&header), NOW);
+
+        assert_that(&result).is_ok();
+    }
+
+    #[test]
+    fn given_hmac_signature_for_different_path_request_is_rejected() {
+        let header = hmac_header(
+            &request(&Method::DELETE, "/queries/bitcoin/regtest/blocks/1"),
+            NOW,
+        );
+
+        let result = verify(&hmac(), &request(&Method::DELETE, PATH), Some(&header), NOW);
+
+        assert_that(&result).is_err_containing(Error::InvalidCredential);
+    }
+
+    #[test]
+    fn given_hmac_signature_for_different_query_request_is_rejected() {
+        let signed = SignedParts {
+            query: Some("return_as=transaction_id"),
+            ..request(&Method::GET, PATH)
+        };
+        let header = hmac_header(&signed, NOW);
+
+        let result = verify(
+            &hmac(),
+            &SignedParts {
+                query: Some("return_as=transaction"),
+                ..signed
+            },
+            Some(&header),
+            NOW,
+        );
+
+        assert_that(&result).is_err_containing(Error::InvalidCredential);
+    }
+
+    #[test]
+    fn given_hmac_signature_for_different_body_request_is_rejected() {
+        let signed_hash = content_hash(br#"{"min_height":1}"#);
+        let sent_hash = content_hash(br#"{"min_height":2}"#);
+        let signed = SignedParts {
+            content_hash: Some(&signed_hash),
+            has_body: true,
+            ..request(&Method::POST, PATH)
+        };
+        let header = hmac_header(&signed, NOW);
+
+        let result = verify(
+            &hmac(),
+            &SignedParts {
+                content_hash: Some(&sent_hash),
+                ..signed
+            },
+            Some(&header),
+            NOW,
+        );
+
+        assert_that(&result).is_err_containing(Error::InvalidCredential);
+    }
+
+    #[test]
+    fn given_body_without_content_hash_hmac_request_is_rejected() {
+        let signed = request(&Method::POST, PATH);
+        let header = hmac_header(&signed, NOW);
+
+        let result = verify(
+            &hmac(),
+            &SignedParts {
+                has_body: true,
+                ..signed
+            },
+            Some(&header),
+            NOW,
+        );
+
+        assert_that(&result).is_err_containing(Error::UnsignedBody);
+    }
+
+    #[test]
+    fn given_old_hmac_signature_request_is_rejected() {
+        let request = request(&Method::GET, PATH);
+        let header = hmac_header(&request, NOW - MAX_CLOCK_SKEW_SECS - 1);
+
+        let result = verify(&hmac(), &request, Some(&header), NOW);
+
+        assert_that(&result).is_err_containing(Error::ExpiredTimestamp);
+    }
+
+    #[test]
+    fn given_bearer_token_when_hmac_is_configured_request_is_rejected() {
+        let result = verify(
+            &hmac(),
+            &request(&Method::GET, PATH),
+            Some("Bearer s3cr3t"),
+            NOW,
+        );
+
+        assert_that(&result).is_err_containing(Error::MalformedCredential);
+    }
+}
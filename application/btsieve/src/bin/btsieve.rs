@@ -2,25 +2,34 @@
 #![deny(unsafe_code)]
 
 use bitcoin_rpc_client::{rpc::BlockchainInfo, BitcoinCoreClient, BitcoinRpcApi};
-use bitcoin_support::Network as BitcoinNetwork;
+use bitcoin_support::{Chain, MinedBlock, Network as BitcoinNetwork};
 use btsieve::{
-    bitcoin::{self, bitcoind_zmq_listener::bitcoin_block_listener},
-    ethereum::{self, ethereum_web3_block_poller::ethereum_block_listener},
+    authentication::authenticate,
+    bitcoin::{
+        self, bitcoind_zmq_listener::bitcoin_block_listener, block_replayer::bitcoin_block_replayer,
+    },
+    block_recording::{self, BlockRecorder},
+    ethereum::{
+        self, block_replayer::ethereum_block_replayer,
+        ethereum_web3_block_poller::ethereum_block_listener,
+    },
     load_settings::{load_settings, Opt},
-    logging, route_factory, settings, InMemoryQueryRepository, InMemoryQueryResultRepository,
-    QueryMatch, QueryResultRepository,
+    logging, route_factory, settings, Rescan,
 };
 use ethereum_support::{
     web3::{
         self,
         transports::{EventLoopHandle, Http},
-        Web3,
+        Transport, Web3,
     },
-    Network as EthereumNetwork,
+    Block, ChainId, Network as EthereumNetwork, Transaction,
 };
 use failure::Fail;
-use futures::{future::Future, stream::Stream};
-use std::{string::ToString, sync::Arc};
+use futures::{
+    future::{self, Future},
+    stream::Stream,
+};
+use std::{path::Path, string::ToString, sync::Arc};
 use structopt::StructOpt;
 use tokio::runtime::Runtime;
 use warp::{self, filters::BoxedFilter, Filter, Reply};
@@ -31,6 +40,18 @@ enum Error {
     ConnectionError { ledger: String },
     #[fail(display = "Unknown ledger network: {} for ledger {}", network, ledger)]
     UnknownLedgerVersion { network: String, ledger: String },
+    #[fail(
+        display = "Configured chain id {} does not match chain id {} of the Ethereum node",
+        configured, actual
+    )]
+    ChainIdMismatch {
+        configured: ChainId,
+        actual: ChainId,
+    },
+    #[fail(display = "Could not record blocks to: {}", path)]
+    BlockRecording { path: String },
+    #[fail(display = "Could not replay blocks from: {}", path)]
+    BlockReplay { path: String },
 }
 
 impl From<web3::Error> for Error {
@@ -51,7 +72,8 @@ fn main() -> Result<(), failure::Error> {
 
     log::info!("Starting up with {:#?}", settings);
 
-    let bitcoin_routes = create_bitcoin_routes(&mut runtime, settings.bitcoin)?;
+    let bitcoin_routes = create_bitcoin_routes(&mut runtime, settings.bitcoin, Chain::Bitcoin)?;
+    let litecoin_routes = create_bitcoin_routes(&mut runtime, settings.litecoin, Chain::Litecoin)?;
 
     let (ethereum_routes, _event_loop) = create_ethereum_routes(&mut runtime, settings.ethereum)?;
 
@@ -59,234 +81,375 @@ fn main() -> Result<(), failure::Error> {
     let ping_200 = warp::path("health").map(warp::reply);
     let ping_route = warp::get2().and(ping_200);
 
-    let routes = ping_route.or(bitcoin_routes.or(ethereum_routes)).with(log);
+    let query_routes = authenticate(settings.http_api.authentication.clone())
+        .and(bitcoin_routes.or(litecoin_routes).or(ethereum_routes))
+        .recover(btsieve::customize_error);
+
+    let routes = ping_route.or(query_routes).with(log);
 
     warp::serve(routes).run((settings.http_api.address_bind, settings.http_api.port_bind));
     Ok(())
 }
 
+/// Serves the routes of `chain`, which can be Bitcoin or any of the forks that
+/// share bitcoind's RPC and ZMQ interface.
 fn create_bitcoin_routes(
     runtime: &mut Runtime,
     settings: Option<settings::Bitcoin>,
+    chain: Chain,
 ) -> Result<BoxedFilter<(impl Reply,)>, Error> {
-    let block_query_repository =
-        Arc::new(InMemoryQueryRepository::<bitcoin::BlockQuery>::default());
-    let transaction_query_repository =
-        Arc::new(InMemoryQueryRepository::<bitcoin::TransactionQuery>::default());
-
-    let block_query_result_repository = Arc::new(InMemoryQueryResultRepository::default());
-    let transaction_query_result_repository = Arc::new(InMemoryQueryResultRepository::default());
+    let repositories = Arc::new(bitcoin::QueryRepositories::default());
+    let ledger_name = chain.name();
 
-    let (client, network) = if let Some(settings) = settings {
+    let (client, rescan, network) = if let Some(settings) = settings {
         let bitcoin_rpc_client = bitcoin_rpc_client::BitcoinCoreClient::new(
             settings.node_url.as_str(),
             settings.node_username.as_str(),
             settings.node_password.as_str(),
         );
-        let blockchain_info = get_bitcoin_info(&bitcoin_rpc_client)?;
-        log::info!("Connected to Bitcoin: {:?}.", blockchain_info);
-        let network: &str = BitcoinNetwork::from(blockchain_info.chain).into();
-
-        log::trace!("Setting up bitcoin routes to {:?}.", network);
+        let blockchain_info = get_bitcoin_info(&bitcoin_rpc_client, chain)?;
+        log::info!("Connected to {:?}: {:?}.", chain, blockchain_info);
+        let bitcoin_network = BitcoinNetwork::from(blockchain_info.chain);
+        let network: &str = bitcoin_network.into();
 
-        log::info!("Connect BitcoinZmqListener to {}.", settings.zmq_endpoint);
+        log::trace!("Setting up {} routes to {:?}.", ledger_name, network);
 
         {
-            let block_query_repository = Arc::clone(&block_query_repository);
-            let transaction_query_repository = Arc::clone(&transaction_query_repository);
-
-            let block_query_result_repository = Arc::clone(&block_query_result_repository);
-            let transaction_query_result_repository =
-                Arc::clone(&transaction_query_result_repository);
-
-            let blocks = bitcoin_block_listener(settings.zmq_endpoint.as_str())
-                .expect("Should return a Bitcoind received for MinedBlocks");
+            let repositories = Arc::clone(&repositories);
+
+            let blocks: Box<dyn Stream<Item = MinedBlock, Error = ()> + Send> =
+                match &settings.replay_blocks {
+                    Some(replay) => bitcoin_block_replayer(&replay.from, replay.pace_secs)
+                        .map_err(|e| replay_error(&replay.from, e))?,
+                    None => {
+                        log::info!("Connect BitcoinZmqListener to {}.", settings.zmq_endpoint);
+                        Box::new(
+                            bitcoin_block_listener(settings.zmq_endpoint.as_str())
+                                .expect("Should return a Bitcoind received for MinedBlocks"),
+                        )
+                    }
+                };
+
+            let recorder = create_block_recorder(settings.record_blocks_to.as_ref())?;
+            // Litecoin's proof-of-work is based on scrypt rather than on the
+            // block hash, so we can only verify Bitcoin's headers.
+            let mut header_chain = match chain {
+                Chain::Bitcoin => Some(bitcoin::HeaderChain::new(
+                    bitcoin_network,
+                    settings.checkpoint,
+                )),
+                Chain::Litecoin => None,
+            };
+            let block_fetcher = bitcoin::BlockFetcher::new(
+                settings.node_url.clone(),
+                settings.node_username.clone(),
+                settings.node_password.clone(),
+            );
 
             let bitcoin_processor = blocks.for_each(move |block| {
-                bitcoin::check_block_queries(block_query_repository.clone(), block.clone())
-                    .for_each(|QueryMatch(id, block_id)| {
-                        block_query_result_repository.add_result(id.0, block_id);
-                    });
-
-                bitcoin::check_transaction_queries(
-                    transaction_query_repository.clone(),
-                    block.clone(),
-                )
-                .for_each(|QueryMatch(id, block_id)| {
-                    transaction_query_result_repository.add_result(id.0, block_id);
-                });
+                let blocks = match header_chain.as_mut() {
+                    Some(header_chain) => header_chain.accept(block, |id| block_fetcher.header(id)),
+                    None => vec![block],
+                };
+
+                for block in blocks {
+                    if let Some(recorder) = &recorder {
+                        if let Err(e) =
+                            recorder.record(&bitcoin::block_replayer::RecordedBlock::from(&block))
+                        {
+                            log::error!("Could not record {:?} block: {:?}", chain, e);
+                        }
+                    }
+
+                    repositories.process_block(&block);
+                }
 
                 Ok(())
             });
             runtime.spawn(bitcoin_processor);
         }
-        (Some(Arc::from(bitcoin_rpc_client)), Some(network))
+        let rescanner = bitcoin::Rescanner::new(
+            Arc::clone(&repositories),
+            bitcoin::BlockFetcher::new(
+                settings.node_url,
+                settings.node_username,
+                settings.node_password,
+            ),
+        );
+        (
+            Some(Arc::new(
+                bitcoin::transaction_fetcher::TransactionFetcher::new(bitcoin_rpc_client),
+            )),
+            Some(Arc::new(rescanner) as Arc<dyn Rescan>),
+            Some(network),
+        )
     } else {
-        (None, None)
+        (None, None, None)
     };
 
-    let ledger_name = "bitcoin";
+    let list_routes =
+        route_factory::create_list_endpoint(Arc::clone(&repositories), ledger_name, network);
 
     let transaction_routes =
         route_factory::create_endpoints::<bitcoin::queries::transaction::ReturnAs, _, _, _, _>(
-            transaction_query_repository,
-            transaction_query_result_repository,
+            Arc::clone(&repositories.transaction_queries),
+            Arc::clone(&repositories.transaction_query_results),
             client.clone(),
+            rescan.clone(),
             ledger_name,
             network,
         );
 
     let block_routes =
         route_factory::create_endpoints::<bitcoin::queries::block::ReturnAs, _, _, _, _>(
-            block_query_repository,
-            block_query_result_repository,
+            Arc::clone(&repositories.block_queries),
+            Arc::clone(&repositories.block_query_results),
             client,
+            rescan,
             ledger_name,
             network,
         );
 
-    Ok(transaction_routes.or(block_routes).boxed())
+    Ok(list_routes.or(transaction_routes).or(block_routes).boxed())
 }
 
 fn create_ethereum_routes(
     runtime: &mut Runtime,
     settings: Option<settings::Ethereum>,
 ) -> Result<(BoxedFilter<(impl Reply,)>, Option<EventLoopHandle>), Error> {
-    let transaction_query_repository =
-        Arc::new(InMemoryQueryRepository::<ethereum::TransactionQuery>::default());
-    let block_query_repository =
-        Arc::new(InMemoryQueryRepository::<ethereum::BlockQuery>::default());
-    let log_query_repository = Arc::new(InMemoryQueryRepository::<ethereum::EventQuery>::default());
-    let transaction_query_result_repository = Arc::new(InMemoryQueryResultRepository::default());
-    let block_query_result_repository = Arc::new(InMemoryQueryResultRepository::default());
-    let log_query_result_repository = Arc::new(InMemoryQueryResultRepository::default());
-
-    let (client, network, event_loop) = if let Some(settings) = settings {
+    let repositories = Arc::new(ethereum::QueryRepositories::default());
+    let trace_blocks = settings
+        .as_ref()
+        .map_or(false, |settings| settings.trace_blocks);
+
+    let (client, rescan, network, event_loop) = if let Some(settings) = settings {
         log::info!("Starting Ethereum Listener on {}", settings.node_url);
 
         let (event_loop, transport) =
             Http::new(settings.node_url.as_str()).expect("unable to connect to Ethereum node");
         let web3_client = Arc::new(Web3::new(transport));
 
-        let network = get_ethereum_info(web3_client.clone())?.into();
+        let network = get_ethereum_info(&web3_client, settings.chain.clone())?;
 
         log::trace!("Setting up ethereum routes to {:?}", network);
 
         {
-            let block_query_repository = block_query_repository.clone();
-            let transaction_query_repository = transaction_query_repository.clone();
-            let log_query_repository = log_query_repository.clone();
-
-            let block_query_result_repository = block_query_result_repository.clone();
-            let transaction_query_result_repository = transaction_query_result_repository.clone();
-            let log_query_result_repository = log_query_result_repository.clone();
-
+            let repositories = Arc::clone(&repositories);
             let web3_client = web3_client.clone();
 
-            let blocks = ethereum_block_listener(web3_client.clone(), settings.poll_interval_secs)
-                .expect("Should return a Web3 block poller");
+            let blocks: Box<dyn Stream<Item = Block<Transaction>, Error = ()> + Send> =
+                match &settings.replay_blocks {
+                    Some(replay) => Box::new(
+                        ethereum_block_replayer(&replay.from, replay.pace_secs)
+                            .map_err(|e| replay_error(&replay.from, e))?
+                            .map(|recorded| recorded.block),
+                    ),
+                    None => {
+                        ethereum_block_listener(web3_client.clone(), settings.poll_interval_secs)
+                            .expect("Should return a Web3 block poller")
+                    }
+                };
+
+            let recorder = create_block_recorder(settings.record_blocks_to.as_ref())?.map(Arc::new);
 
             let executor = runtime.executor();
             let web3_processor = blocks.for_each(move |block| {
-                ethereum::check_block_queries(block_query_repository.clone(), block.clone())
-                    .for_each(|QueryMatch(id, block_id)| {
-                        block_query_result_repository.add_result(id.0, block_id);
-                    });
-
-                ethereum::check_transaction_queries(
-                    transaction_query_repository.clone(),
-                    block.clone(),
-                )
-                .for_each(|QueryMatch(id, transaction_id)| {
-                    transaction_query_result_repository.add_result(id.0, transaction_id);
-                });
-
-                let log_query_result_repository = log_query_result_repository.clone();
-                let log_query_future = ethereum::check_log_queries(
-                    log_query_repository.clone(),
-                    web3_client.clone(),
-                    block,
-                )
-                .for_each(move |QueryMatch(id, transaction_id)| {
-                    log_query_result_repository.add_result(id.0, transaction_id);
-                    Ok(())
-                });
-
-                executor.spawn(log_query_future);
-                Ok(())
+                if trace_blocks {
+                    executor.spawn(repositories.process_traces(&web3_client, &block));
+                }
+
+                executor.spawn(repositories.process_block(web3_client.clone(), block.clone()));
+
+                // Waiting for the receipts before taking the next block keeps
+                // the recording in the order the blocks were mined in
+                match &recorder {
+                    Some(recorder) => {
+                        let recorder = Arc::clone(recorder);
+                        future::Either::A(
+                            ethereum::block_replayer::fetch_receipts(&web3_client, block)
+                                .map(move |recorded_block| {
+                                    if let Err(e) = recorder.record(&recorded_block) {
+                                        log::error!("Could not record Ethereum block: {:?}", e);
+                                    }
+                                })
+                                .map_err(|e| {
+                                    log::error!("Could not fetch receipts for recording: {:?}", e)
+                                }),
+                        )
+                    }
+                    None => future::Either::B(future::ok(())),
+                }
             });
 
             runtime.spawn(web3_processor);
         }
-        (Some(web3_client), Some(network), Some(event_loop))
+        let rescanner = ethereum::Rescanner::new(Arc::clone(&repositories), web3_client.clone());
+        (
+            Some(web3_client),
+            Some(Arc::new(rescanner) as Arc<dyn Rescan>),
+            Some(network),
+            Some(event_loop),
+        )
     } else {
-        (None, None, None)
+        (None, None, None, None)
     };
 
     let ledger_name = "ethereum";
 
+    let list_routes =
+        route_factory::create_list_endpoint(Arc::clone(&repositories), ledger_name, network);
+
     let transaction_routes =
         route_factory::create_endpoints::<ethereum::queries::transaction::ReturnAs, _, _, _, _>(
-            transaction_query_repository,
-            transaction_query_result_repository,
+            Arc::clone(&repositories.transaction_queries),
+            Arc::clone(&repositories.transaction_query_results),
             client.clone(),
+            rescan.clone(),
             ledger_name,
             network,
         );
 
     let block_routes =
         route_factory::create_endpoints::<ethereum::queries::block::ReturnAs, _, _, _, _>(
-            block_query_repository,
-            block_query_result_repository,
+            Arc::clone(&repositories.block_queries),
+            Arc::clone(&repositories.block_query_results),
             client.clone(),
+            rescan.clone(),
             ledger_name,
             network,
         );
 
     let bloom_routes =
         route_factory::create_endpoints::<ethereum::queries::event::ReturnAs, _, _, _, _>(
-            log_query_repository,
-            log_query_result_repository,
+            Arc::clone(&repositories.log_queries),
+            Arc::clone(&repositories.log_query_results),
             client.clone(),
+            rescan.clone(),
             ledger_name,
             network,
         );
 
+    let trace_routes =
+        route_factory::create_endpoints::<ethereum::queries::transaction::ReturnAs, _, _, _, _>(
+            Arc::clone(&repositories.trace_queries),
+            Arc::clone(&repositories.trace_query_results),
+            client.clone(),
+            rescan.clone(),
+            ledger_name,
+            network,
+        );
+
+    // Without tracing, trace queries would silently never match
+    let trace_routes = warp::any()
+        .and_then(move || {
+            if trace_blocks {
+                Ok(())
+            } else {
+                Err(warp::reject::not_found())
+            }
+        })
+        .untuple_one()
+        .and(trace_routes);
+
     Ok((
-        transaction_routes.or(block_routes).or(bloom_routes).boxed(),
+        list_routes
+            .or(transaction_routes)
+            .or(block_routes)
+            .or(bloom_routes)
+            .or(trace_routes)
+            .boxed(),
         event_loop,
     ))
 }
 
-fn get_bitcoin_info(client: &BitcoinCoreClient) -> Result<BlockchainInfo, Error> {
+fn create_block_recorder<T: serde::Serialize, P: AsRef<Path>>(
+    path: Option<P>,
+) -> Result<Option<BlockRecorder<T>>, Error> {
+    path.map(|path| {
+        let path = path.as_ref();
+        log::info!("Recording blocks to {:?}", path);
+
+        BlockRecorder::create(path).map_err(|e| {
+            log::error!("Could not open block recording {:?}: {:?}", path, e);
+            Error::BlockRecording {
+                path: path.display().to_string(),
+            }
+        })
+    })
+    .transpose()
+}
+
+fn replay_error(path: &Path, error: block_recording::Error) -> Error {
+    log::error!("Could not read block recording {:?}: {:?}", path, error);
+    Error::BlockReplay {
+        path: path.display().to_string(),
+    }
+}
+
+fn get_bitcoin_info(client: &BitcoinCoreClient, chain: Chain) -> Result<BlockchainInfo, Error> {
     client
         .get_blockchain_info()
         .map_err(|error| {
             log::error!(
-                "Could not retrieve network version from ledger Bitcoin: {:?}",
+                "Could not retrieve network version from ledger {:?}: {:?}",
+                chain,
                 error
             );
             Error::ConnectionError {
-                ledger: String::from("Bitcoin"),
+                ledger: format!("{:?}", chain),
             }
         })?
         .map_err(|error| {
-            log::error!("Could not connect to ledger Bitcoin: {:?}", error);
+            log::error!("Could not connect to ledger {:?}: {:?}", chain, error);
             Error::ConnectionError {
-                ledger: String::from("Bitcoin"),
+                ledger: format!("{:?}", chain),
             }
         })
 }
 
-fn get_ethereum_info(client: Arc<Web3<Http>>) -> Result<EthereumNetwork, Error> {
-    let network = client.net().version().wait()?;
-    log::trace!("Connected to ethereum {:?}", network);
-    let network = EthereumNetwork::from_network_id(network);
-    if network == EthereumNetwork::Unknown {
-        return Err(Error::UnknownLedgerVersion {
-            network: network.to_string(),
-            ledger: String::from("Ethereum"),
-        });
+/// Returns the name the routes of the connected chain are served under.
+fn get_ethereum_info(
+    client: &Web3<Http>,
+    chain: Option<settings::EvmChain>,
+) -> Result<&'static str, Error> {
+    let chain_id = get_chain_id(client)?;
+    log::trace!("Connected to ethereum chain {}", chain_id);
+
+    match chain {
+        Some(chain) => {
+            if chain.id != chain_id {
+                return Err(Error::ChainIdMismatch {
+                    configured: chain.id,
+                    actual: chain_id,
+                });
+            }
+            // The routes live as long as btsieve itself
+            Ok(Box::leak(chain.name.into_boxed_str()))
+        }
+        None => match EthereumNetwork::from(chain_id) {
+            EthereumNetwork::Unknown => Err(Error::UnknownLedgerVersion {
+                network: chain_id.to_string(),
+                ledger: String::from("Ethereum"),
+            }),
+            network => Ok(network.into()),
+        },
+    }
+}
+
+fn get_chain_id(client: &Web3<Http>) -> Result<ChainId, Error> {
+    let unknown_chain_id = |chain_id: String| Error::UnknownLedgerVersion {
+        network: chain_id,
+        ledger: String::from("Ethereum"),
+    };
+
+    // Nodes without EIP-695 support use the chain id as network id
+    match client.transport().execute("eth_chainId", vec![]).wait() {
+        Ok(serde_json::Value::String(chain_id)) => {
+            ChainId::from_hex_quantity(&chain_id).map_err(|_| unknown_chain_id(chain_id))
+        }
+        _ => {
+            let network_id = client.net().version().wait()?;
+            network_id.parse().map_err(|_| unknown_chain_id(network_id))
+        }
     }
-    Ok(network)
 }
@@ -0,0 +1,91 @@
+use bitcoin_support::{deserialize, Block, BlockHeader, BlockId, EncodeError, MinedBlock};
+use derivative::Derivative;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+
+#[derive(Debug)]
+pub enum Error {
+    Connection(reqwest::Error),
+    Rpc(serde_json::Value),
+    Hex(hex::FromHexError),
+    Deserialize(EncodeError),
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<serde_json::Value>,
+}
+
+/// Fetches blocks bitcoind did not announce over ZMQ. The `HeaderChain` needs
+/// their headers to connect the blocks that follow them, rescans need them in
+/// full.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct BlockFetcher {
+    client: reqwest::Client,
+    node_url: url::Url,
+    username: String,
+    #[derivative(Debug = "ignore")]
+    password: String,
+}
+
+impl BlockFetcher {
+    pub fn new(node_url: url::Url, username: String, password: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            node_url,
+            username,
+            password,
+        }
+    }
+
+    /// Blocks until bitcoind answered
+    pub fn header(&self, id: &BlockId) -> Result<BlockHeader, Error> {
+        let header: String = self.call("getblockheader", json!([id.to_string(), false]))?;
+
+        deserialize(&decode_hex(&header)?).map_err(Error::Deserialize)
+    }
+
+    /// Blocks until bitcoind answered
+    pub fn block_at(&self, height: u32) -> Result<MinedBlock, Error> {
+        let id: String = self.call("getblockhash", json!([height]))?;
+        let block: String = self.call("getblock", json!([id, 0]))?;
+
+        let block: Block = deserialize(&decode_hex(&block)?).map_err(Error::Deserialize)?;
+
+        Ok(MinedBlock::new(block, height))
+    }
+
+    fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T, Error> {
+        let response: RpcResponse<T> = self
+            .client
+            .post(self.node_url.clone())
+            .basic_auth(&self.username, Some(&self.password))
+            .json(&json!({
+                "jsonrpc": "1.0",
+                "id": "btsieve",
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .and_then(|mut response| response.json())
+            .map_err(Error::Connection)?;
+
+        match response {
+            RpcResponse {
+                result: Some(result),
+                ..
+            } => Ok(result),
+            RpcResponse { error, .. } => Err(Error::Rpc(error.unwrap_or_default())),
+        }
+    }
+}
+
+fn decode_hex(hex: &str) -> Result<Vec<u8>, Error> {
+    hex::decode(hex).map_err(Error::Hex)
+}
@@ -0,0 +1,99 @@
+use crate::block_recording::{self, Error};
+use bitcoin_support::{deserialize, serialize_hex, MinedBlock};
+use futures::stream::Stream;
+use serde::{Deserialize, Serialize};
+use std::{path::Path, time::Duration};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedBlock {
+    pub height: u32,
+    pub raw_block: String,
+}
+
+impl From<&MinedBlock> for RecordedBlock {
+    fn from(block: &MinedBlock) -> Self {
+        RecordedBlock {
+            height: block.height,
+            raw_block: serialize_hex(block.as_ref()),
+        }
+    }
+}
+
+impl RecordedBlock {
+    pub fn into_mined_block(self) -> Result<MinedBlock, Error> {
+        let bytes =
+            hex::decode(&self.raw_block).map_err(|e| Error::InvalidBlock(format!("{:?}", e)))?;
+        let block = deserialize(&bytes).map_err(|e| Error::InvalidBlock(format!("{:?}", e)))?;
+
+        Ok(MinedBlock::new(block, self.height))
+    }
+}
+
+pub fn bitcoin_block_replayer<P: AsRef<Path>>(
+    recording: P,
+    pace: Duration,
+) -> Result<Box<dyn Stream<Item = MinedBlock, Error = ()> + Send>, Error> {
+    let blocks = block_recording::read_recording::<RecordedBlock, _>(recording)?
+        .into_iter()
+        .map(RecordedBlock::into_mined_block)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    log::info!("Replaying {} recorded Bitcoin blocks", blocks.len());
+
+    Ok(block_recording::replay(blocks, pace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_recording::BlockRecorder;
+    use bitcoin_support::{BitcoinHash, Block, BlockHeader, Sha256dHash};
+    use futures::Future;
+    use spectral::prelude::*;
+
+    fn mined_block(nonce: u32, height: u32) -> MinedBlock {
+        MinedBlock::new(
+            Block {
+                header: BlockHeader {
+                    version: 1,
+                    prev_blockhash: Sha256dHash::default(),
+                    merkle_root: Sha256dHash::default(),
+                    time: 0,
+                    bits: 1,
+                    nonce,
+                },
+                txdata: vec![],
+            },
+            height,
+        )
+    }
+
+    #[test]
+    fn given_recorded_blocks_replays_them_in_order() {
+        let directory = tempfile::tempdir().unwrap();
+        let recording = directory.path().join("recording.jsonl");
+
+        let first = mined_block(1, 41);
+        let second = mined_block(2, 42);
+
+        let recorder = BlockRecorder::<RecordedBlock>::create(&recording).unwrap();
+        recorder.record(&RecordedBlock::from(&first)).unwrap();
+        recorder.record(&RecordedBlock::from(&second)).unwrap();
+
+        let replayed = bitcoin_block_replayer(&recording, Duration::from_secs(0))
+            .unwrap()
+            .collect()
+            .wait()
+            .unwrap();
+
+        let replayed = replayed
+            .iter()
+            .map(|block| (block.as_ref().bitcoin_hash(), block.height))
+            .collect::<Vec<_>>();
+
+        assert_that(&replayed).is_equal_to(vec![
+            (first.as_ref().bitcoin_hash(), 41),
+            (second.as_ref().bitcoin_hash(), 42),
+        ]);
+    }
+}
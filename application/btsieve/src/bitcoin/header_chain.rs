@@ -0,0 +1,704 @@
+use bitcoin_support::{
+    BitcoinHash, Block, BlockHeader, BlockId, MerkleRoot, MinedBlock, Network, Uint256,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Debug};
+
+/// Blocks between two difficulty adjustments
+const RETARGET_INTERVAL: u32 = 2016;
+/// Two weeks, the time `RETARGET_INTERVAL` blocks are supposed to take
+const TARGET_TIMESPAN: u32 = 14 * 24 * 60 * 60;
+const TARGET_SPACING: u32 = 10 * 60;
+/// Headers this far below the tip are pruned. A retarget needs exactly this
+/// many ancestors, and reorgs deeper than that are treated as final.
+pub const FINALITY_DEPTH: u32 = RETARGET_INTERVAL;
+/// How many blocks and headers may wait for their parent at the same time
+const MAX_ORPHANS: usize = 2 * FINALITY_DEPTH as usize;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    MerkleRootMismatch,
+    TargetAboveLimit { bits: u32 },
+    InsufficientProofOfWork,
+    UnknownParent(BlockId),
+    UnexpectedDifficulty { expected: u32, actual: u32 },
+    DifficultyOutOfBounds { bits: u32 },
+    UnexpectedHeader { expected: BlockId, actual: BlockId },
+}
+
+/// A block that is known to be part of the chain. If configured, the header
+/// chain starts there instead of at the first block it is given.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Checkpoint {
+    pub hash: BlockId,
+    pub height: u32,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// The blocks that extend the chain because of the addition, parents
+    /// before their children. Headers added without their block are not
+    /// part of it.
+    Connected(Vec<MinedBlock>),
+    /// The addition waits until the chain reaches `missing`, its oldest
+    /// unknown ancestor.
+    Orphaned { missing: BlockId },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct StoredHeader {
+    header: BlockHeader,
+    height: u32,
+}
+
+#[derive(Debug)]
+struct Pending {
+    header: BlockHeader,
+    block: Option<MinedBlock>,
+}
+
+/// The headers of the blocks btsieve has seen, used to verify new blocks
+/// before queries are matched against them. This way, a compromised bitcoind
+/// cannot make btsieve report transactions in blocks that were never mined.
+///
+/// Like a light client, the chain starts from the configured checkpoint, or
+/// the first block it is given, and from then on only accepts blocks that
+/// extend a known header, carry enough proof-of-work for the correct
+/// difficulty and whose transactions match the merkle root of their header.
+/// Blocks whose parent is unknown wait until their ancestors are added.
+#[derive(Debug)]
+pub struct HeaderChain {
+    network: Network,
+    checkpoint: Option<Checkpoint>,
+    headers: HashMap<BlockId, StoredHeader>,
+    /// Blocks and headers waiting for their parent, by the id of the parent
+    orphans: HashMap<BlockId, Vec<Pending>>,
+    /// The parent of every orphan, by the id of the orphan
+    orphan_parents: HashMap<BlockId, BlockId>,
+    tip_height: u32,
+}
+
+impl HeaderChain {
+    pub fn new(network: Network, checkpoint: Option<Checkpoint>) -> Self {
+        Self {
+            network,
+            checkpoint,
+            headers: HashMap::new(),
+            orphans: HashMap::new(),
+            orphan_parents: HashMap::new(),
+            tip_height: 0,
+        }
+    }
+
+    /// Verifies `block` and adds it to the chain.
+    pub fn add(&mut self, block: MinedBlock) -> Result<Outcome, Error> {
+        let header = {
+            let block: &Block = block.as_ref();
+
+            if block.merkle_root() != block.header.merkle_root {
+                return Err(Error::MerkleRootMismatch);
+            }
+
+            block.header
+        };
+
+        self.insert(Pending {
+            header,
+            block: Some(block),
+        })
+    }
+
+    /// Adds the header of a block that was not announced, but is the
+    /// ancestor of one that was.
+    pub fn add_ancestor(&mut self, header: BlockHeader) -> Result<Outcome, Error> {
+        self.insert(Pending {
+            header,
+            block: None,
+        })
+    }
+
+    /// Adds `block` and, if its parent is unknown, the headers of its
+    /// ancestors as returned by `fetch_header`. Returns the blocks that were
+    /// connected to the chain, errors are only logged.
+    pub fn accept<E: Debug>(
+        &mut self,
+        block: MinedBlock,
+        mut fetch_header: impl FnMut(&BlockId) -> Result<BlockHeader, E>,
+    ) -> Vec<MinedBlock> {
+        let id = block.as_ref().bitcoin_hash();
+        let mut outcome = self.add(block);
+
+        for _ in 0..=FINALITY_DEPTH {
+            let missing = match outcome {
+                Ok(Outcome::Connected(blocks)) => return blocks,
+                Ok(Outcome::Orphaned { missing }) => missing,
+                Err(e) => {
+                    log::error!("Rejecting block {} or its ancestors: {:?}", id, e);
+                    return Vec::new();
+                }
+            };
+
+            log::debug!("Fetching header of block {} to connect {}", missing, id);
+
+            outcome = match fetch_header(&missing) {
+                Ok(ref header) if header.bitcoin_hash() != missing => {
+                    Err(Error::UnexpectedHeader {
+                        expected: missing,
+                        actual: header.bitcoin_hash(),
+                    })
+                }
+                Ok(header) => self.add_ancestor(header),
+                Err(e) => {
+                    log::error!("Could not fetch header of block {}: {:?}", missing, e);
+                    return Vec::new();
+                }
+            };
+        }
+
+        log::error!(
+            "Block {} does not connect to the chain within {} blocks",
+            id,
+            FINALITY_DEPTH
+        );
+        Vec::new()
+    }
+
+    fn insert(&mut self, pending: Pending) -> Result<Outcome, Error> {
+        let header = pending.header;
+        let id = header.bitcoin_hash();
+
+        if self.headers.contains_key(&id) {
+            return Ok(Outcome::Connected(Vec::new()));
+        }
+
+        if self.orphan_parents.contains_key(&id) {
+            return Ok(Outcome::Orphaned {
+                missing: self.oldest_missing_ancestor(&id),
+            });
+        }
+
+        self.verify_proof_of_work(&header)?;
+
+        if self.anchor_height(&pending).is_some()
+            || self.headers.contains_key(&header.prev_blockhash)
+        {
+            return self.connect(pending).map(Outcome::Connected);
+        }
+
+        if self.orphan_parents.len() >= MAX_ORPHANS {
+            log::warn!(
+                "Dropping {} blocks that did not connect to the chain",
+                self.orphan_parents.len()
+            );
+            self.orphans.clear();
+            self.orphan_parents.clear();
+        }
+
+        self.orphan_parents.insert(id, header.prev_blockhash);
+        self.orphans
+            .entry(header.prev_blockhash)
+            .or_insert_with(Vec::new)
+            .push(pending);
+
+        Ok(Outcome::Orphaned {
+            missing: self.oldest_missing_ancestor(&id),
+        })
+    }
+
+    /// Connects `first` and every orphan that waited for it
+    fn connect(&mut self, first: Pending) -> Result<Vec<MinedBlock>, Error> {
+        let mut connected = Vec::new();
+        let mut waiting = vec![first];
+        let mut is_first = true;
+
+        while let Some(pending) = waiting.pop() {
+            let id = pending.header.bitcoin_hash();
+            self.orphan_parents.remove(&id);
+
+            match self.connect_one(pending) {
+                Ok(block) => {
+                    connected.extend(block);
+                    waiting.extend(self.orphans.remove(&id).unwrap_or_default());
+                }
+                Err(e) if is_first => return Err(e),
+                Err(e) => {
+                    log::warn!("Dropping block {} that waited for its parent: {:?}", id, e);
+                    self.drop_orphans_of(id);
+                }
+            }
+
+            is_first = false;
+        }
+
+        self.prune();
+
+        Ok(connected)
+    }
+
+    fn connect_one(&mut self, pending: Pending) -> Result<Option<MinedBlock>, Error> {
+        let header = pending.header;
+        let id = header.bitcoin_hash();
+
+        let height = match self.anchor_height(&pending) {
+            Some(height) => {
+                log::info!(
+                    "Starting Bitcoin header chain at block {} with height {}",
+                    id,
+                    height
+                );
+                height
+            }
+            None => {
+                let parent = *self
+                    .headers
+                    .get(&header.prev_blockhash)
+                    .ok_or(Error::UnknownParent(header.prev_blockhash))?;
+                let height = parent.height + 1;
+
+                self.verify_difficulty(&header, &parent, height)?;
+                height
+            }
+        };
+
+        self.headers.insert(id, StoredHeader { header, height });
+        self.tip_height = self.tip_height.max(height);
+
+        Ok(pending.block)
+    }
+
+    /// The height of `pending` if the chain starts with it
+    fn anchor_height(&self, pending: &Pending) -> Option<u32> {
+        if !self.headers.is_empty() {
+            return None;
+        }
+
+        match (self.checkpoint, &pending.block) {
+            (Some(checkpoint), _) if checkpoint.hash == pending.header.bitcoin_hash() => {
+                Some(checkpoint.height)
+            }
+            (None, Some(block)) => Some(coinbase_height(block.as_ref()).unwrap_or(block.height)),
+            _ => None,
+        }
+    }
+
+    fn oldest_missing_ancestor(&self, orphan: &BlockId) -> BlockId {
+        let mut missing = self.orphan_parents[orphan];
+
+        while let Some(parent) = self.orphan_parents.get(&missing) {
+            missing = *parent;
+        }
+
+        missing
+    }
+
+    fn drop_orphans_of(&mut self, parent: BlockId) {
+        let mut parents = vec![parent];
+
+        while let Some(parent) = parents.pop() {
+            for orphan in self.orphans.remove(&parent).unwrap_or_default() {
+                let id = orphan.header.bitcoin_hash();
+                self.orphan_parents.remove(&id);
+                parents.push(id);
+            }
+        }
+    }
+
+    /// Forgets the headers that are more than `FINALITY_DEPTH` below the tip
+    fn prune(&mut self) {
+        let lowest = self.tip_height.saturating_sub(FINALITY_DEPTH);
+
+        self.headers.retain(|_, stored| stored.height >= lowest);
+    }
+
+    fn verify_proof_of_work(&self, header: &BlockHeader) -> Result<(), Error> {
+        let pow_limit = target_from_compact(pow_limit_bits(self.network));
+        if header.target() > pow_limit {
+            return Err(Error::TargetAboveLimit { bits: header.bits });
+        }
+
+        if header.spv_validate(&header.target()).is_err() {
+            return Err(Error::InsufficientProofOfWork);
+        }
+
+        Ok(())
+    }
+
+    fn verify_difficulty(
+        &self,
+        header: &BlockHeader,
+        parent: &StoredHeader,
+        height: u32,
+    ) -> Result<(), Error> {
+        let actual = header.bits;
+
+        if self.network == Network::Regtest {
+            return expect_bits(parent.header.bits, actual);
+        }
+
+        if height % RETARGET_INTERVAL != 0 {
+            if self.network == Network::Testnet {
+                return self.verify_testnet_difficulty(header, parent);
+            }
+
+            return expect_bits(parent.header.bits, actual);
+        }
+
+        match self.ancestor(parent, height - RETARGET_INTERVAL) {
+            Some(first) => {
+                let timespan = parent.header.time.saturating_sub(first.header.time);
+
+                expect_bits(
+                    retarget_bits(parent.header.bits, timespan, self.network),
+                    actual,
+                )
+            }
+            // Without the whole period, we can only check that the new target
+            // is within the bounds a retarget can move it to.
+            None => {
+                let previous = parent.header.target();
+                let target = header.target();
+                let four = Uint256::from_u64(4).unwrap();
+
+                if target < previous / four || target > previous.mul_u32(4) {
+                    Err(Error::DifficultyOutOfBounds { bits: actual })
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Testnet allows a block with the minimum difficulty if it is more than
+    /// 20 minutes younger than its parent. Other blocks have to use the
+    /// difficulty of the last block that was not mined at the minimum.
+    fn verify_testnet_difficulty(
+        &self,
+        header: &BlockHeader,
+        parent: &StoredHeader,
+    ) -> Result<(), Error> {
+        let pow_limit_bits = pow_limit_bits(self.network);
+
+        if header.time > parent.header.time + 2 * TARGET_SPACING {
+            return expect_bits(pow_limit_bits, header.bits);
+        }
+
+        let mut last = parent;
+        while last.height % RETARGET_INTERVAL != 0 && last.header.bits == pow_limit_bits {
+            match self.headers.get(&last.header.prev_blockhash) {
+                Some(previous) => last = previous,
+                None => break,
+            }
+        }
+
+        expect_bits(last.header.bits, header.bits)
+    }
+
+    fn ancestor<'a>(&'a self, from: &'a StoredHeader, height: u32) -> Option<&'a StoredHeader> {
+        let mut current = from;
+
+        while current.height > height {
+            current = self.headers.get(&current.header.prev_blockhash)?;
+        }
+
+        Some(current)
+    }
+}
+
+fn expect_bits(expected: u32, actual: u32) -> Result<(), Error> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(Error::UnexpectedDifficulty { expected, actual })
+    }
+}
+
+/// The difficulty of the next period, computed the same way bitcoind does it
+pub fn retarget_bits(bits: u32, timespan: u32, network: Network) -> u32 {
+    let timespan = timespan.max(TARGET_TIMESPAN / 4).min(TARGET_TIMESPAN * 4);
+
+    let target = target_from_compact(bits).mul_u32(timespan)
+        / Uint256::from_u64(u64::from(TARGET_TIMESPAN)).unwrap();
+    let pow_limit = target_from_compact(pow_limit_bits(network));
+
+    BlockHeader::compact_target_from_u256(&target.min(pow_limit))
+}
+
+fn pow_limit_bits(network: Network) -> u32 {
+    match network {
+        Network::Mainnet | Network::Testnet => 0x1d00_ffff,
+        Network::Regtest => 0x207f_ffff,
+    }
+}
+
+/// Only handles positive targets with an exponent of at least 3, which covers
+/// every difficulty Bitcoin ever had
+fn target_from_compact(bits: u32) -> Uint256 {
+    let mantissa = u64::from(bits & 0x00ff_ffff);
+    let exponent = (bits >> 24) as usize;
+
+    Uint256::from_u64(mantissa).unwrap() << (8 * (exponent - 3))
+}
+
+/// BIP34 requires version 2 blocks to start their coinbase with their height
+fn coinbase_height(block: &Block) -> Option<u32> {
+    if block.header.version < 2 {
+        return None;
+    }
+
+    let script_sig = block.txdata.first()?.input.first()?.script_sig.as_bytes();
+
+    match *script_sig.first()? {
+        0x00 => Some(0),
+        opcode @ 0x51..=0x60 => Some(u32::from(opcode - 0x50)),
+        length @ 0x01..=0x04 => {
+            let bytes = script_sig.get(1..=length as usize)?;
+
+            Some(
+                bytes
+                    .iter()
+                    .rev()
+                    .fold(0, |height, byte| (height << 8) | u32::from(*byte)),
+            )
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin_support::{Hash, OutPoint, Script, Sha256dHash, Transaction, TxIn, TxOut};
+    use spectral::prelude::*;
+
+    const REGTEST_BITS: u32 = 0x207f_ffff;
+
+    fn coinbase(height: u8) -> Transaction {
+        Transaction {
+            version: 1,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Script::from(vec![0x01, height]),
+                sequence: 0xffff_ffff,
+                witness: vec![],
+            }],
+            output: vec![TxOut {
+                value: 50,
+                script_pubkey: Script::new(),
+            }],
+        }
+    }
+
+    fn mine(prev_blockhash: Sha256dHash, height: u8, bits: u32) -> MinedBlock {
+        let mut block = Block {
+            header: BlockHeader {
+                version: 0x2000_0000,
+                prev_blockhash,
+                merkle_root: Sha256dHash::default(),
+                time: 1_556_000_000 + u32::from(height) * TARGET_SPACING,
+                bits,
+                nonce: 0,
+            },
+            txdata: vec![coinbase(height)],
+        };
+        block.header.merkle_root = block.merkle_root();
+
+        while block.header.spv_validate(&block.header.target()).is_err() {
+            block.header.nonce += 1;
+        }
+
+        MinedBlock::new(block, u32::from(height))
+    }
+
+    fn id(block: &MinedBlock) -> BlockId {
+        block.as_ref().bitcoin_hash()
+    }
+
+    fn connected(blocks: &[&MinedBlock]) -> Outcome {
+        Outcome::Connected(blocks.iter().map(|block| (*block).clone()).collect())
+    }
+
+    fn height(chain: &HeaderChain, block: &MinedBlock) -> Option<u32> {
+        chain.headers.get(&id(block)).map(|stored| stored.height)
+    }
+
+    #[test]
+    fn given_chain_of_regtest_blocks_each_is_accepted() {
+        let mut chain = HeaderChain::new(Network::Regtest, None);
+
+        let first = mine(Sha256dHash::default(), 1, REGTEST_BITS);
+        let second = mine(id(&first), 2, REGTEST_BITS);
+        let third = mine(id(&second), 3, REGTEST_BITS);
+
+        assert_that(&chain.add(first.clone())).is_ok_containing(connected(&[&first]));
+        assert_that(&chain.add(second.clone())).is_ok_containing(connected(&[&second]));
+        assert_that(&chain.add(third.clone())).is_ok_containing(connected(&[&third]));
+        assert_that(&height(&chain, &third)).is_equal_to(Some(3));
+    }
+
+    #[test]
+    fn given_block_with_unknown_parent_it_waits_for_the_parent() {
+        let mut chain = HeaderChain::new(Network::Regtest, None);
+
+        let first = mine(Sha256dHash::default(), 1, REGTEST_BITS);
+        let second = mine(id(&first), 2, REGTEST_BITS);
+        let third = mine(id(&second), 3, REGTEST_BITS);
+
+        chain.add(first).unwrap();
+
+        assert_that(&chain.add(third.clone())).is_ok_containing(Outcome::Orphaned {
+            missing: id(&second),
+        });
+        assert_that(&chain.add(second.clone())).is_ok_containing(connected(&[&second, &third]));
+        assert_that(&height(&chain, &third)).is_equal_to(Some(3));
+    }
+
+    #[test]
+    fn given_gap_in_announced_blocks_it_is_backfilled() {
+        let mut chain = HeaderChain::new(Network::Regtest, None);
+
+        let first = mine(Sha256dHash::default(), 1, REGTEST_BITS);
+        let second = mine(id(&first), 2, REGTEST_BITS);
+        let third = mine(id(&second), 3, REGTEST_BITS);
+        let fourth = mine(id(&third), 4, REGTEST_BITS);
+
+        let headers: HashMap<BlockId, BlockHeader> = vec![&second, &third]
+            .into_iter()
+            .map(|block| (id(block), block.as_ref().header))
+            .collect();
+
+        chain.add(first).unwrap();
+        let blocks = chain.accept(fourth.clone(), |missing| {
+            headers.get(missing).cloned().ok_or("unknown block")
+        });
+
+        assert_that(&blocks).is_equal_to(vec![fourth.clone()]);
+        assert_that(&height(&chain, &fourth)).is_equal_to(Some(4));
+    }
+
+    #[test]
+    fn given_node_returns_wrong_header_backfill_is_rejected() {
+        let mut chain = HeaderChain::new(Network::Regtest, None);
+
+        let first = mine(Sha256dHash::default(), 1, REGTEST_BITS);
+        let second = mine(id(&first), 2, REGTEST_BITS);
+        let third = mine(id(&second), 3, REGTEST_BITS);
+        let unrelated = mine(id(&first), 42, REGTEST_BITS);
+
+        chain.add(first).unwrap();
+        let blocks = chain.accept(third.clone(), |_| Ok::<_, ()>(unrelated.as_ref().header));
+
+        assert_that(&blocks).is_empty();
+        assert_that(&height(&chain, &third)).is_none();
+    }
+
+    #[test]
+    fn given_checkpoint_chain_starts_there() {
+        let first = mine(Sha256dHash::default(), 1, REGTEST_BITS);
+        let second = mine(id(&first), 2, REGTEST_BITS);
+        let third = mine(id(&second), 3, REGTEST_BITS);
+
+        let mut chain = HeaderChain::new(
+            Network::Regtest,
+            Some(Checkpoint {
+                hash: id(&second),
+                height: 1002,
+            }),
+        );
+
+        assert_that(&chain.add(first.clone())).is_ok_containing(Outcome::Orphaned {
+            missing: Sha256dHash::default(),
+        });
+        assert_that(&chain.add(third.clone())).is_ok_containing(Outcome::Orphaned {
+            missing: id(&second),
+        });
+        assert_that(&chain.add_ancestor(second.as_ref().header))
+            .is_ok_containing(connected(&[&third]));
+        assert_that(&height(&chain, &third)).is_equal_to(Some(1003));
+        assert_that(&height(&chain, &first)).is_none();
+    }
+
+    #[test]
+    fn headers_below_finality_depth_are_pruned() {
+        let mut chain = HeaderChain::new(Network::Regtest, None);
+
+        let first = mine(Sha256dHash::default(), 1, REGTEST_BITS);
+        let mut tip = first.clone();
+        chain.add(first.clone()).unwrap();
+
+        for _ in 0..=FINALITY_DEPTH {
+            tip = mine(id(&tip), 2, REGTEST_BITS);
+            chain.add(tip.clone()).unwrap();
+        }
+
+        assert_that(&height(&chain, &first)).is_none();
+        assert_that(&height(&chain, &tip)).is_equal_to(Some(FINALITY_DEPTH + 2));
+        assert_that(&chain.headers.len()).is_equal_to(FINALITY_DEPTH as usize + 1);
+    }
+
+    #[test]
+    fn given_block_without_proof_of_work_it_is_rejected() {
+        let mut chain = HeaderChain::new(Network::Regtest, None);
+
+        let mut block = mine(Sha256dHash::default(), 1, REGTEST_BITS)
+            .as_ref()
+            .clone();
+        block.header.bits = 0x1d00_ffff;
+
+        assert_that(&chain.add(MinedBlock::new(block, 1)))
+            .is_err_containing(Error::InsufficientProofOfWork);
+    }
+
+    #[test]
+    fn given_regtest_block_on_mainnet_it_is_rejected() {
+        let mut chain = HeaderChain::new(Network::Mainnet, None);
+
+        let block = mine(Sha256dHash::default(), 1, REGTEST_BITS);
+
+        assert_that(&chain.add(block))
+            .is_err_containing(Error::TargetAboveLimit { bits: REGTEST_BITS });
+    }
+
+    #[test]
+    fn given_block_with_changed_difficulty_on_regtest_it_is_rejected() {
+        let mut chain = HeaderChain::new(Network::Regtest, None);
+
+        let first = mine(Sha256dHash::default(), 1, REGTEST_BITS);
+        let second = mine(id(&first), 2, 0x207f_fffe);
+
+        chain.add(first).unwrap();
+
+        assert_that(&chain.add(second)).is_err_containing(Error::UnexpectedDifficulty {
+            expected: REGTEST_BITS,
+            actual: 0x207f_fffe,
+        });
+    }
+
+    #[test]
+    fn given_block_with_injected_transaction_it_is_rejected() {
+        let mut chain = HeaderChain::new(Network::Regtest, None);
+
+        let mut block = mine(Sha256dHash::default(), 1, REGTEST_BITS)
+            .as_ref()
+            .clone();
+        block.txdata.push(coinbase(42));
+
+        assert_that(&chain.add(MinedBlock::new(block, 1)))
+            .is_err_containing(Error::MerkleRootMismatch);
+    }
+
+    #[test]
+    fn first_mainnet_retarget_is_computed_like_bitcoind() {
+        // Blocks 30240 to 32255 took 1022578 seconds
+        let bits = retarget_bits(0x1d00_ffff, 1_022_578, Network::Mainnet);
+
+        assert_that(&bits).is_equal_to(0x1d00_d86a);
+    }
+
+    #[test]
+    fn retarget_never_exceeds_pow_limit() {
+        let bits = retarget_bits(0x1d00_ffff, 10 * TARGET_TIMESPAN, Network::Mainnet);
+
+        assert_that(&bits).is_equal_to(0x1d00_ffff);
+    }
+}
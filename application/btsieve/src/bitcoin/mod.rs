@@ -1,8 +1,18 @@
 pub mod bitcoind_zmq_listener;
+pub mod block_fetcher;
 pub mod block_processor;
+pub mod block_replayer;
+pub mod header_chain;
 pub mod queries;
+pub mod repositories;
+pub mod rescanner;
+pub mod transaction_fetcher;
 
 pub use self::{
+    block_fetcher::BlockFetcher,
     block_processor::{check_block_queries, check_transaction_queries},
+    header_chain::{Checkpoint, HeaderChain},
     queries::{BlockQuery, TransactionQuery},
+    repositories::QueryRepositories,
+    rescanner::Rescanner,
 };
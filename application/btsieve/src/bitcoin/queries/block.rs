@@ -1,14 +1,18 @@
 use crate::{
-    bitcoin::queries::{to_sha256d_hash, PayloadKind},
+    bitcoin::{
+        queries::{to_sha256d_hash, PayloadKind},
+        transaction_fetcher::TransactionFetcher,
+    },
     query_result_repository::QueryResult,
     route_factory::{Error, QueryType, ToHttpPayload},
 };
-use bitcoin_rpc_client::BitcoinCoreClient;
 use bitcoin_support::MinedBlock;
 use derivative::Derivative;
+use futures::future::{self, Future};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
 pub struct BlockQuery {
     pub min_height: Option<u32>,
 }
@@ -19,7 +23,7 @@ impl QueryType for BlockQuery {
     }
 }
 
-#[derive(Deserialize, Derivative, Debug)]
+#[derive(Deserialize, Derivative, Debug, Clone, Copy)]
 #[derivative(Default)]
 #[serde(rename_all = "snake_case")]
 pub enum ReturnAs {
@@ -28,22 +32,24 @@ pub enum ReturnAs {
 }
 
 impl ToHttpPayload<ReturnAs> for QueryResult {
-    type Client = BitcoinCoreClient;
+    type Client = TransactionFetcher;
     type Item = PayloadKind;
 
     fn to_http_payload(
         &self,
         return_as: &ReturnAs,
-        _: &BitcoinCoreClient,
-    ) -> Result<Vec<Self::Item>, Error> {
-        Ok(self
+        _: &Arc<TransactionFetcher>,
+    ) -> Box<dyn Future<Item = Vec<Self::Item>, Error = Error> + Send> {
+        let payload = self
             .0
             .iter()
             .filter_map(to_sha256d_hash)
             .map(|id| match return_as {
                 ReturnAs::BlockId => PayloadKind::Id { id },
             })
-            .collect())
+            .collect();
+
+        Box::new(future::ok(payload))
     }
 }
 
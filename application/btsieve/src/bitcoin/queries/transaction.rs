@@ -1,18 +1,26 @@
 use crate::{
-    bitcoin::queries::{to_sha256d_hash, PayloadKind},
+    bitcoin::{
+        queries::{to_sha256d_hash, PayloadKind},
+        transaction_fetcher::TransactionFetcher,
+    },
     query_result_repository::QueryResult,
-    route_factory::{Error, QueryType, ToHttpPayload},
+    route_factory::{Error, QueryType, ToHttpPayload, MAX_CONCURRENT_PAYLOAD_REQUESTS},
 };
-use bitcoin_rpc_client::{BitcoinCoreClient, BitcoinRpcApi};
 use bitcoin_support::{
-    Address, OutPoint, SpendsFrom, SpendsFromWith, SpendsTo, SpendsWith, Transaction, TransactionId,
+    ChainAddress, OutPoint, SpendsFrom, SpendsFromWith, SpendsTo, SpendsWith, Transaction,
+    TransactionId,
 };
 use derivative::Derivative;
+use futures::{
+    future::{self, Future},
+    stream::{self, Stream},
+};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
 pub struct TransactionQuery {
-    pub to_address: Option<Address>,
+    pub to_address: Option<ChainAddress>,
     pub from_outpoint: Option<OutPoint>,
     pub unlock_script: Option<Vec<Vec<u8>>>,
 }
@@ -23,7 +31,7 @@ impl QueryType for TransactionQuery {
     }
 }
 
-#[derive(Deserialize, Derivative, Debug, PartialEq)]
+#[derive(Deserialize, Derivative, Debug, Clone, Copy, PartialEq)]
 #[derivative(Default)]
 #[serde(rename_all = "snake_case")]
 pub enum ReturnAs {
@@ -33,45 +41,46 @@ pub enum ReturnAs {
 }
 
 impl ToHttpPayload<ReturnAs> for QueryResult {
-    type Client = BitcoinCoreClient;
+    type Client = TransactionFetcher;
     type Item = PayloadKind;
 
     fn to_http_payload(
         &self,
         return_as: &ReturnAs,
-        client: &BitcoinCoreClient,
-    ) -> Result<Vec<Self::Item>, Error> {
-        // Close over some local variables for easier usage of the method
-        let to_payload = |id: TransactionId| to_payload(client, return_as, id);
-
-        self.0
+        client: &Arc<TransactionFetcher>,
+    ) -> Box<dyn Future<Item = Vec<Self::Item>, Error = Error> + Send> {
+        let client = Arc::clone(client);
+        let return_as = *return_as;
+        let ids = self
+            .0
             .iter()
             .filter_map(to_sha256d_hash)
-            .map(to_payload)
-            // .collect for Vec<Result<PayloadKind, Error>> transforms it into
-            // Result<Vec<PayloadKind>, Error> returning the first Error or the whole
-            // collection.
-            // We want this because the the Error means something is wrong with the connection to
-            // our bitcoin node and skipping is unlikely to help since the next call will fail as
-            // well. That is why we simply fail the whole function.
-            .collect()
+            .collect::<Vec<_>>();
+
+        Box::new(
+            stream::iter_ok(ids)
+                .map(move |id| to_payload(&client, return_as, id))
+                .buffered(MAX_CONCURRENT_PAYLOAD_REQUESTS)
+                // We fail the whole payload on the first Error because it means something is
+                // wrong with the connection to our bitcoin node and skipping is unlikely to
+                // help since the next call will fail as well.
+                .collect(),
+        )
     }
 }
 
 fn to_payload(
-    client: &BitcoinCoreClient,
-    return_as: &ReturnAs,
+    client: &TransactionFetcher,
+    return_as: ReturnAs,
     id: TransactionId,
-) -> Result<PayloadKind, Error> {
+) -> Box<dyn Future<Item = PayloadKind, Error = Error> + Send> {
     match return_as {
-        ReturnAs::TransactionId => Ok(PayloadKind::Id { id }),
-        ReturnAs::Transaction => match client.get_raw_transaction_verbose(&id) {
-            Ok(Ok(transaction)) => Ok(PayloadKind::Transaction {
-                transaction: transaction.into(),
-            }),
-            Ok(Err(e)) => Err(Error::BitcoinRpcResponse(e)),
-            Err(e) => Err(Error::BitcoinRpcConnection(e)),
-        },
+        ReturnAs::TransactionId => Box::new(future::ok(PayloadKind::Id { id })),
+        ReturnAs::Transaction => Box::new(
+            client
+                .transaction(id)
+                .map(|transaction| PayloadKind::Transaction { transaction }),
+        ),
     }
 }
 
@@ -87,7 +96,7 @@ impl TransactionQuery {
 
                 result = result
                     && match to_address {
-                        Some(to_address) => transaction.spends_to(to_address),
+                        Some(to_address) => transaction.spends_to(to_address.address()),
                         _ => result,
                     };
 
@@ -146,6 +155,20 @@ mod tests {
         assert_that(&result).is_true();
     }
 
+    #[test]
+    fn given_transaction_with_to_then_litecoin_encoded_to_address_query_matches() {
+        let tx = parse_raw_tx(WITNESS_TX);
+
+        let query = TransactionQuery {
+            to_address: Some("M8MfmL2K3jA7hdmU2TptLTvKH9c6T7Y2XL".parse().unwrap()),
+            from_outpoint: None,
+            unlock_script: None,
+        };
+
+        let result = query.matches(&tx);
+        assert_that(&result).is_true();
+    }
+
     #[test]
     fn given_a_witness_transaction_with_unlock_script_then_unlock_script_query_matches() {
         let tx = parse_raw_tx(WITNESS_TX);
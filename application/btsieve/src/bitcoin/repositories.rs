@@ -0,0 +1,57 @@
+use crate::{
+    bitcoin::{check_block_queries, check_transaction_queries, BlockQuery, TransactionQuery},
+    query_listing::{list_queries, ListQueries, ListedQuery},
+    InMemoryQueryRepository, InMemoryQueryResultRepository, LastBlock, QueryMatch,
+    QueryResultRepository,
+};
+use bitcoin_support::MinedBlock;
+use std::sync::Arc;
+
+/// All Bitcoin queries and their results. Shared between the HTTP routes and
+/// the block listener feeding new blocks into `process_block`.
+#[derive(Debug, Default)]
+pub struct QueryRepositories {
+    pub block_queries: Arc<InMemoryQueryRepository<BlockQuery>>,
+    pub transaction_queries: Arc<InMemoryQueryRepository<TransactionQuery>>,
+    pub block_query_results: Arc<InMemoryQueryResultRepository<BlockQuery>>,
+    pub transaction_query_results: Arc<InMemoryQueryResultRepository<TransactionQuery>>,
+    pub last_block: LastBlock,
+}
+
+impl QueryRepositories {
+    pub fn process_block(&self, block: &MinedBlock) {
+        self.last_block.update(u64::from(block.height));
+
+        check_block_queries(self.block_queries.clone(), block.clone()).for_each(
+            |QueryMatch(id, block_id)| {
+                self.block_query_results.add_result(id.0, block_id);
+            },
+        );
+
+        check_transaction_queries(self.transaction_queries.clone(), block.clone()).for_each(
+            |QueryMatch(id, transaction_id)| {
+                self.transaction_query_results
+                    .add_result(id.0, transaction_id);
+            },
+        );
+    }
+}
+
+impl ListQueries for QueryRepositories {
+    fn list_queries(&self, ledger_name: &'static str, network: &str) -> Vec<ListedQuery> {
+        let mut queries = list_queries(
+            &*self.block_queries,
+            &*self.block_query_results,
+            ledger_name,
+            network,
+        );
+        queries.extend(list_queries(
+            &*self.transaction_queries,
+            &*self.transaction_query_results,
+            ledger_name,
+            network,
+        ));
+
+        queries
+    }
+}
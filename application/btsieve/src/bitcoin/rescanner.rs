@@ -0,0 +1,58 @@
+use crate::{
+    bitcoin::{BlockFetcher, QueryRepositories},
+    Rescan,
+};
+use debug_stub_derive::DebugStub;
+use futures::future::{self, Future};
+use futures_cpupool::{Builder, CpuPool};
+use std::sync::Arc;
+
+/// Fetches the blocks to rescan from the node, one after the other on a
+/// dedicated thread as the `BlockFetcher` blocks.
+#[derive(DebugStub)]
+pub struct Rescanner {
+    repositories: Arc<QueryRepositories>,
+    fetcher: Arc<BlockFetcher>,
+    #[debug_stub = "CpuPool"]
+    pool: CpuPool,
+}
+
+impl Rescanner {
+    pub fn new(repositories: Arc<QueryRepositories>, fetcher: BlockFetcher) -> Self {
+        Self {
+            repositories,
+            fetcher: Arc::new(fetcher),
+            pool: Builder::new()
+                .pool_size(1)
+                .name_prefix("bitcoin-rescan-")
+                .create(),
+        }
+    }
+}
+
+impl Rescan for Rescanner {
+    fn last_block(&self) -> Option<u64> {
+        self.repositories.last_block.get()
+    }
+
+    fn rescan(&self, from_block: u64) -> Box<dyn Future<Item = (), Error = ()> + Send> {
+        let last_block = match self.last_block() {
+            Some(last_block) => last_block,
+            None => return Box::new(future::ok(())),
+        };
+        let repositories = Arc::clone(&self.repositories);
+        let fetcher = Arc::clone(&self.fetcher);
+
+        Box::new(self.pool.spawn_fn(move || {
+            for height in from_block..=last_block {
+                let block = fetcher.block_at(height as u32).map_err(|e| {
+                    log::error!("Could not fetch block {} to rescan: {:?}", height, e)
+                })?;
+
+                repositories.process_block(&block);
+            }
+
+            Ok(())
+        }))
+    }
+}
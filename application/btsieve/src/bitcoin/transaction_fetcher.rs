@@ -0,0 +1,62 @@
+use crate::{route_factory::Error, transaction_cache::TransactionCache};
+use bitcoin_rpc_client::{BitcoinCoreClient, BitcoinRpcApi};
+use bitcoin_support::{Transaction, TransactionId};
+use debug_stub_derive::DebugStub;
+use futures::future::{self, Future};
+use futures_cpupool::{Builder, CpuPool};
+use std::sync::Arc;
+
+/// bitcoind connections are handled by a blocking client, this is how many
+/// RPC calls can be in flight at the same time.
+const RPC_THREADS: usize = 4;
+const CACHE_CAPACITY: usize = 1000;
+
+/// Fetches transactions from bitcoind without blocking the caller by running
+/// the RPC calls on a dedicated thread pool.
+#[derive(DebugStub)]
+pub struct TransactionFetcher {
+    #[debug_stub = "BitcoinCoreClient"]
+    client: Arc<BitcoinCoreClient>,
+    #[debug_stub = "CpuPool"]
+    pool: CpuPool,
+    cache: Arc<TransactionCache<TransactionId, Transaction>>,
+}
+
+impl TransactionFetcher {
+    pub fn new(client: BitcoinCoreClient) -> Self {
+        Self {
+            client: Arc::new(client),
+            pool: Builder::new()
+                .pool_size(RPC_THREADS)
+                .name_prefix("bitcoin-rpc-")
+                .create(),
+            cache: Arc::new(TransactionCache::new(CACHE_CAPACITY)),
+        }
+    }
+
+    pub fn transaction(
+        &self,
+        id: TransactionId,
+    ) -> Box<dyn Future<Item = Transaction, Error = Error> + Send> {
+        if let Some(transaction) = self.cache.get(&id) {
+            log::trace!("Serving transaction {} from cache", id);
+            return Box::new(future::ok(transaction));
+        }
+
+        let client = Arc::clone(&self.client);
+        let cache = Arc::clone(&self.cache);
+
+        Box::new(
+            self.pool
+                .spawn_fn(move || match client.get_raw_transaction_verbose(&id) {
+                    Ok(Ok(transaction)) => {
+                        let transaction: Transaction = transaction.into();
+                        cache.insert(id, transaction.clone());
+                        Ok(transaction)
+                    }
+                    Ok(Err(e)) => Err(Error::BitcoinRpcResponse(e)),
+                    Err(e) => Err(Error::BitcoinRpcConnection(e)),
+                }),
+        )
+    }
+}
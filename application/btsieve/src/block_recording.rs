@@ -0,0 +1,93 @@
+use futures::stream::{self, Stream};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    marker::PhantomData,
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tokio::timer::Interval;
+
+/// Recordings are stored as JSON lines: one recorded block per line, in the
+/// order they have been received from the ledger.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Json(serde_json::Error),
+    InvalidBlock(String),
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+#[derive(Debug)]
+pub struct BlockRecorder<T> {
+    file: Mutex<BufWriter<File>>,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Serialize> BlockRecorder<T> {
+    /// Appends to the recording at `path`, creating it if it does not exist.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            file: Mutex::new(BufWriter::new(file)),
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn record(&self, block: &T) -> Result<(), Error> {
+        let mut file = self.file.lock().unwrap();
+
+        serde_json::to_writer(&mut *file, block)?;
+        writeln!(file)?;
+        file.flush()?;
+
+        Ok(())
+    }
+}
+
+pub fn read_recording<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<Vec<T>, Error> {
+    let file = File::open(path)?;
+
+    BufReader::new(file)
+        .lines()
+        .filter(|line| {
+            line.as_ref()
+                .map(|line| !line.trim().is_empty())
+                .unwrap_or(true)
+        })
+        .map(|line| Ok(serde_json::from_str(&line?)?))
+        .collect()
+}
+
+/// Emits one block every `pace`. A `pace` of zero emits all blocks at once.
+pub fn replay<T: Send + 'static>(
+    blocks: Vec<T>,
+    pace: Duration,
+) -> Box<dyn Stream<Item = T, Error = ()> + Send> {
+    let blocks = stream::iter_ok(blocks);
+
+    if pace == Duration::from_secs(0) {
+        return Box::new(blocks);
+    }
+
+    Box::new(
+        Interval::new(Instant::now(), pace)
+            .map_err(|e| log::error!("Replay timer failed: {:?}", e))
+            .zip(blocks)
+            .map(|(_, block)| block),
+    )
+}
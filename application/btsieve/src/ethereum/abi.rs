@@ -0,0 +1,416 @@
+use ethereum_support::{web3::types::Log, Address, H256, U256};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamKind {
+    Address,
+    Uint256,
+    Bytes32,
+}
+
+#[derive(Debug)]
+pub struct Param {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub indexed: bool,
+}
+
+/// Describes an event the way a contract ABI does: indexed parameters are
+/// read from the topics (after the event signature), all others are read as
+/// 32 byte words from the log data.
+#[derive(Debug)]
+pub struct EventAbi {
+    pub name: &'static str,
+    pub signature: &'static str,
+    pub params: &'static [Param],
+}
+
+// keccak('Transfer(address,address,uint256)')
+pub const ERC20_TRANSFER: EventAbi = EventAbi {
+    name: "Transfer",
+    signature: "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+    params: &[
+        Param {
+            name: "from",
+            kind: ParamKind::Address,
+            indexed: true,
+        },
+        Param {
+            name: "to",
+            kind: ParamKind::Address,
+            indexed: true,
+        },
+        Param {
+            name: "value",
+            kind: ParamKind::Uint256,
+            indexed: false,
+        },
+    ],
+};
+
+// ERC721 shares the signature of the ERC20 event but indexes its third
+// parameter, the token id, hence its logs have no data
+pub const ERC721_TRANSFER: EventAbi = EventAbi {
+    name: "Erc721Transfer",
+    signature: "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+    params: &[
+        Param {
+            name: "from",
+            kind: ParamKind::Address,
+            indexed: true,
+        },
+        Param {
+            name: "to",
+            kind: ParamKind::Address,
+            indexed: true,
+        },
+        Param {
+            name: "token_id",
+            kind: ParamKind::Uint256,
+            indexed: true,
+        },
+    ],
+};
+
+// keccak256(Redeemed()), the rfc003 HTLCs log the secret as data
+pub const RFC003_REDEEMED: EventAbi = EventAbi {
+    name: "Redeemed",
+    signature: "0xB8CAC300E37F03AD332E581DEA21B2F0B84EAAADC184A295FEF71E81F44A7413",
+    params: &[Param {
+        name: "secret",
+        kind: ParamKind::Bytes32,
+        indexed: false,
+    }],
+};
+
+// keccak256(Refunded())
+pub const RFC003_REFUNDED: EventAbi = EventAbi {
+    name: "Refunded",
+    signature: "0x5D26862916391BF49478B2F5103B0720A842B45EF145A268F2CD1FB2AED55178",
+    params: &[],
+};
+
+// keccak256(HtlcCreated(bytes32,address)), logged by the ERC20 and the CREATE2
+// HTLC factory
+pub const RFC003_HTLC_CREATED: EventAbi = EventAbi {
+    name: "HtlcCreated",
+    signature: "0x5831AB05FE6B1C890204F3FC8BB6D339735E5CCAB9F63D483FA0624738D27A94",
+    params: &[
+        Param {
+            name: "init_code_hash",
+            kind: ParamKind::Bytes32,
+            indexed: true,
+        },
+        Param {
+            name: "htlc",
+            kind: ParamKind::Address,
+            indexed: true,
+        },
+    ],
+};
+
+const SWAP_ID: Param = Param {
+    name: "swap_id",
+    kind: ParamKind::Bytes32,
+    indexed: true,
+};
+
+// keccak256(SwapFunded(bytes32,address,uint256,uint256)), logged by the Ether
+// HTLC registry like the two events below
+pub const RFC003_SWAP_FUNDED: EventAbi = EventAbi {
+    name: "SwapFunded",
+    signature: "0xC6B94D83B8A0913D9EEDEF49DB2ACAE6A84ED61902027E7BAFF6BBB1235A8368",
+    params: &[
+        SWAP_ID,
+        Param {
+            name: "redeem_address",
+            kind: ParamKind::Address,
+            indexed: false,
+        },
+        Param {
+            name: "refund_timestamp",
+            kind: ParamKind::Uint256,
+            indexed: false,
+        },
+        Param {
+            name: "value",
+            kind: ParamKind::Uint256,
+            indexed: false,
+        },
+    ],
+};
+
+// keccak256(SwapRedeemed(bytes32,bytes32))
+pub const RFC003_SWAP_REDEEMED: EventAbi = EventAbi {
+    name: "SwapRedeemed",
+    signature: "0x5998DDDC670A21D983A1C5E883050A483C96F4F95EF42A8825DDF63C29647AF3",
+    params: &[
+        SWAP_ID,
+        Param {
+            name: "secret",
+            kind: ParamKind::Bytes32,
+            indexed: false,
+        },
+    ],
+};
+
+// keccak256(SwapRefunded(bytes32))
+pub const RFC003_SWAP_REFUNDED: EventAbi = EventAbi {
+    name: "SwapRefunded",
+    signature: "0xC506B8643B74E7A2FBB400C79E929DD5DFFFB301FB07AFB3B0E8E7C47925CF5A",
+    params: &[SWAP_ID],
+};
+
+pub const KNOWN_EVENTS: &[EventAbi] = &[
+    ERC20_TRANSFER,
+    ERC721_TRANSFER,
+    RFC003_REDEEMED,
+    RFC003_REFUNDED,
+    RFC003_HTLC_CREATED,
+    RFC003_SWAP_FUNDED,
+    RFC003_SWAP_REDEEMED,
+    RFC003_SWAP_REFUNDED,
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Token {
+    Address(Address),
+    Uint(U256),
+    FixedBytes(H256),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodedLog {
+    pub address: Address,
+    pub event: &'static str,
+    #[serde(flatten)]
+    pub params: BTreeMap<&'static str, Token>,
+}
+
+impl EventAbi {
+    pub fn topic(&self) -> H256 {
+        self.signature.into()
+    }
+
+    /// Events that share a signature, like the ERC20 and the ERC721
+    /// `Transfer`, are told apart by the number of topics of their logs
+    pub fn decode(&self, log: &Log) -> Option<DecodedLog> {
+        let indexed_params = self.params.iter().filter(|param| param.indexed).count();
+
+        if log.topics.first() != Some(&self.topic()) || log.topics.len() != 1 + indexed_params {
+            return None;
+        }
+
+        let mut topics = log.topics.iter().skip(1);
+        let mut words = log.data.0.chunks(32);
+        let mut params = BTreeMap::new();
+
+        for param in self.params {
+            let word = if param.indexed {
+                topics.next().map(|topic| &topic[..])
+            } else {
+                words.next().filter(|word| word.len() == 32)
+            };
+
+            let word = match word {
+                Some(word) => word,
+                None => {
+                    log::warn!(
+                        "log of {} event is missing parameter {}: {:?}",
+                        self.name,
+                        param.name,
+                        log
+                    );
+                    return None;
+                }
+            };
+
+            params.insert(param.name, decode_word(param.kind, word));
+        }
+
+        Some(DecodedLog {
+            address: log.address,
+            event: self.name,
+            params,
+        })
+    }
+}
+
+fn decode_word(kind: ParamKind, word: &[u8]) -> Token {
+    match kind {
+        ParamKind::Address => Token::Address(Address::from_slice(&word[12..])),
+        ParamKind::Uint256 => Token::Uint(U256::from_big_endian(word)),
+        ParamKind::Bytes32 => Token::FixedBytes(H256::from_slice(word)),
+    }
+}
+
+pub fn decode_log(log: &Log) -> Option<DecodedLog> {
+    KNOWN_EVENTS.iter().find_map(|event| event.decode(log))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_support::Bytes;
+    use spectral::prelude::*;
+
+    const CONTRACT_ADDRESS: &str = "0xe46FB33e4DB653De84cB0E0E8b810A6c4cD39d59";
+
+    fn log(topics: Vec<H256>, data: Vec<u8>) -> Log {
+        Log {
+            address: CONTRACT_ADDRESS.into(),
+            topics,
+            data: Bytes(data),
+            block_hash: Some(2.into()),
+            block_number: Some(1.into()),
+            transaction_hash: Some(3.into()),
+            transaction_index: Some(0.into()),
+            log_index: Some(0.into()),
+            transaction_log_index: Some(0.into()),
+            log_type: None,
+            removed: Some(false),
+        }
+    }
+
+    #[test]
+    fn given_a_transfer_log_decodes_from_to_and_value() {
+        let from = "0x00000000000000000000000000a329c0648769a73afac7f9381e08fb43dbea72";
+        let to = "0x0000000000000000000000000A81e8be41b21f651a71aaB1A85c6813b8bBcCf8";
+        let mut value = vec![0u8; 32];
+        value[31] = 42;
+
+        let log = log(vec![ERC20_TRANSFER.topic(), from.into(), to.into()], value);
+
+        let decoded = decode_log(&log);
+
+        let decoded = assert_that(&decoded).is_some().subject;
+        assert_that(&decoded.event).is_equal_to("Transfer");
+        assert_that(&decoded.params.get("from")).is_equal_to(Some(&Token::Address(
+            "0x00a329c0648769a73afac7f9381e08fb43dbea72".into(),
+        )));
+        assert_that(&decoded.params.get("to")).is_equal_to(Some(&Token::Address(
+            "0x0A81e8be41b21f651a71aaB1A85c6813b8bBcCf8".into(),
+        )));
+        assert_that(&decoded.params.get("value")).is_equal_to(Some(&Token::Uint(42.into())));
+    }
+
+    #[test]
+    fn given_an_erc721_transfer_log_decodes_token_id() {
+        let from = "0x00000000000000000000000000a329c0648769a73afac7f9381e08fb43dbea72";
+        let to = "0x0000000000000000000000000A81e8be41b21f651a71aaB1A85c6813b8bBcCf8";
+
+        let log = log(
+            vec![ERC721_TRANSFER.topic(), from.into(), to.into(), 42.into()],
+            vec![],
+        );
+
+        let decoded = decode_log(&log);
+
+        let decoded = assert_that(&decoded).is_some().subject;
+        assert_that(&decoded.event).is_equal_to("Erc721Transfer");
+        assert_that(&decoded.params.get("to")).is_equal_to(Some(&Token::Address(
+            "0x0A81e8be41b21f651a71aaB1A85c6813b8bBcCf8".into(),
+        )));
+        assert_that(&decoded.params.get("token_id")).is_equal_to(Some(&Token::Uint(42.into())));
+    }
+
+    #[test]
+    fn erc20_transfer_does_not_decode_erc721_transfer_log_and_vice_versa() {
+        let erc721_log = log(
+            vec![ERC721_TRANSFER.topic(), 1.into(), 2.into(), 42.into()],
+            vec![],
+        );
+        let erc20_log = log(
+            vec![ERC20_TRANSFER.topic(), 1.into(), 2.into()],
+            vec![0u8; 32],
+        );
+
+        assert_that(&ERC20_TRANSFER.decode(&erc721_log)).is_none();
+        assert_that(&ERC721_TRANSFER.decode(&erc20_log)).is_none();
+    }
+
+    #[test]
+    fn given_a_redeemed_log_decodes_secret() {
+        let secret = vec![7u8; 32];
+
+        let log = log(vec![RFC003_REDEEMED.topic()], secret.clone());
+
+        let decoded = decode_log(&log);
+
+        let decoded = assert_that(&decoded).is_some().subject;
+        assert_that(&decoded.event).is_equal_to("Redeemed");
+        assert_that(&decoded.params.get("secret"))
+            .is_equal_to(Some(&Token::FixedBytes(H256::from_slice(&secret))));
+    }
+
+    #[test]
+    fn given_an_htlc_created_log_decodes_init_code_hash_and_htlc() {
+        let init_code_hash = H256::from([9u8; 32]);
+        let htlc = "0x0000000000000000000000000A81e8be41b21f651a71aaB1A85c6813b8bBcCf8";
+
+        let log = log(
+            vec![RFC003_HTLC_CREATED.topic(), init_code_hash, htlc.into()],
+            vec![],
+        );
+
+        let decoded = decode_log(&log);
+
+        let decoded = assert_that(&decoded).is_some().subject;
+        assert_that(&decoded.event).is_equal_to("HtlcCreated");
+        assert_that(&decoded.params.get("init_code_hash"))
+            .is_equal_to(Some(&Token::FixedBytes(init_code_hash)));
+        assert_that(&decoded.params.get("htlc")).is_equal_to(Some(&Token::Address(
+            "0x0A81e8be41b21f651a71aaB1A85c6813b8bBcCf8".into(),
+        )));
+    }
+
+    #[test]
+    fn given_a_swap_funded_log_decodes_swap_id_and_terms() {
+        let swap_id = H256::from([9u8; 32]);
+        let mut data = vec![0u8; 96];
+        data[12..32].copy_from_slice(&[2u8; 20]);
+        data[60..64].copy_from_slice(&2_000_000_000u32.to_be_bytes());
+        data[95] = 42;
+
+        let log = log(vec![RFC003_SWAP_FUNDED.topic(), swap_id], data);
+
+        let decoded = decode_log(&log);
+
+        let decoded = assert_that(&decoded).is_some().subject;
+        assert_that(&decoded.event).is_equal_to("SwapFunded");
+        assert_that(&decoded.params.get("swap_id")).is_equal_to(Some(&Token::FixedBytes(swap_id)));
+        assert_that(&decoded.params.get("redeem_address"))
+            .is_equal_to(Some(&Token::Address(Address::from([2u8; 20]))));
+        assert_that(&decoded.params.get("refund_timestamp"))
+            .is_equal_to(Some(&Token::Uint(2_000_000_000u32.into())));
+        assert_that(&decoded.params.get("value")).is_equal_to(Some(&Token::Uint(42.into())));
+    }
+
+    #[test]
+    fn given_a_transfer_log_without_data_does_not_decode() {
+        let log = log(vec![ERC20_TRANSFER.topic(), 1.into(), 2.into()], vec![]);
+
+        assert_that(&decode_log(&log)).is_none();
+    }
+
+    #[test]
+    fn given_an_unknown_log_does_not_decode() {
+        let log = log(vec![1.into()], vec![]);
+
+        assert_that(&decode_log(&log)).is_none();
+    }
+
+    #[test]
+    fn decoded_log_serializes_params_next_to_event() {
+        let log = log(vec![RFC003_REFUNDED.topic()], vec![]);
+
+        let decoded = serde_json::to_string(&decode_log(&log).unwrap()).unwrap();
+
+        assert_eq!(
+            decoded,
+            r#"{"address":"0xe46fb33e4db653de84cb0e0e8b810a6c4cd39d59","event":"Refunded"}"#
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    ethereum::{BlockQuery, EventQuery, TransactionQuery},
+    ethereum::{trace::Trace, BlockQuery, EventQuery, TraceQuery, TransactionQuery},
     web3::types::{Block, Transaction},
     ArcQueryRepository, QueryMatch,
 };
@@ -131,3 +131,30 @@ pub fn check_log_queries(
 
     stream::futures_ordered(result_futures).filter_map(|x| x)
 }
+
+pub fn check_trace_queries(
+    trace_queries: ArcQueryRepository<TraceQuery>,
+    traces: Vec<Trace>,
+) -> impl Iterator<Item = QueryMatch> {
+    trace_queries.all().flat_map(move |(query_id, query)| {
+        traces
+            .iter()
+            .filter(|trace| {
+                log::trace!("Matching query {:#?} against trace {:#?}", query, trace);
+                query.matches(trace)
+            })
+            // A transaction matches once, no matter how many of its traces do
+            .filter_map(|trace| trace.transaction_hash)
+            .unique()
+            .map(|transaction_id| {
+                log::trace!(
+                    "Transaction {:?} matches Query-ID: {:?}",
+                    transaction_id,
+                    query_id
+                );
+
+                QueryMatch(query_id.into(), format!("{:x}", transaction_id))
+            })
+            .collect::<Vec<_>>()
+    })
+}
@@ -0,0 +1,47 @@
+use crate::{
+    block_recording::{self, Error},
+    ethereum::queries::create_receipt_future,
+    route_factory,
+};
+use ethereum_support::{
+    web3::{transports::Http, Web3},
+    Block, Transaction, TransactionReceipt,
+};
+use futures::{
+    future::{self, Future},
+    stream::Stream,
+};
+use serde::{Deserialize, Serialize};
+use std::{path::Path, time::Duration};
+
+/// Log queries need the receipts of a block, which is why they are recorded
+/// together with it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedBlock {
+    pub block: Block<Transaction>,
+    pub receipts: Vec<TransactionReceipt>,
+}
+
+pub fn fetch_receipts(
+    client: &Web3<Http>,
+    block: Block<Transaction>,
+) -> impl Future<Item = RecordedBlock, Error = route_factory::Error> {
+    let receipt_futures = block
+        .transactions
+        .iter()
+        .map(|transaction| create_receipt_future(client, transaction.hash).map(|receipt| *receipt))
+        .collect::<Vec<_>>();
+
+    future::join_all(receipt_futures).map(move |receipts| RecordedBlock { block, receipts })
+}
+
+pub fn ethereum_block_replayer<P: AsRef<Path>>(
+    recording: P,
+    pace: Duration,
+) -> Result<Box<dyn Stream<Item = RecordedBlock, Error = ()> + Send>, Error> {
+    let blocks = block_recording::read_recording::<RecordedBlock, _>(recording)?;
+
+    log::info!("Replaying {} recorded Ethereum blocks", blocks.len());
+
+    Ok(block_recording::replay(blocks, pace))
+}
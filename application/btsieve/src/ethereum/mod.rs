@@ -1,8 +1,17 @@
+pub mod abi;
 pub mod block_processor;
+pub mod block_replayer;
 pub mod ethereum_web3_block_poller;
 pub mod queries;
+pub mod repositories;
+pub mod rescanner;
+pub mod trace;
 
 pub use self::{
-    block_processor::{check_block_queries, check_log_queries, check_transaction_queries},
-    queries::{BlockQuery, EventQuery, TransactionQuery},
+    block_processor::{
+        check_block_queries, check_log_queries, check_trace_queries, check_transaction_queries,
+    },
+    queries::{BlockQuery, EventQuery, TraceQuery, TransactionQuery},
+    repositories::QueryRepositories,
+    rescanner::Rescanner,
 };
@@ -8,9 +8,11 @@ use ethereum_support::{
     web3::{transports::Http, types::U256, Web3},
     Block, Transaction,
 };
+use futures::future::{self, Future};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
 pub struct BlockQuery {
     pub min_timestamp_secs: u64,
 }
@@ -28,7 +30,7 @@ impl QueryType for BlockQuery {
     }
 }
 
-#[derive(Deserialize, Derivative, Debug)]
+#[derive(Deserialize, Derivative, Debug, Clone, Copy)]
 #[derivative(Default)]
 #[serde(rename_all = "snake_case")]
 pub enum ReturnAs {
@@ -43,16 +45,18 @@ impl ToHttpPayload<ReturnAs> for QueryResult {
     fn to_http_payload(
         &self,
         return_as: &ReturnAs,
-        _: &Web3<Http>,
-    ) -> Result<Vec<Self::Item>, Error> {
-        Ok(self
+        _: &Arc<Web3<Http>>,
+    ) -> Box<dyn Future<Item = Vec<Self::Item>, Error = Error> + Send> {
+        let payload = self
             .0
             .iter()
             .filter_map(to_h256)
             .map(|id| match return_as {
                 ReturnAs::BlockId => PayloadKind::Id { id },
             })
-            .collect())
+            .collect();
+
+        Box::new(future::ok(payload))
     }
 }
 
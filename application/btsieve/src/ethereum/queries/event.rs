@@ -1,7 +1,10 @@
 use crate::{
-    ethereum::queries::{create_receipt_future, create_transaction_future, to_h256, PayloadKind},
+    ethereum::{
+        abi,
+        queries::{create_receipt_future, create_transaction_future, to_h256, PayloadKind},
+    },
     query_result_repository::QueryResult,
-    route_factory::{Error, QueryType, ToHttpPayload},
+    route_factory::{Error, QueryType, ToHttpPayload, MAX_CONCURRENT_PAYLOAD_REQUESTS},
 };
 use derivative::Derivative;
 use ethbloom::Input;
@@ -15,14 +18,15 @@ use ethereum_support::{
 };
 use futures::{
     future::{self, Future},
-    stream::{FuturesOrdered, Stream},
+    stream::{self, Stream},
 };
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 struct Topic(H256);
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct EventQuery {
     event_matchers: Vec<EventMatcher>,
 }
@@ -53,7 +57,7 @@ pub struct EventQuery {
 /// .. ] //Other data omitted
 /// }
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 struct EventMatcher {
     address: Option<Address>,
     data: Option<Bytes>,
@@ -115,7 +119,7 @@ impl QueryType for EventQuery {
     }
 }
 
-#[derive(Deserialize, Derivative, Debug)]
+#[derive(Deserialize, Derivative, Debug, Clone, Copy)]
 #[derivative(Default)]
 #[serde(rename_all = "snake_case")]
 pub enum ReturnAs {
@@ -124,6 +128,7 @@ pub enum ReturnAs {
     Transaction,
     Receipt,
     TransactionAndReceipt,
+    DecodedLogs,
 }
 
 impl ToHttpPayload<ReturnAs> for QueryResult {
@@ -133,25 +138,26 @@ impl ToHttpPayload<ReturnAs> for QueryResult {
     fn to_http_payload(
         &self,
         return_as: &ReturnAs,
-        client: &Web3<Http>,
-    ) -> Result<Vec<Self::Item>, Error> {
-        let to_payload = |transaction_id: H256| to_payload(client, transaction_id, return_as);
-
-        self.0
-            .iter()
-            .filter_map(to_h256)
-            .map(to_payload)
-            .collect::<FuturesOrdered<_>>()
-            .collect()
-            .wait()
+        client: &Arc<Web3<Http>>,
+    ) -> Box<dyn Future<Item = Vec<Self::Item>, Error = Error> + Send> {
+        let client = Arc::clone(client);
+        let return_as = *return_as;
+        let transaction_ids = self.0.iter().filter_map(to_h256).collect::<Vec<_>>();
+
+        Box::new(
+            stream::iter_ok(transaction_ids)
+                .map(move |transaction_id| to_payload(&client, transaction_id, return_as))
+                .buffered(MAX_CONCURRENT_PAYLOAD_REQUESTS)
+                .collect(),
+        )
     }
 }
 
 fn to_payload(
     client: &Web3<Http>,
     transaction_id: H256,
-    return_as: &ReturnAs,
-) -> Box<dyn Future<Item = PayloadKind, Error = Error>> {
+    return_as: ReturnAs,
+) -> Box<dyn Future<Item = PayloadKind, Error = Error> + Send> {
     let tx_future = create_transaction_future(client, transaction_id);
     let receipt_future = create_receipt_future(client, transaction_id);
 
@@ -169,6 +175,12 @@ fn to_payload(
                 receipt,
             },
         )),
+        ReturnAs::DecodedLogs => Box::new(tx_future.join(receipt_future).map(
+            move |(transaction, receipt)| PayloadKind::TransactionAndDecodedLogs {
+                transaction,
+                logs: receipt.logs.iter().filter_map(abi::decode_log).collect(),
+            },
+        )),
     }
 }
 
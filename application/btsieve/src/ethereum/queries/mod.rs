@@ -1,9 +1,12 @@
 pub mod block;
 pub mod event;
+pub mod trace;
 pub mod transaction;
 
-pub use self::{block::BlockQuery, event::EventQuery, transaction::TransactionQuery};
-use crate::route_factory::Error;
+pub use self::{
+    block::BlockQuery, event::EventQuery, trace::TraceQuery, transaction::TransactionQuery,
+};
+use crate::{ethereum::abi::DecodedLog, route_factory::Error};
 use ethereum_support::{
     clean_0x,
     web3::{transports::Http, Web3},
@@ -40,6 +43,10 @@ pub enum PayloadKind {
         transaction: Box<Transaction>,
         receipt: Box<TransactionReceipt>,
     },
+    TransactionAndDecodedLogs {
+        transaction: Box<Transaction>,
+        logs: Vec<DecodedLog>,
+    },
 }
 
 pub fn create_transaction_future(
@@ -0,0 +1,178 @@
+use crate::{
+    ethereum::trace::{Action, Trace},
+    route_factory::QueryType,
+};
+use ethereum_support::{Address, U256};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceKind {
+    Call,
+    Create,
+    #[serde(alias = "suicide")]
+    Selfdestruct,
+}
+
+/// Matches the internal calls, contract creations and selfdestructs of a
+/// transaction, which a `TransactionQuery` cannot see. For a selfdestruct,
+/// `from_address` is the destroyed contract and `to_address` the beneficiary
+/// of its balance. For a contract creation, `to_address` is the new contract.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+pub struct TraceQuery {
+    kind: Option<TraceKind>,
+    from_address: Option<Address>,
+    to_address: Option<Address>,
+    min_value: Option<U256>,
+}
+
+impl TraceQuery {
+    pub fn matches(&self, trace: &Trace) -> bool {
+        if trace.error.is_some() {
+            return false;
+        }
+
+        let (kind, from, to, value) = match &trace.action {
+            Action::Call(call) => (TraceKind::Call, call.from, Some(call.to), call.value),
+            Action::Create(create) => (
+                TraceKind::Create,
+                create.from,
+                trace.result.as_ref().and_then(|result| result.address),
+                create.value,
+            ),
+            Action::Suicide(suicide) => (
+                TraceKind::Selfdestruct,
+                suicide.address,
+                Some(suicide.refund_address),
+                suicide.balance,
+            ),
+            Action::Reward(_) => return false,
+        };
+
+        match self {
+            Self {
+                kind: query_kind,
+                from_address,
+                to_address,
+                min_value,
+            } => {
+                let mut result = true;
+
+                if let Some(query_kind) = query_kind {
+                    result = result && (*query_kind == kind);
+                }
+
+                if let Some(from_address) = from_address {
+                    result = result && (from == *from_address);
+                }
+
+                if let Some(to_address) = to_address {
+                    result = result && (to == Some(*to_address));
+                }
+
+                if let Some(min_value) = min_value {
+                    result = result && (value >= *min_value);
+                }
+                result
+            }
+        }
+    }
+}
+
+impl QueryType for TraceQuery {
+    fn route() -> &'static str {
+        "traces"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ethereum::trace::{Call, CallType, Suicide, TraceResult};
+    use ethereum_support::{Bytes, H256};
+    use spectral::prelude::*;
+
+    fn contract() -> Address {
+        "0bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".parse().unwrap()
+    }
+
+    fn beneficiary() -> Address {
+        "0aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap()
+    }
+
+    fn trace(action: Action) -> Trace {
+        Trace {
+            action,
+            result: Some(TraceResult { address: None }),
+            transaction_hash: Some(H256::from(123)),
+            trace_address: vec![0],
+            error: None,
+        }
+    }
+
+    fn selfdestruct() -> Trace {
+        trace(Action::Suicide(Suicide {
+            address: contract(),
+            refund_address: beneficiary(),
+            balance: U256::from(400),
+        }))
+    }
+
+    #[test]
+    fn given_selfdestruct_query_selfdestruct_to_beneficiary_matches() {
+        let query = TraceQuery {
+            kind: Some(TraceKind::Selfdestruct),
+            from_address: Some(contract()),
+            to_address: Some(beneficiary()),
+            min_value: None,
+        };
+
+        assert_that(&query.matches(&selfdestruct())).is_true();
+    }
+
+    #[test]
+    fn given_call_query_selfdestruct_doesnt_match() {
+        let query = TraceQuery {
+            kind: Some(TraceKind::Call),
+            from_address: Some(contract()),
+            to_address: None,
+            min_value: None,
+        };
+
+        assert_that(&query.matches(&selfdestruct())).is_false();
+    }
+
+    #[test]
+    fn given_min_value_query_internal_value_transfer_matches_only_above() {
+        let query = TraceQuery {
+            kind: None,
+            from_address: None,
+            to_address: Some(beneficiary()),
+            min_value: Some(U256::from(100)),
+        };
+
+        let call = |value: u64| {
+            trace(Action::Call(Call {
+                from: contract(),
+                to: beneficiary(),
+                value: U256::from(value),
+                input: Bytes::from(vec![]),
+                call_type: CallType::Call,
+            }))
+        };
+
+        assert_that(&query.matches(&call(100))).is_true();
+        assert_that(&query.matches(&call(99))).is_false();
+    }
+
+    #[test]
+    fn given_reverted_trace_nothing_matches() {
+        let query = TraceQuery::default();
+
+        let mut reverted = selfdestruct();
+        reverted.error = Some("Reverted".to_string());
+
+        assert_that(&query.matches(&selfdestruct())).is_true();
+        assert_that(&query.matches(&reverted)).is_false();
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     ethereum::queries::{create_transaction_future, to_h256, PayloadKind},
     query_result_repository::QueryResult,
-    route_factory::{Error, QueryType, ToHttpPayload},
+    route_factory::{Error, QueryType, ToHttpPayload, MAX_CONCURRENT_PAYLOAD_REQUESTS},
 };
 use derivative::Derivative;
 use ethereum_support::{
@@ -10,11 +10,12 @@ use ethereum_support::{
 };
 use futures::{
     future::{self, Future},
-    stream::{FuturesOrdered, Stream},
+    stream::{self, Stream},
 };
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
 pub struct TransactionQuery {
     from_address: Option<Address>,
     to_address: Option<Address>,
@@ -67,7 +68,7 @@ impl QueryType for TransactionQuery {
     }
 }
 
-#[derive(Deserialize, Derivative, Debug)]
+#[derive(Deserialize, Derivative, Debug, Clone, Copy)]
 #[derivative(Default)]
 #[serde(rename_all = "snake_case")]
 pub enum ReturnAs {
@@ -83,25 +84,26 @@ impl ToHttpPayload<ReturnAs> for QueryResult {
     fn to_http_payload(
         &self,
         return_as: &ReturnAs,
-        client: &Web3<Http>,
-    ) -> Result<Vec<Self::Item>, Error> {
-        let to_payload = |transaction_id: H256| to_payload(client, transaction_id, return_as);
-
-        self.0
-            .iter()
-            .filter_map(to_h256)
-            .map(to_payload)
-            .collect::<FuturesOrdered<_>>()
-            .collect()
-            .wait()
+        client: &Arc<Web3<Http>>,
+    ) -> Box<dyn Future<Item = Vec<Self::Item>, Error = Error> + Send> {
+        let client = Arc::clone(client);
+        let return_as = *return_as;
+        let transaction_ids = self.0.iter().filter_map(to_h256).collect::<Vec<_>>();
+
+        Box::new(
+            stream::iter_ok(transaction_ids)
+                .map(move |transaction_id| to_payload(&client, transaction_id, return_as))
+                .buffered(MAX_CONCURRENT_PAYLOAD_REQUESTS)
+                .collect(),
+        )
     }
 }
 
 fn to_payload(
     client: &Web3<Http>,
     transaction_id: H256,
-    return_as: &ReturnAs,
-) -> Box<dyn Future<Item = PayloadKind, Error = Error>> {
+    return_as: ReturnAs,
+) -> Box<dyn Future<Item = PayloadKind, Error = Error> + Send> {
     match return_as {
         ReturnAs::Transaction => Box::new(
             create_transaction_future(client, transaction_id)
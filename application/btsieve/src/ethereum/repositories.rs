@@ -0,0 +1,128 @@
+use crate::{
+    ethereum::{
+        check_block_queries, check_log_queries, check_trace_queries, check_transaction_queries,
+        trace::fetch_block_traces, BlockQuery, EventQuery, TraceQuery, TransactionQuery,
+    },
+    query_listing::{list_queries, ListQueries, ListedQuery},
+    web3::{
+        transports::Http,
+        types::{Block, Transaction},
+        Web3,
+    },
+    InMemoryQueryRepository, InMemoryQueryResultRepository, LastBlock, QueryMatch,
+    QueryResultRepository,
+};
+use futures::{future, Future, Stream};
+use std::sync::Arc;
+
+/// All Ethereum queries and their results. Shared between the HTTP routes and
+/// the block listener feeding new blocks into `process_block`.
+#[derive(Debug, Default)]
+pub struct QueryRepositories {
+    pub block_queries: Arc<InMemoryQueryRepository<BlockQuery>>,
+    pub transaction_queries: Arc<InMemoryQueryRepository<TransactionQuery>>,
+    pub log_queries: Arc<InMemoryQueryRepository<EventQuery>>,
+    pub trace_queries: Arc<InMemoryQueryRepository<TraceQuery>>,
+    pub block_query_results: Arc<InMemoryQueryResultRepository<BlockQuery>>,
+    pub transaction_query_results: Arc<InMemoryQueryResultRepository<TransactionQuery>>,
+    pub log_query_results: Arc<InMemoryQueryResultRepository<EventQuery>>,
+    pub trace_query_results: Arc<InMemoryQueryResultRepository<TraceQuery>>,
+    pub last_block: LastBlock,
+}
+
+impl QueryRepositories {
+    /// Block and transaction queries are matched right away. Matching log
+    /// queries requires fetching receipts, the returned future has to be
+    /// spawned for that to happen.
+    pub fn process_block(
+        &self,
+        client: Arc<Web3<Http>>,
+        block: Block<Transaction>,
+    ) -> impl Future<Item = (), Error = ()> + Send {
+        if let Some(number) = block.number {
+            self.last_block.update(number.as_u64());
+        }
+
+        check_block_queries(self.block_queries.clone(), block.clone()).for_each(
+            |QueryMatch(id, block_id)| {
+                self.block_query_results.add_result(id.0, block_id);
+            },
+        );
+
+        check_transaction_queries(self.transaction_queries.clone(), block.clone()).for_each(
+            |QueryMatch(id, transaction_id)| {
+                self.transaction_query_results
+                    .add_result(id.0, transaction_id);
+            },
+        );
+
+        let log_query_results = Arc::clone(&self.log_query_results);
+        check_log_queries(self.log_queries.clone(), client, block).for_each(
+            move |QueryMatch(id, transaction_id)| {
+                log_query_results.add_result(id.0, transaction_id);
+                Ok(())
+            },
+        )
+    }
+
+    /// Matches trace queries against the traces of the block. Only call this
+    /// if the node is a parity node with tracing enabled.
+    pub fn process_traces(
+        &self,
+        client: &Web3<Http>,
+        block: &Block<Transaction>,
+    ) -> impl Future<Item = (), Error = ()> + Send {
+        let block_number = match block.number {
+            Some(block_number) => block_number,
+            None => return future::Either::A(future::ok(())),
+        };
+
+        let trace_queries = Arc::clone(&self.trace_queries);
+        let trace_query_results = Arc::clone(&self.trace_query_results);
+
+        future::Either::B(
+            fetch_block_traces(client, block_number)
+                .map(move |traces| {
+                    check_trace_queries(trace_queries, traces).for_each(
+                        |QueryMatch(id, transaction_id)| {
+                            trace_query_results.add_result(id.0, transaction_id);
+                        },
+                    )
+                })
+                .map_err(move |e| {
+                    log::error!("Could not fetch traces of block {}: {:?}", block_number, e)
+                }),
+        )
+    }
+}
+
+impl ListQueries for QueryRepositories {
+    fn list_queries(&self, ledger_name: &'static str, network: &str) -> Vec<ListedQuery> {
+        let mut queries = list_queries(
+            &*self.block_queries,
+            &*self.block_query_results,
+            ledger_name,
+            network,
+        );
+        queries.extend(list_queries(
+            &*self.transaction_queries,
+            &*self.transaction_query_results,
+            ledger_name,
+            network,
+        ));
+        queries.extend(list_queries(
+            &*self.log_queries,
+            &*self.log_query_results,
+            ledger_name,
+            network,
+        ));
+        queries.extend(list_queries(
+            &*self.trace_queries,
+            &*self.trace_query_results,
+            ledger_name,
+            network,
+        ));
+
+        queries
+    }
+}
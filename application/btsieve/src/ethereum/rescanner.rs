@@ -0,0 +1,62 @@
+use crate::{
+    ethereum::QueryRepositories,
+    web3::{
+        transports::Http,
+        types::{BlockId, BlockNumber},
+        Web3,
+    },
+    Rescan,
+};
+use debug_stub_derive::DebugStub;
+use futures::{
+    future::{self, Future},
+    stream::{self, Stream},
+};
+use std::sync::Arc;
+
+/// Fetches the blocks to rescan from the node. Traces are not rescanned.
+#[derive(DebugStub)]
+pub struct Rescanner {
+    repositories: Arc<QueryRepositories>,
+    #[debug_stub = "Web3"]
+    client: Arc<Web3<Http>>,
+}
+
+impl Rescanner {
+    pub fn new(repositories: Arc<QueryRepositories>, client: Arc<Web3<Http>>) -> Self {
+        Self {
+            repositories,
+            client,
+        }
+    }
+}
+
+impl Rescan for Rescanner {
+    fn last_block(&self) -> Option<u64> {
+        self.repositories.last_block.get()
+    }
+
+    fn rescan(&self, from_block: u64) -> Box<dyn Future<Item = (), Error = ()> + Send> {
+        let last_block = match self.last_block() {
+            Some(last_block) => last_block,
+            None => return Box::new(future::ok(())),
+        };
+        let repositories = Arc::clone(&self.repositories);
+        let client = Arc::clone(&self.client);
+        let block_client = Arc::clone(&self.client);
+
+        Box::new(
+            stream::iter_ok(from_block..=last_block)
+                .and_then(move |number| {
+                    block_client
+                        .eth()
+                        .block_with_txs(BlockId::Number(BlockNumber::Number(number)))
+                        .map_err(move |e| {
+                            log::error!("Could not fetch block {} to rescan: {:?}", number, e)
+                        })
+                })
+                .filter_map(|block| block)
+                .for_each(move |block| repositories.process_block(Arc::clone(&client), block)),
+        )
+    }
+}
@@ -0,0 +1,187 @@
+use ethereum_support::{
+    web3::{self, transports::Http, Transport, Web3},
+    Address, Bytes, H256, U128, U256,
+};
+use futures::Future;
+use serde::Deserialize;
+
+#[derive(Debug)]
+pub enum Error {
+    Web3(web3::Error),
+    Json(serde_json::Error),
+}
+
+/// A single entry of parity's `trace_block` response. Besides top-level
+/// transactions, this includes every internal call, contract creation and
+/// selfdestruct they caused.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Trace {
+    #[serde(flatten)]
+    pub action: Action,
+    pub result: Option<TraceResult>,
+    /// `None` for block and uncle rewards
+    pub transaction_hash: Option<H256>,
+    /// Empty for the top-level call of a transaction
+    #[serde(default)]
+    pub trace_address: Vec<usize>,
+    /// Set if the action was reverted, in which case it had no effect
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "action", rename_all = "lowercase")]
+pub enum Action {
+    Call(Call),
+    Create(Create),
+    Suicide(Suicide),
+    Reward(Reward),
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CallType {
+    None,
+    Call,
+    CallCode,
+    DelegateCall,
+    StaticCall,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Call {
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    pub input: Bytes,
+    pub call_type: CallType,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Create {
+    pub from: Address,
+    pub value: U256,
+    pub init: Bytes,
+}
+
+/// Parity still calls `selfdestruct` by its old name.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Suicide {
+    pub address: Address,
+    pub refund_address: Address,
+    pub balance: U256,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Reward {
+    pub author: Address,
+    pub value: U256,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct TraceResult {
+    /// Only set for contract creations
+    pub address: Option<Address>,
+}
+
+/// Requires a parity node, other nodes do not offer the `trace` API.
+pub fn fetch_block_traces(
+    client: &Web3<Http>,
+    block_number: U128,
+) -> Box<dyn Future<Item = Vec<Trace>, Error = Error> + Send> {
+    let block_number = match serde_json::to_value(block_number) {
+        Ok(block_number) => block_number,
+        Err(e) => return Box::new(futures::future::err(Error::Json(e))),
+    };
+
+    Box::new(
+        client
+            .transport()
+            .execute("trace_block", vec![block_number])
+            .map_err(Error::Web3)
+            .and_then(|traces| serde_json::from_value(traces).map_err(Error::Json)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral::prelude::*;
+
+    #[test]
+    fn can_deserialize_parity_traces() {
+        let traces = r#"[
+          {
+            "action": {
+              "callType": "call",
+              "from": "0xa00f2cac7bad9285ecfd59e8860f5b2d8622e099",
+              "gas": "0x1a1f8",
+              "input": "0x",
+              "to": "0x0bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+              "value": "0xde0b6b3a7640000"
+            },
+            "blockHash": "0x7eb25504e4c202cf3d62fd585d3e238f592c780cca82dacb2ed3cb5b38883add",
+            "blockNumber": 3068185,
+            "result": { "gasUsed": "0x0", "output": "0x" },
+            "subtraces": 1,
+            "traceAddress": [],
+            "transactionHash": "0x07da28d752aba3b9dd7060005e554719c6205c8a3aea358599fc9b245c52f1f6",
+            "transactionPosition": 0,
+            "type": "call"
+          },
+          {
+            "action": {
+              "address": "0x0bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+              "balance": "0xde0b6b3a7640000",
+              "refundAddress": "0x0aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+            },
+            "blockHash": "0x7eb25504e4c202cf3d62fd585d3e238f592c780cca82dacb2ed3cb5b38883add",
+            "blockNumber": 3068185,
+            "result": null,
+            "subtraces": 0,
+            "traceAddress": [0],
+            "transactionHash": "0x07da28d752aba3b9dd7060005e554719c6205c8a3aea358599fc9b245c52f1f6",
+            "transactionPosition": 0,
+            "type": "suicide"
+          },
+          {
+            "action": {
+              "author": "0x0aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+              "rewardType": "block",
+              "value": "0x4563918244f40000"
+            },
+            "blockHash": "0x7eb25504e4c202cf3d62fd585d3e238f592c780cca82dacb2ed3cb5b38883add",
+            "blockNumber": 3068185,
+            "result": null,
+            "subtraces": 0,
+            "traceAddress": [],
+            "transactionHash": null,
+            "transactionPosition": null,
+            "type": "reward"
+          }
+        ]"#;
+
+        let traces = serde_json::from_str::<Vec<Trace>>(traces);
+
+        let traces = assert_that(&traces).is_ok().subject;
+        assert_that(traces).has_length(3);
+        assert_that(&traces[1]).is_equal_to(Trace {
+            action: Action::Suicide(Suicide {
+                address: "0bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".parse().unwrap(),
+                refund_address: "0aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap(),
+                balance: U256::from(1_000_000_000_000_000_000u64),
+            }),
+            result: None,
+            transaction_hash: Some(
+                "07da28d752aba3b9dd7060005e554719c6205c8a3aea358599fc9b245c52f1f6"
+                    .parse()
+                    .unwrap(),
+            ),
+            trace_address: vec![0],
+            error: None,
+        });
+    }
+}
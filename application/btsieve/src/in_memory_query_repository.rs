@@ -1,9 +1,17 @@
-use crate::query_repository::{Error, QueryRepository};
+use crate::query_repository::{Error, QueryEntry, QueryRepository, SavedQuery};
+use chrono::{DateTime, Utc};
 use std::{collections::HashMap, sync::RwLock};
 
+#[derive(Debug)]
+struct Entry<T> {
+    query: T,
+    created_at: DateTime<Utc>,
+    reference_count: u32,
+}
+
 #[derive(Debug)]
 struct State<T> {
-    storage: HashMap<u32, T>,
+    storage: HashMap<u32, Entry<T>>,
     next_index: u32,
 }
 
@@ -23,34 +31,93 @@ impl<Q> Default for InMemoryQueryRepository<Q> {
     }
 }
 
-impl<T: Send + Sync + Clone + 'static> QueryRepository<T> for InMemoryQueryRepository<T> {
+impl<T: Send + Sync + Clone + PartialEq + 'static> QueryRepository<T>
+    for InMemoryQueryRepository<T>
+{
     fn all(&self) -> Box<dyn Iterator<Item = (u32, T)>> {
         let state = self.state.read().unwrap();
 
-        Box::new(state.storage.clone().into_iter())
+        Box::new(
+            state
+                .storage
+                .iter()
+                .map(|(id, entry)| (*id, entry.query.clone()))
+                .collect::<Vec<_>>()
+                .into_iter(),
+        )
+    }
+
+    fn entries(&self) -> Vec<QueryEntry<T>> {
+        let state = self.state.read().unwrap();
+
+        state
+            .storage
+            .iter()
+            .map(|(id, entry)| QueryEntry {
+                id: *id,
+                query: entry.query.clone(),
+                created_at: entry.created_at,
+                reference_count: entry.reference_count,
+            })
+            .collect()
     }
 
     fn get(&self, id: u32) -> Option<T> {
         let state = self.state.read().unwrap();
 
-        state.storage.get(&id).cloned()
+        state.storage.get(&id).map(|entry| entry.query.clone())
     }
 
-    fn save(&self, entity: T) -> Result<u32, Error<T>> {
+    fn save(&self, entity: T) -> Result<SavedQuery, Error<T>> {
         let mut state = self.state.write().unwrap();
 
+        if let Some((id, entry)) = state
+            .storage
+            .iter_mut()
+            .find(|(_, entry)| entry.query == entity)
+        {
+            entry.reference_count += 1;
+
+            return Ok(SavedQuery {
+                id: *id,
+                reference_count: entry.reference_count,
+            });
+        }
+
         let id = state.next_index;
 
-        state.storage.insert(id, entity);
+        state.storage.insert(
+            id,
+            Entry {
+                query: entity,
+                created_at: Utc::now(),
+                reference_count: 1,
+            },
+        );
         state.next_index += 1;
 
-        Ok(id)
+        Ok(SavedQuery {
+            id,
+            reference_count: 1,
+        })
     }
 
-    fn delete(&self, id: u32) {
+    fn delete(&self, id: u32) -> u32 {
         let mut state = self.state.write().unwrap();
 
-        state.storage.remove(&id);
+        let reference_count = match state.storage.get_mut(&id) {
+            Some(entry) => {
+                entry.reference_count -= 1;
+                entry.reference_count
+            }
+            None => return 0,
+        };
+
+        if reference_count == 0 {
+            state.storage.remove(&id);
+        }
+
+        reference_count
     }
 }
 
@@ -62,14 +129,17 @@ mod tests {
     #[derive(Debug, PartialEq, Clone)]
     struct MyEntity;
 
+    #[derive(Debug, PartialEq, Clone)]
+    struct OtherEntity(u32);
+
     #[test]
     fn given_entity_when_inserted_can_be_retrieved_with_generated_id() {
         let repository = InMemoryQueryRepository::default();
 
-        let id = repository.save(MyEntity);
+        let saved = repository.save(MyEntity);
 
-        assert_that(&id).is_ok();
-        assert_that(&repository.get(id.unwrap()))
+        assert_that(&saved).is_ok();
+        assert_that(&repository.get(saved.unwrap().id))
             .is_some()
             .is_equal_to(&MyEntity);
     }
@@ -78,9 +148,57 @@ mod tests {
     fn given_entity_when_deleted_is_no_longer_there() {
         let repository = InMemoryQueryRepository::default();
 
-        let id = repository.save(MyEntity).unwrap();
+        let id = repository.save(MyEntity).unwrap().id;
         repository.delete(id);
 
         assert_that(&repository.get(id)).is_none()
     }
+
+    #[test]
+    fn given_identical_entity_when_inserted_returns_existing_id() {
+        let repository = InMemoryQueryRepository::default();
+
+        let first = repository.save(OtherEntity(1)).unwrap();
+        let second = repository.save(OtherEntity(1)).unwrap();
+        let third = repository.save(OtherEntity(2)).unwrap();
+
+        assert_that(&second).is_equal_to(SavedQuery {
+            id: first.id,
+            reference_count: 2,
+        });
+        assert_that(&third.id).is_not_equal_to(first.id);
+        assert_that(&repository.all().count()).is_equal_to(2);
+    }
+
+    #[test]
+    fn given_entity_with_two_references_when_deleted_once_is_still_there() {
+        let repository = InMemoryQueryRepository::default();
+
+        let id = repository.save(MyEntity).unwrap().id;
+        repository.save(MyEntity).unwrap();
+
+        assert_that(&repository.delete(id)).is_equal_to(1);
+        assert_that(&repository.get(id)).is_some();
+
+        assert_that(&repository.delete(id)).is_equal_to(0);
+        assert_that(&repository.get(id)).is_none();
+    }
+
+    #[test]
+    fn given_saved_entity_entries_contain_creation_time_and_reference_count() {
+        let repository = InMemoryQueryRepository::default();
+        let before = Utc::now();
+
+        let id = repository.save(OtherEntity(1)).unwrap().id;
+        repository.save(OtherEntity(1)).unwrap();
+
+        let entries = repository.entries();
+
+        assert_that(&entries).has_length(1);
+        let entry = &entries[0];
+        assert_that(&entry.id).is_equal_to(id);
+        assert_that(&entry.query).is_equal_to(OtherEntity(1));
+        assert_that(&entry.reference_count).is_equal_to(2);
+        assert_that(&(entry.created_at >= before)).is_true();
+    }
 }
@@ -19,11 +19,12 @@ impl<Q: Send + Sync + Clone + 'static> QueryResultRepository<Q>
     fn add_result(&self, id: u32, tx_id: String) {
         let mut storage = self.storage.write().unwrap();
 
-        let mut query_result = storage.remove(&id).unwrap_or_default();
+        let query_result = storage.entry(id).or_default();
 
-        query_result.0.push(tx_id);
-
-        storage.insert(id, query_result);
+        // Rescans process blocks a second time
+        if !query_result.0.contains(&tx_id) {
+            query_result.0.push(tx_id);
+        }
     }
 
     fn delete(&self, id: u32) {
@@ -66,4 +67,17 @@ mod tests {
         query_results.contains(String::from("foobar"));
         query_results.contains(String::from("baz"));
     }
+
+    #[test]
+    fn given_existing_result_does_not_add_it_again() {
+        let repository = InMemoryQueryResultRepository::<()>::default();
+
+        repository.add_result(1, String::from("foobar"));
+        repository.add_result(1, String::from("foobar"));
+
+        assert_that(&repository.get(1))
+            .is_some()
+            .map(|r| &r.0)
+            .has_length(1);
+    }
 }
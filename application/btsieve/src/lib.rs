@@ -1,21 +1,26 @@
 #![warn(rust_2018_idioms)]
 #![deny(unsafe_code)]
 
+pub mod authentication;
 pub mod bitcoin;
+pub mod block_recording;
 pub mod ethereum;
 mod in_memory_query_repository;
 mod in_memory_query_result_repository;
 pub mod load_settings;
 pub mod logging;
+mod query_listing;
 mod query_repository;
 mod query_result_repository;
+mod rescan;
 pub mod route_factory;
 mod routes;
 pub mod settings;
+mod transaction_cache;
 
 pub use crate::{
-    in_memory_query_repository::*, in_memory_query_result_repository::*, query_repository::*,
-    query_result_repository::*, route_factory::*, routes::*,
+    in_memory_query_repository::*, in_memory_query_result_repository::*, query_listing::*,
+    query_repository::*, query_result_repository::*, rescan::*, route_factory::*, routes::*,
 };
 pub use ethereum_support::web3;
 use std::{cmp::Ordering, sync::Arc};
@@ -0,0 +1,169 @@
+use crate::{
+    query_repository::{QueryEntry, QueryRepository},
+    query_result_repository::QueryResultRepository,
+    route_factory::QueryType,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A query as shown by `GET /queries/{ledger}/{network}`.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ListedQuery {
+    pub location: String,
+    #[serde(rename = "type")]
+    pub query_type: &'static str,
+    pub query: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub reference_count: u32,
+    pub match_count: usize,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, PartialEq)]
+pub struct ListQueriesParams {
+    #[serde(rename = "type")]
+    pub query_type: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+impl ListQueriesParams {
+    pub fn matches(&self, query: &ListedQuery) -> bool {
+        let type_matches = self
+            .query_type
+            .as_ref()
+            .map_or(true, |query_type| query_type == query.query_type);
+        let created_after = self
+            .created_after
+            .map_or(true, |created_after| query.created_at >= created_after);
+        let created_before = self
+            .created_before
+            .map_or(true, |created_before| query.created_at < created_before);
+
+        type_matches && created_after && created_before
+    }
+}
+
+/// Implemented by everything holding the queries of one ledger, so they can
+/// all be listed through a single endpoint.
+pub trait ListQueries: Send + Sync + 'static {
+    fn list_queries(&self, ledger_name: &'static str, network: &str) -> Vec<ListedQuery>;
+}
+
+pub fn list_queries<Q, QR, QRR>(
+    query_repository: &QR,
+    query_result_repository: &QRR,
+    ledger_name: &'static str,
+    network: &str,
+) -> Vec<ListedQuery>
+where
+    Q: QueryType + Serialize,
+    QR: QueryRepository<Q>,
+    QRR: QueryResultRepository<Q>,
+{
+    let query_type = Q::route();
+
+    query_repository
+        .entries()
+        .into_iter()
+        .map(
+            |QueryEntry {
+                 id,
+                 query,
+                 created_at,
+                 reference_count,
+             }| ListedQuery {
+                location: format!("/queries/{}/{}/{}/{}", ledger_name, network, query_type, id),
+                query_type,
+                query: serde_json::to_value(query).unwrap_or(serde_json::Value::Null),
+                created_at,
+                reference_count,
+                match_count: query_result_repository
+                    .get(id)
+                    .map_or(0, |result| result.0.len()),
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InMemoryQueryRepository, InMemoryQueryResultRepository};
+    use chrono::TimeZone;
+    use spectral::prelude::*;
+
+    fn listed_query(query_type: &'static str, created_at: DateTime<Utc>) -> ListedQuery {
+        ListedQuery {
+            location: format!("/queries/bitcoin/regtest/{}/1", query_type),
+            query_type,
+            query: serde_json::Value::Null,
+            created_at,
+            reference_count: 1,
+            match_count: 0,
+        }
+    }
+
+    #[test]
+    fn given_no_filters_every_query_matches() {
+        let params = ListQueriesParams::default();
+
+        assert_that(&params.matches(&listed_query("blocks", Utc.timestamp(1_000, 0)))).is_true();
+    }
+
+    #[test]
+    fn given_type_filter_only_queries_of_that_type_match() {
+        let params = ListQueriesParams {
+            query_type: Some("transactions".to_string()),
+            ..ListQueriesParams::default()
+        };
+
+        assert_that(&params.matches(&listed_query("transactions", Utc.timestamp(1_000, 0))))
+            .is_true();
+        assert_that(&params.matches(&listed_query("blocks", Utc.timestamp(1_000, 0)))).is_false();
+    }
+
+    #[test]
+    fn given_creation_time_filters_only_queries_within_range_match() {
+        let params = ListQueriesParams {
+            created_after: Some(Utc.timestamp(1_000, 0)),
+            created_before: Some(Utc.timestamp(2_000, 0)),
+            ..ListQueriesParams::default()
+        };
+
+        assert_that(&params.matches(&listed_query("blocks", Utc.timestamp(999, 0)))).is_false();
+        assert_that(&params.matches(&listed_query("blocks", Utc.timestamp(1_000, 0)))).is_true();
+        assert_that(&params.matches(&listed_query("blocks", Utc.timestamp(2_000, 0)))).is_false();
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    struct MyQuery(u32);
+
+    impl QueryType for MyQuery {
+        fn route() -> &'static str {
+            "things"
+        }
+    }
+
+    #[test]
+    fn given_saved_query_with_results_listing_contains_match_count() {
+        let query_repository = InMemoryQueryRepository::default();
+        let query_result_repository = InMemoryQueryResultRepository::<MyQuery>::default();
+
+        let id = query_repository.save(MyQuery(42)).unwrap().id;
+        query_result_repository.add_result(id, "0x01".to_string());
+        query_result_repository.add_result(id, "0x02".to_string());
+
+        let listed = list_queries(
+            &query_repository,
+            &query_result_repository,
+            "bitcoin",
+            "regtest",
+        );
+
+        assert_that(&listed).has_length(1);
+        assert_that(&listed[0].location)
+            .is_equal_to(format!("/queries/bitcoin/regtest/things/{}", id));
+        assert_that(&listed[0].query).is_equal_to(serde_json::json!(42));
+        assert_that(&listed[0].match_count).is_equal_to(2);
+    }
+}
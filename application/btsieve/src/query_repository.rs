@@ -1,3 +1,5 @@
+use chrono::{DateTime, Utc};
+
 #[derive(Debug)]
 pub enum Error<T> {
     FailedToStore(T),
@@ -5,9 +7,30 @@ pub enum Error<T> {
     Internal,
 }
 
+/// Saving a query identical to an existing one does not create a new query.
+/// Instead, the id of the existing one is returned and its reference count is
+/// incremented.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SavedQuery {
+    pub id: u32,
+    pub reference_count: u32,
+}
+
+/// A stored query together with the bookkeeping btsieve keeps about it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryEntry<T> {
+    pub id: u32,
+    pub query: T,
+    pub created_at: DateTime<Utc>,
+    pub reference_count: u32,
+}
+
 pub trait QueryRepository<T>: Send + Sync + 'static {
     fn all(&self) -> Box<dyn Iterator<Item = (u32, T)>>;
+    fn entries(&self) -> Vec<QueryEntry<T>>;
     fn get(&self, id: u32) -> Option<T>;
-    fn save(&self, entity: T) -> Result<u32, Error<T>>;
-    fn delete(&self, id: u32);
+    fn save(&self, entity: T) -> Result<SavedQuery, Error<T>>;
+    /// Releases one reference to the query and returns how many are left. The
+    /// query is only removed once no references are left.
+    fn delete(&self, id: u32) -> u32;
 }
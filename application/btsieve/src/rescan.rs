@@ -0,0 +1,46 @@
+use futures::Future;
+use std::sync::RwLock;
+
+/// Lets a query created with `?from_block=` match in the blocks btsieve
+/// processed before the query existed. comit_node relies on this to move a
+/// query to another btsieve instance without missing any of its matches.
+pub trait Rescan: Send + Sync + 'static {
+    fn last_block(&self) -> Option<u64>;
+    /// Processes the blocks from `from_block` up to `last_block` again.
+    /// Results are only recorded once, so queries that already saw these
+    /// blocks are not affected.
+    fn rescan(&self, from_block: u64) -> Box<dyn Future<Item = (), Error = ()> + Send>;
+}
+
+/// The height of the newest block a ledger's queries were matched against
+#[derive(Debug, Default)]
+pub struct LastBlock(RwLock<Option<u64>>);
+
+impl LastBlock {
+    pub fn get(&self) -> Option<u64> {
+        *self.0.read().unwrap()
+    }
+
+    /// Rescanned blocks are older than the last one, so they never move it
+    /// backwards.
+    pub fn update(&self, height: u64) {
+        let mut last_block = self.0.write().unwrap();
+        *last_block = Some(last_block.map_or(height, |last_block| last_block.max(height)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral::prelude::*;
+
+    #[test]
+    fn given_an_older_block_last_block_stays() {
+        let last_block = LastBlock::default();
+
+        last_block.update(42);
+        last_block.update(41);
+
+        assert_that(&last_block.get()).is_equal_to(Some(42));
+    }
+}
@@ -1,14 +1,18 @@
 use crate::{
+    authentication,
+    query_listing::{ListQueries, ListQueriesParams},
     query_repository::QueryRepository,
     query_result_repository::{QueryResult, QueryResultRepository},
+    rescan::Rescan,
     routes::{self, HttpApiProblemStdError},
     web3,
 };
 use ethereum_support::H256;
+use futures::Future;
 use routes::Error as RouteError;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{fmt::Debug, sync::Arc};
-use warp::{self, filters::BoxedFilter, Filter, Reply};
+use warp::{self, filters::BoxedFilter, Filter, Rejection, Reply};
 
 #[derive(Debug)]
 pub enum Error {
@@ -22,15 +26,18 @@ pub trait QueryType {
     fn route() -> &'static str;
 }
 
+/// Upper bound of ledger requests a single payload has in flight at once.
+pub const MAX_CONCURRENT_PAYLOAD_REQUESTS: usize = 8;
+
 pub trait ToHttpPayload<R> {
     type Client: 'static + Send + Sync;
-    type Item: Serialize + Debug;
+    type Item: Serialize + Debug + Send + 'static;
 
     fn to_http_payload(
         &self,
         return_as: &R,
-        client: &Self::Client,
-    ) -> Result<Vec<Self::Item>, Error>;
+        client: &Arc<Self::Client>,
+    ) -> Box<dyn Future<Item = Vec<Self::Item>, Error = Error> + Send>;
 }
 
 #[derive(Deserialize, Serialize, Default, Debug, Eq, PartialEq, Hash)]
@@ -39,6 +46,13 @@ pub struct QueryParams<R> {
     pub return_as: R,
 }
 
+#[derive(Deserialize, Serialize, Default, Debug, Eq, PartialEq, Hash)]
+pub struct CreateQueryParams {
+    /// Also matches the query against the blocks from this height on, see
+    /// `Rescan`
+    pub from_block: Option<u64>,
+}
+
 pub fn create_endpoints<
     R,
     Q: QueryType + DeserializeOwned + Serialize + Debug + Send + 'static,
@@ -49,6 +63,7 @@ pub fn create_endpoints<
     query_repository: Arc<QR>,
     query_result_repository: Arc<QRR>,
     client: Option<Arc<C>>,
+    rescan: Option<Arc<dyn Rescan>>,
     ledger_name: &'static str,
     registered_network: Option<&'static str>,
 ) -> BoxedFilter<(impl Reply,)>
@@ -78,43 +93,40 @@ where
         )
     });
 
-    // validate network function
-    let validate_network =
-        warp::path::param::<String>().and_then(move |network| match registered_network {
-            Some(registered_network) => {
-                if network != registered_network {
-                    log::error!("Invalid network passed: {:?}", network);
-                    Err::<String, _>(warp::reject::custom(HttpApiProblemStdError {
-                        http_api_problem: RouteError::NetworkNotFound.into(),
-                    }))
-                } else {
-                    Ok(network)
-                }
-            }
-            None => {
-                log::error!("Ledger network not defined {:?}", ledger_name);
-                Err::<String, _>(warp::reject::custom(HttpApiProblemStdError {
-                    http_api_problem: RouteError::NetworkNotFound.into(),
-                }))
-            }
-        });
-
     // concat with validators, ledger and network
     let path = path
         .and(warp::path(ledger_name))
         .and(validate_ledger)
-        .and(validate_network)
+        .and(validate_network(ledger_name, registered_network))
         .and(warp::path(&route));
 
     let query_repository = warp::any().map(move || Arc::clone(&query_repository));
     let query_result_repository = warp::any().map(move || Arc::clone(&query_result_repository));
+    let rescan = warp::any().map(move || rescan.clone());
+    let create_params = warp::query::<CreateQueryParams>()
+        .or(warp::any().map(CreateQueryParams::default))
+        .unify();
+
+    let create_batch = warp::post2()
+        .and(path.clone())
+        .and(warp::path("batch"))
+        .and(warp::path::end())
+        .and(query_repository.clone())
+        .and(warp::any().map(move || ledger_name))
+        .and(warp::any().map(move || route))
+        .and(rescan.clone())
+        .and(create_params.clone())
+        .and(authentication::json_body())
+        .and_then(routes::create_queries);
 
     let create = warp::post2()
         .and(path.clone())
         .and(query_repository.clone())
         .and(warp::any().map(move || ledger_name))
         .and(warp::any().map(move || route))
-        .and(warp::body::json())
+        .and(rescan)
+        .and(create_params)
+        .and(authentication::json_body())
         .and_then(routes::create_query);
 
     let retrieve = warp::get2()
@@ -132,9 +144,69 @@ where
         .and(warp::path::param::<u32>())
         .and_then(routes::delete_query);
 
-    create
+    create_batch
+        .or(create)
         .or(retrieve)
         .or(delete)
         .recover(routes::customize_error)
         .boxed()
 }
+
+/// Lists the queries of all types registered for a ledger, e.g.
+/// `GET /queries/bitcoin/regtest?type=transactions&created_after=...`.
+pub fn create_list_endpoint<L: ListQueries>(
+    repositories: Arc<L>,
+    ledger_name: &'static str,
+    registered_network: Option<&'static str>,
+) -> BoxedFilter<(impl Reply,)> {
+    // The network is only validated once the whole path matched, otherwise
+    // this endpoint would answer requests meant for the query endpoints.
+    let network = warp::path::param::<String>()
+        .and(warp::path::end())
+        .and_then(move |network| check_network(network, ledger_name, registered_network));
+
+    warp::get2()
+        .and(warp::path("queries"))
+        .and(warp::path(ledger_name))
+        .and(network)
+        .and(warp::any().map(move || Arc::clone(&repositories)))
+        .and(warp::any().map(move || ledger_name))
+        .and(warp::query::<ListQueriesParams>())
+        .and_then(routes::list_ledger_queries)
+        .recover(routes::customize_error)
+        .boxed()
+}
+
+fn validate_network(
+    ledger_name: &'static str,
+    registered_network: Option<&'static str>,
+) -> BoxedFilter<(String,)> {
+    warp::path::param::<String>()
+        .and_then(move |network| check_network(network, ledger_name, registered_network))
+        .boxed()
+}
+
+fn check_network(
+    network: String,
+    ledger_name: &'static str,
+    registered_network: Option<&'static str>,
+) -> Result<String, Rejection> {
+    match registered_network {
+        Some(registered_network) => {
+            if network != registered_network {
+                log::error!("Invalid network passed: {:?}", network);
+                Err(warp::reject::custom(HttpApiProblemStdError {
+                    http_api_problem: RouteError::NetworkNotFound.into(),
+                }))
+            } else {
+                Ok(network)
+            }
+        }
+        None => {
+            log::error!("Ledger network not defined {:?}", ledger_name);
+            Err(warp::reject::custom(HttpApiProblemStdError {
+                http_api_problem: RouteError::NetworkNotFound.into(),
+            }))
+        }
+    }
+}
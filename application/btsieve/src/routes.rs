@@ -1,8 +1,11 @@
 use crate::{
-    query_repository::QueryRepository,
+    query_listing::{ListQueries, ListQueriesParams},
+    query_repository::{QueryRepository, SavedQuery},
     query_result_repository::{QueryResult, QueryResultRepository},
-    route_factory::{QueryParams, ToHttpPayload},
+    rescan::Rescan,
+    route_factory::{CreateQueryParams, QueryParams, ToHttpPayload},
 };
+use futures::future::{self, Future};
 use http::StatusCode;
 use http_api_problem::HttpApiProblem;
 use serde::{Deserialize, Serialize};
@@ -20,6 +23,8 @@ pub enum Error {
     QueryNotFound,
     NetworkNotFound,
     LedgerNotConnected,
+    Unauthorized,
+    MalformedBody(String),
 }
 
 #[derive(Debug)]
@@ -60,6 +65,14 @@ impl From<Error> for HttpApiProblem {
                 HttpApiProblem::with_title_and_type_from_status(StatusCode::SERVICE_UNAVAILABLE)
                     .set_detail("The requested ledger is not connected.")
             }
+            Unauthorized => {
+                HttpApiProblem::with_title_and_type_from_status(StatusCode::UNAUTHORIZED)
+                    .set_detail("Missing or invalid credentials.")
+            }
+            MalformedBody(reason) => {
+                HttpApiProblem::with_title_and_type_from_status(StatusCode::BAD_REQUEST)
+                    .set_detail(format!("The body of the request is malformed: {}", reason))
+            }
         }
     }
 }
@@ -83,26 +96,103 @@ pub fn create_query<Q: Send, QR: QueryRepository<Q>, C: 'static + Send + Sync>(
     query_repository: Arc<QR>,
     ledger_name: &'static str,
     query_type: &'static str,
+    rescan: Option<Arc<dyn Rescan>>,
+    params: CreateQueryParams,
     query: Q,
 ) -> Result<impl Reply, Rejection> {
-    let result = query_repository.save(query);
+    let last_block = rescan.as_ref().and_then(|rescan| rescan.last_block());
+    let saved = query_repository
+        .save(query)
+        .map_err(|_| query_save_rejection())?;
+    start_rescan(rescan, params);
+
+    let response = CreateQueryResponse::new(saved, &network, ledger_name, query_type, last_block);
+
+    // A query identical to an existing one is not created again
+    let status = if response.reference_count == 1 {
+        warp::http::StatusCode::CREATED
+    } else {
+        warp::http::StatusCode::OK
+    };
+    let location = response.location.clone();
+    let reply = warp::reply::with_status(warp::reply::json(&response), status);
+
+    Ok(warp::reply::with_header(reply, "Location", location))
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn create_queries<Q: Send, QR: QueryRepository<Q>, C: 'static + Send + Sync>(
+    _client: Arc<C>,
+    network: String,
+    query_repository: Arc<QR>,
+    ledger_name: &'static str,
+    query_type: &'static str,
+    rescan: Option<Arc<dyn Rescan>>,
+    params: CreateQueryParams,
+    queries: Vec<Q>,
+) -> Result<impl Reply, Rejection> {
+    let last_block = rescan.as_ref().and_then(|rescan| rescan.last_block());
+    let saved = save_queries(&*query_repository, queries)?;
+    start_rescan(rescan, params);
 
-    match result {
-        Ok(id) => {
-            let uri = format!("/queries/{}/{}/{}/{}", ledger_name, network, query_type, id);
-            let reply = warp::reply::with_status(warp::reply(), warp::http::StatusCode::CREATED);
-            Ok(warp::reply::with_header(reply, "Location", uri))
+    // The reference count of each query tells which of them already existed
+    let status = if saved.iter().all(|saved| saved.reference_count == 1) {
+        warp::http::StatusCode::CREATED
+    } else {
+        warp::http::StatusCode::OK
+    };
+    let responses = saved
+        .into_iter()
+        .map(|saved| CreateQueryResponse::new(saved, &network, ledger_name, query_type, last_block))
+        .collect::<Vec<_>>();
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&responses),
+        status,
+    ))
+}
+
+/// Saves either all queries or none of them. If one of them fails, the
+/// references taken on the queries saved before it are released again.
+fn save_queries<Q, QR: QueryRepository<Q>>(
+    query_repository: &QR,
+    queries: Vec<Q>,
+) -> Result<Vec<SavedQuery>, Rejection> {
+    let mut saved = Vec::with_capacity(queries.len());
+
+    for query in queries {
+        match query_repository.save(query) {
+            Ok(saved_query) => saved.push(saved_query),
+            Err(_) => {
+                for SavedQuery { id, .. } in saved {
+                    query_repository.delete(id);
+                }
+
+                return Err(query_save_rejection());
+            }
         }
-        Err(_) => Err(warp::reject::custom(HttpApiProblemStdError {
-            http_api_problem: Error::QuerySave.into(),
-        })),
+    }
+
+    Ok(saved)
+}
+
+fn query_save_rejection() -> Rejection {
+    warp::reject::custom(HttpApiProblemStdError {
+        http_api_problem: Error::QuerySave.into(),
+    })
+}
+
+fn start_rescan(rescan: Option<Arc<dyn Rescan>>, params: CreateQueryParams) {
+    if let (Some(rescan), Some(from_block)) = (rescan, params.from_block) {
+        log::info!("Rescanning blocks from {}", from_block);
+        tokio::spawn(rescan.rescan(from_block));
     }
 }
 
 #[allow(clippy::needless_pass_by_value)]
 pub fn retrieve_query<
-    R: Debug + Default,
-    Q: Serialize + Send + Debug,
+    R: Debug + Default + Send + 'static,
+    Q: Serialize + Send + Debug + 'static,
     QR: QueryRepository<Q>,
     QRR: QueryResultRepository<Q>,
     C: 'static + Send + Sync,
@@ -113,36 +203,40 @@ pub fn retrieve_query<
     query_result_repository: Arc<QRR>,
     id: u32,
     query_params: QueryParams<R>,
-) -> Result<impl Reply, Rejection>
+) -> Box<dyn Future<Item = warp::reply::Json, Error = Rejection> + Send>
 where
     for<'de> R: Deserialize<'de>,
     QueryResult: ToHttpPayload<R, Client = C>,
 {
-    query_repository
-        .get(id)
-        .ok_or(Error::QueryNotFound)
-        .and_then(|query| {
-            query_result_repository
-                .get(id)
-                .unwrap_or_default()
-                .to_http_payload(&query_params.return_as, &client)
-                .map(|matches| RetrieveQueryResponse { query, matches })
-                .map(|response| warp::reply::json(&response))
-                .map_err(|e| {
-                    log::error!(
-                        "failed to transform result for query {} to payload {:?}: {:?}.",
-                        id,
-                        query_params.return_as,
-                        e
-                    );
-                    Error::TransformToPayload
+    let query = match query_repository.get(id) {
+        Some(query) => query,
+        None => {
+            return Box::new(future::err(warp::reject::custom(HttpApiProblemStdError {
+                http_api_problem: Error::QueryNotFound.into(),
+            })))
+        }
+    };
+
+    let return_as = query_params.return_as;
+
+    Box::new(
+        query_result_repository
+            .get(id)
+            .unwrap_or_default()
+            .to_http_payload(&return_as, &client)
+            .map(|matches| warp::reply::json(&RetrieveQueryResponse { query, matches }))
+            .map_err(move |e| {
+                log::error!(
+                    "failed to transform result for query {} to payload {:?}: {:?}.",
+                    id,
+                    return_as,
+                    e
+                );
+                warp::reject::custom(HttpApiProblemStdError {
+                    http_api_problem: Error::TransformToPayload.into(),
                 })
-        })
-        .map_err(|e| {
-            warp::reject::custom(HttpApiProblemStdError {
-                http_api_problem: e.into(),
-            })
-        })
+            }),
+    )
 }
 
 #[allow(clippy::needless_pass_by_value)]
@@ -158,8 +252,9 @@ pub fn delete_query<
     query_result_repository: Arc<QRR>,
     id: u32,
 ) -> Result<impl Reply, Rejection> {
-    query_repository.delete(id);
-    query_result_repository.delete(id);
+    if query_repository.delete(id) == 0 {
+        query_result_repository.delete(id);
+    }
 
     Ok(warp::reply::with_status(
         warp::reply(),
@@ -167,8 +262,138 @@ pub fn delete_query<
     ))
 }
 
+#[allow(clippy::needless_pass_by_value)]
+pub fn list_ledger_queries<L: ListQueries>(
+    network: String,
+    repositories: Arc<L>,
+    ledger_name: &'static str,
+    params: ListQueriesParams,
+) -> Result<impl Reply, Rejection> {
+    let mut queries = repositories
+        .list_queries(ledger_name, &network)
+        .into_iter()
+        .filter(|query| params.matches(query))
+        .collect::<Vec<_>>();
+    queries.sort_by(|a, b| {
+        a.created_at
+            .cmp(&b.created_at)
+            .then_with(|| a.location.cmp(&b.location))
+    });
+
+    Ok(warp::reply::json(&queries))
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CreateQueryResponse {
+    location: String,
+    reference_count: u32,
+    /// The block to pass as `from_block` when creating this query on another
+    /// instance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_block: Option<u64>,
+}
+
+impl CreateQueryResponse {
+    /// The last block has to be read before the query is saved. Clients pass
+    /// it back as `from_block` and the block processed while saving must not
+    /// be skipped.
+    fn new(
+        saved: SavedQuery,
+        network: &str,
+        ledger_name: &'static str,
+        query_type: &'static str,
+        last_block: Option<u64>,
+    ) -> Self {
+        Self {
+            location: format!(
+                "/queries/{}/{}/{}/{}",
+                ledger_name, network, query_type, saved.id
+            ),
+            reference_count: saved.reference_count,
+            last_block,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Clone, Default)]
 pub struct RetrieveQueryResponse<Q, T> {
     query: Q,
     matches: T,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{query_repository, InMemoryQueryRepository, QueryEntry};
+    use spectral::prelude::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum TestQuery {
+        Storable(u32),
+        Unstorable,
+    }
+
+    #[derive(Debug, Default)]
+    struct RefusingRepository(InMemoryQueryRepository<TestQuery>);
+
+    impl QueryRepository<TestQuery> for RefusingRepository {
+        fn all(&self) -> Box<dyn Iterator<Item = (u32, TestQuery)>> {
+            self.0.all()
+        }
+
+        fn entries(&self) -> Vec<QueryEntry<TestQuery>> {
+            self.0.entries()
+        }
+
+        fn get(&self, id: u32) -> Option<TestQuery> {
+            self.0.get(id)
+        }
+
+        fn save(
+            &self,
+            entity: TestQuery,
+        ) -> Result<SavedQuery, query_repository::Error<TestQuery>> {
+            match entity {
+                TestQuery::Unstorable => Err(query_repository::Error::FailedToStore(entity)),
+                entity => self.0.save(entity),
+            }
+        }
+
+        fn delete(&self, id: u32) -> u32 {
+            self.0.delete(id)
+        }
+    }
+
+    #[test]
+    fn given_a_query_of_a_batch_fails_to_save_the_others_are_released() {
+        let repository = RefusingRepository::default();
+        repository.save(TestQuery::Storable(1)).unwrap();
+
+        let saved = save_queries(
+            &repository,
+            vec![
+                TestQuery::Storable(1),
+                TestQuery::Storable(2),
+                TestQuery::Unstorable,
+            ],
+        );
+
+        assert_that(&saved).is_err();
+        let entries = repository.entries();
+        assert_that(&entries).has_length(1);
+        assert_that(&entries[0].query).is_equal_to(TestQuery::Storable(1));
+        assert_that(&entries[0].reference_count).is_equal_to(1);
+    }
+
+    #[test]
+    fn given_a_batch_of_storable_queries_all_of_them_are_saved() {
+        let repository = RefusingRepository::default();
+
+        let saved = save_queries(
+            &repository,
+            vec![TestQuery::Storable(1), TestQuery::Storable(2)],
+        );
+
+        assert_that(&saved.map(|saved| saved.len())).is_ok_containing(2);
+    }
+}
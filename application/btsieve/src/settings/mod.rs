@@ -1,10 +1,18 @@
 mod serde_duration;
 mod serde_log;
 
+use crate::bitcoin::Checkpoint;
 use config::{Config, ConfigError, File};
+use derivative::Derivative;
+use ethereum_support::ChainId;
 use log::LevelFilter;
 use serde::Deserialize;
-use std::{ffi::OsStr, net::IpAddr, path::Path, time::Duration};
+use std::{
+    ffi::OsStr,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
@@ -12,6 +20,8 @@ pub struct Settings {
     pub log_level: LevelFilter,
     pub http_api: HttpApi,
     pub bitcoin: Option<Bitcoin>,
+    /// A litecoind speaks the same RPC and ZMQ interface as bitcoind
+    pub litecoin: Option<Bitcoin>,
     pub ethereum: Option<Ethereum>,
 }
 
@@ -23,6 +33,26 @@ fn default_log() -> LevelFilter {
 pub struct HttpApi {
     pub address_bind: IpAddr,
     pub port_bind: u16,
+    pub authentication: Option<Authentication>,
+}
+
+/// Credentials every request to the `/queries` routes has to present in its
+/// `Authorization` header.
+#[derive(Derivative, Deserialize, Clone, PartialEq)]
+#[derivative(Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Authentication {
+    /// `Authorization: Bearer <token>`
+    Bearer {
+        #[derivative(Debug = "ignore")]
+        token: String,
+    },
+    /// `Authorization: HMAC <timestamp>:<signature>`, see the
+    /// `btsieve_auth` crate for how the signature is computed.
+    Hmac {
+        #[derivative(Debug = "ignore")]
+        secret: String,
+    },
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -32,6 +62,13 @@ pub struct Bitcoin {
     pub node_url: url::Url,
     pub node_username: String,
     pub node_password: String,
+    pub record_blocks_to: Option<PathBuf>,
+    /// Feeds a recording into the queries instead of the blocks bitcoind
+    /// announces over ZMQ.
+    pub replay_blocks: Option<Replay>,
+    /// Where to start verifying headers instead of the first block bitcoind
+    /// announces. Ignored for Litecoin, whose headers are not verified.
+    pub checkpoint: Option<Checkpoint>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -40,6 +77,35 @@ pub struct Ethereum {
     pub node_url: url::Url,
     #[serde(with = "serde_duration")]
     pub poll_interval_secs: Duration,
+    pub record_blocks_to: Option<PathBuf>,
+    /// Feeds a recording into the queries instead of polling the node for
+    /// new blocks.
+    pub replay_blocks: Option<Replay>,
+    /// Enables trace queries, which match internal calls and selfdestructs
+    /// using `trace_block`. Requires a parity node started with
+    /// `--tracing on`.
+    #[serde(default)]
+    pub trace_blocks: bool,
+    /// Serves an EVM chain that is not one of the well-known networks under
+    /// `/queries/ethereum/<name>`. The node has to report the same chain id.
+    pub chain: Option<EvmChain>,
+}
+
+/// A file written through `record_blocks_to`. The node is still needed to
+/// answer the RPC calls of the routes.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Replay {
+    pub from: PathBuf,
+    /// Time between two replayed blocks, all of them are replayed at once if
+    /// omitted.
+    #[serde(with = "serde_duration", default)]
+    pub pace_secs: Duration,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct EvmChain {
+    pub id: ChainId,
+    pub name: String,
 }
 
 impl Settings {
@@ -59,6 +125,7 @@ impl Settings {
 mod tests {
 
     use super::*;
+    use bitcoin_support::{BlockId, FromHex};
     use spectral::prelude::*;
 
     #[test]
@@ -75,6 +142,8 @@ mod tests {
         let settings = settings?;
         assert_that(&settings.ethereum.is_some()).is_true();
         assert_that(&settings.bitcoin.is_some()).is_false();
+        assert_that(&settings.ethereum.map(|ethereum| ethereum.trace_blocks))
+            .is_equal_to(Some(false));
 
         Ok(())
     }
@@ -90,6 +159,60 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn can_read_config_with_bitcoin_checkpoint() -> Result<(), failure::Error> {
+        let settings = Settings::read("./config/bitcoin_only.toml");
+
+        let settings = settings?;
+        assert_that(&settings.bitcoin.and_then(|bitcoin| bitcoin.checkpoint)).is_equal_to(Some(
+            Checkpoint {
+                hash: BlockId::from_hex(
+                    "0f9188f13cb7b2c71f2a335e3a4fc328bf5beb436012afca590b1a11466e2206",
+                )
+                .unwrap(),
+                height: 0,
+            },
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn can_read_config_with_block_replays() -> Result<(), failure::Error> {
+        let settings = Settings::read("./config/replay.toml");
+
+        let settings = settings?;
+        assert_that(&settings.bitcoin.and_then(|bitcoin| bitcoin.replay_blocks)).is_equal_to(Some(
+            Replay {
+                from: PathBuf::from("./recordings/bitcoin.jsonl"),
+                pace_secs: Duration::from_secs(0),
+            },
+        ));
+        assert_that(
+            &settings
+                .ethereum
+                .and_then(|ethereum| ethereum.replay_blocks),
+        )
+        .is_equal_to(Some(Replay {
+            from: PathBuf::from("./recordings/ethereum.jsonl"),
+            pace_secs: Duration::from_secs(5),
+        }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn can_read_config_with_litecoin() -> Result<(), failure::Error> {
+        let settings = Settings::read("./config/litecoin.toml");
+
+        let settings = settings?;
+        assert_that(&settings.bitcoin.is_some()).is_true();
+        assert_that(&settings.litecoin.map(|litecoin| litecoin.zmq_endpoint))
+            .is_equal_to(Some("tcp://127.0.0.1:28333".to_string()));
+
+        Ok(())
+    }
+
     #[test]
     fn can_deserialize_log_level() -> Result<(), failure::Error> {
         let settings = Settings::read("./config/btsieve.toml");
@@ -101,4 +224,51 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn can_read_config_with_hmac_authentication() -> Result<(), failure::Error> {
+        let settings = Settings::read("./config/hmac_authentication.toml");
+
+        let settings = settings?;
+        assert_that(&settings.http_api.authentication).is_equal_to(Some(Authentication::Hmac {
+            secret: "7bf76dfc5cde5e4b7bdd2ad14ae8b2f0".to_string(),
+        }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn can_read_config_with_trace_blocks_enabled() -> Result<(), failure::Error> {
+        let settings = Settings::read("./config/ethereum_traces.toml");
+
+        let settings = settings?;
+        assert_that(&settings.ethereum.map(|ethereum| ethereum.trace_blocks))
+            .is_equal_to(Some(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn can_read_config_with_custom_evm_chain() -> Result<(), failure::Error> {
+        let settings = Settings::read("./config/evm_chain.toml");
+
+        let settings = settings?;
+        assert_that(&settings.ethereum.and_then(|ethereum| ethereum.chain)).is_equal_to(Some(
+            EvmChain {
+                id: ChainId::new(1337),
+                name: "devchain".to_string(),
+            },
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn default_config_has_no_authentication() -> Result<(), failure::Error> {
+        let settings = Settings::read("./config/btsieve.toml");
+
+        let settings = settings?;
+        assert_that(&settings.http_api.authentication).is_none();
+
+        Ok(())
+    }
 }
@@ -0,0 +1,87 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    sync::Mutex,
+};
+
+#[derive(Debug)]
+struct State<K, V> {
+    entries: HashMap<K, V>,
+    insertion_order: VecDeque<K>,
+}
+
+/// Keeps the last `capacity` inserted entries. Once full, the oldest entry is
+/// evicted. Transactions never change for a given id so there is no need to
+/// invalidate entries.
+#[derive(Debug)]
+pub struct TransactionCache<K, V> {
+    capacity: usize,
+    state: Mutex<State<K, V>>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TransactionCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(State {
+                entries: HashMap::with_capacity(capacity),
+                insertion_order: VecDeque::with_capacity(capacity),
+            }),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let state = self.state.lock().unwrap();
+
+        state.entries.get(key).cloned()
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+
+        if state.entries.insert(key.clone(), value).is_some() {
+            return;
+        }
+
+        state.insertion_order.push_back(key);
+
+        if state.insertion_order.len() > self.capacity {
+            if let Some(oldest) = state.insertion_order.pop_front() {
+                state.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral::prelude::*;
+
+    #[test]
+    fn given_inserted_entry_can_be_retrieved() {
+        let cache = TransactionCache::new(2);
+
+        cache.insert(1, "foo");
+
+        assert_that(&cache.get(&1)).is_some().is_equal_to(&"foo");
+        assert_that(&cache.get(&2)).is_none();
+    }
+
+    #[test]
+    fn given_full_cache_evicts_oldest_entry() {
+        let cache = TransactionCache::new(2);
+
+        cache.insert(1, "foo");
+        cache.insert(2, "bar");
+        cache.insert(3, "baz");
+
+        assert_that(&cache.get(&1)).is_none();
+        assert_that(&cache.get(&2)).is_some().is_equal_to(&"bar");
+        assert_that(&cache.get(&3)).is_some().is_equal_to(&"baz");
+    }
+}
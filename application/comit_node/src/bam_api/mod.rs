@@ -3,23 +3,50 @@ use crate::{
     comit_client::SwapDeclineReason,
     swap_protocols::{
         asset::AssetKind,
-        ledger::{Bitcoin, Ethereum, LedgerKind},
+        ledger::{Bitcoin, Ethereum, HtlcKind, LedgerKind, Lightning, Litecoin},
+        rfc003::HashFunction,
         SwapProtocol,
     },
 };
 use bam::json::Header;
-use ethereum_support::Erc20Token;
+use ethereum_support::{ChainId, Erc20Token, Erc721Token, Network};
 use std::fmt;
 
 fn fail_serialize_unknown<D: fmt::Debug>(unknown: D) -> serde_json::Error {
     serde::de::Error::custom(format!("serialization of {:?} is undefined.", unknown))
 }
 
+/// Peers that predate arbitrary EVM chains only send the name of a
+/// well-known network, hence it is still sent along with the chain id of the
+/// well-known networks.
+fn take_ethereum_chain_id(header: &mut Header) -> Result<ChainId, serde_json::Error> {
+    match header.take_parameter::<Option<ChainId>>("chain_id")? {
+        Some(chain_id) => Ok(chain_id),
+        None => header
+            .take_parameter::<Network>("network")?
+            .chain_id()
+            .ok_or_else(|| serde::de::Error::custom("unknown ethereum network")),
+    }
+}
+
 impl FromBamHeader for LedgerKind {
     fn from_bam_header(mut header: Header) -> Result<Self, serde_json::Error> {
         Ok(match header.value::<String>()?.as_str() {
-            "bitcoin" => LedgerKind::Bitcoin(Bitcoin::new(header.take_parameter("network")?)),
-            "ethereum" => LedgerKind::Ethereum(Ethereum::new(header.take_parameter("network")?)),
+            "bitcoin" => LedgerKind::Bitcoin(Bitcoin {
+                network: header.take_parameter("network")?,
+                htlc: header
+                    .take_parameter::<Option<HtlcKind>>("htlc")?
+                    .unwrap_or_default(),
+                refund_after_blocks: header.take_parameter("refund_after_blocks")?,
+            }),
+            "ethereum" => LedgerKind::Ethereum(Ethereum {
+                chain_id: take_ethereum_chain_id(&mut header)?,
+                erc20_htlc_factory: header.take_parameter("erc20_htlc_factory")?,
+                ether_htlc_registry: header.take_parameter("ether_htlc_registry")?,
+                create2_htlc_factory: header.take_parameter("create2_htlc_factory")?,
+            }),
+            "litecoin" => LedgerKind::Litecoin(Litecoin::new(header.take_parameter("network")?)),
+            "lightning" => LedgerKind::Lightning(Lightning::new(header.take_parameter("network")?)),
             other => LedgerKind::Unknown(other.to_string()),
         })
     }
@@ -29,10 +56,44 @@ impl ToBamHeader for LedgerKind {
     fn to_bam_header(&self) -> Result<Header, serde_json::Error> {
         Ok(match self {
             LedgerKind::Bitcoin(bitcoin) => {
-                Header::with_str_value("bitcoin").with_parameter("network", bitcoin.network)?
+                let mut header =
+                    Header::with_str_value("bitcoin").with_parameter("network", bitcoin.network)?;
+
+                if bitcoin.htlc != HtlcKind::default() {
+                    header = header.with_parameter("htlc", bitcoin.htlc)?
+                }
+                if let Some(blocks) = bitcoin.refund_after_blocks {
+                    header = header.with_parameter("refund_after_blocks", blocks)?
+                }
+
+                header
             }
             LedgerKind::Ethereum(ethereum) => {
-                Header::with_str_value("ethereum").with_parameter("network", ethereum.network)?
+                let mut header = Header::with_str_value("ethereum")
+                    .with_parameter("chain_id", ethereum.chain_id)?;
+
+                // See `take_ethereum_chain_id`
+                match Network::from(ethereum.chain_id) {
+                    Network::Unknown => {}
+                    network => header = header.with_parameter("network", network)?,
+                }
+                if let Some(factory) = ethereum.erc20_htlc_factory {
+                    header = header.with_parameter("erc20_htlc_factory", factory)?
+                }
+                if let Some(registry) = ethereum.ether_htlc_registry {
+                    header = header.with_parameter("ether_htlc_registry", registry)?
+                }
+                if let Some(factory) = ethereum.create2_htlc_factory {
+                    header = header.with_parameter("create2_htlc_factory", factory)?
+                }
+
+                header
+            }
+            LedgerKind::Litecoin(litecoin) => {
+                Header::with_str_value("litecoin").with_parameter("network", litecoin.network)?
+            }
+            LedgerKind::Lightning(lightning) => {
+                Header::with_str_value("lightning").with_parameter("network", lightning.network)?
             }
             unknown @ LedgerKind::Unknown(_) => return Err(fail_serialize_unknown(unknown)),
         })
@@ -57,15 +118,32 @@ impl ToBamHeader for SwapProtocol {
     }
 }
 
+impl FromBamHeader for HashFunction {
+    fn from_bam_header(header: Header) -> Result<Self, serde_json::Error> {
+        header.value()
+    }
+}
+
+impl ToBamHeader for HashFunction {
+    fn to_bam_header(&self) -> Result<Header, serde_json::Error> {
+        Header::with_value(self)
+    }
+}
+
 impl FromBamHeader for AssetKind {
     fn from_bam_header(mut header: Header) -> Result<Self, serde_json::Error> {
         Ok(match header.value::<String>()?.as_str() {
             "bitcoin" => AssetKind::Bitcoin(header.take_parameter("quantity")?),
             "ether" => AssetKind::Ether(header.take_parameter("quantity")?),
+            "litecoin" => AssetKind::Litecoin(header.take_parameter("quantity")?),
             "erc20" => AssetKind::Erc20(Erc20Token::new(
                 header.take_parameter("address")?,
                 header.take_parameter("quantity")?,
             )),
+            "erc721" => AssetKind::Erc721(Erc721Token::new(
+                header.take_parameter("address")?,
+                header.take_parameter("token_id")?,
+            )),
             other => AssetKind::Unknown(other.to_string()),
         })
     }
@@ -83,6 +161,12 @@ impl ToBamHeader for AssetKind {
             AssetKind::Erc20(erc20) => Header::with_str_value("erc20")
                 .with_parameter("address", erc20.token_contract)?
                 .with_parameter("quantity", erc20.quantity)?,
+            AssetKind::Erc721(erc721) => Header::with_str_value("erc721")
+                .with_parameter("address", erc721.token_contract)?
+                .with_parameter("token_id", erc721.token_id)?,
+            AssetKind::Litecoin(litecoin) => {
+                Header::with_str_value("litecoin").with_parameter("quantity", litecoin)?
+            }
             unknown @ AssetKind::Unknown(_) => return Err(fail_serialize_unknown(unknown)),
         })
     }
@@ -109,14 +193,20 @@ impl ToBamHeader for SwapDeclineReason {
 #[cfg(test)]
 mod tests {
 
-    use ethereum_support::{Address, Erc20Quantity, Erc20Token, U256};
+    use ethereum_support::{Address, ChainId, Erc20Quantity, Erc20Token, Erc721Token, U256};
 
     use crate::{
-        bam_ext::ToBamHeader,
-        swap_protocols::{asset::AssetKind, LedgerKind},
+        bam_ext::{FromBamHeader, Offer, ToBamHeader},
+        swap_protocols::{
+            asset::AssetKind,
+            ledger::{Bitcoin, Ethereum, HtlcKind},
+            rfc003::HashFunction,
+            LedgerKind, SwapProtocol,
+        },
     };
     use bam::json::Header;
     use spectral::prelude::*;
+    use std::fmt;
 
     #[test]
     fn erc20_quantity_to_bam_header() -> Result<(), serde_json::Error> {
@@ -136,6 +226,190 @@ mod tests {
         Ok(())
     }
 
+    fn assert_roundtrips<T>(value: T, expected: Header) -> Result<(), serde_json::Error>
+    where
+        T: ToBamHeader + FromBamHeader + PartialEq + fmt::Debug,
+    {
+        let header = value.to_bam_header()?;
+
+        assert_eq!(header, expected);
+        assert_eq!(T::from_bam_header(header)?, value);
+
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrips_through_bam_header() -> Result<(), serde_json::Error> {
+        assert_roundtrips(
+            AssetKind::from(Erc721Token::new(Address::zero(), U256::from(42))),
+            Header::with_str_value("erc721")
+                .with_parameter("address", "0x0000000000000000000000000000000000000000")?
+                .with_parameter("token_id", "0x2a")?,
+        )?;
+        assert_roundtrips(
+            LedgerKind::from(Ethereum::new(ChainId::new(1337))),
+            Header::with_str_value("ethereum").with_parameter("chain_id", 1337)?,
+        )?;
+        assert_roundtrips(
+            LedgerKind::from(Ethereum {
+                erc20_htlc_factory: Some(Address::from([1u8; 20])),
+                ..Ethereum::new(ChainId::new(1337))
+            }),
+            Header::with_str_value("ethereum")
+                .with_parameter("chain_id", 1337)?
+                .with_parameter(
+                    "erc20_htlc_factory",
+                    "0x0101010101010101010101010101010101010101",
+                )?,
+        )?;
+        assert_roundtrips(
+            LedgerKind::from(Ethereum {
+                ether_htlc_registry: Some(Address::from([2u8; 20])),
+                ..Ethereum::new(ChainId::new(1337))
+            }),
+            Header::with_str_value("ethereum")
+                .with_parameter("chain_id", 1337)?
+                .with_parameter(
+                    "ether_htlc_registry",
+                    "0x0202020202020202020202020202020202020202",
+                )?,
+        )?;
+        assert_roundtrips(
+            LedgerKind::from(Ethereum {
+                create2_htlc_factory: Some(Address::from([3u8; 20])),
+                ..Ethereum::new(ChainId::new(1337))
+            }),
+            Header::with_str_value("ethereum")
+                .with_parameter("chain_id", 1337)?
+                .with_parameter(
+                    "create2_htlc_factory",
+                    "0x0303030303030303030303030303030303030303",
+                )?,
+        )?;
+        assert_roundtrips(
+            LedgerKind::from(Bitcoin {
+                refund_after_blocks: Some(144),
+                ..Bitcoin::new(bitcoin_support::Network::Regtest)
+            }),
+            Header::with_str_value("bitcoin")
+                .with_parameter("network", "regtest")?
+                .with_parameter("refund_after_blocks", 144)?,
+        )?;
+        assert_roundtrips(
+            LedgerKind::from(Bitcoin {
+                network: bitcoin_support::Network::Regtest,
+                htlc: HtlcKind::Taproot,
+                refund_after_blocks: None,
+            }),
+            Header::with_str_value("bitcoin")
+                .with_parameter("network", "regtest")?
+                .with_parameter("htlc", "taproot")?,
+        )?;
+        assert_roundtrips(
+            HashFunction::Keccak256,
+            Header::with_str_value("KECCAK-256"),
+        )?;
+        assert_roundtrips(
+            vec![SwapProtocol::Rfc003],
+            Header::with_value(vec!["COMIT-RFC-003"])?,
+        )
+    }
+
+    #[test]
+    fn unknown_protocols_in_list_are_kept() -> Result<(), serde_json::Error> {
+        assert_eq!(
+            Vec::<SwapProtocol>::from_bam_header(Header::with_value(vec![
+                "COMIT-RFC-004",
+                "COMIT-RFC-003"
+            ])?)?,
+            vec![
+                SwapProtocol::Unknown("COMIT-RFC-004".to_string()),
+                SwapProtocol::Rfc003
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn well_known_ethereum_network_is_sent_alongside_chain_id() -> Result<(), serde_json::Error> {
+        let header = LedgerKind::from(Ethereum::new(ChainId::REGTEST)).to_bam_header()?;
+
+        assert_eq!(
+            header,
+            Header::with_str_value("ethereum")
+                .with_parameter("chain_id", 17)?
+                .with_parameter("network", "regtest")?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn ethereum_ledger_can_be_read_from_legacy_network_parameter() -> Result<(), serde_json::Error>
+    {
+        let header = Header::with_str_value("ethereum").with_parameter("network", "regtest")?;
+
+        match LedgerKind::from_bam_header(header)? {
+            LedgerKind::Ethereum(ethereum) => {
+                assert_that(&ethereum).is_equal_to(Ethereum::new(ChainId::REGTEST))
+            }
+            other => panic!("expected ethereum ledger, got {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_hash_function_does_not_deserialize() {
+        let header = Header::with_str_value("BLAKE2B-256");
+
+        assert_that(&HashFunction::from_bam_header(header)).is_err();
+    }
+
+    #[test]
+    fn offer_without_fallbacks_is_a_plain_header() -> Result<(), serde_json::Error> {
+        let offer = Offer::of(&[SwapProtocol::Rfc003]).expect("one protocol is offered");
+        let header = offer.to_bam_header()?;
+
+        assert_eq!(header, Header::with_str_value("COMIT-RFC-003"));
+        assert_eq!(Offer::<SwapProtocol>::from_bam_header(header)?, offer);
+
+        Ok(())
+    }
+
+    #[test]
+    fn offer_of_preferences_sends_the_others_as_fallbacks() -> Result<(), serde_json::Error> {
+        let header = Header::with_str_value("COMIT-RFC-004")
+            .with_parameter("fallbacks", vec!["COMIT-RFC-003"])?;
+
+        assert_eq!(
+            Offer::of(&[
+                SwapProtocol::Unknown("COMIT-RFC-004".to_string()),
+                SwapProtocol::Rfc003
+            ]),
+            Some(Offer::<SwapProtocol>::from_bam_header(header)?)
+        );
+        assert_eq!(Offer::<SwapProtocol>::of(&[]), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn negotiation_picks_most_preferred_supported_protocol() -> Result<(), serde_json::Error> {
+        let header = Header::with_str_value("COMIT-RFC-004")
+            .with_parameter("fallbacks", vec!["COMIT-RFC-003"])?;
+        let offer = Offer::<SwapProtocol>::from_bam_header(header)?;
+
+        assert_eq!(
+            offer.negotiate(&[SwapProtocol::Rfc003]),
+            Some(&SwapProtocol::Rfc003)
+        );
+        assert_eq!(offer.negotiate(&[]), None);
+
+        Ok(())
+    }
+
     #[test]
     fn serializing_unknown_ledgerkind_doesnt_panic() {
         let ledger_kind = LedgerKind::Unknown("USD".to_string());
@@ -10,3 +10,92 @@ where
 pub trait ToBamHeader {
     fn to_bam_header(&self) -> Result<Header, serde_json::Error>;
 }
+
+impl<T: FromBamHeader> FromBamHeader for Vec<T> {
+    fn from_bam_header(header: Header) -> Result<Self, serde_json::Error> {
+        header
+            .value::<Vec<serde_json::Value>>()?
+            .into_iter()
+            .map(|value| T::from_bam_header(Header::with_json_value(value)))
+            .collect()
+    }
+}
+
+impl<T: ToBamHeader> ToBamHeader for Vec<T> {
+    fn to_bam_header(&self) -> Result<Header, serde_json::Error> {
+        let values = self
+            .iter()
+            .map(|item| item.to_bam_header()?.value::<serde_json::Value>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Header::with_value(values)
+    }
+}
+
+/// The values a node accepts for a negotiable header, most preferred first.
+///
+/// The most preferred value is sent as the header value and the others in its
+/// `fallbacks` parameter. Nodes that don't negotiate simply see the header
+/// value, which keeps an offer without fallbacks identical to a plain header.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Offer<T> {
+    preferred: T,
+    fallbacks: Vec<T>,
+}
+
+impl<T> Offer<T> {
+    /// Offers `preferences`, most preferred first. `None` if there is
+    /// nothing to offer.
+    pub fn of(preferences: &[T]) -> Option<Self>
+    where
+        T: Clone,
+    {
+        let (preferred, fallbacks) = preferences.split_first()?;
+
+        Some(Offer {
+            preferred: preferred.clone(),
+            fallbacks: fallbacks.to_vec(),
+        })
+    }
+
+    /// Picks the most preferred offered value that is also in `supported`
+    pub fn negotiate(&self, supported: &[T]) -> Option<&T>
+    where
+        T: PartialEq,
+    {
+        std::iter::once(&self.preferred)
+            .chain(self.fallbacks.iter())
+            .find(|offered| supported.contains(offered))
+    }
+}
+
+impl<T: FromBamHeader> FromBamHeader for Offer<T> {
+    fn from_bam_header(mut header: Header) -> Result<Self, serde_json::Error> {
+        let fallbacks = match header.take_parameter::<Option<serde_json::Value>>("fallbacks")? {
+            Some(fallbacks) => Vec::<T>::from_bam_header(Header::with_json_value(fallbacks))?,
+            None => Vec::new(),
+        };
+
+        Ok(Offer {
+            preferred: T::from_bam_header(header)?,
+            fallbacks,
+        })
+    }
+}
+
+impl<T: ToBamHeader> ToBamHeader for Offer<T> {
+    fn to_bam_header(&self) -> Result<Header, serde_json::Error> {
+        let header = self.preferred.to_bam_header()?;
+
+        if self.fallbacks.is_empty() {
+            return Ok(header);
+        }
+
+        header.with_parameter(
+            "fallbacks",
+            self.fallbacks
+                .to_bam_header()?
+                .value::<serde_json::Value>()?,
+        )
+    }
+}
@@ -1,11 +1,15 @@
 #![warn(unused_extern_crates, missing_debug_implementations, rust_2018_idioms)]
 #![deny(unsafe_code)]
 
+use bitcoin_support::Chain;
+#[cfg(feature = "embedded-btsieve")]
+use comit_node::btsieve::EmbeddedBtsieve;
 use comit_node::{
-    btsieve::BtsieveHttpClient,
+    btsieve::{BtsieveHttpClient, FailoverBtsieve},
     comit_client::Client,
     comit_i_routes,
     http_api::route_factory,
+    lnd::LndHttpClient,
     load_settings::{load_settings, Opt},
     logging,
     network::{self, BamPeers},
@@ -15,9 +19,10 @@ use comit_node::{
         self,
         metadata_store::MetadataStore,
         rfc003::state_store::{InMemoryStateStore, StateStore},
-        InMemoryMetadataStore, SwapId,
+        InMemoryMetadataStore, LedgerEventDependencies, SwapId,
     },
 };
+use ethereum_support::web3::transports::EventLoopHandle;
 use futures::{stream, Future, Stream};
 use libp2p::{
     identity::{self, ed25519},
@@ -28,6 +33,7 @@ use std::{
     sync::{Arc, Mutex},
 };
 use structopt::StructOpt;
+use url::Url;
 
 fn main() -> Result<(), failure::Error> {
     let opt = Opt::from_args();
@@ -41,10 +47,18 @@ fn main() -> Result<(), failure::Error> {
 
     let metadata_store = Arc::new(InMemoryMetadataStore::default());
     let state_store = Arc::new(InMemoryStateStore::default());
-    let btsieve_client = create_btsieve_api_client(&settings);
+    let (ledger_events, _event_loop) = create_ledger_events(&settings, &mut runtime)?;
+    let ledger_events = match &settings.lightning {
+        Some(lnd) => ledger_events.with_lightning(LndHttpClient::new(
+            lnd.url.clone(),
+            lnd.macaroon.clone(),
+            lnd.poll_interval_secs,
+        )),
+        None => ledger_events,
+    };
 
     let bob_protocol_dependencies = swap_protocols::bob::ProtocolDependencies {
-        ledger_events: btsieve_client.clone().into(),
+        ledger_events: ledger_events.clone(),
         metadata_store: Arc::clone(&metadata_store),
         state_store: Arc::clone(&state_store),
         seed: settings.comit.secret_seed,
@@ -55,7 +69,11 @@ fn main() -> Result<(), failure::Error> {
     log::info!("Starting with peer_id: {}", local_peer_id);
 
     let transport = libp2p::build_development_transport(local_key_pair);
-    let behaviour = network::Behaviour::new(bob_protocol_dependencies, runtime.executor())?;
+    let behaviour = network::Behaviour::new(
+        bob_protocol_dependencies,
+        settings.rfc003.clone(),
+        runtime.executor(),
+    )?;
 
     let mut swarm = Swarm::new(transport, behaviour, local_peer_id.clone());
 
@@ -66,7 +84,7 @@ fn main() -> Result<(), failure::Error> {
     let swarm = Arc::new(Mutex::new(swarm));
 
     let alice_protocol_dependencies = swap_protocols::alice::ProtocolDependencies {
-        ledger_events: btsieve_client.into(),
+        ledger_events,
         metadata_store: Arc::clone(&metadata_store),
         state_store: Arc::clone(&state_store),
         seed: settings.comit.secret_seed,
@@ -104,9 +122,80 @@ fn derive_key_pair(secret_seed: &Seed) -> identity::Keypair {
     identity::Keypair::Ed25519(key.into())
 }
 
-fn create_btsieve_api_client(settings: &ComitNodeSettings) -> BtsieveHttpClient {
+#[cfg(not(feature = "embedded-btsieve"))]
+#[derive(Debug, failure::Fail)]
+enum Error {
+    #[fail(display = "btsieve can only be embedded if built with the embedded-btsieve feature")]
+    EmbeddedBtsieveNotAvailable,
+}
+
+#[cfg(feature = "embedded-btsieve")]
+fn create_ledger_events(
+    settings: &ComitNodeSettings,
+    runtime: &mut tokio::runtime::Runtime,
+) -> Result<(LedgerEventDependencies, Option<EventLoopHandle>), failure::Error> {
+    match &settings.btsieve.embedded {
+        Some(embedded) => {
+            log::info!("Starting embedded btsieve");
+            let (btsieve, event_loop) =
+                EmbeddedBtsieve::start(&settings.btsieve, embedded, runtime)?;
+            // The embedded btsieve does not watch Litecoin
+            let litecoin = create_litecoin_api_client(settings, &settings.btsieve.url);
+
+            Ok((LedgerEventDependencies::new(btsieve, litecoin), event_loop))
+        }
+        None => Ok((create_remote_ledger_events(settings), None)),
+    }
+}
+
+#[cfg(not(feature = "embedded-btsieve"))]
+fn create_ledger_events(
+    settings: &ComitNodeSettings,
+    _runtime: &mut tokio::runtime::Runtime,
+) -> Result<(LedgerEventDependencies, Option<EventLoopHandle>), failure::Error> {
+    match &settings.btsieve.embedded {
+        Some(_) => Err(Error::EmbeddedBtsieveNotAvailable.into()),
+        None => Ok((create_remote_ledger_events(settings), None)),
+    }
+}
+
+fn create_remote_ledger_events(settings: &ComitNodeSettings) -> LedgerEventDependencies {
+    let btsieve = &settings.btsieve;
+
+    if btsieve.fallback_urls.is_empty() {
+        return LedgerEventDependencies::new(
+            create_btsieve_api_client(settings, &btsieve.url),
+            create_litecoin_api_client(settings, &btsieve.url),
+        );
+    }
+
+    let urls = || std::iter::once(&btsieve.url).chain(btsieve.fallback_urls.iter());
+
+    let instances = urls()
+        .map(|url| create_btsieve_api_client(settings, url))
+        .collect();
+    let litecoin_instances = urls()
+        .map(|url| create_litecoin_api_client(settings, url))
+        .collect();
+
+    LedgerEventDependencies::new(
+        FailoverBtsieve::new(
+            instances,
+            btsieve.bitcoin.poll_interval_secs,
+            btsieve.ethereum.poll_interval_secs,
+        ),
+        FailoverBtsieve::new(
+            litecoin_instances,
+            btsieve.litecoin.poll_interval_secs,
+            btsieve.ethereum.poll_interval_secs,
+        ),
+    )
+}
+
+fn create_btsieve_api_client(settings: &ComitNodeSettings, url: &Url) -> BtsieveHttpClient {
     BtsieveHttpClient::new(
-        &settings.btsieve.url,
+        url,
+        settings.btsieve.authentication.clone(),
         settings.btsieve.bitcoin.poll_interval_secs,
         settings.btsieve.bitcoin.network.as_str(),
         settings.btsieve.ethereum.poll_interval_secs,
@@ -114,6 +203,14 @@ fn create_btsieve_api_client(settings: &ComitNodeSettings) -> BtsieveHttpClient
     )
 }
 
+fn create_litecoin_api_client(settings: &ComitNodeSettings, url: &Url) -> BtsieveHttpClient {
+    create_btsieve_api_client(settings, url).with_utxo_ledger(
+        Chain::Litecoin,
+        settings.btsieve.litecoin.network.as_str(),
+        settings.btsieve.litecoin.poll_interval_secs,
+    )
+}
+
 fn spawn_warp_instance<T: MetadataStore<SwapId>, S: StateStore, C: Client, BP: BamPeers>(
     settings: &ComitNodeSettings,
     metadata_store: Arc<T>,
@@ -2,15 +2,16 @@ use crate::{
     btsieve::{Error, Query, QueryId},
     swap_protocols::ledger::Bitcoin,
 };
-use bitcoin_support::{Address, OutPoint, Transaction, TransactionId};
+use bitcoin_support::{ChainAddress, OutPoint, Script, Transaction, TransactionId};
 use futures::Future;
 use serde::Serialize;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Serialize, Eq, Hash, PartialEq)]
 #[serde(untagged)]
 pub enum BitcoinQuery {
     Transaction {
-        to_address: Option<bitcoin_support::Address>,
+        to_address: Option<ChainAddress>,
         from_outpoint: Option<bitcoin_support::OutPoint>,
         unlock_script: Option<Vec<Vec<u8>>>,
     },
@@ -20,7 +21,7 @@ pub enum BitcoinQuery {
 }
 
 impl BitcoinQuery {
-    pub fn deploy_htlc(address: Address) -> Self {
+    pub fn deploy_htlc(address: ChainAddress) -> Self {
         BitcoinQuery::Transaction {
             to_address: Some(address),
             from_outpoint: None,
@@ -43,6 +44,16 @@ impl BitcoinQuery {
             unlock_script: Some(vec![vec![1u8]]),
         }
     }
+
+    /// Taproot HTLCs have no branches to tell redeem and refund apart by,
+    /// but each spend reveals the leaf it uses.
+    pub fn spend_htlc_through_leaf(htlc_location: OutPoint, leaf: &Script) -> Self {
+        BitcoinQuery::Transaction {
+            to_address: None,
+            from_outpoint: Some(htlc_location),
+            unlock_script: Some(vec![leaf.clone().into_bytes()]),
+        }
+    }
 }
 
 impl Query for BitcoinQuery {}
@@ -52,6 +63,11 @@ pub trait QueryBitcoin {
         &self,
         query: BitcoinQuery,
     ) -> Box<dyn Future<Item = QueryId<Bitcoin>, Error = Error> + Send>;
+    /// Creates all queries with as few requests to btsieve as possible.
+    fn create_batch(
+        &self,
+        queries: Vec<BitcoinQuery>,
+    ) -> Box<dyn Future<Item = Vec<QueryId<Bitcoin>>, Error = Error> + Send>;
 
     fn delete(&self, query: &QueryId<Bitcoin>) -> Box<dyn Future<Item = (), Error = Error> + Send>;
     fn txid_results(
@@ -66,18 +82,37 @@ pub trait QueryBitcoin {
         &self,
         query: &QueryId<Bitcoin>,
     ) -> Box<dyn Future<Item = Transaction, Error = Error> + Send>;
+    /// Resolves with the number of blocks a block query matched as soon as
+    /// `is_enough` accepts it.
+    fn block_count_reaching(
+        &self,
+        query: &QueryId<Bitcoin>,
+        is_enough: Arc<dyn Fn(usize) -> bool + Send + Sync>,
+    ) -> Box<dyn Future<Item = usize, Error = Error> + Send>;
+}
+
+/// Block queries match the hashes of blocks, which are no transaction ids
+/// but come in the same shape.
+pub(crate) fn count_if_enough(
+    block_hashes: &[TransactionId],
+    is_enough: &dyn Fn(usize) -> bool,
+) -> Vec<usize> {
+    Some(block_hashes.len())
+        .filter(|count| is_enough(*count))
+        .into_iter()
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use bitcoin_support::{Address, FromHex, OutPoint, Sha256dHash};
+    use bitcoin_support::{FromHex, OutPoint, Sha256dHash};
     use std::str::FromStr;
 
     #[test]
     fn given_a_bitcoin_transaction_query_with_toaddress_it_serializes_ok() {
         let to_address =
-            Some(Address::from_str("bcrt1qcqslz7lfn34dl096t5uwurff9spen5h4v2pmap").unwrap());
+            Some(ChainAddress::from_str("bcrt1qcqslz7lfn34dl096t5uwurff9spen5h4v2pmap").unwrap());
         let from_outpoint = None;
         let unlock_script = None;
         let query = BitcoinQuery::Transaction {
@@ -92,6 +127,22 @@ mod tests {
         )
     }
 
+    #[test]
+    fn given_a_litecoin_address_it_serializes_with_litecoin_encoding() {
+        let query = BitcoinQuery::Transaction {
+            to_address: Some(
+                ChainAddress::from_str("rltc1qcqslz7lfn34dl096t5uwurff9spen5h4j8mj2l").unwrap(),
+            ),
+            from_outpoint: None,
+            unlock_script: None,
+        };
+        let query = serde_json::to_string(&query).unwrap();
+        assert_eq!(
+            query,
+            r#"{"to_address":"rltc1qcqslz7lfn34dl096t5uwurff9spen5h4j8mj2l","from_outpoint":null,"unlock_script":null}"#
+        )
+    }
+
     #[test]
     fn given_an_empty_bitcoin_transaction_query_it_serializes_ok() {
         let to_address = None;
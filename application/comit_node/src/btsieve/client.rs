@@ -1,22 +1,32 @@
 use crate::{
     btsieve::{
-        bitcoin::{BitcoinQuery, QueryBitcoin},
+        bitcoin::{count_if_enough, BitcoinQuery, QueryBitcoin},
         ethereum::{EthereumQuery, QueryEthereum},
         poll_until_item::poll_until_item,
         Error, Query, QueryId,
     },
+    settings::BtsieveAuthentication,
     swap_protocols::ledger::{Bitcoin, Ethereum, Ledger},
 };
+use bitcoin_support::Chain;
+use btsieve_auth::{content_hash, hmac_authorization, CONTENT_SHA256_HEADER};
+use chrono::Utc;
 use core::time::Duration;
-use futures::{stream::Stream, Async};
-use reqwest::{header::LOCATION, r#async::Client, StatusCode, Url};
-use serde::Deserialize;
+use futures::{future, stream::Stream, Async};
+use reqwest::{
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    r#async::{Client, RequestBuilder},
+    Method, StatusCode, Url,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use tokio::prelude::future::Future;
 
 #[derive(Debug, Clone)]
 pub struct BtsieveHttpClient {
     client: Client,
     endpoint: Url,
+    authentication: Option<BtsieveAuthentication>,
     create_bitcoin_transaction_query_endpoint: Url,
     create_bitcoin_block_query_endpoint: Url,
     create_ethereum_transaction_query_endpoint: Url,
@@ -26,10 +36,18 @@ pub struct BtsieveHttpClient {
     bitcoin_poll_interval: Duration,
 }
 
-mod payloads {
+pub(super) mod payloads {
 
     use serde::Deserialize;
 
+    #[derive(Debug, Deserialize)]
+    pub struct CreatedQuery {
+        pub location: String,
+        pub reference_count: u32,
+        #[serde(default)]
+        pub last_block: Option<u64>,
+    }
+
     #[derive(Debug, Deserialize)]
     pub struct TransactionId<T> {
         pub id: T,
@@ -45,6 +63,20 @@ mod payloads {
         pub transaction: T,
         pub receipt: R,
     }
+
+    #[derive(Debug, Deserialize)]
+    pub struct TransactionAndDecodedLogs<T, L> {
+        pub transaction: T,
+        pub logs: Vec<L>,
+    }
+}
+
+/// A query btsieve created, see `BtsieveHttpClient::create_bitcoin_query_from`
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreatedQuery<L: Ledger> {
+    pub id: QueryId<L>,
+    /// The block btsieve had processed last when the query was created
+    pub last_block: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -55,6 +87,7 @@ struct QueryResponse<T> {
 impl BtsieveHttpClient {
     pub fn new(
         endpoint: &Url,
+        authentication: Option<BtsieveAuthentication>,
         ethereum_poll_interval: Duration,
         ethereum_network: &str,
         bitcoin_poll_interval: Duration,
@@ -63,6 +96,7 @@ impl BtsieveHttpClient {
         Self {
             client: Client::new(),
             endpoint: endpoint.clone(),
+            authentication,
             create_bitcoin_transaction_query_endpoint: endpoint
                 .join(format!("queries/bitcoin/{}/transactions", bitcoin_network).as_ref())
                 .expect("invalid url"),
@@ -83,24 +117,107 @@ impl BtsieveHttpClient {
         }
     }
 
+    /// Points the Bitcoin queries of this client at `chain`, which btsieve
+    /// serves with the same query engine as Bitcoin.
+    pub fn with_utxo_ledger(self, chain: Chain, network: &str, poll_interval: Duration) -> Self {
+        let create_transaction_query_endpoint = self
+            .endpoint
+            .join(format!("queries/{}/{}/transactions", chain.name(), network).as_ref())
+            .expect("invalid url");
+        let create_block_query_endpoint = self
+            .endpoint
+            .join(format!("queries/{}/{}/blocks", chain.name(), network).as_ref())
+            .expect("invalid url");
+
+        Self {
+            create_bitcoin_transaction_query_endpoint: create_transaction_query_endpoint,
+            create_bitcoin_block_query_endpoint: create_block_query_endpoint,
+            bitcoin_poll_interval: poll_interval,
+            ..self
+        }
+    }
+
+    /// Every request to btsieve has to go through here or `post_json` so that
+    /// it carries the configured credential.
+    fn request(&self, method: Method, url: Url) -> RequestBuilder {
+        self.signed_request(method, url, Vec::new())
+    }
+
+    fn post_json<T: Serialize>(&self, url: Url, body: &T) -> RequestBuilder {
+        let body = serde_json::to_vec(body).expect("queries can always be serialized to JSON");
+
+        self.signed_request(Method::POST, url, body)
+            .header(CONTENT_TYPE, "application/json")
+    }
+
+    fn signed_request(&self, method: Method, url: Url, body: Vec<u8>) -> RequestBuilder {
+        let content_hash = content_hash(&body);
+        let authorization = self.authentication.as_ref().map(|authentication| {
+            authorization(
+                authentication,
+                &method,
+                &url,
+                &content_hash,
+                Utc::now().timestamp(),
+            )
+        });
+        let mut request = self.client.request(method, url);
+
+        if let Some(authorization) = authorization {
+            request = request
+                .header(AUTHORIZATION, authorization)
+                .header(CONTENT_SHA256_HEADER, content_hash);
+        }
+
+        if body.is_empty() {
+            request
+        } else {
+            request.body(body)
+        }
+    }
+
+    fn bitcoin_query_endpoint(&self, query: &BitcoinQuery) -> Url {
+        match query {
+            BitcoinQuery::Transaction { .. } => {
+                self.create_bitcoin_transaction_query_endpoint.clone()
+            }
+            BitcoinQuery::Block { .. } => self.create_bitcoin_block_query_endpoint.clone(),
+        }
+    }
+
+    fn ethereum_query_endpoint(&self, query: &EthereumQuery) -> Url {
+        match query {
+            EthereumQuery::Transaction { .. } => {
+                self.create_ethereum_transaction_query_endpoint.clone()
+            }
+            EthereumQuery::Block { .. } => self.create_ethereum_block_query_endpoint.clone(),
+            EthereumQuery::Event { .. } => self.create_ethereum_event_query_endpoint.clone(),
+        }
+    }
+
     fn _create<L: Ledger, Q: Query>(
         &self,
         create_endpoint: Url,
         query: Q,
-    ) -> Box<dyn Future<Item = QueryId<L>, Error = Error> + Send> {
+        from_block: Option<u64>,
+    ) -> Box<dyn Future<Item = CreatedQuery<L>, Error = Error> + Send> {
         log::debug!("Creating {:?} at {}", query, create_endpoint);
 
+        let mut create_endpoint = create_endpoint;
+        if let Some(from_block) = from_block {
+            create_endpoint.set_query(Some(&format!("from_block={}", from_block)));
+        }
+
         let endpoint = self.endpoint.clone();
-        let query_id = self
-            .client
-            .post(create_endpoint)
-            .json(&query)
+        let created_query = self
+            .post_json(create_endpoint, &query)
             .send()
             .map_err(move |e| {
                 Error::FailedRequest(format!("Failed to create {:?} because {:?}", query, e))
             })
-            .and_then(move |response| {
-                if response.status() != StatusCode::CREATED {
+            .and_then(move |mut response| {
+                // btsieve answers with 200 if an identical query already exists
+                if response.status() != StatusCode::CREATED && response.status() != StatusCode::OK {
                     if let Ok(Async::Ready(bytes)) = response.into_body().concat2().poll() {
                         log::error!(
                             "Failed to create query. btsieve returned: {}",
@@ -109,42 +226,82 @@ impl BtsieveHttpClient {
                         );
                     }
 
-                    return Err(Error::MalformedResponse(
+                    return future::Either::A(future::err(Error::MalformedResponse(
                         "Could not create query".to_string(),
-                    ));
+                    )));
                 }
 
-                response
-                    .headers()
-                    .get(LOCATION)
-                    .ok_or_else(|| {
-                        Error::MalformedResponse(
-                            "Location header was not present in response".to_string(),
-                        )
-                    })
-                    .and_then(|value| {
-                        value.to_str().map_err(|e| {
-                            Error::MalformedResponse(format!(
-                                "Unable to extract Location from response: {:?}",
-                                e
-                            ))
-                        })
-                    })
-                    .and_then(|location| {
-                        endpoint.join(location).map_err(|e| {
-                            Error::MalformedResponse(format!(
-                                "Failed to parse {} as URL: {:?}",
-                                location, e
-                            ))
-                        })
-                    })
+                future::Either::B(response.json::<payloads::CreatedQuery>().map_err(|e| {
+                    Error::MalformedResponse(format!(
+                        "Unable to deserialize created query: {:?}",
+                        e
+                    ))
+                }))
+            })
+            .and_then(move |created_query| {
+                let id = endpoint.join(&created_query.location).map_err(|e| {
+                    Error::MalformedResponse(format!(
+                        "Failed to parse {} as URL: {:?}",
+                        created_query.location, e
+                    ))
+                })?;
+
+                Ok(CreatedQuery {
+                    id: QueryId::new(id),
+                    last_block: created_query.last_block,
+                })
+            })
+            .inspect(|created_query| {
+                log::info!(
+                    "Created new query at location {}",
+                    created_query.id.as_ref()
+                );
+            });
+
+        Box::new(created_query)
+    }
+
+    fn _create_batch<L: Ledger, Q: Query>(
+        &self,
+        create_endpoint: Url,
+        queries: Vec<Q>,
+    ) -> Box<dyn Future<Item = Vec<QueryId<L>>, Error = Error> + Send> {
+        log::debug!("Creating {:?} at {}", queries, create_endpoint);
+
+        let endpoint = self.endpoint.clone();
+        let mut batch_endpoint = create_endpoint;
+        if let Ok(mut segments) = batch_endpoint.path_segments_mut() {
+            segments.push("batch");
+        }
+
+        let query_ids = self
+            .post_json(batch_endpoint, &queries)
+            .send()
+            .and_then(|mut response| response.json::<Vec<payloads::CreatedQuery>>())
+            .map_err(move |e| {
+                Error::FailedRequest(format!("Failed to create {:?} because {:?}", queries, e))
             })
-            .inspect(|query_id| {
-                log::info!("Created new query at location {}", query_id);
+            .and_then(move |created_queries| {
+                created_queries
+                    .into_iter()
+                    .map(|created_query| {
+                        endpoint
+                            .join(&created_query.location)
+                            .map(QueryId::new)
+                            .map_err(|e| {
+                                Error::MalformedResponse(format!(
+                                    "Failed to parse {} as URL: {:?}",
+                                    created_query.location, e
+                                ))
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
             })
-            .map(QueryId::new);
+            .inspect(|query_ids| {
+                log::info!("Created new queries at locations {:?}", query_ids);
+            });
 
-        Box::new(query_id)
+        Box::new(query_ids)
     }
 
     pub fn fetch_ids<L: Ledger>(
@@ -153,8 +310,7 @@ impl BtsieveHttpClient {
     ) -> Box<dyn Future<Item = Vec<L::TxId>, Error = Error> + Send> {
         let url = query.as_ref().clone();
         let transactions = self
-            .client
-            .get(url.clone())
+            .request(Method::GET, url.clone())
             .send()
             .and_then(|mut response| {
                 response.json::<QueryResponse<payloads::TransactionId<L::TxId>>>()
@@ -184,8 +340,7 @@ impl BtsieveHttpClient {
         url.set_query(Some("return_as=transaction"));
 
         let transactions = self
-            .client
-            .get(url.clone())
+            .request(Method::GET, url.clone())
             .send()
             .and_then(|mut response| {
                 response.json::<QueryResponse<payloads::Transaction<L::Transaction>>>()
@@ -207,13 +362,37 @@ impl BtsieveHttpClient {
         Box::new(transactions)
     }
 
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// Creates `query` and has btsieve match it against the blocks from
+    /// `from_block` on as well, even if they were processed before.
+    pub fn create_bitcoin_query_from(
+        &self,
+        query: BitcoinQuery,
+        from_block: Option<u64>,
+    ) -> Box<dyn Future<Item = CreatedQuery<Bitcoin>, Error = Error> + Send> {
+        let endpoint = self.bitcoin_query_endpoint(&query);
+        self._create(endpoint, query, from_block)
+    }
+
+    /// See `create_bitcoin_query_from`
+    pub fn create_ethereum_query_from(
+        &self,
+        query: EthereumQuery,
+        from_block: Option<u64>,
+    ) -> Box<dyn Future<Item = CreatedQuery<Ethereum>, Error = Error> + Send> {
+        let endpoint = self.ethereum_query_endpoint(&query);
+        self._create(endpoint, query, from_block)
+    }
+
     pub fn delete<L: Ledger>(
         &self,
         query: &QueryId<L>,
     ) -> Box<dyn Future<Item = (), Error = Error> + Send> {
         Box::new(
-            self.client
-                .delete(query.as_ref().clone())
+            self.request(Method::DELETE, query.as_ref().clone())
                 .send()
                 .map(|_| ())
                 .map_err(|e| {
@@ -225,6 +404,7 @@ impl BtsieveHttpClient {
 
 mod ethereum {
     use super::*;
+    use crate::btsieve::{DecodedLog, TransactionAndDecodedLogs};
     use ethereum_support::{Transaction, TransactionAndReceipt, TransactionReceipt, H256};
 
     impl QueryEthereum for BtsieveHttpClient {
@@ -232,14 +412,32 @@ mod ethereum {
             &self,
             query: EthereumQuery,
         ) -> Box<dyn Future<Item = QueryId<Ethereum>, Error = Error> + Send> {
-            let endpoint = match &query {
-                EthereumQuery::Transaction { .. } => {
-                    self.create_ethereum_transaction_query_endpoint.clone()
+            Box::new(
+                self.create_ethereum_query_from(query, None)
+                    .map(|created_query| created_query.id),
+            )
+        }
+
+        fn create_batch(
+            &self,
+            queries: Vec<EthereumQuery>,
+        ) -> Box<dyn Future<Item = Vec<QueryId<Ethereum>>, Error = Error> + Send> {
+            let endpoints = queries
+                .iter()
+                .map(|query| self.ethereum_query_endpoint(query))
+                .collect::<Vec<_>>();
+
+            match endpoints.first() {
+                Some(endpoint) if endpoints.iter().all(|other| other == endpoint) => {
+                    self._create_batch(endpoint.clone(), queries)
                 }
-                EthereumQuery::Block { .. } => self.create_ethereum_block_query_endpoint.clone(),
-                EthereumQuery::Event { .. } => self.create_ethereum_event_query_endpoint.clone(),
-            };
-            self._create(endpoint, query)
+                _ => Box::new(future::join_all(
+                    queries
+                        .into_iter()
+                        .map(|query| QueryEthereum::create(self, query))
+                        .collect::<Vec<_>>(),
+                )),
+            }
         }
 
         fn delete(
@@ -277,43 +475,96 @@ mod ethereum {
             &self,
             query: &QueryId<Ethereum>,
         ) -> Box<dyn Future<Item = TransactionAndReceipt, Error = Error> + Send> {
-            let poll_client = self.client.clone();
+            let poll_client = self.clone();
             let query = query.clone();
-
             poll_until_item(self.ethereum_poll_interval, move || {
-                let mut url = query.as_ref().clone();
-                url.set_query(Some("return_as=transaction_and_receipt"));
-
-                let results = poll_client
-                    .get(url.clone())
-                    .send()
-                    .and_then(|mut response| {
-                        response.json::<QueryResponse<
-                            payloads::TransactionAndReceipt<Transaction, TransactionReceipt>,
-                        >>()
-                    })
-                    .map_err(move |e| {
-                        Error::FailedRequest(format!(
-                            "Failed to fetch full results for {:?} because {:?}",
-                            url, e
-                        ))
-                    })
-                    .map(|response| {
-                        response
-                            .matches
-                            .into_iter()
-                            .map(|payload| TransactionAndReceipt {
-                                transaction: payload.transaction,
-                                receipt: payload.receipt,
-                            })
-                            .collect()
-                    });
+                poll_client.fetch_transactions_and_receipts(&query)
+            })
+        }
 
-                Box::new(results)
+        fn transaction_and_decoded_logs_first_result(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = TransactionAndDecodedLogs, Error = Error> + Send> {
+            let poll_client = self.clone();
+            let query = query.clone();
+            poll_until_item(self.ethereum_poll_interval, move || {
+                poll_client.fetch_transactions_and_decoded_logs(&query)
             })
         }
     }
 
+    impl BtsieveHttpClient {
+        pub fn fetch_transactions_and_receipts(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = Vec<TransactionAndReceipt>, Error = Error> + Send> {
+            let mut url = query.as_ref().clone();
+            url.set_query(Some("return_as=transaction_and_receipt"));
+
+            let results = self
+                .request(Method::GET, url.clone())
+                .send()
+                .and_then(|mut response| {
+                    response.json::<QueryResponse<
+                        payloads::TransactionAndReceipt<Transaction, TransactionReceipt>,
+                    >>()
+                })
+                .map_err(move |e| {
+                    Error::FailedRequest(format!(
+                        "Failed to fetch full results for {:?} because {:?}",
+                        url, e
+                    ))
+                })
+                .map(|response| {
+                    response
+                        .matches
+                        .into_iter()
+                        .map(|payload| TransactionAndReceipt {
+                            transaction: payload.transaction,
+                            receipt: payload.receipt,
+                        })
+                        .collect()
+                });
+
+            Box::new(results)
+        }
+
+        pub fn fetch_transactions_and_decoded_logs(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = Vec<TransactionAndDecodedLogs>, Error = Error> + Send> {
+            let mut url = query.as_ref().clone();
+            url.set_query(Some("return_as=decoded_logs"));
+
+            let results = self
+                .request(Method::GET, url.clone())
+                .send()
+                .and_then(|mut response| {
+                    response.json::<QueryResponse<
+                        payloads::TransactionAndDecodedLogs<Transaction, DecodedLog>,
+                    >>()
+                })
+                .map_err(move |e| {
+                    Error::FailedRequest(format!(
+                        "Failed to fetch decoded results for {:?} because {:?}",
+                        url, e
+                    ))
+                })
+                .map(|response| {
+                    response
+                        .matches
+                        .into_iter()
+                        .map(|payload| TransactionAndDecodedLogs {
+                            transaction: payload.transaction,
+                            logs: payload.logs,
+                        })
+                        .collect()
+                });
+
+            Box::new(results)
+        }
+    }
 }
 
 mod bitcoin {
@@ -324,13 +575,32 @@ mod bitcoin {
             &self,
             query: BitcoinQuery,
         ) -> Box<dyn Future<Item = QueryId<Bitcoin>, Error = Error> + Send> {
-            let endpoint = match &query {
-                BitcoinQuery::Transaction { .. } => {
-                    self.create_bitcoin_transaction_query_endpoint.clone()
+            Box::new(
+                self.create_bitcoin_query_from(query, None)
+                    .map(|created_query| created_query.id),
+            )
+        }
+
+        fn create_batch(
+            &self,
+            queries: Vec<BitcoinQuery>,
+        ) -> Box<dyn Future<Item = Vec<QueryId<Bitcoin>>, Error = Error> + Send> {
+            let endpoints = queries
+                .iter()
+                .map(|query| self.bitcoin_query_endpoint(query))
+                .collect::<Vec<_>>();
+
+            match endpoints.first() {
+                Some(endpoint) if endpoints.iter().all(|other| other == endpoint) => {
+                    self._create_batch(endpoint.clone(), queries)
                 }
-                BitcoinQuery::Block { .. } => self.create_bitcoin_block_query_endpoint.clone(),
-            };
-            self._create(endpoint, query)
+                _ => Box::new(future::join_all(
+                    queries
+                        .into_iter()
+                        .map(|query| QueryBitcoin::create(self, query))
+                        .collect::<Vec<_>>(),
+                )),
+            }
         }
 
         fn delete(
@@ -362,6 +632,43 @@ mod bitcoin {
                 poll_client.fetch_transactions(&query)
             })
         }
+
+        fn block_count_reaching(
+            &self,
+            query: &QueryId<Bitcoin>,
+            is_enough: Arc<dyn Fn(usize) -> bool + Send + Sync>,
+        ) -> Box<dyn Future<Item = usize, Error = Error> + Send> {
+            let poll_client = self.clone();
+            let query = query.clone();
+            poll_until_item(self.bitcoin_poll_interval, move || {
+                let is_enough = Arc::clone(&is_enough);
+                poll_client
+                    .fetch_ids(&query)
+                    .map(move |block_hashes| count_if_enough(&block_hashes, &*is_enough))
+            })
+        }
+    }
+}
+
+/// Builds the `Authorization` header value btsieve expects, see `btsieve_auth`
+/// for what HMAC signatures cover.
+fn authorization(
+    authentication: &BtsieveAuthentication,
+    method: &Method,
+    url: &Url,
+    content_hash: &str,
+    timestamp: i64,
+) -> String {
+    match authentication {
+        BtsieveAuthentication::Bearer { token } => format!("Bearer {}", token),
+        BtsieveAuthentication::Hmac { secret } => hmac_authorization(
+            secret,
+            method.as_str(),
+            url.path(),
+            url.query(),
+            content_hash,
+            timestamp,
+        ),
     }
 }
 
@@ -377,4 +684,51 @@ mod test {
 
         let _: QueryResponse<TransactionId> = serde_json::from_str(json).unwrap();
     }
+
+    #[test]
+    fn given_litecoin_client_bitcoin_queries_go_to_litecoin_routes() {
+        let client = BtsieveHttpClient::new(
+            &Url::parse("http://localhost:8181").unwrap(),
+            None,
+            Duration::from_secs(20),
+            "regtest",
+            Duration::from_secs(300),
+            "regtest",
+        )
+        .with_utxo_ledger(Chain::Litecoin, "testnet", Duration::from_secs(75));
+
+        let query = BitcoinQuery::Block { min_height: None };
+
+        assert_eq!(
+            client.bitcoin_query_endpoint(&query).as_str(),
+            "http://localhost:8181/queries/litecoin/testnet/blocks"
+        );
+    }
+
+    #[test]
+    fn hmac_authorization_signs_method_query_and_body() {
+        let authentication = BtsieveAuthentication::Hmac {
+            secret: "s3cr3t".to_string(),
+        };
+        let url = Url::parse(
+            "http://localhost:8181/queries/bitcoin/regtest/transactions/1?return_as=transaction",
+        )
+        .unwrap();
+        let other_query = Url::parse(
+            "http://localhost:8181/queries/bitcoin/regtest/transactions/1?return_as=transaction_id",
+        )
+        .unwrap();
+        let no_body = content_hash(b"");
+        let sign = |method: &Method, url: &Url, content_hash: &str| {
+            authorization(&authentication, method, url, content_hash, 1_556_000_000)
+        };
+
+        let get = sign(&Method::GET, &url, &no_body);
+
+        assert!(get.starts_with("HMAC 1556000000:"));
+        assert_eq!(get.len(), "HMAC 1556000000:".len() + 64);
+        assert_ne!(get, sign(&Method::DELETE, &url, &no_body));
+        assert_ne!(get, sign(&Method::GET, &other_query, &no_body));
+        assert_ne!(get, sign(&Method::GET, &url, &content_hash(b"{}")));
+    }
 }
@@ -0,0 +1,816 @@
+use crate::{
+    btsieve::{
+        bitcoin::{count_if_enough, BitcoinQuery, QueryBitcoin},
+        client::payloads,
+        ethereum::{DecodedLog, EthereumQuery, QueryEthereum, TransactionAndDecodedLogs},
+        poll_until_item::poll_until_item,
+        Error, Query, QueryId,
+    },
+    settings,
+    swap_protocols::ledger::{Bitcoin, Ethereum, Ledger},
+};
+use ::btsieve::{
+    bitcoin::{
+        self as btsieve_bitcoin, bitcoind_zmq_listener::bitcoin_block_listener,
+        transaction_fetcher::TransactionFetcher, BlockFetcher, HeaderChain,
+    },
+    ethereum::{self as btsieve_ethereum, ethereum_web3_block_poller::ethereum_block_listener},
+    QueryRepository, QueryResult, QueryResultRepository, QueryType, ToHttpPayload,
+};
+use bitcoin_rpc_client::BitcoinCoreClient;
+use bitcoin_support::Network as BitcoinNetwork;
+use debug_stub_derive::DebugStub;
+use ethereum_support::{
+    web3::{
+        transports::{EventLoopHandle, Http},
+        Web3,
+    },
+    TransactionAndReceipt,
+};
+use failure::Fail;
+use futures::{future, Future, Stream};
+use reqwest::Url;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fmt::Debug, sync::Arc, time::Duration};
+use tokio::runtime::Runtime;
+
+#[derive(Debug, Fail)]
+pub enum StartError {
+    #[fail(display = "Could not listen to bitcoind blocks on {}", endpoint)]
+    BitcoinZmq { endpoint: String },
+    #[fail(display = "Could not connect to Ethereum node at {}", url)]
+    Ethereum { url: String },
+    #[fail(display = "Unknown Bitcoin network {}", network)]
+    BitcoinNetwork { network: String },
+}
+
+/// Runs btsieve's block listeners and query repositories inside of comit_node.
+/// Queries and results are converted through the same JSON representation the
+/// HTTP API uses, so both modes behave the same.
+#[derive(DebugStub, Clone)]
+pub struct EmbeddedBtsieve {
+    bitcoin_repositories: Arc<btsieve_bitcoin::QueryRepositories>,
+    #[debug_stub = "TransactionFetcher"]
+    bitcoin_client: Option<Arc<TransactionFetcher>>,
+    ethereum_repositories: Arc<btsieve_ethereum::QueryRepositories>,
+    #[debug_stub = "Web3"]
+    ethereum_client: Option<Arc<Web3<Http>>>,
+    bitcoin_poll_interval: Duration,
+    ethereum_poll_interval: Duration,
+}
+
+fn bitcoin_network(network: &str) -> Result<BitcoinNetwork, StartError> {
+    serde_json::from_value(network.into()).map_err(|_| StartError::BitcoinNetwork {
+        network: network.to_string(),
+    })
+}
+
+impl EmbeddedBtsieve {
+    /// Spawns the block listeners for all configured ledgers on `runtime`.
+    /// The returned `EventLoopHandle` drives the Ethereum connection and has
+    /// to be kept alive as long as btsieve is used.
+    pub fn start(
+        settings: &settings::Btsieve,
+        embedded: &settings::EmbeddedBtsieve,
+        runtime: &mut Runtime,
+    ) -> Result<(Self, Option<EventLoopHandle>), StartError> {
+        let bitcoin_repositories = Arc::new(btsieve_bitcoin::QueryRepositories::default());
+        let ethereum_repositories = Arc::new(btsieve_ethereum::QueryRepositories::default());
+
+        let bitcoin_client = match &embedded.bitcoin {
+            Some(bitcoin) => {
+                let blocks =
+                    bitcoin_block_listener(bitcoin.zmq_endpoint.as_str()).map_err(|e| {
+                        log::error!("Could not connect to bitcoind ZMQ: {:?}", e);
+                        StartError::BitcoinZmq {
+                            endpoint: bitcoin.zmq_endpoint.clone(),
+                        }
+                    })?;
+
+                let mut header_chain = HeaderChain::new(
+                    bitcoin_network(&settings.bitcoin.network)?,
+                    bitcoin.checkpoint,
+                );
+                let block_fetcher = BlockFetcher::new(
+                    bitcoin.node_url.clone(),
+                    bitcoin.node_username.clone(),
+                    bitcoin.node_password.clone(),
+                );
+
+                let repositories = Arc::clone(&bitcoin_repositories);
+                runtime.spawn(blocks.for_each(move |block| {
+                    for block in header_chain.accept(block, |id| block_fetcher.header(id)) {
+                        repositories.process_block(&block);
+                    }
+                    Ok(())
+                }));
+
+                Some(Arc::new(TransactionFetcher::new(BitcoinCoreClient::new(
+                    bitcoin.node_url.as_str(),
+                    bitcoin.node_username.as_str(),
+                    bitcoin.node_password.as_str(),
+                ))))
+            }
+            None => None,
+        };
+
+        let (ethereum_client, event_loop) = match &embedded.ethereum {
+            Some(ethereum) => {
+                let connection_error = |e| {
+                    log::error!("Could not connect to Ethereum node: {:?}", e);
+                    StartError::Ethereum {
+                        url: ethereum.node_url.to_string(),
+                    }
+                };
+
+                let (event_loop, transport) =
+                    Http::new(ethereum.node_url.as_str()).map_err(connection_error)?;
+                let client = Arc::new(Web3::new(transport));
+
+                let blocks = ethereum_block_listener(
+                    Arc::clone(&client),
+                    settings.ethereum.poll_interval_secs,
+                )
+                .map_err(connection_error)?;
+
+                let repositories = Arc::clone(&ethereum_repositories);
+                let block_client = Arc::clone(&client);
+                let executor = runtime.executor();
+                runtime.spawn(blocks.for_each(move |block| {
+                    executor.spawn(repositories.process_block(Arc::clone(&block_client), block));
+                    Ok(())
+                }));
+
+                (Some(client), Some(event_loop))
+            }
+            None => (None, None),
+        };
+
+        Ok((
+            Self {
+                bitcoin_repositories,
+                bitcoin_client,
+                ethereum_repositories,
+                ethereum_client,
+                bitcoin_poll_interval: settings.bitcoin.poll_interval_secs,
+                ethereum_poll_interval: settings.ethereum.poll_interval_secs,
+            },
+            event_loop,
+        ))
+    }
+}
+
+/// The location of a query in the embedded btsieve mirrors its HTTP route.
+fn query_id<L: Ledger>(ledger_name: &str, route: &str, id: u32) -> QueryId<L> {
+    let location = format!(
+        "embedded://btsieve/queries/{}/{}/{}",
+        ledger_name, route, id
+    );
+
+    QueryId::new(Url::parse(&location).expect("location is always a valid url"))
+}
+
+fn parse_query_id<L: Ledger>(query: &QueryId<L>) -> Result<(String, u32), Error> {
+    let malformed = || Error::MalformedResponse(format!("Unknown query {}", query.as_ref()));
+
+    let segments = query
+        .as_ref()
+        .path_segments()
+        .ok_or_else(malformed)?
+        .collect::<Vec<_>>();
+
+    match segments.as_slice() {
+        ["queries", _, route, id] => {
+            Ok(((*route).to_string(), id.parse().map_err(|_| malformed())?))
+        }
+        _ => Err(malformed()),
+    }
+}
+
+fn convert<T: DeserializeOwned, S: Serialize>(value: S) -> Result<T, Error> {
+    serde_json::to_value(value)
+        .and_then(serde_json::from_value)
+        .map_err(|e| {
+            log::error!(
+                "Could not convert between comit_node and btsieve types: {:?}",
+                e
+            );
+            Error::Internal
+        })
+}
+
+fn not_connected<T: Send + 'static>(
+    ledger_name: &str,
+) -> Box<dyn Future<Item = T, Error = Error> + Send> {
+    Box::new(future::err(Error::FailedRequest(format!(
+        "{} is not connected to the embedded btsieve",
+        ledger_name
+    ))))
+}
+
+fn save<L: Ledger, Q: Query, BQ: QueryType + DeserializeOwned + Debug, QR: QueryRepository<BQ>>(
+    repository: &QR,
+    ledger_name: &str,
+    query: Q,
+) -> Result<QueryId<L>, Error> {
+    let saved = repository.save(convert(&query)?).map_err(|e| {
+        log::error!("Failed to save {:?}: {:?}", query, e);
+        Error::Internal
+    })?;
+
+    let query_id = query_id(ledger_name, BQ::route(), saved.id);
+    log::info!("Created new query at location {}", query_id.as_ref());
+
+    Ok(query_id)
+}
+
+fn delete<BQ, QR: QueryRepository<BQ>, QRR: QueryResultRepository<BQ>>(
+    repository: &QR,
+    results: &QRR,
+    id: u32,
+) {
+    if repository.delete(id) == 0 {
+        results.delete(id);
+    }
+}
+
+/// Fetches the payloads of all matches, `return_as` is the same value the
+/// HTTP client passes as `?return_as=`.
+fn fetch_payloads<R, C, BQ, QRR, T>(
+    results: &QRR,
+    id: u32,
+    return_as: &str,
+    client: &Arc<C>,
+) -> Box<dyn Future<Item = Vec<T>, Error = Error> + Send>
+where
+    R: DeserializeOwned,
+    C: Send + Sync + 'static,
+    QRR: QueryResultRepository<BQ>,
+    QueryResult: ToHttpPayload<R, Client = C>,
+    T: DeserializeOwned + Send + 'static,
+{
+    let return_as = match convert::<R, _>(return_as) {
+        Ok(return_as) => return_as,
+        Err(e) => return Box::new(future::err(e)),
+    };
+
+    Box::new(
+        results
+            .get(id)
+            .unwrap_or_default()
+            .to_http_payload(&return_as, client)
+            .map_err(|e| Error::FailedRequest(format!("Failed to fetch results: {:?}", e)))
+            .and_then(|matches| matches.into_iter().map(convert).collect()),
+    )
+}
+
+mod bitcoin {
+    use super::*;
+    use ::btsieve::bitcoin::queries::{block, transaction};
+    use bitcoin_support::{Transaction, TransactionId};
+
+    const LEDGER_NAME: &str = "bitcoin";
+
+    impl EmbeddedBtsieve {
+        fn bitcoin_payloads<T: DeserializeOwned + Send + 'static>(
+            &self,
+            query: &QueryId<Bitcoin>,
+            return_as: &str,
+        ) -> Box<dyn Future<Item = Vec<T>, Error = Error> + Send> {
+            let client = match &self.bitcoin_client {
+                Some(client) => client,
+                None => return not_connected(LEDGER_NAME),
+            };
+            let (route, id) = match parse_query_id(query) {
+                Ok(route_and_id) => route_and_id,
+                Err(e) => return Box::new(future::err(e)),
+            };
+            let repositories = &self.bitcoin_repositories;
+
+            if route == btsieve_bitcoin::TransactionQuery::route() {
+                fetch_payloads::<transaction::ReturnAs, _, _, _, _>(
+                    &*repositories.transaction_query_results,
+                    id,
+                    return_as,
+                    client,
+                )
+            } else {
+                fetch_payloads::<block::ReturnAs, _, _, _, _>(
+                    &*repositories.block_query_results,
+                    id,
+                    return_as,
+                    client,
+                )
+            }
+        }
+    }
+
+    impl QueryBitcoin for EmbeddedBtsieve {
+        fn create(
+            &self,
+            query: BitcoinQuery,
+        ) -> Box<dyn Future<Item = QueryId<Bitcoin>, Error = Error> + Send> {
+            if self.bitcoin_client.is_none() {
+                return not_connected(LEDGER_NAME);
+            }
+
+            let repositories = &self.bitcoin_repositories;
+            Box::new(future::result(match query {
+                BitcoinQuery::Transaction { .. } => {
+                    save(&*repositories.transaction_queries, LEDGER_NAME, query)
+                }
+                BitcoinQuery::Block { .. } => {
+                    save(&*repositories.block_queries, LEDGER_NAME, query)
+                }
+            }))
+        }
+
+        fn create_batch(
+            &self,
+            queries: Vec<BitcoinQuery>,
+        ) -> Box<dyn Future<Item = Vec<QueryId<Bitcoin>>, Error = Error> + Send> {
+            Box::new(future::join_all(
+                queries
+                    .into_iter()
+                    .map(|query| QueryBitcoin::create(self, query))
+                    .collect::<Vec<_>>(),
+            ))
+        }
+
+        fn delete(
+            &self,
+            query: &QueryId<Bitcoin>,
+        ) -> Box<dyn Future<Item = (), Error = Error> + Send> {
+            let repositories = &self.bitcoin_repositories;
+
+            Box::new(future::result(parse_query_id(query).map(|(route, id)| {
+                if route == btsieve_bitcoin::TransactionQuery::route() {
+                    delete(
+                        &*repositories.transaction_queries,
+                        &*repositories.transaction_query_results,
+                        id,
+                    )
+                } else {
+                    delete(
+                        &*repositories.block_queries,
+                        &*repositories.block_query_results,
+                        id,
+                    )
+                }
+            })))
+        }
+
+        fn txid_results(
+            &self,
+            query: &QueryId<Bitcoin>,
+        ) -> Box<dyn Future<Item = Vec<TransactionId>, Error = Error> + Send> {
+            Box::new(self.bitcoin_payloads(query, "transaction_id").map(
+                |payloads: Vec<payloads::TransactionId<TransactionId>>| {
+                    payloads.into_iter().map(|payload| payload.id).collect()
+                },
+            ))
+        }
+
+        fn transaction_results(
+            &self,
+            query: &QueryId<Bitcoin>,
+        ) -> Box<dyn Future<Item = Vec<Transaction>, Error = Error> + Send> {
+            Box::new(self.bitcoin_payloads(query, "transaction").map(
+                |payloads: Vec<payloads::Transaction<Transaction>>| {
+                    payloads
+                        .into_iter()
+                        .map(|payload| payload.transaction)
+                        .collect()
+                },
+            ))
+        }
+
+        fn transaction_first_result(
+            &self,
+            query: &QueryId<Bitcoin>,
+        ) -> Box<dyn Future<Item = Transaction, Error = Error> + Send> {
+            let btsieve = self.clone();
+            let query = query.clone();
+            poll_until_item(self.bitcoin_poll_interval, move || {
+                QueryBitcoin::transaction_results(&btsieve, &query)
+            })
+        }
+
+        fn block_count_reaching(
+            &self,
+            query: &QueryId<Bitcoin>,
+            is_enough: Arc<dyn Fn(usize) -> bool + Send + Sync>,
+        ) -> Box<dyn Future<Item = usize, Error = Error> + Send> {
+            let btsieve = self.clone();
+            let query = query.clone();
+            poll_until_item(self.bitcoin_poll_interval, move || {
+                let is_enough = Arc::clone(&is_enough);
+                btsieve.bitcoin_payloads(&query, "block_id").map(
+                    move |payloads: Vec<payloads::TransactionId<TransactionId>>| {
+                        let block_hashes = payloads
+                            .into_iter()
+                            .map(|payload| payload.id)
+                            .collect::<Vec<_>>();
+                        count_if_enough(&block_hashes, &*is_enough)
+                    },
+                )
+            })
+        }
+    }
+}
+
+mod ethereum {
+    use super::*;
+    use ::btsieve::ethereum::queries::{block, event, transaction};
+    use ethereum_support::{Transaction, TransactionReceipt, H256};
+
+    const LEDGER_NAME: &str = "ethereum";
+
+    impl EmbeddedBtsieve {
+        fn ethereum_payloads<T: DeserializeOwned + Send + 'static>(
+            &self,
+            query: &QueryId<Ethereum>,
+            return_as: &str,
+        ) -> Box<dyn Future<Item = Vec<T>, Error = Error> + Send> {
+            let client = match &self.ethereum_client {
+                Some(client) => client,
+                None => return not_connected(LEDGER_NAME),
+            };
+            let (route, id) = match parse_query_id(query) {
+                Ok(route_and_id) => route_and_id,
+                Err(e) => return Box::new(future::err(e)),
+            };
+            let repositories = &self.ethereum_repositories;
+
+            if route == btsieve_ethereum::TransactionQuery::route() {
+                fetch_payloads::<transaction::ReturnAs, _, _, _, _>(
+                    &*repositories.transaction_query_results,
+                    id,
+                    return_as,
+                    client,
+                )
+            } else if route == btsieve_ethereum::EventQuery::route() {
+                fetch_payloads::<event::ReturnAs, _, _, _, _>(
+                    &*repositories.log_query_results,
+                    id,
+                    return_as,
+                    client,
+                )
+            } else {
+                fetch_payloads::<block::ReturnAs, _, _, _, _>(
+                    &*repositories.block_query_results,
+                    id,
+                    return_as,
+                    client,
+                )
+            }
+        }
+    }
+
+    impl QueryEthereum for EmbeddedBtsieve {
+        fn create(
+            &self,
+            query: EthereumQuery,
+        ) -> Box<dyn Future<Item = QueryId<Ethereum>, Error = Error> + Send> {
+            if self.ethereum_client.is_none() {
+                return not_connected(LEDGER_NAME);
+            }
+
+            let repositories = &self.ethereum_repositories;
+            Box::new(future::result(match query {
+                EthereumQuery::Transaction { .. } => {
+                    save(&*repositories.transaction_queries, LEDGER_NAME, query)
+                }
+                EthereumQuery::Block { .. } => {
+                    save(&*repositories.block_queries, LEDGER_NAME, query)
+                }
+                EthereumQuery::Event { .. } => save(&*repositories.log_queries, LEDGER_NAME, query),
+            }))
+        }
+
+        fn create_batch(
+            &self,
+            queries: Vec<EthereumQuery>,
+        ) -> Box<dyn Future<Item = Vec<QueryId<Ethereum>>, Error = Error> + Send> {
+            Box::new(future::join_all(
+                queries
+                    .into_iter()
+                    .map(|query| QueryEthereum::create(self, query))
+                    .collect::<Vec<_>>(),
+            ))
+        }
+
+        fn delete(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = (), Error = Error> + Send> {
+            let repositories = &self.ethereum_repositories;
+
+            Box::new(future::result(parse_query_id(query).map(|(route, id)| {
+                if route == btsieve_ethereum::TransactionQuery::route() {
+                    delete(
+                        &*repositories.transaction_queries,
+                        &*repositories.transaction_query_results,
+                        id,
+                    )
+                } else if route == btsieve_ethereum::EventQuery::route() {
+                    delete(
+                        &*repositories.log_queries,
+                        &*repositories.log_query_results,
+                        id,
+                    )
+                } else {
+                    delete(
+                        &*repositories.block_queries,
+                        &*repositories.block_query_results,
+                        id,
+                    )
+                }
+            })))
+        }
+
+        fn txid_results(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = Vec<H256>, Error = Error> + Send> {
+            Box::new(self.ethereum_payloads(query, "transaction_id").map(
+                |payloads: Vec<payloads::TransactionId<H256>>| {
+                    payloads.into_iter().map(|payload| payload.id).collect()
+                },
+            ))
+        }
+
+        fn transaction_results(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = Vec<Transaction>, Error = Error> + Send> {
+            Box::new(self.ethereum_payloads(query, "transaction").map(
+                |payloads: Vec<payloads::Transaction<Transaction>>| {
+                    payloads
+                        .into_iter()
+                        .map(|payload| payload.transaction)
+                        .collect()
+                },
+            ))
+        }
+
+        fn transaction_first_result(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = Transaction, Error = Error> + Send> {
+            let btsieve = self.clone();
+            let query = query.clone();
+            poll_until_item(self.ethereum_poll_interval, move || {
+                QueryEthereum::transaction_results(&btsieve, &query)
+            })
+        }
+
+        fn transaction_and_receipt_first_result(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = TransactionAndReceipt, Error = Error> + Send> {
+            let btsieve = self.clone();
+            let query = query.clone();
+            poll_until_item(self.ethereum_poll_interval, move || {
+                btsieve
+                    .ethereum_payloads(&query, "transaction_and_receipt")
+                    .map(
+                        |payloads: Vec<
+                            payloads::TransactionAndReceipt<Transaction, TransactionReceipt>,
+                        >| {
+                            payloads
+                                .into_iter()
+                                .map(|payload| TransactionAndReceipt {
+                                    transaction: payload.transaction,
+                                    receipt: payload.receipt,
+                                })
+                                .collect()
+                        },
+                    )
+            })
+        }
+
+        fn transaction_and_decoded_logs_first_result(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = TransactionAndDecodedLogs, Error = Error> + Send> {
+            let btsieve = self.clone();
+            let query = query.clone();
+            poll_until_item(self.ethereum_poll_interval, move || {
+                btsieve.ethereum_payloads(&query, "decoded_logs").map(
+                    |payloads: Vec<
+                        payloads::TransactionAndDecodedLogs<Transaction, DecodedLog>,
+                    >| {
+                        payloads
+                            .into_iter()
+                            .map(|payload| TransactionAndDecodedLogs {
+                                transaction: payload.transaction,
+                                logs: payload.logs,
+                            })
+                            .collect()
+                    },
+                )
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_support::{Block, Bytes, Transaction, H160, H2048, H256, U256};
+    use serde_json::json;
+    use spectral::prelude::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use warp::{self, Filter};
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    /// Answers the JSON-RPC calls btsieve's Ethereum block listener makes.
+    /// Its only block is announced once `mine` is called.
+    struct FakeEthereumNode {
+        url: Url,
+        mined: Arc<AtomicBool>,
+    }
+
+    impl FakeEthereumNode {
+        fn spawn(runtime: &mut Runtime, transaction: Transaction) -> Self {
+            let mined = Arc::new(AtomicBool::new(false));
+            let block = block(transaction.clone());
+
+            let is_mined = Arc::clone(&mined);
+            let rpc =
+                warp::post2()
+                    .and(warp::body::json())
+                    .map(move |request: serde_json::Value| {
+                        let result = match request["method"].as_str() {
+                            Some("eth_newBlockFilter") => json!("0x1"),
+                            Some("eth_blockNumber") => json!("0x0"),
+                            Some("eth_getFilterChanges")
+                                if is_mined.swap(false, Ordering::SeqCst) =>
+                            {
+                                json!([block.hash])
+                            }
+                            Some("eth_getFilterChanges") => json!([]),
+                            Some("eth_getBlockByHash") => json!(block),
+                            Some("eth_getTransactionByHash") => json!(transaction),
+                            _ => json!(null),
+                        };
+
+                        warp::reply::json(&json!({
+                            "jsonrpc": "2.0",
+                            "id": request["id"],
+                            "result": result,
+                        }))
+                    });
+            let (address, server) = warp::serve(rpc).bind_ephemeral(([127, 0, 0, 1], 0));
+            runtime.spawn(server);
+
+            Self {
+                url: Url::parse(&format!("http://{}/", address)).unwrap(),
+                mined,
+            }
+        }
+
+        fn mine(&self) {
+            self.mined.store(true, Ordering::SeqCst);
+        }
+    }
+
+    fn block(transaction: Transaction) -> Block<Transaction> {
+        Block {
+            hash: Some(H256::from(1)),
+            parent_hash: H256::from(0),
+            uncles_hash: H256::from(0),
+            author: H160::from(0),
+            state_root: H256::from(0),
+            transactions_root: H256::from(0),
+            receipts_root: H256::from(0),
+            number: Some(U256::from(1)),
+            gas_used: U256::from(0),
+            gas_limit: U256::from(0),
+            extra_data: Bytes::from(vec![]),
+            logs_bloom: H2048::from(0),
+            timestamp: U256::from(0),
+            difficulty: U256::from(0),
+            total_difficulty: U256::from(0),
+            seal_fields: vec![],
+            uncles: vec![],
+            transactions: vec![transaction],
+            size: None,
+            mix_hash: None,
+            nonce: None,
+        }
+    }
+
+    fn contract_deployment(data: Vec<u8>) -> Transaction {
+        Transaction {
+            hash: H256::from(2),
+            nonce: U256::from(0),
+            block_hash: Some(H256::from(1)),
+            block_number: Some(U256::from(1)),
+            transaction_index: None,
+            from: H160::from(3),
+            to: None,
+            value: U256::from(0),
+            gas_price: U256::from(0),
+            gas: U256::from(0),
+            input: Bytes::from(data),
+        }
+    }
+
+    fn settings() -> settings::Btsieve {
+        let poll_parameters = settings::PollParameters {
+            poll_interval_secs: POLL_INTERVAL,
+            network: "regtest".into(),
+        };
+
+        settings::Btsieve {
+            url: Url::parse("http://localhost:8181").unwrap(),
+            fallback_urls: Vec::new(),
+            authentication: None,
+            embedded: None,
+            bitcoin: poll_parameters.clone(),
+            ethereum: poll_parameters.clone(),
+            litecoin: poll_parameters,
+        }
+    }
+
+    #[test]
+    fn given_a_block_with_a_matching_transaction_query_returns_it() {
+        let mut runtime = Runtime::new().unwrap();
+        let transaction = contract_deployment(vec![1, 2, 3]);
+        let node = FakeEthereumNode::spawn(&mut runtime, transaction.clone());
+
+        let embedded = settings::EmbeddedBtsieve {
+            bitcoin: None,
+            ethereum: Some(settings::EmbeddedEthereum {
+                node_url: node.url.clone(),
+            }),
+        };
+        let (btsieve, _event_loop) =
+            EmbeddedBtsieve::start(&settings(), &embedded, &mut runtime).unwrap();
+
+        let query = runtime
+            .block_on(QueryEthereum::create(
+                &btsieve,
+                EthereumQuery::contract_deployment(Bytes::from(vec![1, 2, 3])),
+            ))
+            .unwrap();
+        node.mine();
+
+        let first_result =
+            runtime.block_on(QueryEthereum::transaction_first_result(&btsieve, &query));
+        assert_that(&first_result).is_ok_containing(transaction);
+
+        let deleted = runtime.block_on(QueryEthereum::delete(&btsieve, &query));
+        assert_that(&deleted).is_ok();
+        let results = runtime.block_on(QueryEthereum::txid_results(&btsieve, &query));
+        assert_that(&results).is_ok_containing(Vec::new());
+    }
+
+    #[test]
+    fn given_no_node_for_a_ledger_queries_are_refused() {
+        let mut runtime = Runtime::new().unwrap();
+        let embedded = settings::EmbeddedBtsieve {
+            bitcoin: None,
+            ethereum: None,
+        };
+        let (btsieve, event_loop) =
+            EmbeddedBtsieve::start(&settings(), &embedded, &mut runtime).unwrap();
+
+        let query = runtime.block_on(QueryBitcoin::create(
+            &btsieve,
+            BitcoinQuery::Block {
+                min_height: Some(1),
+            },
+        ));
+
+        assert_that(&event_loop.is_none()).is_true();
+        assert_that(&query).is_err();
+    }
+
+    #[test]
+    fn query_id_roundtrips_route_and_id() {
+        let query_id = query_id::<Ethereum>("ethereum", "logs", 42);
+
+        assert_that(&parse_query_id(&query_id)).is_ok_containing(("logs".to_string(), 42));
+    }
+
+    #[test]
+    fn given_a_comit_node_query_converts_to_btsieve_query() {
+        let query = BitcoinQuery::Transaction {
+            to_address: None,
+            from_outpoint: None,
+            unlock_script: Some(vec![vec![1u8]]),
+        };
+
+        let converted = convert::<btsieve_bitcoin::TransactionQuery, _>(&query);
+
+        assert_that(&converted).is_ok_containing(btsieve_bitcoin::TransactionQuery {
+            to_address: None,
+            from_outpoint: None,
+            unlock_script: Some(vec![vec![1u8]]),
+        });
+    }
+}
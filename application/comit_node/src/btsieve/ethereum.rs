@@ -3,7 +3,7 @@ use crate::{
     swap_protocols::ledger::Ethereum,
 };
 use ethereum_support::{
-    web3::types::{Address, Bytes, Transaction, H256},
+    web3::types::{Address, Bytes, Transaction, H256, U256},
     TransactionAndReceipt,
 };
 use futures::Future;
@@ -50,11 +50,69 @@ pub struct Topic(pub H256);
 
 impl Query for EthereumQuery {}
 
+/// A log btsieve decoded using the ABI of one of the events it knows about.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct DecodedLog {
+    pub address: Address,
+    #[serde(flatten)]
+    pub event: DecodedEvent,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(tag = "event")]
+pub enum DecodedEvent {
+    Transfer {
+        from: Address,
+        to: Address,
+        value: U256,
+    },
+    Erc721Transfer {
+        from: Address,
+        to: Address,
+        token_id: U256,
+    },
+    Redeemed {
+        secret: H256,
+    },
+    Refunded,
+    HtlcCreated {
+        init_code_hash: H256,
+        htlc: Address,
+    },
+    SwapFunded {
+        swap_id: H256,
+        redeem_address: Address,
+        refund_timestamp: U256,
+        value: U256,
+    },
+    SwapRedeemed {
+        swap_id: H256,
+        secret: H256,
+    },
+    SwapRefunded {
+        swap_id: H256,
+    },
+    /// An event btsieve learned to decode after this comit_node was built
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug)]
+pub struct TransactionAndDecodedLogs {
+    pub transaction: Transaction,
+    pub logs: Vec<DecodedLog>,
+}
+
 pub trait QueryEthereum {
     fn create(
         &self,
         query: EthereumQuery,
     ) -> Box<dyn Future<Item = QueryId<Ethereum>, Error = Error> + Send>;
+    /// Creates all queries with as few requests to btsieve as possible.
+    fn create_batch(
+        &self,
+        queries: Vec<EthereumQuery>,
+    ) -> Box<dyn Future<Item = Vec<QueryId<Ethereum>>, Error = Error> + Send>;
 
     fn delete(&self, query: &QueryId<Ethereum>)
         -> Box<dyn Future<Item = (), Error = Error> + Send>;
@@ -74,6 +132,10 @@ pub trait QueryEthereum {
         &self,
         query: &QueryId<Ethereum>,
     ) -> Box<dyn Future<Item = TransactionAndReceipt, Error = Error> + Send>;
+    fn transaction_and_decoded_logs_first_result(
+        &self,
+        query: &QueryId<Ethereum>,
+    ) -> Box<dyn Future<Item = TransactionAndDecodedLogs, Error = Error> + Send>;
 }
 
 #[cfg(test)]
@@ -166,4 +228,41 @@ mod tests {
         let query = serde_json::to_string(&query).unwrap();
         assert_eq!(query, r#"{"event_matchers":[{"address":"0x8457037fcd80a8650c4692d7fcfc1d0a96b92867","data":"0x01","topics":["0x0000000000000000000000000000000000000000000000000000000000000001"]}]}"#)
     }
+
+    #[test]
+    fn decoded_transfer_log_deserializes_correctly() {
+        let json = r#"{"address":"0x8457037fcd80a8650c4692d7fcfc1d0a96b92867","event":"Transfer","from":"0x0000000000000000000000000000000000000001","to":"0x0000000000000000000000000000000000000002","value":"0x2a"}"#;
+
+        let log: DecodedLog = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            log,
+            DecodedLog {
+                address: "8457037fcd80a8650c4692d7fcfc1d0a96b92867".into(),
+                event: DecodedEvent::Transfer {
+                    from: "0000000000000000000000000000000000000001".into(),
+                    to: "0000000000000000000000000000000000000002".into(),
+                    value: U256::from(42),
+                },
+            }
+        )
+    }
+
+    #[test]
+    fn decoded_refunded_log_deserializes_correctly() {
+        let json = r#"{"address":"0x8457037fcd80a8650c4692d7fcfc1d0a96b92867","event":"Refunded"}"#;
+
+        let log: DecodedLog = serde_json::from_str(json).unwrap();
+
+        assert_eq!(log.event, DecodedEvent::Refunded)
+    }
+
+    #[test]
+    fn decoded_log_of_unknown_event_deserializes_as_unknown() {
+        let json = r#"{"address":"0x8457037fcd80a8650c4692d7fcfc1d0a96b92867","event":"Approval","owner":"0x0000000000000000000000000000000000000001","value":"0x2a"}"#;
+
+        let log: DecodedLog = serde_json::from_str(json).unwrap();
+
+        assert_eq!(log.event, DecodedEvent::Unknown)
+    }
 }
@@ -0,0 +1,625 @@
+use crate::{
+    btsieve::{
+        bitcoin::{count_if_enough, BitcoinQuery, QueryBitcoin},
+        client::{BtsieveHttpClient, CreatedQuery},
+        ethereum::{EthereumQuery, QueryEthereum, TransactionAndDecodedLogs},
+        poll_until_item::poll_until_item,
+        Error, Query, QueryId,
+    },
+    swap_protocols::ledger::{Bitcoin, Ethereum, Ledger},
+};
+use futures::{future, Future};
+use reqwest::Url;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+#[derive(Debug, Clone)]
+struct Tracked<Q> {
+    query: Q,
+    instance: usize,
+    location: Url,
+    /// The block the first instance had processed last when the query was
+    /// created there
+    last_block: Option<u64>,
+}
+
+/// Queries we handed out, keyed by the location they were first created at.
+/// That location stays the `QueryId` of the query, even after it has been
+/// re-created on another instance.
+type TrackedQueries<Q> = Arc<Mutex<HashMap<Url, Tracked<Q>>>>;
+
+/// Spreads queries over several btsieve instances watching the same ledgers.
+///
+/// New queries are created on the currently active instance. Once a request
+/// to an instance fails, the next instance becomes active and every query
+/// that fails on the broken instance is re-created there. A re-created query
+/// is matched from the block the first instance had processed last when the
+/// query was created, so all instances have to watch the same chains.
+#[derive(Debug, Clone)]
+pub struct FailoverBtsieve {
+    instances: Arc<Vec<BtsieveHttpClient>>,
+    active: Arc<AtomicUsize>,
+    bitcoin_queries: TrackedQueries<BitcoinQuery>,
+    ethereum_queries: TrackedQueries<EthereumQuery>,
+    bitcoin_poll_interval: Duration,
+    ethereum_poll_interval: Duration,
+}
+
+type Create<L, Q> = fn(
+    &BtsieveHttpClient,
+    Q,
+    Option<u64>,
+) -> Box<dyn Future<Item = CreatedQuery<L>, Error = Error> + Send>;
+
+impl FailoverBtsieve {
+    pub fn new(
+        instances: Vec<BtsieveHttpClient>,
+        bitcoin_poll_interval: Duration,
+        ethereum_poll_interval: Duration,
+    ) -> Self {
+        assert!(
+            !instances.is_empty(),
+            "at least one btsieve instance is needed"
+        );
+
+        Self {
+            instances: Arc::new(instances),
+            active: Arc::new(AtomicUsize::new(0)),
+            bitcoin_queries: Arc::new(Mutex::new(HashMap::new())),
+            ethereum_queries: Arc::new(Mutex::new(HashMap::new())),
+            bitcoin_poll_interval,
+            ethereum_poll_interval,
+        }
+    }
+
+    /// Marks `failed` as broken and returns the instance to use instead.
+    fn fail_over_from(&self, failed: usize) -> usize {
+        let next = (failed + 1) % self.instances.len();
+
+        if self.active.compare_and_swap(failed, next, Ordering::SeqCst) == failed {
+            log::warn!(
+                "btsieve at {} failed, switching to {}",
+                self.instances[failed].endpoint(),
+                self.instances[next].endpoint()
+            );
+        }
+
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Tries every instance once, starting with `first`, until one of them
+    /// creates the query.
+    fn create_on_healthy_instance<L: Ledger, Q: Query>(
+        &self,
+        create: Create<L, Q>,
+        query: Q,
+        from_block: Option<u64>,
+        first: usize,
+        attempts_left: usize,
+    ) -> Box<dyn Future<Item = (usize, CreatedQuery<L>), Error = Error> + Send> {
+        let failover = self.clone();
+
+        Box::new(
+            create(&self.instances[first], query.clone(), from_block)
+                .map(move |created_query| (first, created_query))
+                .or_else(move |e| {
+                    if attempts_left <= 1 {
+                        return Box::new(future::err(e))
+                            as Box<dyn Future<Item = _, Error = _> + Send>;
+                    }
+
+                    log::warn!("Failed to create {:?}: {:?}", query, e);
+                    let next = failover.fail_over_from(first);
+
+                    failover.create_on_healthy_instance(
+                        create,
+                        query,
+                        from_block,
+                        next,
+                        attempts_left - 1,
+                    )
+                }),
+        )
+    }
+
+    fn create_tracked<L: Ledger, Q: Query>(
+        &self,
+        tracked_queries: &TrackedQueries<Q>,
+        create: Create<L, Q>,
+        query: Q,
+    ) -> Box<dyn Future<Item = QueryId<L>, Error = Error> + Send> {
+        let tracked_queries = Arc::clone(tracked_queries);
+        let instances = Arc::clone(&self.instances);
+        let active = self.active.load(Ordering::SeqCst);
+
+        Box::new(
+            self.create_on_healthy_instance(create, query.clone(), None, active, instances.len())
+                .map(move |(instance, CreatedQuery { id, last_block })| {
+                    log::info!(
+                        "{} is backed by btsieve at {}",
+                        id.as_ref(),
+                        instances[instance].endpoint()
+                    );
+
+                    tracked_queries.lock().unwrap().insert(
+                        id.as_ref().clone(),
+                        Tracked {
+                            query,
+                            instance,
+                            location: id.as_ref().clone(),
+                            last_block,
+                        },
+                    );
+
+                    id
+                }),
+        )
+    }
+
+    /// Runs `operation` against the instance backing `query_id`. If that
+    /// instance fails, the query is re-created on a healthy instance and
+    /// `operation` is retried there once.
+    fn with_failover<L: Ledger, Q: Query, T: Send + 'static>(
+        &self,
+        tracked_queries: &TrackedQueries<Q>,
+        create: Create<L, Q>,
+        query_id: &QueryId<L>,
+        operation: fn(
+            &BtsieveHttpClient,
+            &QueryId<L>,
+        ) -> Box<dyn Future<Item = T, Error = Error> + Send>,
+    ) -> Box<dyn Future<Item = T, Error = Error> + Send> {
+        let handed_out = query_id.as_ref().clone();
+        let tracked = tracked_queries.lock().unwrap().get(&handed_out).cloned();
+
+        let tracked = match tracked {
+            Some(tracked) => tracked,
+            None => {
+                let active = self.active.load(Ordering::SeqCst);
+                return operation(&self.instances[active], query_id);
+            }
+        };
+
+        let failover = self.clone();
+        let tracked_queries = Arc::clone(tracked_queries);
+
+        Box::new(
+            operation(
+                &self.instances[tracked.instance],
+                &QueryId::new(tracked.location.clone()),
+            )
+            .or_else(move |e| {
+                log::warn!(
+                    "btsieve at {} failed for {}: {:?}",
+                    failover.instances[tracked.instance].endpoint(),
+                    handed_out,
+                    e
+                );
+
+                let next = failover.fail_over_from(tracked.instance);
+                let instances = Arc::clone(&failover.instances);
+
+                failover
+                    .create_on_healthy_instance(
+                        create,
+                        tracked.query.clone(),
+                        tracked.last_block,
+                        next,
+                        instances.len() - 1,
+                    )
+                    .and_then(move |(instance, CreatedQuery { id, last_block })| {
+                        log::info!(
+                            "{} is now backed by btsieve at {}, matching from block {:?}",
+                            handed_out,
+                            instances[instance].endpoint(),
+                            tracked.last_block
+                        );
+
+                        tracked_queries.lock().unwrap().insert(
+                            handed_out,
+                            Tracked {
+                                query: tracked.query,
+                                instance,
+                                location: id.as_ref().clone(),
+                                last_block: tracked.last_block.or(last_block),
+                            },
+                        );
+
+                        operation(&instances[instance], &id)
+                    })
+            }),
+        )
+    }
+
+    fn delete_tracked<L: Ledger, Q: Query>(
+        &self,
+        tracked_queries: &TrackedQueries<Q>,
+        query_id: &QueryId<L>,
+    ) -> Box<dyn Future<Item = (), Error = Error> + Send> {
+        match tracked_queries.lock().unwrap().remove(query_id.as_ref()) {
+            Some(tracked) => {
+                self.instances[tracked.instance].delete::<L>(&QueryId::new(tracked.location))
+            }
+            None => {
+                let active = self.active.load(Ordering::SeqCst);
+                self.instances[active].delete(query_id)
+            }
+        }
+    }
+}
+
+mod bitcoin {
+    use super::*;
+    use bitcoin_support::{Transaction, TransactionId};
+
+    fn create(
+        client: &BtsieveHttpClient,
+        query: BitcoinQuery,
+        from_block: Option<u64>,
+    ) -> Box<dyn Future<Item = CreatedQuery<Bitcoin>, Error = Error> + Send> {
+        client.create_bitcoin_query_from(query, from_block)
+    }
+
+    impl QueryBitcoin for FailoverBtsieve {
+        fn create(
+            &self,
+            query: BitcoinQuery,
+        ) -> Box<dyn Future<Item = QueryId<Bitcoin>, Error = Error> + Send> {
+            self.create_tracked(&self.bitcoin_queries, create, query)
+        }
+
+        fn create_batch(
+            &self,
+            queries: Vec<BitcoinQuery>,
+        ) -> Box<dyn Future<Item = Vec<QueryId<Bitcoin>>, Error = Error> + Send> {
+            Box::new(future::join_all(
+                queries
+                    .into_iter()
+                    .map(|query| QueryBitcoin::create(self, query))
+                    .collect::<Vec<_>>(),
+            ))
+        }
+
+        fn delete(
+            &self,
+            query: &QueryId<Bitcoin>,
+        ) -> Box<dyn Future<Item = (), Error = Error> + Send> {
+            self.delete_tracked(&self.bitcoin_queries, query)
+        }
+
+        fn txid_results(
+            &self,
+            query: &QueryId<Bitcoin>,
+        ) -> Box<dyn Future<Item = Vec<TransactionId>, Error = Error> + Send> {
+            self.with_failover(&self.bitcoin_queries, create, query, |client, query| {
+                client.fetch_ids(query)
+            })
+        }
+
+        fn transaction_results(
+            &self,
+            query: &QueryId<Bitcoin>,
+        ) -> Box<dyn Future<Item = Vec<Transaction>, Error = Error> + Send> {
+            self.with_failover(&self.bitcoin_queries, create, query, |client, query| {
+                client.fetch_transactions(query)
+            })
+        }
+
+        fn transaction_first_result(
+            &self,
+            query: &QueryId<Bitcoin>,
+        ) -> Box<dyn Future<Item = Transaction, Error = Error> + Send> {
+            let failover = self.clone();
+            let query = query.clone();
+            poll_until_item(self.bitcoin_poll_interval, move || {
+                QueryBitcoin::transaction_results(&failover, &query)
+            })
+        }
+
+        fn block_count_reaching(
+            &self,
+            query: &QueryId<Bitcoin>,
+            is_enough: Arc<dyn Fn(usize) -> bool + Send + Sync>,
+        ) -> Box<dyn Future<Item = usize, Error = Error> + Send> {
+            let failover = self.clone();
+            let query = query.clone();
+            poll_until_item(self.bitcoin_poll_interval, move || {
+                let is_enough = Arc::clone(&is_enough);
+                QueryBitcoin::txid_results(&failover, &query)
+                    .map(move |block_hashes| count_if_enough(&block_hashes, &*is_enough))
+            })
+        }
+    }
+}
+
+mod ethereum {
+    use super::*;
+    use ethereum_support::{Transaction, TransactionAndReceipt, H256};
+
+    fn create(
+        client: &BtsieveHttpClient,
+        query: EthereumQuery,
+        from_block: Option<u64>,
+    ) -> Box<dyn Future<Item = CreatedQuery<Ethereum>, Error = Error> + Send> {
+        client.create_ethereum_query_from(query, from_block)
+    }
+
+    impl QueryEthereum for FailoverBtsieve {
+        fn create(
+            &self,
+            query: EthereumQuery,
+        ) -> Box<dyn Future<Item = QueryId<Ethereum>, Error = Error> + Send> {
+            self.create_tracked(&self.ethereum_queries, create, query)
+        }
+
+        fn create_batch(
+            &self,
+            queries: Vec<EthereumQuery>,
+        ) -> Box<dyn Future<Item = Vec<QueryId<Ethereum>>, Error = Error> + Send> {
+            Box::new(future::join_all(
+                queries
+                    .into_iter()
+                    .map(|query| QueryEthereum::create(self, query))
+                    .collect::<Vec<_>>(),
+            ))
+        }
+
+        fn delete(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = (), Error = Error> + Send> {
+            self.delete_tracked(&self.ethereum_queries, query)
+        }
+
+        fn txid_results(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = Vec<H256>, Error = Error> + Send> {
+            self.with_failover(&self.ethereum_queries, create, query, |client, query| {
+                client.fetch_ids(query)
+            })
+        }
+
+        fn transaction_results(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = Vec<Transaction>, Error = Error> + Send> {
+            self.with_failover(&self.ethereum_queries, create, query, |client, query| {
+                client.fetch_transactions(query)
+            })
+        }
+
+        fn transaction_first_result(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = Transaction, Error = Error> + Send> {
+            let failover = self.clone();
+            let query = query.clone();
+            poll_until_item(self.ethereum_poll_interval, move || {
+                QueryEthereum::transaction_results(&failover, &query)
+            })
+        }
+
+        fn transaction_and_receipt_first_result(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = TransactionAndReceipt, Error = Error> + Send> {
+            let failover = self.clone();
+            let query = query.clone();
+            poll_until_item(self.ethereum_poll_interval, move || {
+                failover.with_failover(
+                    &failover.ethereum_queries,
+                    create,
+                    &query,
+                    |client, query| client.fetch_transactions_and_receipts(query),
+                )
+            })
+        }
+
+        fn transaction_and_decoded_logs_first_result(
+            &self,
+            query: &QueryId<Ethereum>,
+        ) -> Box<dyn Future<Item = TransactionAndDecodedLogs, Error = Error> + Send> {
+            let failover = self.clone();
+            let query = query.clone();
+            poll_until_item(self.ethereum_poll_interval, move || {
+                failover.with_failover(
+                    &failover.ethereum_queries,
+                    create,
+                    &query,
+                    |client, query| client.fetch_transactions_and_decoded_logs(query),
+                )
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin_support::{FromHex, TransactionId};
+    use serde_json::json;
+    use spectral::prelude::*;
+    use std::{net::TcpListener, sync::atomic::AtomicBool};
+    use tokio::runtime::Runtime;
+    use warp::{self, http::StatusCode, Filter};
+
+    const LOCATION: &str = "/queries/bitcoin/regtest/transactions/1";
+
+    /// Serves a single Bitcoin transaction query until it is taken down
+    struct FakeBtsieve {
+        url: Url,
+        down: Arc<AtomicBool>,
+        created_from: Arc<Mutex<Vec<Option<String>>>>,
+    }
+
+    impl FakeBtsieve {
+        fn spawn(runtime: &mut Runtime, last_block: u64, matches: Vec<TransactionId>) -> Self {
+            let down = Arc::new(AtomicBool::new(false));
+            let created_from = Arc::new(Mutex::new(Vec::new()));
+
+            let is_down = Arc::clone(&down);
+            let up = warp::any()
+                .and_then(move || {
+                    if is_down.load(Ordering::SeqCst) {
+                        Err(warp::reject::not_found())
+                    } else {
+                        Ok(())
+                    }
+                })
+                .untuple_one();
+            let query = warp::query::raw()
+                .map(Some)
+                .or(warp::any().map(|| None))
+                .unify();
+
+            let created = Arc::clone(&created_from);
+            let create = warp::post2()
+                .and(warp::path!(
+                    "queries" / "bitcoin" / "regtest" / "transactions"
+                ))
+                .and(query)
+                .map(move |query: Option<String>| {
+                    created.lock().unwrap().push(query);
+
+                    warp::reply::with_status(
+                        warp::reply::json(&json!({
+                            "location": LOCATION,
+                            "reference_count": 1,
+                            "last_block": last_block,
+                        })),
+                        StatusCode::CREATED,
+                    )
+                });
+            let retrieve = warp::get2()
+                .and(warp::path!(
+                    "queries" / "bitcoin" / "regtest" / "transactions" / u32
+                ))
+                .map(move |_| {
+                    let matches = matches
+                        .iter()
+                        .map(|id| json!({ "id": id }))
+                        .collect::<Vec<_>>();
+
+                    warp::reply::json(&json!({ "query": {}, "matches": matches }))
+                });
+
+            let (address, server) =
+                warp::serve(up.and(create.or(retrieve))).bind_ephemeral(([127, 0, 0, 1], 0));
+            runtime.spawn(server);
+
+            Self {
+                url: Url::parse(&format!("http://{}/", address)).unwrap(),
+                down,
+                created_from,
+            }
+        }
+
+        fn go_down(&self) {
+            self.down.store(true, Ordering::SeqCst);
+        }
+
+        fn created_from(&self) -> Vec<Option<String>> {
+            self.created_from.lock().unwrap().clone()
+        }
+    }
+
+    fn unreachable_url() -> Url {
+        let address = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        Url::parse(&format!("http://{}/", address)).unwrap()
+    }
+
+    fn failover(urls: &[&Url]) -> FailoverBtsieve {
+        let instances = urls
+            .iter()
+            .map(|url| {
+                BtsieveHttpClient::new(
+                    url,
+                    None,
+                    Duration::from_secs(1),
+                    "regtest",
+                    Duration::from_secs(1),
+                    "regtest",
+                )
+            })
+            .collect();
+
+        FailoverBtsieve::new(instances, Duration::from_secs(1), Duration::from_secs(1))
+    }
+
+    fn query() -> BitcoinQuery {
+        BitcoinQuery::Transaction {
+            to_address: None,
+            from_outpoint: None,
+            unlock_script: Some(vec![vec![1u8]]),
+        }
+    }
+
+    fn transaction_id() -> TransactionId {
+        TransactionId::from_hex("b29cb185d467b3a5faeb7a3f312175e336dbfcc8e9fecc8ad86e9106031315c2")
+            .unwrap()
+    }
+
+    #[test]
+    fn given_the_active_instance_is_down_creates_the_query_on_the_next_one() {
+        let mut runtime = Runtime::new().unwrap();
+        let next = FakeBtsieve::spawn(&mut runtime, 7, vec![transaction_id()]);
+        let failover = failover(&[&unreachable_url(), &next.url]);
+
+        let query_id = runtime
+            .block_on(QueryBitcoin::create(&failover, query()))
+            .unwrap();
+        let results = runtime.block_on(QueryBitcoin::txid_results(&failover, &query_id));
+
+        assert_that(&next.created_from()).is_equal_to(vec![None]);
+        assert_that(&results).is_ok_containing(vec![transaction_id()]);
+    }
+
+    #[test]
+    fn given_the_backing_instance_goes_down_recreates_the_query_from_its_last_block() {
+        let mut runtime = Runtime::new().unwrap();
+        let first = FakeBtsieve::spawn(&mut runtime, 7, vec![]);
+        let second = FakeBtsieve::spawn(&mut runtime, 9, vec![transaction_id()]);
+        let failover = failover(&[&first.url, &second.url]);
+
+        let query_id = runtime
+            .block_on(QueryBitcoin::create(&failover, query()))
+            .unwrap();
+        first.go_down();
+        let results = runtime.block_on(QueryBitcoin::txid_results(&failover, &query_id));
+
+        assert_that(&results).is_ok_containing(vec![transaction_id()]);
+        assert_that(&query_id.as_ref()).is_equal_to(&first.url.join(LOCATION).unwrap());
+        assert_that(&second.created_from()).is_equal_to(vec![Some("from_block=7".to_string())]);
+    }
+
+    #[test]
+    fn given_a_recreated_query_keeps_using_the_new_instance() {
+        let mut runtime = Runtime::new().unwrap();
+        let first = FakeBtsieve::spawn(&mut runtime, 7, vec![]);
+        let second = FakeBtsieve::spawn(&mut runtime, 9, vec![transaction_id()]);
+        let failover = failover(&[&first.url, &second.url]);
+
+        let query_id = runtime
+            .block_on(QueryBitcoin::create(&failover, query()))
+            .unwrap();
+        first.go_down();
+        runtime
+            .block_on(QueryBitcoin::txid_results(&failover, &query_id))
+            .unwrap();
+        let results = runtime.block_on(QueryBitcoin::txid_results(&failover, &query_id));
+
+        assert_that(&results).is_ok_containing(vec![transaction_id()]);
+        assert_that(&second.created_from()).has_length(1);
+    }
+}
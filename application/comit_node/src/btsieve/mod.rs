@@ -1,4 +1,6 @@
-pub use self::{bitcoin::*, client::*, ethereum::*};
+#[cfg(feature = "embedded-btsieve")]
+pub use self::embedded::{EmbeddedBtsieve, StartError};
+pub use self::{bitcoin::*, client::*, ethereum::*, failover::FailoverBtsieve};
 use crate::swap_protocols::ledger::Ledger;
 use failure::Fail;
 use reqwest::Url;
@@ -7,7 +9,10 @@ use std::{fmt::Debug, hash::Hash, marker::PhantomData};
 
 mod bitcoin;
 mod client;
+#[cfg(feature = "embedded-btsieve")]
+mod embedded;
 mod ethereum;
+mod failover;
 mod poll_until_item;
 
 #[derive(Clone, Debug, PartialOrd, PartialEq)]
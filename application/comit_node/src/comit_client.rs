@@ -1,6 +1,6 @@
 use crate::{
     node_id::NodeId,
-    swap_protocols::{self, asset::Asset},
+    swap_protocols::{self, asset::Asset, SwapProtocol},
 };
 use futures::Future;
 use serde::{Deserialize, Serialize};
@@ -41,8 +41,14 @@ pub enum RequestError {
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SwapReject {
-    Declined { reason: Option<SwapDeclineReason> },
+    Declined {
+        reason: Option<SwapDeclineReason>,
+    },
     Rejected,
+    /// None of the protocols we offered is one the other node supports
+    Unsupported {
+        supported_protocols: Vec<SwapProtocol>,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
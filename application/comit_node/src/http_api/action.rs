@@ -1,7 +1,8 @@
 use crate::{
     http_api::problem,
     swap_protocols::{
-        actions::{bitcoin, ethereum},
+        actions::{bitcoin, ethereum, lightning},
+        rfc003::{Secret, SecretHash},
         SwapId, Timestamp,
     },
 };
@@ -22,7 +23,9 @@ pub trait ListRequiredFields {
 #[serde(untagged)]
 pub enum ActionExecutionParameters {
     BitcoinAddressAndFee {
-        address: bitcoin_support::Address,
+        /// Parsed for the chain of the action, the same address can be valid
+        /// on more than one
+        address: String,
         fee_per_byte: String,
     },
     None {},
@@ -33,7 +36,7 @@ pub enum ActionExecutionParameters {
 #[serde(tag = "type", content = "payload")]
 pub enum ActionResponseBody {
     BitcoinSendAmountToAddress {
-        to: bitcoin_support::Address,
+        to: bitcoin_support::ChainAddress,
         amount: bitcoin_support::BitcoinQuantity,
         network: bitcoin_support::Network,
     },
@@ -42,19 +45,47 @@ pub enum ActionResponseBody {
         network: bitcoin_support::Network,
         min_median_block_time: Option<Timestamp>,
     },
+    LitecoinSendAmountToAddress {
+        to: bitcoin_support::ChainAddress,
+        amount: bitcoin_support::LitecoinQuantity,
+        network: bitcoin_support::Network,
+    },
+    LitecoinBroadcastSignedTransaction {
+        hex: String,
+        network: bitcoin_support::Network,
+        min_median_block_time: Option<Timestamp>,
+    },
     EthereumDeployContract {
         data: ethereum_support::Bytes,
         amount: ethereum_support::EtherQuantity,
         gas_limit: ethereum_support::U256,
-        network: ethereum_support::Network,
+        chain_id: ethereum_support::ChainId,
     },
     EthereumCallContract {
         contract_address: ethereum_support::Address,
         data: ethereum_support::Bytes,
+        amount: ethereum_support::EtherQuantity,
         gas_limit: ethereum_support::U256,
-        network: ethereum_support::Network,
+        chain_id: ethereum_support::ChainId,
         min_block_timestamp: Option<Timestamp>,
     },
+    LightningSendPayment {
+        to: secp256k1_support::PublicKey,
+        amount: bitcoin_support::BitcoinQuantity,
+        secret_hash: SecretHash,
+        final_cltv_delta: u32,
+        network: bitcoin_support::Network,
+    },
+    LightningAddHoldInvoice {
+        amount: bitcoin_support::BitcoinQuantity,
+        secret_hash: SecretHash,
+        cltv_expiry: u32,
+        network: bitcoin_support::Network,
+    },
+    LightningSettleInvoice {
+        secret: Secret,
+        network: bitcoin_support::Network,
+    },
     None,
 }
 
@@ -62,6 +93,7 @@ impl ActionResponseBody {
     fn bitcoin_broadcast_signed_transaction(
         transaction: &bitcoin_support::Transaction,
         network: bitcoin_support::Network,
+        chain: bitcoin_support::Chain,
     ) -> Self {
         let min_median_block_time = if transaction.lock_time == 0 {
             None
@@ -72,10 +104,23 @@ impl ActionResponseBody {
             Some(Timestamp::from(min_median_block_time))
         };
 
-        ActionResponseBody::BitcoinBroadcastSignedTransaction {
-            hex: bitcoin_support::serialize_hex(transaction),
-            network,
-            min_median_block_time,
+        let hex = bitcoin_support::serialize_hex(transaction);
+
+        match chain {
+            bitcoin_support::Chain::Bitcoin => {
+                ActionResponseBody::BitcoinBroadcastSignedTransaction {
+                    hex,
+                    network,
+                    min_median_block_time,
+                }
+            }
+            bitcoin_support::Chain::Litecoin => {
+                ActionResponseBody::LitecoinBroadcastSignedTransaction {
+                    hex,
+                    network,
+                    min_median_block_time,
+                }
+            }
         }
     }
 }
@@ -99,11 +144,22 @@ impl IntoResponsePayload for bitcoin::SendToAddress {
                     amount,
                     network,
                 } = self;
-                Ok(ActionResponseBody::BitcoinSendAmountToAddress {
-                    to,
-                    amount,
-                    network,
-                })
+                match to.chain() {
+                    bitcoin_support::Chain::Bitcoin => {
+                        Ok(ActionResponseBody::BitcoinSendAmountToAddress {
+                            to,
+                            amount,
+                            network,
+                        })
+                    }
+                    bitcoin_support::Chain::Litecoin => {
+                        Ok(ActionResponseBody::LitecoinSendAmountToAddress {
+                            to,
+                            amount: amount.into(),
+                            network,
+                        })
+                    }
+                }
             }
             _ => Err(problem::unexpected_query_parameters(
                 "bitcoin::SendToAddress",
@@ -135,9 +191,19 @@ impl IntoResponsePayload for bitcoin::SpendOutput {
                         .set_detail("Query parameter fee-per-byte is not a valid float.")
                 })?;
 
+                let address = self.chain.parse_address(&address).map_err(|_| {
+                    HttpApiProblem::new("Invalid query parameter.")
+                        .set_status(StatusCode::BAD_REQUEST)
+                        .set_detail(format!(
+                            "Query parameter address is not a {} address.",
+                            self.chain.name()
+                        ))
+                })?;
+
                 let network = self.network;
+                let chain = self.chain;
                 let transaction = self
-                    .spend_to(address)
+                    .spend_to(address.into_address())
                     .sign_with_rate(fee_per_byte)
                     .map_err(|e| {
                         log::error!("Could not sign Bitcoin transaction: {:?}", e);
@@ -150,6 +216,7 @@ impl IntoResponsePayload for bitcoin::SpendOutput {
                 Ok(ActionResponseBody::bitcoin_broadcast_signed_transaction(
                     &transaction,
                     network,
+                    chain,
                 ))
             }
             _ => Err(problem::missing_query_parameters(
@@ -202,14 +269,14 @@ impl IntoResponsePayload for ethereum::DeployContract {
             data,
             amount,
             gas_limit,
-            network,
+            chain_id,
         } = self;
         match query_params {
             ActionExecutionParameters::None {} => Ok(ActionResponseBody::EthereumDeployContract {
                 data,
                 amount,
                 gas_limit,
-                network,
+                chain_id,
             }),
             _ => Err(problem::unexpected_query_parameters(
                 "ethereum::ContractDeploy",
@@ -233,16 +300,18 @@ impl IntoResponsePayload for ethereum::CallContract {
         let ethereum::CallContract {
             to,
             data,
+            amount,
             gas_limit,
-            network,
+            chain_id,
             min_block_timestamp,
         } = self;
         match query_params {
             ActionExecutionParameters::None {} => Ok(ActionResponseBody::EthereumCallContract {
                 contract_address: to,
                 data,
+                amount,
                 gas_limit,
-                network,
+                chain_id,
                 min_block_timestamp,
             }),
             _ => Err(problem::unexpected_query_parameters(
@@ -259,6 +328,116 @@ impl ListRequiredFields for ethereum::CallContract {
     }
 }
 
+impl IntoResponsePayload for ethereum::DeployOrCallContract {
+    fn into_response_payload(
+        self,
+        query_params: ActionExecutionParameters,
+    ) -> Result<ActionResponseBody, HttpApiProblem> {
+        match self {
+            ethereum::DeployOrCallContract::Deploy(deploy) => {
+                deploy.into_response_payload(query_params)
+            }
+            ethereum::DeployOrCallContract::Call(call) => call.into_response_payload(query_params),
+        }
+    }
+}
+
+impl ListRequiredFields for ethereum::DeployOrCallContract {
+    fn list_required_fields() -> Vec<siren::Field> {
+        vec![]
+    }
+}
+
+impl IntoResponsePayload for lightning::SendPayment {
+    fn into_response_payload(
+        self,
+        query_params: ActionExecutionParameters,
+    ) -> Result<ActionResponseBody, HttpApiProblem> {
+        let lightning::SendPayment {
+            to,
+            amount,
+            secret_hash,
+            final_cltv_delta,
+            network,
+        } = self;
+        match query_params {
+            ActionExecutionParameters::None {} => Ok(ActionResponseBody::LightningSendPayment {
+                to,
+                amount,
+                secret_hash,
+                final_cltv_delta,
+                network,
+            }),
+            _ => Err(problem::unexpected_query_parameters(
+                "lightning::SendPayment",
+                vec!["address".into(), "fee_per_byte".into()],
+            )),
+        }
+    }
+}
+
+impl ListRequiredFields for lightning::SendPayment {
+    fn list_required_fields() -> Vec<siren::Field> {
+        vec![]
+    }
+}
+
+impl IntoResponsePayload for lightning::AddHoldInvoice {
+    fn into_response_payload(
+        self,
+        query_params: ActionExecutionParameters,
+    ) -> Result<ActionResponseBody, HttpApiProblem> {
+        let lightning::AddHoldInvoice {
+            amount,
+            secret_hash,
+            cltv_expiry,
+            network,
+        } = self;
+        match query_params {
+            ActionExecutionParameters::None {} => Ok(ActionResponseBody::LightningAddHoldInvoice {
+                amount,
+                secret_hash,
+                cltv_expiry,
+                network,
+            }),
+            _ => Err(problem::unexpected_query_parameters(
+                "lightning::AddHoldInvoice",
+                vec!["address".into(), "fee_per_byte".into()],
+            )),
+        }
+    }
+}
+
+impl ListRequiredFields for lightning::AddHoldInvoice {
+    fn list_required_fields() -> Vec<siren::Field> {
+        vec![]
+    }
+}
+
+impl IntoResponsePayload for lightning::SettleInvoice {
+    fn into_response_payload(
+        self,
+        query_params: ActionExecutionParameters,
+    ) -> Result<ActionResponseBody, HttpApiProblem> {
+        let lightning::SettleInvoice { secret, network } = self;
+        match query_params {
+            ActionExecutionParameters::None {} => {
+                Ok(ActionResponseBody::LightningSettleInvoice { secret, network })
+            }
+            _ => Err(problem::unexpected_query_parameters(
+                "lightning::SettleInvoice",
+                vec!["address".into(), "fee_per_byte".into()],
+            )),
+        }
+    }
+}
+
+impl ListRequiredFields for lightning::SettleInvoice {
+    fn list_required_fields() -> Vec<siren::Field> {
+        vec![]
+    }
+}
+
 impl ListRequiredFields for Infallible {
     fn list_required_fields() -> Vec<siren::Field> {
         unreachable!("how did you manage to construct Infallible?")
@@ -294,7 +473,7 @@ mod test {
         assert_eq!(
             res,
             Ok(ActionExecutionParameters::BitcoinAddressAndFee {
-                address: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap(),
+                address: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".to_string(),
                 fee_per_byte: "10.59".to_string(),
             })
         );
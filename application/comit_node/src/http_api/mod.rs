@@ -26,22 +26,71 @@ use crate::{
         ledger::{FromHttpLedger, HttpLedger},
     },
     swap_protocols::{
-        ledger::{Bitcoin, Ethereum},
+        ledger::{Bitcoin, Ethereum, HtlcKind, InvoicePayment, Lightning, Litecoin},
+        rfc003::SecretHash,
         SwapProtocol,
     },
 };
-use bitcoin_support::BitcoinQuantity;
-use ethereum_support::{Erc20Token, EtherQuantity};
+use bitcoin_support::{BitcoinQuantity, LitecoinQuantity};
+use ethereum_support::{Erc20Token, Erc721Token, EtherQuantity};
 use serde::{ser::SerializeStruct, Serialize, Serializer};
 
 #[derive(Debug)]
 pub struct Http<I>(pub I);
 
-impl_serialize_http!(Bitcoin { "network" => network });
-impl_from_http_ledger!(Bitcoin { network });
+/// The HTLC kind and the relative refund timelock are only mentioned if they
+/// differ from what every peer understands.
+impl Serialize for Http<Bitcoin> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let is_default_htlc = self.0.htlc == HtlcKind::default();
+        let fields =
+            2 + usize::from(!is_default_htlc) + usize::from(self.0.refund_after_blocks.is_some());
+        let mut state = serializer.serialize_struct("", fields)?;
+
+        state.serialize_field("name", "bitcoin")?;
+        state.serialize_field("network", &self.0.network)?;
+        if !is_default_htlc {
+            state.serialize_field("htlc", &self.0.htlc)?;
+        }
+        if let Some(blocks) = self.0.refund_after_blocks {
+            state.serialize_field("refund_after_blocks", &blocks)?;
+        }
+
+        state.end()
+    }
+}
+
+impl FromHttpLedger for Bitcoin {
+    fn from_http_ledger(mut ledger: HttpLedger) -> Result<Self, ledger::Error> {
+        ledger.is_ledger("bitcoin")?;
+
+        let htlc = match ledger.parameter("htlc") {
+            Err(ledger::Error::ParameterNotFound) => HtlcKind::default(),
+            htlc => htlc?,
+        };
+        let refund_after_blocks = match ledger.parameter("refund_after_blocks") {
+            Err(ledger::Error::ParameterNotFound) => None,
+            blocks => Some(blocks?),
+        };
+
+        Ok(Bitcoin {
+            network: ledger.parameter("network")?,
+            htlc,
+            refund_after_blocks,
+        })
+    }
+}
 impl_serialize_http!(BitcoinQuantity := "bitcoin" { "quantity" });
 impl_from_http_quantity_asset!(BitcoinQuantity, Bitcoin);
 
+impl_serialize_http!(Litecoin { "network" => network });
+impl_from_http_ledger!(Litecoin { network });
+impl_serialize_http!(LitecoinQuantity := "litecoin" { "quantity" });
+impl_from_http_quantity_asset!(LitecoinQuantity, Litecoin);
+
 impl Serialize for Http<bitcoin_support::Transaction> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -60,12 +109,106 @@ impl Serialize for Http<bitcoin_support::PubkeyHash> {
     }
 }
 
-impl_serialize_http!(Ethereum { "network" => network });
-impl_from_http_ledger!(Ethereum { network });
+impl_serialize_http!(Lightning { "network" => network });
+impl_from_http_ledger!(Lightning { network });
+
+/// A payment is identified by the hash it is locked to, just like the hold
+/// invoice it pays.
+impl Serialize for Http<InvoicePayment> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.payment_hash.serialize(serializer)
+    }
+}
+
+impl Serialize for Http<SecretHash> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+impl Serialize for Http<secp256k1_support::PublicKey> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+/// The contracts HTLCs can be deployed through or kept in are only mentioned
+/// if there are any.
+impl Serialize for Http<Ethereum> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let fields = 2
+            + usize::from(self.0.erc20_htlc_factory.is_some())
+            + usize::from(self.0.ether_htlc_registry.is_some())
+            + usize::from(self.0.create2_htlc_factory.is_some());
+        let mut state = serializer.serialize_struct("", fields)?;
+
+        state.serialize_field("name", "ethereum")?;
+        state.serialize_field("chain_id", &self.0.chain_id)?;
+        if let Some(factory) = self.0.erc20_htlc_factory {
+            state.serialize_field("erc20_htlc_factory", &factory)?;
+        }
+        if let Some(registry) = self.0.ether_htlc_registry {
+            state.serialize_field("ether_htlc_registry", &registry)?;
+        }
+        if let Some(factory) = self.0.create2_htlc_factory {
+            state.serialize_field("create2_htlc_factory", &factory)?;
+        }
+
+        state.end()
+    }
+}
 impl_serialize_http!(EtherQuantity := "ether" { "quantity" });
 impl_serialize_http!(Erc20Token := "erc20" { "quantity" => quantity, "token_contract" => token_contract });
+impl_serialize_http!(Erc721Token := "erc721" { "token_id" => token_id, "token_contract" => token_contract });
 impl_from_http_quantity_asset!(EtherQuantity, Ether);
 
+impl FromHttpLedger for Ethereum {
+    fn from_http_ledger(mut ledger: HttpLedger) -> Result<Self, ledger::Error> {
+        ledger.is_ledger("ethereum")?;
+
+        // `network` predates arbitrary EVM chains and can only name the
+        // well-known ones.
+        let chain_id = match ledger.parameter("chain_id") {
+            Err(ledger::Error::ParameterNotFound) => ledger
+                .parameter::<ethereum_support::Network>("network")?
+                .chain_id()
+                .ok_or(ledger::Error::UnknownNetwork)?,
+            chain_id => chain_id?,
+        };
+        let erc20_htlc_factory = match ledger.parameter("erc20_htlc_factory") {
+            Err(ledger::Error::ParameterNotFound) => None,
+            factory => Some(factory?),
+        };
+        let ether_htlc_registry = match ledger.parameter("ether_htlc_registry") {
+            Err(ledger::Error::ParameterNotFound) => None,
+            registry => Some(registry?),
+        };
+        let create2_htlc_factory = match ledger.parameter("create2_htlc_factory") {
+            Err(ledger::Error::ParameterNotFound) => None,
+            factory => Some(factory?),
+        };
+
+        Ok(Ethereum {
+            chain_id,
+            erc20_htlc_factory,
+            ether_htlc_registry,
+            create2_htlc_factory,
+        })
+    }
+}
+
 impl FromHttpAsset for Erc20Token {
     fn from_http_asset(mut asset: HttpAsset) -> Result<Self, asset::Error> {
         asset.is_asset("erc20")?;
@@ -77,6 +220,17 @@ impl FromHttpAsset for Erc20Token {
     }
 }
 
+impl FromHttpAsset for Erc721Token {
+    fn from_http_asset(mut asset: HttpAsset) -> Result<Self, asset::Error> {
+        asset.is_asset("erc721")?;
+
+        Ok(Erc721Token::new(
+            asset.parameter("token_contract")?,
+            asset.parameter("token_id")?,
+        ))
+    }
+}
+
 impl Serialize for Http<ethereum_support::Transaction> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -119,14 +273,26 @@ impl Serialize for Http<SwapProtocol> {
 #[cfg(test)]
 mod tests {
     use crate::{
-        http_api::Http,
-        swap_protocols::ledger::{Bitcoin, Ethereum},
+        http_api::{
+            asset::{FromHttpAsset, HttpAsset},
+            ledger::{FromHttpLedger, HttpLedger},
+            Http,
+        },
+        swap_protocols::{
+            ledger::{
+                Bitcoin, Ethereum, HtlcKind, InvoicePayment, InvoicePaymentState, Lightning,
+                Litecoin,
+            },
+            rfc003::{HashFunction, Secret},
+        },
     };
     use bitcoin_support::{
-        self, BitcoinQuantity, FromHex, OutPoint, PubkeyHash, Script, Sha256dHash, TxIn,
+        self, BitcoinQuantity, FromHex, LitecoinQuantity, OutPoint, PubkeyHash, Script,
+        Sha256dHash, TxIn,
     };
     use ethereum_support::{
-        self, Address, Bytes, Erc20Quantity, Erc20Token, EtherQuantity, H160, H256, U256,
+        self, Address, Bytes, Erc20Quantity, Erc20Token, Erc721Token, EtherQuantity, H160, H256,
+        U256,
     };
     use std::convert::TryFrom;
 
@@ -158,10 +324,25 @@ mod tests {
         assert_eq!(&pay_serialized, r#"{"name":"erc20","quantity":"100000000000","token_contract":"0xb97048628db6b661d4c2aa833e95dbe1a905b280"}"#);
     }
 
+    #[test]
+    fn http_erc721_token_roundtrips_through_json() {
+        let token = Erc721Token::new(
+            Address::from("0xB97048628DB6B661D4C2aA833e95Dbe1A905B280"),
+            U256::from(42),
+        );
+        let json = r#"{"name":"erc721","token_id":"0x2a","token_contract":"0xb97048628db6b661d4c2aa833e95dbe1a905b280"}"#;
+
+        assert_eq!(&serde_json::to_string(&Http(token)).unwrap(), json);
+
+        let asset: HttpAsset = serde_json::from_str(json).unwrap();
+
+        assert_eq!(Erc721Token::from_http_asset(asset).unwrap(), token);
+    }
+
     #[test]
     fn http_ledger_serializes_correctly_to_json() {
         let bitcoin = Bitcoin::new(bitcoin_support::Network::Regtest);
-        let ethereum = Ethereum::new(ethereum_support::Network::Regtest);
+        let ethereum = Ethereum::new(ethereum_support::ChainId::REGTEST);
 
         let bitcoin = Http(bitcoin);
         let ethereum = Http(ethereum);
@@ -173,9 +354,143 @@ mod tests {
             &bitcoin_serialized,
             r#"{"name":"bitcoin","network":"regtest"}"#
         );
+        assert_eq!(&ethereum_serialized, r#"{"name":"ethereum","chain_id":17}"#);
+    }
+
+    #[test]
+    fn http_bitcoin_ledger_mentions_htlc_kind_only_if_not_default() {
+        let taproot = Bitcoin {
+            network: bitcoin_support::Network::Regtest,
+            htlc: HtlcKind::Taproot,
+            refund_after_blocks: None,
+        };
+
+        assert_eq!(
+            &serde_json::to_string(&Http(taproot)).unwrap(),
+            r#"{"name":"bitcoin","network":"regtest","htlc":"taproot"}"#
+        );
+
+        let with_htlc: HttpLedger =
+            serde_json::from_str(r#"{"name":"bitcoin","network":"regtest","htlc":"taproot"}"#)
+                .unwrap();
+        let without_htlc: HttpLedger =
+            serde_json::from_str(r#"{"name":"bitcoin","network":"regtest"}"#).unwrap();
+
+        assert_eq!(Bitcoin::from_http_ledger(with_htlc).unwrap(), taproot);
+        assert_eq!(
+            Bitcoin::from_http_ledger(without_htlc).unwrap(),
+            Bitcoin::new(bitcoin_support::Network::Regtest)
+        );
+    }
+
+    #[test]
+    fn http_bitcoin_ledger_mentions_refund_after_blocks_only_if_set() {
+        let relative = Bitcoin {
+            refund_after_blocks: Some(144),
+            ..Bitcoin::new(bitcoin_support::Network::Regtest)
+        };
+
+        assert_eq!(
+            &serde_json::to_string(&Http(relative)).unwrap(),
+            r#"{"name":"bitcoin","network":"regtest","refund_after_blocks":144}"#
+        );
+
+        let ledger: HttpLedger = serde_json::from_str(
+            r#"{"name":"bitcoin","network":"regtest","refund_after_blocks":144}"#,
+        )
+        .unwrap();
+
+        assert_eq!(Bitcoin::from_http_ledger(ledger).unwrap(), relative);
+    }
+
+    #[test]
+    fn http_ethereum_ledger_mentions_htlc_contracts_only_if_set() {
+        let regtest = Ethereum::new(ethereum_support::ChainId::REGTEST);
+        let cases = vec![
+            (
+                Ethereum {
+                    erc20_htlc_factory: Some(ethereum_support::Address::from([1u8; 20])),
+                    ..regtest
+                },
+                r#"{"name":"ethereum","chain_id":17,"erc20_htlc_factory":"0x0101010101010101010101010101010101010101"}"#,
+            ),
+            (
+                Ethereum {
+                    ether_htlc_registry: Some(ethereum_support::Address::from([2u8; 20])),
+                    ..regtest
+                },
+                r#"{"name":"ethereum","chain_id":17,"ether_htlc_registry":"0x0202020202020202020202020202020202020202"}"#,
+            ),
+            (
+                Ethereum {
+                    create2_htlc_factory: Some(ethereum_support::Address::from([3u8; 20])),
+                    ..regtest
+                },
+                r#"{"name":"ethereum","chain_id":17,"create2_htlc_factory":"0x0303030303030303030303030303030303030303"}"#,
+            ),
+        ];
+
+        for (ethereum, json) in cases {
+            assert_eq!(&serde_json::to_string(&Http(ethereum)).unwrap(), json);
+
+            let ledger: HttpLedger = serde_json::from_str(json).unwrap();
+
+            assert_eq!(Ethereum::from_http_ledger(ledger).unwrap(), ethereum);
+        }
+    }
+
+    #[test]
+    fn http_ethereum_ledger_is_identified_by_chain_id_or_network() {
+        let by_chain_id: HttpLedger =
+            serde_json::from_str(r#"{"name":"ethereum","chain_id":1337}"#).unwrap();
+        let by_network: HttpLedger =
+            serde_json::from_str(r#"{"name":"ethereum","network":"ropsten"}"#).unwrap();
+        let by_unknown_network: HttpLedger =
+            serde_json::from_str(r#"{"name":"ethereum","network":"unknown"}"#).unwrap();
+
+        assert_eq!(
+            Ethereum::from_http_ledger(by_chain_id).unwrap(),
+            Ethereum::new(ethereum_support::ChainId::new(1337))
+        );
+        assert_eq!(
+            Ethereum::from_http_ledger(by_network).unwrap(),
+            Ethereum::new(ethereum_support::ChainId::ROPSTEN)
+        );
+        assert!(Ethereum::from_http_ledger(by_unknown_network).is_err());
+    }
+
+    #[test]
+    fn http_litecoin_serializes_correctly_to_json() {
+        let litecoin = Http(Litecoin::new(bitcoin_support::Network::Testnet));
+        let quantity = Http(LitecoinQuantity::from_litoshi(150_000_000));
+
+        assert_eq!(
+            &serde_json::to_string(&litecoin).unwrap(),
+            r#"{"name":"litecoin","network":"testnet"}"#
+        );
+        assert_eq!(
+            &serde_json::to_string(&quantity).unwrap(),
+            r#"{"name":"litecoin","quantity":"150000000"}"#
+        );
+    }
+
+    #[test]
+    fn http_lightning_serializes_correctly_to_json() {
+        let lightning = Http(Lightning::new(bitcoin_support::Network::Regtest));
+        let secret = Secret::from(*b"hello world, you are beautiful!!");
+        let invoice_payment = Http(InvoicePayment {
+            payment_hash: secret.hash(HashFunction::Sha256),
+            amount: BitcoinQuantity::from_satoshi(100_000),
+            state: InvoicePaymentState::Accepted,
+        });
+
+        assert_eq!(
+            &serde_json::to_string(&lightning).unwrap(),
+            r#"{"name":"lightning","network":"regtest"}"#
+        );
         assert_eq!(
-            &ethereum_serialized,
-            r#"{"name":"ethereum","network":"regtest"}"#
+            serde_json::to_string(&invoice_payment).unwrap(),
+            format!(r#""{:x}""#, secret.hash(HashFunction::Sha256))
         );
     }
 
@@ -52,6 +52,34 @@ pub fn unsupported() -> HttpApiProblem {
         .set_detail("The requested combination of ledgers and assets is not supported.")
 }
 
+pub fn invalid_previous_hop(reason: &str) -> HttpApiProblem {
+    log::error!("Cannot forward swap: {}", reason);
+    HttpApiProblem::new("Invalid previous hop.")
+        .set_status(StatusCode::BAD_REQUEST)
+        .set_detail(reason.to_owned())
+}
+
+pub fn invalid_partial_fill(reason: &str) -> HttpApiProblem {
+    log::error!("Cannot fill swap partially: {}", reason);
+    HttpApiProblem::new("Invalid partial fill.")
+        .set_status(StatusCode::BAD_REQUEST)
+        .set_detail(reason.to_owned())
+}
+
+pub fn invalid_split(reason: &str) -> HttpApiProblem {
+    log::error!("Cannot split swap: {}", reason);
+    HttpApiProblem::new("Invalid split.")
+        .set_status(StatusCode::BAD_REQUEST)
+        .set_detail(reason.to_owned())
+}
+
+pub fn next_hop_not_accepted() -> HttpApiProblem {
+    log::error!("Swap cannot be accepted before its next hop");
+    HttpApiProblem::new("Next hop not accepted.")
+        .set_status(StatusCode::CONFLICT)
+        .set_detail("A forwarded swap can only be accepted once its next hop has been accepted.")
+}
+
 pub fn deserialize(e: serde_json::Error) -> HttpApiProblem {
     log::error!("Failed to deserialize body: {:?}", e);
     HttpApiProblem::new("Invalid body.")
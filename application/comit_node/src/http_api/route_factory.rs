@@ -39,6 +39,8 @@ pub fn create<T: MetadataStore<SwapId>, S: state_store::StateStore, C: Client, B
         .and(warp::path::end())
         .and(warp::post2())
         .and(protocol_dependencies.clone())
+        .and(metadata_store.clone())
+        .and(state_store.clone())
         .and(warp::body::json())
         .and_then(http_api::routes::rfc003::post_swap);
 
@@ -1,5 +1,8 @@
 use crate::{
-    http_api::swap_resource::{build_rfc003_siren_entity, IncludeState},
+    http_api::{
+        routes::rfc003::hops::Hops,
+        swap_resource::{build_rfc003_siren_entity, IncludeState},
+    },
     swap_protocols::{rfc003::state_store::StateStore, MetadataStore, SwapId},
 };
 use http_api_problem::HttpApiProblem;
@@ -10,8 +13,17 @@ pub fn handle_get_swaps<T: MetadataStore<SwapId>, S: StateStore>(
 ) -> Result<siren::Entity, HttpApiProblem> {
     let mut entity = siren::Entity::default().with_class_member("swaps");
 
-    for (id, metadata) in metadata_store.all()?.into_iter() {
-        let sub_entity = build_rfc003_siren_entity(state_store, id, metadata, IncludeState::No)?;
+    let swaps = metadata_store.all()?;
+
+    for (id, metadata) in swaps.iter().cloned() {
+        let hops = Hops::load(metadata_store, state_store, &id, &metadata)?;
+        let splits = swaps
+            .iter()
+            .filter(|(_, split)| split.split_from == Some(id))
+            .map(|(split, _)| *split)
+            .collect();
+        let sub_entity =
+            build_rfc003_siren_entity(state_store, id, metadata, hops, splits, IncludeState::No)?;
         entity.push_sub_entity(siren::SubEntity::from_entity(sub_entity, &["item"]));
     }
 
@@ -1,10 +1,11 @@
 use crate::{
     http_api::action::ListRequiredFields,
     swap_protocols::{
-        ledger::{Bitcoin, Ethereum},
+        ledger::{Ethereum, Lightning},
         rfc003::{
             actions::Accept,
-            messages::{AcceptResponseBody, IntoAcceptResponseBody},
+            bitcoin::UtxoLedger,
+            messages::{AcceptResponseBody, IntoAcceptResponseBody, PartialFill},
             Ledger, SecretSource,
         },
     },
@@ -16,7 +17,7 @@ pub struct OnlyRedeem<L: Ledger> {
     pub alpha_ledger_redeem_identity: L::Identity,
 }
 
-impl ListRequiredFields for Accept<Ethereum, Bitcoin> {
+impl<L: UtxoLedger> ListRequiredFields for Accept<Ethereum, L> {
     fn list_required_fields() -> Vec<siren::Field> {
         vec![siren::Field {
             name: "alpha_ledger_redeem_identity".to_owned(),
@@ -28,14 +29,17 @@ impl ListRequiredFields for Accept<Ethereum, Bitcoin> {
     }
 }
 
-impl IntoAcceptResponseBody<Ethereum, Bitcoin> for OnlyRedeem<Ethereum> {
+impl<L: UtxoLedger> IntoAcceptResponseBody<Ethereum, L> for OnlyRedeem<Ethereum> {
     fn into_accept_response_body(
         self,
+        _: Ethereum,
+        beta_ledger: L,
         secret_source: &dyn SecretSource,
-    ) -> AcceptResponseBody<Ethereum, Bitcoin> {
+    ) -> AcceptResponseBody<Ethereum, L> {
         AcceptResponseBody {
             alpha_ledger_redeem_identity: self.alpha_ledger_redeem_identity,
-            beta_ledger_refund_identity: secret_source.secp256k1_refund().into(),
+            beta_ledger_refund_identity: beta_ledger.identity(secret_source.secp256k1_refund()),
+            partial_fill: None,
         }
     }
 }
@@ -45,7 +49,7 @@ pub struct OnlyRefund<L: Ledger> {
     pub beta_ledger_refund_identity: L::Identity,
 }
 
-impl ListRequiredFields for Accept<Bitcoin, Ethereum> {
+impl<L: UtxoLedger> ListRequiredFields for Accept<L, Ethereum> {
     fn list_required_fields() -> Vec<siren::Field> {
         vec![siren::Field {
             name: "beta_ledger_refund_identity".to_owned(),
@@ -57,14 +61,152 @@ impl ListRequiredFields for Accept<Bitcoin, Ethereum> {
     }
 }
 
-impl IntoAcceptResponseBody<Bitcoin, Ethereum> for OnlyRefund<Ethereum> {
+impl<L: UtxoLedger> IntoAcceptResponseBody<L, Ethereum> for OnlyRefund<Ethereum> {
     fn into_accept_response_body(
         self,
+        alpha_ledger: L,
+        _: Ethereum,
         secret_source: &dyn SecretSource,
-    ) -> AcceptResponseBody<Bitcoin, Ethereum> {
+    ) -> AcceptResponseBody<L, Ethereum> {
         AcceptResponseBody {
             beta_ledger_refund_identity: self.beta_ledger_refund_identity,
-            alpha_ledger_redeem_identity: secret_source.secp256k1_redeem().into(),
+            alpha_ledger_redeem_identity: alpha_ledger.identity(secret_source.secp256k1_redeem()),
+            partial_fill: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct RedeemAndRefund<AL: Ledger, BL: Ledger> {
+    pub alpha_ledger_redeem_identity: AL::Identity,
+    pub beta_ledger_refund_identity: BL::Identity,
+}
+
+impl ListRequiredFields for Accept<Ethereum, Ethereum> {
+    fn list_required_fields() -> Vec<siren::Field> {
+        vec![
+            siren::Field {
+                name: "alpha_ledger_redeem_identity".to_owned(),
+                class: vec!["ethereum".to_owned(), "address".to_owned()],
+                _type: Some("text".to_owned()),
+                value: None,
+                title: Some("Alpha ledger redeem identity".to_owned()),
+            },
+            siren::Field {
+                name: "beta_ledger_refund_identity".to_owned(),
+                class: vec!["ethereum".to_owned(), "address".to_owned()],
+                _type: Some("text".to_owned()),
+                value: None,
+                title: Some("Beta ledger refund identity".to_owned()),
+            },
+        ]
+    }
+}
+
+impl IntoAcceptResponseBody<Ethereum, Ethereum> for RedeemAndRefund<Ethereum, Ethereum> {
+    fn into_accept_response_body(
+        self,
+        _: Ethereum,
+        _: Ethereum,
+        _: &dyn SecretSource,
+    ) -> AcceptResponseBody<Ethereum, Ethereum> {
+        AcceptResponseBody {
+            alpha_ledger_redeem_identity: self.alpha_ledger_redeem_identity,
+            beta_ledger_refund_identity: self.beta_ledger_refund_identity,
+            partial_fill: None,
+        }
+    }
+}
+
+impl ListRequiredFields for Accept<Lightning, Ethereum> {
+    fn list_required_fields() -> Vec<siren::Field> {
+        vec![
+            siren::Field {
+                name: "alpha_ledger_redeem_identity".to_owned(),
+                class: vec!["lightning".to_owned(), "public_key".to_owned()],
+                _type: Some("text".to_owned()),
+                value: None,
+                title: Some("Alpha ledger redeem identity".to_owned()),
+            },
+            siren::Field {
+                name: "beta_ledger_refund_identity".to_owned(),
+                class: vec!["ethereum".to_owned(), "address".to_owned()],
+                _type: Some("text".to_owned()),
+                value: None,
+                title: Some("Beta ledger refund identity".to_owned()),
+            },
+        ]
+    }
+}
+
+/// Neither identity can be derived from the secret source: Bob receives the
+/// alpha asset on his lnd node.
+impl IntoAcceptResponseBody<Lightning, Ethereum> for RedeemAndRefund<Lightning, Ethereum> {
+    fn into_accept_response_body(
+        self,
+        _: Lightning,
+        _: Ethereum,
+        _: &dyn SecretSource,
+    ) -> AcceptResponseBody<Lightning, Ethereum> {
+        AcceptResponseBody {
+            alpha_ledger_redeem_identity: self.alpha_ledger_redeem_identity,
+            beta_ledger_refund_identity: self.beta_ledger_refund_identity,
+            partial_fill: None,
+        }
+    }
+}
+
+/// Accepts a swap we forwarded to a next hop. Our peer on the next hop funds
+/// the beta HTLC in our place, hence it is refunded to the identity they chose.
+#[derive(Clone, Debug)]
+pub struct Forwarded<P, I> {
+    pub partial_response: P,
+    pub beta_ledger_refund_identity: I,
+}
+
+impl<AL: Ledger, BL: Ledger, P: IntoAcceptResponseBody<AL, BL>> IntoAcceptResponseBody<AL, BL>
+    for Forwarded<P, BL::Identity>
+{
+    fn into_accept_response_body(
+        self,
+        alpha_ledger: AL,
+        beta_ledger: BL,
+        secret_source: &dyn SecretSource,
+    ) -> AcceptResponseBody<AL, BL> {
+        AcceptResponseBody {
+            beta_ledger_refund_identity: self.beta_ledger_refund_identity,
+            ..self.partial_response.into_accept_response_body(
+                alpha_ledger,
+                beta_ledger,
+                secret_source,
+            )
+        }
+    }
+}
+
+/// Accepts a swap for less of both assets than requested
+#[derive(Clone, Debug)]
+pub struct PartiallyFilled<P> {
+    pub partial_response: P,
+    pub partial_fill: Option<PartialFill>,
+}
+
+impl<AL: Ledger, BL: Ledger, P: IntoAcceptResponseBody<AL, BL>> IntoAcceptResponseBody<AL, BL>
+    for PartiallyFilled<P>
+{
+    fn into_accept_response_body(
+        self,
+        alpha_ledger: AL,
+        beta_ledger: BL,
+        secret_source: &dyn SecretSource,
+    ) -> AcceptResponseBody<AL, BL> {
+        AcceptResponseBody {
+            partial_fill: self.partial_fill,
+            ..self.partial_response.into_accept_response_body(
+                alpha_ledger,
+                beta_ledger,
+                secret_source,
+            )
         }
     }
 }
@@ -6,18 +6,26 @@ use crate::{
         },
         problem,
         route_factory::new_action_link,
-        routes::rfc003::decline::DeclineBody,
+        routes::rfc003::{
+            accept::{Forwarded, PartiallyFilled},
+            decline::DeclineBody,
+            hops::{self, Hops},
+        },
     },
     swap_protocols::{
         actions::Actions,
+        asset::Asset,
         rfc003::{
-            actions::{Action, ActionKind},
+            actions::{Accept, Action, ActionKind},
+            messages::{IntoAcceptResponseBody, PartialFill, Request},
             state_store::StateStore,
+            Ledger,
         },
         MetadataStore, SwapId,
     },
 };
 use http_api_problem::HttpApiProblem;
+use serde::de::DeserializeOwned;
 use std::fmt::Debug;
 
 #[allow(clippy::unit_arg, clippy::let_unit_value)]
@@ -34,6 +42,16 @@ pub fn handle_action<T: MetadataStore<SwapId>, S: StateStore>(
         .get(&id)?
         .ok_or_else(problem::swap_not_found)?;
 
+    let hops = Hops::load(metadata_store, state_store, &id, &metadata)?;
+    let next_hop_refund_identity = match (action_kind, hops.next) {
+        (ActionKind::Accept, Some(next_hop)) => Some(hops::next_hop_beta_refund_identity(
+            metadata_store,
+            state_store,
+            next_hop,
+        )?),
+        _ => None,
+    };
+
     with_swap_types!(
         &metadata,
         (|| {
@@ -45,16 +63,15 @@ pub fn handle_action<T: MetadataStore<SwapId>, S: StateStore>(
             state
                 .actions()
                 .into_iter()
+                .filter(|action| hops.is_ours(action))
                 .select_action(action_kind, method)
                 .and_then(|action| match action {
-                    Action::Accept(action) => serde_json::from_value::<AcceptBody>(body)
-                        .map_err(problem::deserialize)
-                        .and_then(|body| {
-                            action
-                                .accept(body)
-                                .map(|_| ActionResponseBody::None)
-                                .map_err(|_| problem::action_already_done(action_kind))
-                        }),
+                    Action::Accept(action) => accept::<AL, BL, AA, BA, AcceptBody>(
+                        &action,
+                        state.request(),
+                        body,
+                        next_hop_refund_identity.clone(),
+                    ),
                     Action::Decline(action) => serde_json::from_value::<DeclineBody>(body)
                         .map_err(problem::deserialize)
                         .and_then(|body| {
@@ -72,6 +89,74 @@ pub fn handle_action<T: MetadataStore<SwapId>, S: StateStore>(
     )
 }
 
+fn accept<
+    AL: Ledger,
+    BL: Ledger,
+    AA: Asset,
+    BA: Asset,
+    B: DeserializeOwned + IntoAcceptResponseBody<AL, BL>,
+>(
+    action: &Accept<AL, BL>,
+    request: Request<AL, BL, AA, BA>,
+    mut body: serde_json::Value,
+    next_hop_refund_identity: Option<serde_json::Value>,
+) -> Result<ActionResponseBody, HttpApiProblem> {
+    let partial_fill = match body
+        .as_object_mut()
+        .and_then(|body| body.remove("partial_fill"))
+    {
+        Some(partial_fill) => serde_json::from_value::<Option<PartialFill>>(partial_fill)
+            .map_err(problem::deserialize)?,
+        None => None,
+    };
+    if partial_fill.is_some() {
+        if next_hop_refund_identity.is_some() {
+            return Err(problem::invalid_partial_fill(
+                "A forwarded swap has to be filled completely.",
+            ));
+        }
+        if request.fill(partial_fill).is_none() {
+            return Err(problem::invalid_partial_fill(
+                "A swap can only be filled with less of both assets at a rate no worse for the requester.",
+            ));
+        }
+    }
+
+    let accepted = match next_hop_refund_identity {
+        None => {
+            let partial_response =
+                serde_json::from_value::<B>(body).map_err(problem::deserialize)?;
+            action.accept(PartiallyFilled {
+                partial_response,
+                partial_fill,
+            })
+        }
+        Some(beta_ledger_refund_identity) => {
+            // The next hop funds the beta HTLC, whatever the body says
+            if let Some(body) = body.as_object_mut() {
+                body.insert(
+                    "beta_ledger_refund_identity".to_owned(),
+                    beta_ledger_refund_identity.clone(),
+                );
+            }
+            let partial_response =
+                serde_json::from_value::<B>(body).map_err(problem::deserialize)?;
+            let beta_ledger_refund_identity =
+                serde_json::from_value::<BL::Identity>(beta_ledger_refund_identity)
+                    .map_err(problem::deserialize)?;
+
+            action.accept(Forwarded {
+                partial_response,
+                beta_ledger_refund_identity,
+            })
+        }
+    };
+
+    accepted
+        .map(|_| ActionResponseBody::None)
+        .map_err(|_| problem::action_already_done(ActionKind::Accept))
+}
+
 trait SelectAction<Accept, Decline, Deploy, Fund, Redeem, Refund>:
     Iterator<Item = Action<Accept, Decline, Deploy, Fund, Redeem, Refund>>
 {
@@ -1,9 +1,10 @@
 use crate::{
     http_api::{
         problem,
+        routes::rfc003::hops::Hops,
         swap_resource::{build_rfc003_siren_entity, IncludeState},
     },
-    swap_protocols::{rfc003::state_store::StateStore, MetadataStore, SwapId},
+    swap_protocols::{metadata_store, rfc003::state_store::StateStore, MetadataStore, SwapId},
 };
 use http_api_problem::HttpApiProblem;
 
@@ -12,9 +13,33 @@ pub fn handle_get_swap<T: MetadataStore<SwapId>, S: StateStore>(
     state_store: &S,
     id: SwapId,
 ) -> Result<siren::Entity, HttpApiProblem> {
+    let (mut entity, hops) = swap_entity(metadata_store, state_store, id)?;
+
+    // A forwarded swap embeds its other hop, which shows the whole route at once
+    for (rel, hop) in &[("previous-hop", hops.previous), ("next-hop", hops.next)] {
+        if let Some(hop) = hop {
+            let (hop_entity, _) = swap_entity(metadata_store, state_store, *hop)?;
+            entity.push_sub_entity(siren::SubEntity::from_entity(hop_entity, &[*rel]));
+        }
+    }
+
+    Ok(entity)
+}
+
+fn swap_entity<T: MetadataStore<SwapId>, S: StateStore>(
+    metadata_store: &T,
+    state_store: &S,
+    id: SwapId,
+) -> Result<(siren::Entity, Hops), HttpApiProblem> {
     let metadata = metadata_store
         .get(&id)?
         .ok_or_else(problem::swap_not_found)?;
 
-    build_rfc003_siren_entity(state_store, id, metadata, IncludeState::Yes)
+    let hops = Hops::load(metadata_store, state_store, &id, &metadata)?;
+    let splits = metadata_store::splits(metadata_store, &id)?;
+
+    let entity =
+        build_rfc003_siren_entity(state_store, id, metadata, hops, splits, IncludeState::Yes)?;
+
+    Ok((entity, hops))
 }
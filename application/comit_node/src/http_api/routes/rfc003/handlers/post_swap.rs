@@ -1,36 +1,91 @@
 use crate::{
-    http_api::{self, asset::HttpAsset, ledger::HttpLedger, problem},
+    http_api::{
+        self,
+        asset::HttpAsset,
+        ledger::HttpLedger,
+        problem,
+        routes::rfc003::hops::{self, PreviousHop},
+    },
     swap_protocols::{
-        asset::Asset,
-        ledger::{Bitcoin, Ethereum},
-        rfc003::{self, alice::AliceSpawner, messages::ToRequest, Ledger, SecretSource},
-        SwapId, Timestamp,
+        asset::{self, Asset, AssetKind},
+        ledger::{Bitcoin, Ethereum, Lightning, Litecoin},
+        metadata_store,
+        rfc003::{
+            self,
+            alice::{self, AliceSpawner, SwapCommunication},
+            bitcoin::UtxoLedger,
+            messages::ToRequest,
+            state_store::StateStore,
+            CreateLedgerEvents, HashFunction, Ledger, SecretHash, SecretSource,
+        },
+        LedgerEventDependencies, LedgerKind, Metadata, MetadataStore, RoleKind, SwapId, Timestamp,
     },
 };
-use bitcoin_support::BitcoinQuantity;
-use ethereum_support::{Erc20Token, EtherQuantity};
+use bitcoin_support::{BitcoinQuantity, LitecoinQuantity};
+use ethereum_support::{Erc20Token, Erc721Token, EtherQuantity, U256};
 use http_api_problem::{HttpApiProblem, StatusCode as HttpStatusCode};
 use libp2p::PeerId;
 use serde::{Deserialize, Serialize};
 
-pub fn handle_post_swap<A: AliceSpawner>(
+pub fn handle_post_swap<A: AliceSpawner, T: MetadataStore<SwapId>, S: StateStore>(
     alice_spawner: &A,
+    metadata_store: &T,
+    state_store: &S,
     request_body_kind: SwapRequestBodyKind,
 ) -> Result<SwapCreated, HttpApiProblem> {
     let id = SwapId::default();
 
     match request_body_kind {
         SwapRequestBodyKind::BitcoinEthereumBitcoinQuantityErc20Token(body) => {
-            alice_spawner.spawn(id, body.peer.clone(), Box::new(body))?
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
         }
         SwapRequestBodyKind::BitcoinEthereumBitcoinQuantityEtherQuantity(body) => {
-            alice_spawner.spawn(id, body.peer.clone(), Box::new(body))?
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
         }
         SwapRequestBodyKind::EthereumBitcoinEtherQuantityBitcoinQuantity(body) => {
-            alice_spawner.spawn(id, body.peer.clone(), Box::new(body))?
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
         }
         SwapRequestBodyKind::EthereumBitcoinErc20TokenBitcoinQuantity(body) => {
-            alice_spawner.spawn(id, body.peer.clone(), Box::new(body))?
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
+        }
+        SwapRequestBodyKind::BitcoinEthereumBitcoinQuantityErc721Token(body) => {
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
+        }
+        SwapRequestBodyKind::EthereumBitcoinErc721TokenBitcoinQuantity(body) => {
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
+        }
+        SwapRequestBodyKind::LitecoinEthereumLitecoinQuantityErc20Token(body) => {
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
+        }
+        SwapRequestBodyKind::LitecoinEthereumLitecoinQuantityEtherQuantity(body) => {
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
+        }
+        SwapRequestBodyKind::EthereumLitecoinEtherQuantityLitecoinQuantity(body) => {
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
+        }
+        SwapRequestBodyKind::EthereumLitecoinErc20TokenLitecoinQuantity(body) => {
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
+        }
+        SwapRequestBodyKind::EthereumEthereumEtherQuantityErc20Token(body) => {
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
+        }
+        SwapRequestBodyKind::EthereumEthereumErc20TokenEtherQuantity(body) => {
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
+        }
+        SwapRequestBodyKind::EthereumEthereumErc20TokenErc20Token(body) => {
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
+        }
+        SwapRequestBodyKind::EthereumEthereumEtherQuantityErc721Token(body) => {
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
+        }
+        SwapRequestBodyKind::EthereumEthereumErc721TokenEtherQuantity(body) => {
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
+        }
+        SwapRequestBodyKind::LightningEthereumBitcoinQuantityEtherQuantity(body) => {
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
+        }
+        SwapRequestBodyKind::LightningEthereumBitcoinQuantityErc20Token(body) => {
+            spawn(alice_spawner, metadata_store, state_store, id, body)?
         }
         SwapRequestBodyKind::UnsupportedCombination(body) => {
             log::error!(
@@ -58,6 +113,140 @@ pub fn handle_post_swap<A: AliceSpawner>(
     Ok(SwapCreated { id })
 }
 
+fn spawn<
+    A: AliceSpawner,
+    T: MetadataStore<SwapId>,
+    S: StateStore,
+    AL: Ledger,
+    BL: Ledger,
+    AA: Asset,
+    BA: Asset,
+    I: ToIdentities<AL, BL> + 'static,
+>(
+    alice_spawner: &A,
+    metadata_store: &T,
+    state_store: &S,
+    id: SwapId,
+    body: SwapRequestBody<AL, BL, AA, BA, I>,
+) -> Result<(), HttpApiProblem>
+where
+    LedgerEventDependencies: CreateLedgerEvents<AL, AA> + CreateLedgerEvents<BL, BA>,
+{
+    let peer = body.peer.clone();
+
+    match (body.previous_hop, body.split_from) {
+        (None, None) => alice_spawner.spawn(id, peer, Box::new(body))?,
+        (Some(previous_hop), None) => {
+            let previous_hop = hops::load_previous_hop(metadata_store, state_store, previous_hop)?;
+            alice_spawner.spawn(id, peer, Box::new(body.forward(previous_hop)?))?
+        }
+        (None, Some(split_from)) => {
+            let parent = load_split_parent(metadata_store, state_store, split_from)?;
+            let remaining = remaining_alpha_asset::<_, _, AL, BL, AA, BA>(
+                metadata_store,
+                state_store,
+                split_from,
+            )?;
+            body.validate_split(&parent, remaining)?;
+            alice_spawner.spawn(id, peer, Box::new(body))?
+        }
+        (Some(_), Some(_)) => {
+            return Err(problem::invalid_split(
+                "A swap cannot both forward and split another swap.",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn load_split_parent<
+    T: MetadataStore<SwapId>,
+    S: StateStore,
+    AL: Ledger,
+    BL: Ledger,
+    AA: Asset,
+    BA: Asset,
+>(
+    metadata_store: &T,
+    state_store: &S,
+    id: SwapId,
+) -> Result<rfc003::messages::Request<AL, BL, AA, BA>, HttpApiProblem> {
+    let metadata = metadata_store
+        .get(&id)?
+        .ok_or_else(problem::swap_not_found)?;
+
+    match metadata {
+        Metadata {
+            role: RoleKind::Alice,
+            previous_hop: None,
+            split_from: None,
+            ..
+        } => (),
+        _ => return Err(problem::invalid_split(
+            "Only swaps we requested, which neither forward nor split another swap, can be split.",
+        )),
+    }
+
+    match state_store.get::<alice::State<AL, BL, AA, BA>>(&id) {
+        Ok(Some(state)) => Ok(state.request()),
+        Ok(None) => Err(problem::state_store()),
+        Err(_) => Err(problem::invalid_split(
+            "A split must swap the same kind of assets on the same ledgers.",
+        )),
+    }
+}
+
+/// How much of the alpha asset the parent `id` was requested with neither
+/// the parent nor its splits have taken yet. The parent is still a live
+/// request that can be accepted in full, hence it takes from its order just
+/// like its splits do. Rejected swaps take nothing, pending ones what they
+/// request and partially filled ones what was filled.
+fn remaining_alpha_asset<
+    T: MetadataStore<SwapId>,
+    S: StateStore,
+    AL: Ledger,
+    BL: Ledger,
+    AA: Asset,
+    BA: Asset,
+>(
+    metadata_store: &T,
+    state_store: &S,
+    id: SwapId,
+) -> Result<U256, HttpApiProblem> {
+    // Partial fills change the request of the parent but not its metadata
+    let mut remaining = metadata_store
+        .get(&id)?
+        .ok_or_else(problem::swap_not_found)?
+        .alpha_asset
+        .base_units()
+        .unwrap_or_default();
+
+    for swap in std::iter::once(id).chain(metadata_store::splits(metadata_store, &id)?) {
+        let state = state_store
+            .get::<alice::State<AL, BL, AA, BA>>(&swap)
+            .ok()
+            .and_then(|state| state)
+            .ok_or_else(problem::state_store)?;
+
+        let taken = match state.swap_communication {
+            SwapCommunication::Rejected { .. } => continue,
+            SwapCommunication::Proposed { request }
+            | SwapCommunication::Accepted { request, .. } => {
+                request.alpha_asset.base_units().unwrap_or_default()
+            }
+        };
+
+        remaining = if taken < remaining {
+            remaining - taken
+        } else {
+            U256::zero()
+        };
+    }
+
+    Ok(remaining)
+}
+
 #[derive(Serialize, Debug)]
 pub struct SwapCreated {
     pub id: SwapId,
@@ -78,6 +267,87 @@ pub enum SwapRequestBodyKind {
     EthereumBitcoinEtherQuantityBitcoinQuantity(
         SwapRequestBody<Ethereum, Bitcoin, EtherQuantity, BitcoinQuantity, OnlyRefund<Ethereum>>,
     ),
+    BitcoinEthereumBitcoinQuantityErc721Token(
+        SwapRequestBody<Bitcoin, Ethereum, BitcoinQuantity, Erc721Token, OnlyRedeem<Ethereum>>,
+    ),
+    EthereumBitcoinErc721TokenBitcoinQuantity(
+        SwapRequestBody<Ethereum, Bitcoin, Erc721Token, BitcoinQuantity, OnlyRefund<Ethereum>>,
+    ),
+    LitecoinEthereumLitecoinQuantityErc20Token(
+        SwapRequestBody<Litecoin, Ethereum, LitecoinQuantity, Erc20Token, OnlyRedeem<Ethereum>>,
+    ),
+    LitecoinEthereumLitecoinQuantityEtherQuantity(
+        SwapRequestBody<Litecoin, Ethereum, LitecoinQuantity, EtherQuantity, OnlyRedeem<Ethereum>>,
+    ),
+    EthereumLitecoinErc20TokenLitecoinQuantity(
+        SwapRequestBody<Ethereum, Litecoin, Erc20Token, LitecoinQuantity, OnlyRefund<Ethereum>>,
+    ),
+    EthereumLitecoinEtherQuantityLitecoinQuantity(
+        SwapRequestBody<Ethereum, Litecoin, EtherQuantity, LitecoinQuantity, OnlyRefund<Ethereum>>,
+    ),
+    EthereumEthereumEtherQuantityErc20Token(
+        SwapRequestBody<
+            Ethereum,
+            Ethereum,
+            EtherQuantity,
+            Erc20Token,
+            RefundAndRedeem<Ethereum, Ethereum>,
+        >,
+    ),
+    EthereumEthereumErc20TokenEtherQuantity(
+        SwapRequestBody<
+            Ethereum,
+            Ethereum,
+            Erc20Token,
+            EtherQuantity,
+            RefundAndRedeem<Ethereum, Ethereum>,
+        >,
+    ),
+    EthereumEthereumErc20TokenErc20Token(
+        SwapRequestBody<
+            Ethereum,
+            Ethereum,
+            Erc20Token,
+            Erc20Token,
+            RefundAndRedeem<Ethereum, Ethereum>,
+        >,
+    ),
+    EthereumEthereumEtherQuantityErc721Token(
+        SwapRequestBody<
+            Ethereum,
+            Ethereum,
+            EtherQuantity,
+            Erc721Token,
+            RefundAndRedeem<Ethereum, Ethereum>,
+        >,
+    ),
+    EthereumEthereumErc721TokenEtherQuantity(
+        SwapRequestBody<
+            Ethereum,
+            Ethereum,
+            Erc721Token,
+            EtherQuantity,
+            RefundAndRedeem<Ethereum, Ethereum>,
+        >,
+    ),
+    LightningEthereumBitcoinQuantityEtherQuantity(
+        SwapRequestBody<
+            Lightning,
+            Ethereum,
+            BitcoinQuantity,
+            EtherQuantity,
+            RefundAndRedeem<Lightning, Ethereum>,
+        >,
+    ),
+    LightningEthereumBitcoinQuantityErc20Token(
+        SwapRequestBody<
+            Lightning,
+            Ethereum,
+            BitcoinQuantity,
+            Erc20Token,
+            RefundAndRedeem<Lightning, Ethereum>,
+        >,
+    ),
     // It is important that these two come last because untagged enums are tried in order
     UnsupportedCombination(Box<UnsupportedSwapRequestBody>),
     MalformedRequest(serde_json::Value),
@@ -96,12 +366,140 @@ pub struct SwapRequestBody<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset, Partial
     beta_ledger: BL,
     alpha_expiry: Timestamp,
     beta_expiry: Timestamp,
+    #[serde(default)]
+    hash_function: HashFunction,
+    #[serde(default)]
+    previous_hop: Option<SwapId>,
+    #[serde(default)]
+    split_from: Option<SwapId>,
     #[serde(flatten)]
     partial_identities: PartialIdentities,
     #[serde(with = "http_api::serde_peer_id")]
     peer: PeerId,
 }
 
+/// A swap request forwarding a swap we received as Bob to the next hop.
+///
+/// It is locked with the secret hash of the previous hop and its beta asset is
+/// redeemed to whoever requested the previous hop, hence we never learn the
+/// secret before they redeem.
+#[derive(Clone, Debug)]
+pub struct NextHop<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset, I> {
+    body: SwapRequestBody<AL, BL, AA, BA, I>,
+    previous_hop: SwapId,
+    secret_hash: SecretHash,
+    hash_function: HashFunction,
+    beta_ledger_redeem_identity: BL::Identity,
+}
+
+impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset, I> SwapRequestBody<AL, BL, AA, BA, I> {
+    /// Checks that this request takes at most the `remaining` base units of
+    /// the alpha asset of `parent`, at a rate that is no worse for us
+    pub fn validate_split(
+        &self,
+        parent: &rfc003::messages::Request<AL, BL, AA, BA>,
+        remaining: U256,
+    ) -> Result<(), HttpApiProblem> {
+        if self.alpha_ledger != parent.alpha_ledger || self.beta_ledger != parent.beta_ledger {
+            return Err(problem::invalid_split(
+                "A split must happen on the ledgers of the swap it is split from.",
+            ));
+        }
+        if !same_asset(&self.alpha_asset, &parent.alpha_asset)
+            || !same_asset(&self.beta_asset, &parent.beta_asset)
+        {
+            return Err(problem::invalid_split(
+                "A split must swap divisible assets of the swap it is split from.",
+            ));
+        }
+        if self
+            .alpha_asset
+            .base_units()
+            .map_or(true, |alpha_asset| alpha_asset > remaining)
+        {
+            return Err(problem::invalid_split(
+                "A split cannot take more than what is left of the swap it is split from.",
+            ));
+        }
+        if !asset::rate_is_at_least(
+            (&self.alpha_asset, &self.beta_asset),
+            (&parent.alpha_asset, &parent.beta_asset),
+        ) {
+            return Err(problem::invalid_split(
+                "A split must have a rate no worse than the swap it is split from.",
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn forward(
+        self,
+        previous_hop: PreviousHop,
+    ) -> Result<NextHop<AL, BL, AA, BA, I>, HttpApiProblem> {
+        let PreviousHop {
+            id,
+            metadata,
+            alpha_expiry,
+            beta_expiry,
+            secret_hash,
+            hash_function,
+            beta_ledger_redeem_identity,
+        } = previous_hop;
+
+        let beta_ledger: LedgerKind = self.beta_ledger.into();
+        let beta_asset: AssetKind = self.beta_asset.into();
+        if metadata.beta_ledger != beta_ledger || metadata.beta_asset != beta_asset {
+            return Err(problem::invalid_previous_hop(
+                "The beta ledger and asset must be the ones of the previous hop.",
+            ));
+        }
+        if beta_expiry != self.beta_expiry {
+            return Err(problem::invalid_previous_hop(
+                "The beta expiry must be the one of the previous hop.",
+            ));
+        }
+        // We need time to redeem the previous hop once our peer revealed the
+        // secret on the beta ledger, and time to refund if they never do.
+        if self.alpha_expiry <= beta_expiry || self.alpha_expiry >= alpha_expiry {
+            return Err(problem::invalid_previous_hop(
+                "The alpha expiry must lie between the beta and alpha expiry of the previous hop.",
+            ));
+        }
+
+        let beta_ledger_redeem_identity =
+            serde_json::from_value(beta_ledger_redeem_identity).map_err(problem::deserialize)?;
+
+        Ok(NextHop {
+            body: self,
+            previous_hop: id,
+            secret_hash,
+            hash_function,
+            beta_ledger_redeem_identity,
+        })
+    }
+}
+
+impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset, I: ToIdentities<AL, BL>>
+    ToRequest<AL, BL, AA, BA> for NextHop<AL, BL, AA, BA, I>
+{
+    fn to_request(
+        &self,
+        secret_source: &dyn SecretSource,
+    ) -> rfc003::messages::Request<AL, BL, AA, BA> {
+        rfc003::messages::Request {
+            secret_hash: self.secret_hash,
+            hash_function: self.hash_function,
+            beta_ledger_redeem_identity: self.beta_ledger_redeem_identity,
+            ..self.body.to_request(secret_source)
+        }
+    }
+
+    fn previous_hop(&self) -> Option<SwapId> {
+        Some(self.previous_hop)
+    }
+}
+
 #[derive(Deserialize, Clone, Debug, PartialEq)]
 pub struct OnlyRedeem<L: Ledger> {
     pub beta_ledger_redeem_identity: L::Identity,
@@ -112,6 +510,12 @@ pub struct OnlyRefund<L: Ledger> {
     pub alpha_ledger_refund_identity: L::Identity,
 }
 
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct RefundAndRedeem<AL: Ledger, BL: Ledger> {
+    pub alpha_ledger_refund_identity: AL::Identity,
+    pub beta_ledger_redeem_identity: BL::Identity,
+}
+
 #[derive(Debug, Clone)]
 pub struct Identities<AL: Ledger, BL: Ledger> {
     pub alpha_ledger_refund_identity: AL::Identity,
@@ -119,7 +523,12 @@ pub struct Identities<AL: Ledger, BL: Ledger> {
 }
 
 pub trait ToIdentities<AL: Ledger, BL: Ledger> {
-    fn to_identities(&self, secret_source: &dyn SecretSource) -> Identities<AL, BL>;
+    fn to_identities(
+        &self,
+        alpha_ledger: AL,
+        beta_ledger: BL,
+        secret_source: &dyn SecretSource,
+    ) -> Identities<AL, BL>;
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
@@ -146,7 +555,11 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset, I: ToIdentities<AL, BL>>
         let Identities {
             alpha_ledger_refund_identity,
             beta_ledger_redeem_identity,
-        } = self.partial_identities.to_identities(secret_source);
+        } = self.partial_identities.to_identities(
+            self.alpha_ledger,
+            self.beta_ledger,
+            secret_source,
+        );
         rfc003::messages::Request {
             alpha_asset: self.alpha_asset,
             beta_asset: self.beta_asset,
@@ -154,27 +567,81 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset, I: ToIdentities<AL, BL>>
             beta_ledger: self.beta_ledger,
             alpha_expiry: self.alpha_expiry,
             beta_expiry: self.beta_expiry,
-            secret_hash: secret_source.secret().hash(),
+            secret_hash: secret_source.secret().hash(self.hash_function),
+            hash_function: self.hash_function,
             alpha_ledger_refund_identity,
             beta_ledger_redeem_identity,
         }
     }
+
+    fn split_from(&self) -> Option<SwapId> {
+        self.split_from
+    }
+}
+
+/// Whether both assets are the same, like tokens of the same contract, in
+/// possibly different quantities
+fn same_asset<A: Asset>(asset: &A, other: &A) -> bool {
+    asset
+        .base_units()
+        .and_then(|base_units| other.with_base_units(base_units))
+        .map_or(false, |other| other == *asset)
+}
+
+impl<L: UtxoLedger> ToIdentities<L, Ethereum> for OnlyRedeem<Ethereum> {
+    fn to_identities(
+        &self,
+        alpha_ledger: L,
+        _: Ethereum,
+        secret_source: &dyn SecretSource,
+    ) -> Identities<L, Ethereum> {
+        Identities {
+            alpha_ledger_refund_identity: alpha_ledger.identity(secret_source.secp256k1_refund()),
+            beta_ledger_redeem_identity: self.beta_ledger_redeem_identity,
+        }
+    }
+}
+
+impl<L: UtxoLedger> ToIdentities<Ethereum, L> for OnlyRefund<Ethereum> {
+    fn to_identities(
+        &self,
+        _: Ethereum,
+        beta_ledger: L,
+        secret_source: &dyn SecretSource,
+    ) -> Identities<Ethereum, L> {
+        Identities {
+            alpha_ledger_refund_identity: self.alpha_ledger_refund_identity,
+            beta_ledger_redeem_identity: beta_ledger.identity(secret_source.secp256k1_redeem()),
+        }
+    }
 }
 
-impl ToIdentities<Bitcoin, Ethereum> for OnlyRedeem<Ethereum> {
-    fn to_identities(&self, secret_source: &dyn SecretSource) -> Identities<Bitcoin, Ethereum> {
+impl ToIdentities<Ethereum, Ethereum> for RefundAndRedeem<Ethereum, Ethereum> {
+    fn to_identities(
+        &self,
+        _: Ethereum,
+        _: Ethereum,
+        _: &dyn SecretSource,
+    ) -> Identities<Ethereum, Ethereum> {
         Identities {
-            alpha_ledger_refund_identity: secret_source.secp256k1_refund().into(),
+            alpha_ledger_refund_identity: self.alpha_ledger_refund_identity,
             beta_ledger_redeem_identity: self.beta_ledger_redeem_identity,
         }
     }
 }
 
-impl ToIdentities<Ethereum, Bitcoin> for OnlyRefund<Ethereum> {
-    fn to_identities(&self, secret_source: &dyn SecretSource) -> Identities<Ethereum, Bitcoin> {
+/// Alice pays from her lnd node, hence its public key can't be derived from
+/// the secret source.
+impl ToIdentities<Lightning, Ethereum> for RefundAndRedeem<Lightning, Ethereum> {
+    fn to_identities(
+        &self,
+        _: Lightning,
+        _: Ethereum,
+        _: &dyn SecretSource,
+    ) -> Identities<Lightning, Ethereum> {
         Identities {
             alpha_ledger_refund_identity: self.alpha_ledger_refund_identity,
-            beta_ledger_redeem_identity: secret_source.secp256k1_redeem().into(),
+            beta_ledger_redeem_identity: self.beta_ledger_redeem_identity,
         }
     }
 }
@@ -183,7 +650,21 @@ impl ToIdentities<Ethereum, Bitcoin> for OnlyRefund<Ethereum> {
 mod tests {
 
     use super::*;
+    use crate::{
+        comit_client::SwapReject,
+        seed::Seed,
+        swap_protocols::{
+            metadata_store::InMemoryMetadataStore,
+            rfc003::{
+                ledger_state::LedgerState,
+                messages::{AcceptResponseBody, PartialFill},
+                state_store::InMemoryStateStore,
+            },
+        },
+    };
+    use ethereum_support::Erc20Quantity;
     use spectral::prelude::*;
+    use std::sync::Arc;
 
     #[test]
     fn can_deserialize_swap_request_body() {
@@ -219,6 +700,9 @@ mod tests {
             beta_ledger: Ethereum::default(),
             alpha_expiry: Timestamp::from(2000000000),
             beta_expiry: Timestamp::from(2000000000),
+            hash_function: HashFunction::Sha256,
+            previous_hop: None,
+            split_from: None,
             partial_identities: OnlyRedeem::<Ethereum> {
                 beta_ledger_redeem_identity: ethereum_support::Address::from(
                     "0x00a329c0648769a73afac7f9381e08fb43dbea72",
@@ -229,4 +713,480 @@ mod tests {
                 .unwrap(),
         })
     }
+
+    #[test]
+    fn can_deserialize_litecoin_swap_request_body() {
+        let body = r#"{
+                "alpha_ledger": {
+                    "name": "ethereum",
+                    "network": "regtest"
+                },
+                "beta_ledger": {
+                    "name": "litecoin",
+                    "network": "regtest"
+                },
+                "alpha_asset": {
+                    "name": "ether",
+                    "quantity": "10000000000000000000"
+                },
+                "beta_asset": {
+                    "name": "litecoin",
+                    "quantity": "100000000"
+                },
+                "alpha_ledger_refund_identity": "0x00a329c0648769a73afac7f9381e08fb43dbea72",
+                "alpha_expiry": 2000000000,
+                "beta_expiry": 2000000000,
+                "peer": "Qma9T5YraSnpRDZqRR4krcSJabThc8nwZuJV3LercPHufi"
+            }"#;
+
+        let body = serde_json::from_str::<SwapRequestBodyKind>(body).unwrap();
+
+        match body {
+            SwapRequestBodyKind::EthereumLitecoinEtherQuantityLitecoinQuantity(body) => {
+                assert_that(&body.beta_asset)
+                    .is_equal_to(LitecoinQuantity::from_litoshi(100_000_000));
+                assert_that(&body.beta_ledger).is_equal_to(Litecoin::default());
+            }
+            _ => panic!(
+                "expected an Ether for Litecoin swap request, got {:?}",
+                body
+            ),
+        }
+    }
+
+    #[test]
+    fn can_deserialize_erc20_for_erc20_swap_request_body() {
+        let body = r#"{
+                "alpha_ledger": {
+                    "name": "ethereum",
+                    "network": "regtest"
+                },
+                "beta_ledger": {
+                    "name": "ethereum",
+                    "network": "regtest"
+                },
+                "alpha_asset": {
+                    "name": "erc20",
+                    "quantity": "1000",
+                    "token_contract": "0xb97048628db6b661d4c2aa833e95dbe1a905b280"
+                },
+                "beta_asset": {
+                    "name": "erc20",
+                    "quantity": "2000",
+                    "token_contract": "0x00a329c0648769a73afac7f9381e08fb43dbea72"
+                },
+                "alpha_ledger_refund_identity": "0x0aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                "beta_ledger_redeem_identity": "0x0bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+                "alpha_expiry": 2000000000,
+                "beta_expiry": 2000000000,
+                "peer": "Qma9T5YraSnpRDZqRR4krcSJabThc8nwZuJV3LercPHufi"
+            }"#;
+
+        let body = serde_json::from_str::<SwapRequestBodyKind>(body);
+
+        match body {
+            Ok(SwapRequestBodyKind::EthereumEthereumErc20TokenErc20Token(body)) => {
+                assert_that(&body.partial_identities).is_equal_to(RefundAndRedeem {
+                    alpha_ledger_refund_identity: ethereum_support::Address::from(
+                        "0x0aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                    ),
+                    beta_ledger_redeem_identity: ethereum_support::Address::from(
+                        "0x0bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+                    ),
+                })
+            }
+            other => panic!("unexpected swap request body kind: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn can_deserialize_lightning_swap_request_body() {
+        let body = r#"{
+                "alpha_ledger": {
+                    "name": "lightning",
+                    "network": "regtest"
+                },
+                "beta_ledger": {
+                    "name": "ethereum",
+                    "chain_id": 17
+                },
+                "alpha_asset": {
+                    "name": "bitcoin",
+                    "quantity": "100000"
+                },
+                "beta_asset": {
+                    "name": "ether",
+                    "quantity": "10000000000000000000"
+                },
+                "alpha_ledger_refund_identity": "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+                "beta_ledger_redeem_identity": "0x0bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+                "alpha_expiry": 2000000000,
+                "beta_expiry": 2000000000,
+                "peer": "Qma9T5YraSnpRDZqRR4krcSJabThc8nwZuJV3LercPHufi"
+            }"#;
+
+        let body = serde_json::from_str::<SwapRequestBodyKind>(body);
+
+        match body {
+            Ok(SwapRequestBodyKind::LightningEthereumBitcoinQuantityEtherQuantity(body)) => {
+                assert_that(&body.alpha_ledger).is_equal_to(Lightning::default());
+                assert_that(&body.alpha_asset).is_equal_to(BitcoinQuantity::from_satoshi(100_000));
+            }
+            other => panic!("unexpected swap request body kind: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn can_deserialize_lightning_erc20_swap_request_body() {
+        let body = r#"{
+                "alpha_ledger": {
+                    "name": "lightning",
+                    "network": "regtest"
+                },
+                "beta_ledger": {
+                    "name": "ethereum",
+                    "chain_id": 17
+                },
+                "alpha_asset": {
+                    "name": "bitcoin",
+                    "quantity": "100000"
+                },
+                "beta_asset": {
+                    "name": "erc20",
+                    "quantity": "2000",
+                    "token_contract": "0x00a329c0648769a73afac7f9381e08fb43dbea72"
+                },
+                "alpha_ledger_refund_identity": "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+                "beta_ledger_redeem_identity": "0x0bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+                "alpha_expiry": 2000000000,
+                "beta_expiry": 2000000000,
+                "peer": "Qma9T5YraSnpRDZqRR4krcSJabThc8nwZuJV3LercPHufi"
+            }"#;
+
+        let body = serde_json::from_str::<SwapRequestBodyKind>(body);
+
+        match body {
+            Ok(SwapRequestBodyKind::LightningEthereumBitcoinQuantityErc20Token(body)) => {
+                assert_that(&body.alpha_ledger).is_equal_to(Lightning::default());
+                assert_that(&body.beta_asset).is_equal_to(Erc20Token::new(
+                    "0x00a329c0648769a73afac7f9381e08fb43dbea72".into(),
+                    Erc20Quantity(U256::from(2000)),
+                ));
+            }
+            other => panic!("unexpected swap request body kind: {:?}", other),
+        }
+    }
+
+    fn bitcoin_for_ether_body(
+    ) -> SwapRequestBody<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity, OnlyRedeem<Ethereum>>
+    {
+        SwapRequestBody {
+            alpha_asset: BitcoinQuantity::from_bitcoin(1.0),
+            beta_asset: EtherQuantity::from_eth(10.0),
+            alpha_ledger: Bitcoin::default(),
+            beta_ledger: Ethereum::default(),
+            alpha_expiry: Timestamp::from(2000000100),
+            beta_expiry: Timestamp::from(2000000000),
+            hash_function: HashFunction::Sha256,
+            previous_hop: Some(SwapId::default()),
+            split_from: None,
+            partial_identities: OnlyRedeem::<Ethereum> {
+                beta_ledger_redeem_identity: ethereum_support::Address::from(
+                    "0x0bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+                ),
+            },
+            peer: "Qma9T5YraSnpRDZqRR4krcSJabThc8nwZuJV3LercPHufi"
+                .parse()
+                .unwrap(),
+        }
+    }
+
+    fn previous_hop(id: SwapId, secret_hash: SecretHash) -> PreviousHop {
+        PreviousHop {
+            id,
+            metadata: Metadata {
+                alpha_ledger: Litecoin::default().into(),
+                beta_ledger: Ethereum::default().into(),
+                alpha_asset: LitecoinQuantity::from_litoshi(100_000_000).into(),
+                beta_asset: EtherQuantity::from_eth(10.0).into(),
+                role: RoleKind::Bob,
+                previous_hop: None,
+                split_from: None,
+            },
+            alpha_expiry: Timestamp::from(2000000200),
+            beta_expiry: Timestamp::from(2000000000),
+            secret_hash,
+            hash_function: HashFunction::Sha256,
+            beta_ledger_redeem_identity: serde_json::json!(
+                "0x0aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+            ),
+        }
+    }
+
+    #[test]
+    fn next_hop_shares_secret_hash_and_beta_redeem_identity_of_previous_hop() {
+        let id = SwapId::default();
+        let secret_hash = SecretHash::from([1u8; SecretHash::LENGTH]);
+        let seed = Seed::from(*b"hello world, you are beautiful!!");
+
+        let next_hop = bitcoin_for_ether_body()
+            .forward(previous_hop(id, secret_hash))
+            .unwrap();
+        let request = next_hop.to_request(&seed);
+
+        assert_that(&next_hop.previous_hop()).is_equal_to(Some(id));
+        assert_that(&request.secret_hash).is_equal_to(secret_hash);
+        assert_that(&request.beta_ledger_redeem_identity).is_equal_to(
+            ethereum_support::Address::from("0x0aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+        );
+    }
+
+    #[test]
+    fn next_hop_must_expire_between_expiries_of_previous_hop() {
+        let secret_hash = SecretHash::from([1u8; SecretHash::LENGTH]);
+
+        let mut body = bitcoin_for_ether_body();
+        body.alpha_expiry = Timestamp::from(2000000300);
+        let next_hop = body.forward(previous_hop(SwapId::default(), secret_hash));
+
+        assert_that(&next_hop.map(|_| ()))
+            .is_err()
+            .map(|p| &p.status)
+            .is_equal_to(Some(HttpStatusCode::BAD_REQUEST));
+    }
+
+    #[test]
+    fn next_hop_must_have_beta_asset_of_previous_hop() {
+        let secret_hash = SecretHash::from([1u8; SecretHash::LENGTH]);
+
+        let mut body = bitcoin_for_ether_body();
+        body.beta_asset = EtherQuantity::from_eth(5.0);
+        let next_hop = body.forward(previous_hop(SwapId::default(), secret_hash));
+
+        assert_that(&next_hop.map(|_| ()))
+            .is_err()
+            .map(|p| &p.status)
+            .is_equal_to(Some(HttpStatusCode::BAD_REQUEST));
+    }
+
+    fn bitcoin_for_ether_parent(
+    ) -> rfc003::messages::Request<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity> {
+        let seed = Seed::from(*b"hello world, you are beautiful!!");
+        let mut body = bitcoin_for_ether_body();
+        body.alpha_asset = BitcoinQuantity::from_bitcoin(4.0);
+        body.beta_asset = EtherQuantity::from_eth(40.0);
+
+        body.to_request(&seed)
+    }
+
+    fn all_of_parent() -> U256 {
+        U256::from(400_000_000)
+    }
+
+    #[test]
+    fn split_at_rate_of_parent_is_valid() {
+        let body = bitcoin_for_ether_body();
+
+        assert_that(&body.validate_split(&bitcoin_for_ether_parent(), all_of_parent())).is_ok();
+    }
+
+    #[test]
+    fn split_at_worse_rate_than_parent_is_invalid() {
+        let mut body = bitcoin_for_ether_body();
+        body.beta_asset = EtherQuantity::from_eth(9.0);
+
+        assert_that(&body.validate_split(&bitcoin_for_ether_parent(), all_of_parent()))
+            .is_err()
+            .map(|p| &p.status)
+            .is_equal_to(Some(HttpStatusCode::BAD_REQUEST));
+    }
+
+    #[test]
+    fn split_of_more_than_remains_of_parent_is_invalid() {
+        let body = bitcoin_for_ether_body();
+
+        assert_that(&body.validate_split(&bitcoin_for_ether_parent(), U256::from(99_999_999)))
+            .is_err()
+            .map(|p| &p.status)
+            .is_equal_to(Some(HttpStatusCode::BAD_REQUEST));
+    }
+
+    fn insert_swap<T: MetadataStore<SwapId>, S: StateStore>(
+        metadata_store: &T,
+        state_store: &S,
+        id: SwapId,
+        split_from: Option<SwapId>,
+        order: rfc003::messages::Request<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>,
+        swap_communication: SwapCommunication<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>,
+    ) {
+        metadata_store
+            .insert(
+                id,
+                Metadata {
+                    alpha_ledger: order.alpha_ledger.into(),
+                    beta_ledger: order.beta_ledger.into(),
+                    alpha_asset: order.alpha_asset.into(),
+                    beta_asset: order.beta_asset.into(),
+                    role: RoleKind::Alice,
+                    previous_hop: None,
+                    split_from,
+                },
+            )
+            .unwrap();
+        state_store.insert(
+            id,
+            alice::State {
+                swap_communication,
+                alpha_ledger_state: LedgerState::NotDeployed,
+                beta_ledger_state: LedgerState::NotDeployed,
+                secret_source: Arc::new(Seed::from(*b"hello world, you are beautiful!!")),
+                error: None,
+            },
+        );
+    }
+
+    fn insert_split<T: MetadataStore<SwapId>, S: StateStore>(
+        metadata_store: &T,
+        state_store: &S,
+        parent: SwapId,
+        swap_communication: SwapCommunication<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>,
+    ) {
+        let request = match &swap_communication {
+            SwapCommunication::Proposed { request }
+            | SwapCommunication::Accepted { request, .. }
+            | SwapCommunication::Rejected { request, .. } => request.clone(),
+        };
+
+        insert_swap(
+            metadata_store,
+            state_store,
+            SwapId::default(),
+            Some(parent),
+            request,
+            swap_communication,
+        );
+    }
+
+    fn with_alpha_asset(
+        bitcoin: f64,
+    ) -> rfc003::messages::Request<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity> {
+        let mut request = bitcoin_for_ether_parent();
+        request.alpha_asset = BitcoinQuantity::from_bitcoin(bitcoin);
+        request
+    }
+
+    fn remaining(
+        metadata_store: &InMemoryMetadataStore<SwapId>,
+        state_store: &InMemoryStateStore<SwapId>,
+        parent: SwapId,
+    ) -> Result<U256, HttpApiProblem> {
+        remaining_alpha_asset::<_, _, Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>(
+            metadata_store,
+            state_store,
+            parent,
+        )
+    }
+
+    #[test]
+    fn splits_that_were_not_rejected_take_from_the_parent() {
+        let metadata_store = InMemoryMetadataStore::default();
+        let state_store = InMemoryStateStore::default();
+        let parent_id = SwapId::default();
+        insert_swap(
+            &metadata_store,
+            &state_store,
+            parent_id,
+            None,
+            bitcoin_for_ether_parent(),
+            SwapCommunication::Rejected {
+                request: bitcoin_for_ether_parent(),
+                response: SwapReject::Rejected,
+            },
+        );
+
+        insert_split(
+            &metadata_store,
+            &state_store,
+            parent_id,
+            SwapCommunication::Proposed {
+                request: with_alpha_asset(1.0),
+            },
+        );
+        insert_split(
+            &metadata_store,
+            &state_store,
+            parent_id,
+            SwapCommunication::Rejected {
+                request: with_alpha_asset(2.0),
+                response: SwapReject::Rejected,
+            },
+        );
+
+        assert_that(&remaining(&metadata_store, &state_store, parent_id))
+            .is_ok_containing(U256::from(300_000_000));
+    }
+
+    #[test]
+    fn pending_parent_and_splits_cannot_take_more_than_the_order() {
+        let metadata_store = InMemoryMetadataStore::default();
+        let state_store = InMemoryStateStore::default();
+        let parent_id = SwapId::default();
+        insert_swap(
+            &metadata_store,
+            &state_store,
+            parent_id,
+            None,
+            bitcoin_for_ether_parent(),
+            SwapCommunication::Proposed {
+                request: bitcoin_for_ether_parent(),
+            },
+        );
+
+        let remaining = remaining(&metadata_store, &state_store, parent_id).unwrap();
+
+        assert_that(&remaining).is_equal_to(U256::zero());
+        assert_that(
+            &bitcoin_for_ether_body().validate_split(&bitcoin_for_ether_parent(), remaining),
+        )
+        .is_err()
+        .map(|p| &p.status)
+        .is_equal_to(Some(HttpStatusCode::BAD_REQUEST));
+    }
+
+    #[test]
+    fn partially_filled_parent_takes_what_was_filled() {
+        let metadata_store = InMemoryMetadataStore::default();
+        let state_store = InMemoryStateStore::default();
+        let parent_id = SwapId::default();
+        let filled = with_alpha_asset(1.0);
+        insert_swap(
+            &metadata_store,
+            &state_store,
+            parent_id,
+            None,
+            bitcoin_for_ether_parent(),
+            SwapCommunication::Accepted {
+                request: filled.clone(),
+                response: AcceptResponseBody {
+                    alpha_ledger_redeem_identity: filled.alpha_ledger_refund_identity,
+                    beta_ledger_refund_identity: filled.beta_ledger_redeem_identity,
+                    partial_fill: Some(PartialFill {
+                        alpha_quantity: U256::from(100_000_000),
+                        beta_quantity: U256::from(10u64) * U256::exp10(18),
+                    }),
+                },
+            },
+        );
+        insert_split(
+            &metadata_store,
+            &state_store,
+            parent_id,
+            SwapCommunication::Proposed {
+                request: with_alpha_asset(2.0),
+            },
+        );
+
+        assert_that(&remaining(&metadata_store, &state_store, parent_id))
+            .is_ok_containing(U256::from(100_000_000));
+    }
 }
@@ -0,0 +1,218 @@
+use crate::{
+    http_api::problem,
+    swap_protocols::{
+        actions::Actions,
+        metadata_store,
+        rfc003::{
+            actions::{Action, ActionKind},
+            state_store::StateStore,
+            HashFunction, LedgerState, SecretHash,
+        },
+        Metadata, MetadataStore, RoleKind, SwapId, Timestamp,
+    },
+};
+use http_api_problem::HttpApiProblem;
+
+/// The parts of a swap we received as Bob that its next hop has to share
+#[derive(Clone, Debug)]
+pub struct PreviousHop {
+    pub id: SwapId,
+    pub metadata: Metadata,
+    pub alpha_expiry: Timestamp,
+    pub beta_expiry: Timestamp,
+    pub secret_hash: SecretHash,
+    pub hash_function: HashFunction,
+    /// Serialized because its type depends on the beta ledger
+    pub beta_ledger_redeem_identity: serde_json::Value,
+}
+
+pub fn load_previous_hop<T: MetadataStore<SwapId>, S: StateStore>(
+    metadata_store: &T,
+    state_store: &S,
+    id: SwapId,
+) -> Result<PreviousHop, HttpApiProblem> {
+    let metadata = metadata_store
+        .get(&id)?
+        .ok_or_else(problem::swap_not_found)?;
+
+    if let RoleKind::Alice = metadata.role {
+        return Err(problem::invalid_previous_hop(
+            "Only swaps requested by a peer can be forwarded.",
+        ));
+    }
+    if metadata_store::next_hop(metadata_store, &id)?.is_some() {
+        return Err(problem::invalid_previous_hop(
+            "The swap has already been forwarded.",
+        ));
+    }
+
+    with_swap_types!(
+        &metadata,
+        (|| {
+            let state = state_store
+                .get::<ROLE>(&id)?
+                .ok_or_else(problem::state_store)?;
+            if !state
+                .actions()
+                .iter()
+                .any(|action| ActionKind::from(action) == ActionKind::Accept)
+            {
+                return Err(problem::invalid_previous_hop(
+                    "Only swaps that are yet to be accepted can be forwarded.",
+                ));
+            }
+            let request = state.request();
+
+            Ok(PreviousHop {
+                id,
+                metadata: metadata.clone(),
+                alpha_expiry: request.alpha_expiry,
+                beta_expiry: request.beta_expiry,
+                secret_hash: request.secret_hash,
+                hash_function: request.hash_function,
+                beta_ledger_redeem_identity: serde_json::to_value(
+                    request.beta_ledger_redeem_identity,
+                )
+                .map_err(problem::serialize)?,
+            })
+        })
+    )
+}
+
+/// The identity the beta HTLC of a forwarded swap refunds to, chosen by our
+/// peer on the next hop who funds it
+pub fn next_hop_beta_refund_identity<T: MetadataStore<SwapId>, S: StateStore>(
+    metadata_store: &T,
+    state_store: &S,
+    next_hop: SwapId,
+) -> Result<serde_json::Value, HttpApiProblem> {
+    let metadata = metadata_store
+        .get(&next_hop)?
+        .ok_or_else(problem::swap_not_found)?;
+
+    with_swap_types!(
+        &metadata,
+        (|| {
+            let state = state_store
+                .get::<ROLE>(&next_hop)?
+                .ok_or_else(problem::state_store)?;
+            let response = state
+                .response()
+                .ok_or_else(problem::next_hop_not_accepted)?;
+
+            serde_json::to_value(response.beta_ledger_refund_identity).map_err(problem::serialize)
+        })
+    )
+}
+
+/// The swaps a swap was forwarded from and to
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Hops {
+    pub previous: Option<SwapId>,
+    pub next: Option<SwapId>,
+    /// Whether the alpha HTLC of the previous hop is funded
+    pub previous_alpha_funded: bool,
+}
+
+impl Hops {
+    pub fn load<T: MetadataStore<SwapId>, S: StateStore>(
+        metadata_store: &T,
+        state_store: &S,
+        id: &SwapId,
+        metadata: &Metadata,
+    ) -> Result<Self, HttpApiProblem> {
+        let previous_alpha_funded = match metadata.previous_hop {
+            Some(previous) => {
+                let previous_metadata = metadata_store
+                    .get(&previous)?
+                    .ok_or_else(problem::swap_not_found)?;
+                alpha_funded(state_store, previous, &previous_metadata)?
+            }
+            None => false,
+        };
+
+        Ok(Hops {
+            previous: metadata.previous_hop,
+            next: metadata_store::next_hop(metadata_store, id)?,
+            previous_alpha_funded,
+        })
+    }
+
+    /// Whether the action is ours to take. The next hop of a forwarded swap
+    /// funds and refunds its beta HTLC in our place. The alpha HTLC of the
+    /// next hop is only funded once the one of its previous hop is, otherwise
+    /// the previous hop's Alice could redeem without ever paying us.
+    pub fn is_ours<Accept, Decline, Deploy, Fund, Redeem, Refund>(
+        &self,
+        action: &Action<Accept, Decline, Deploy, Fund, Redeem, Refund>,
+    ) -> bool {
+        match action {
+            Action::Deploy(_) | Action::Fund(_) => {
+                self.next.is_none() && (self.previous.is_none() || self.previous_alpha_funded)
+            }
+            Action::Refund(_) => self.next.is_none(),
+            Action::Accept(_) | Action::Decline(_) | Action::Redeem(_) => true,
+        }
+    }
+}
+
+fn alpha_funded<S: StateStore>(
+    state_store: &S,
+    id: SwapId,
+    metadata: &Metadata,
+) -> Result<bool, HttpApiProblem> {
+    with_swap_types!(
+        metadata,
+        (|| {
+            let state = state_store
+                .get::<ROLE>(&id)?
+                .ok_or_else(problem::state_store)?;
+
+            Ok(match state.alpha_ledger_state {
+                LedgerState::Funded { .. } => true,
+                _ => false,
+            })
+        })
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral::prelude::*;
+
+    fn forwarded(previous_alpha_funded: bool) -> Hops {
+        Hops {
+            previous: Some(SwapId::default()),
+            next: None,
+            previous_alpha_funded,
+        }
+    }
+
+    fn is_ours(hops: Hops, action: Action<(), (), (), (), (), ()>) -> bool {
+        hops.is_ours(&action)
+    }
+
+    #[test]
+    fn next_hop_is_only_funded_once_alpha_of_previous_hop_is_funded() {
+        assert_that(&is_ours(forwarded(false), Action::Deploy(()))).is_false();
+        assert_that(&is_ours(forwarded(false), Action::Fund(()))).is_false();
+        assert_that(&is_ours(forwarded(false), Action::Refund(()))).is_true();
+
+        assert_that(&is_ours(forwarded(true), Action::Deploy(()))).is_true();
+        assert_that(&is_ours(forwarded(true), Action::Fund(()))).is_true();
+    }
+
+    #[test]
+    fn beta_of_forwarded_swap_is_left_to_next_hop() {
+        let hops = Hops {
+            next: Some(SwapId::default()),
+            ..Hops::default()
+        };
+
+        assert_that(&is_ours(hops, Action::Fund(()))).is_false();
+        assert_that(&is_ours(hops, Action::Refund(()))).is_false();
+        assert_that(&is_ours(hops, Action::Accept(()))).is_true();
+        assert_that(&is_ours(hops, Action::Redeem(()))).is_true();
+    }
+}
@@ -1,6 +1,7 @@
 pub mod accept;
 pub mod decline;
 mod handlers;
+pub mod hops;
 mod swap_state;
 
 use crate::{
@@ -26,18 +27,24 @@ use warp::{Rejection, Reply};
 pub use self::swap_state::SwapState;
 
 #[allow(clippy::needless_pass_by_value)]
-pub fn post_swap<A: AliceSpawner>(
+pub fn post_swap<A: AliceSpawner, T: MetadataStore<SwapId>, S: StateStore>(
     alice_spawner: A,
+    metadata_store: Arc<T>,
+    state_store: Arc<S>,
     request_body_kind: SwapRequestBodyKind,
 ) -> Result<impl Reply, Rejection> {
-    handle_post_swap(&alice_spawner, request_body_kind)
-        .map(|swap_created| {
-            let body = warp::reply::json(&swap_created);
-            let response =
-                warp::reply::with_header(body, header::LOCATION, swap_path(swap_created.id));
-            warp::reply::with_status(response, warp::http::StatusCode::CREATED)
-        })
-        .map_err(into_rejection)
+    handle_post_swap(
+        &alice_spawner,
+        metadata_store.as_ref(),
+        state_store.as_ref(),
+        request_body_kind,
+    )
+    .map(|swap_created| {
+        let body = warp::reply::json(&swap_created);
+        let response = warp::reply::with_header(body, header::LOCATION, swap_path(swap_created.id));
+        warp::reply::with_status(response, warp::http::StatusCode::CREATED)
+    })
+    .map_err(into_rejection)
 }
 
 #[allow(clippy::needless_pass_by_value)]
@@ -2,7 +2,7 @@ use crate::{
     http_api::{Http, SwapStatus},
     swap_protocols::{
         asset::Asset,
-        rfc003::{self, alice, bob, Ledger, SecretHash},
+        rfc003::{self, alice, bob, HashFunction, Ledger, SecretHash},
         Timestamp,
     },
 };
@@ -31,6 +31,7 @@ pub struct SwapCommunication<AI, BI> {
     alpha_refund_identity: Http<AI>,
     beta_refund_identity: Option<Http<BI>>,
     secret_hash: SecretHash,
+    hash_function: HashFunction,
 }
 
 #[derive(Debug, Serialize)]
@@ -67,6 +68,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> From<alice::SwapCommunication
                 alpha_refund_identity: Http(request.alpha_ledger_refund_identity),
                 beta_refund_identity: None,
                 secret_hash: request.secret_hash,
+                hash_function: request.hash_function,
             },
             Accepted { request, response } => Self {
                 status: SwapCommunicationState::Accepted,
@@ -77,6 +79,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> From<alice::SwapCommunication
                 alpha_refund_identity: Http(request.alpha_ledger_refund_identity),
                 beta_refund_identity: Some(Http(response.beta_ledger_refund_identity)),
                 secret_hash: request.secret_hash,
+                hash_function: request.hash_function,
             },
             Rejected { request, .. } => Self {
                 status: SwapCommunicationState::Rejected,
@@ -87,6 +90,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> From<alice::SwapCommunication
                 alpha_refund_identity: Http(request.alpha_ledger_refund_identity),
                 beta_refund_identity: None,
                 secret_hash: request.secret_hash,
+                hash_function: request.hash_function,
             },
         }
     }
@@ -107,6 +111,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> From<bob::SwapCommunication<A
                 alpha_refund_identity: Http(request.alpha_ledger_refund_identity),
                 beta_refund_identity: None,
                 secret_hash: request.secret_hash,
+                hash_function: request.hash_function,
             },
             Accepted { request, response } => Self {
                 status: SwapCommunicationState::Accepted,
@@ -117,6 +122,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> From<bob::SwapCommunication<A
                 alpha_refund_identity: Http(request.alpha_ledger_refund_identity),
                 beta_refund_identity: Some(Http(response.beta_ledger_refund_identity)),
                 secret_hash: request.secret_hash,
+                hash_function: request.hash_function,
             },
             Rejected { request, .. } => Self {
                 status: SwapCommunicationState::Rejected,
@@ -127,6 +133,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> From<bob::SwapCommunication<A
                 alpha_refund_identity: Http(request.alpha_ledger_refund_identity),
                 beta_refund_identity: None,
                 secret_hash: request.secret_hash,
+                hash_function: request.hash_function,
             },
         }
     }
@@ -173,6 +180,7 @@ impl<L: Ledger> From<rfc003::LedgerState<L>> for LedgerState<L::HtlcLocation, L:
                 htlc_location,
                 deploy_transaction,
                 fund_transaction,
+                ..
             } => Self {
                 status,
                 htlc_location: Some(Http(htlc_location)),
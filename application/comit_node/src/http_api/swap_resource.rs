@@ -1,6 +1,10 @@
 use crate::{
     http_api::{
-        action::ToSirenAction, problem, route_factory::swap_path, routes::rfc003::SwapState, Http,
+        action::ToSirenAction,
+        problem,
+        route_factory::swap_path,
+        routes::rfc003::{hops::Hops, SwapState},
+        Http,
     },
     swap_protocols::{
         actions::Actions,
@@ -71,6 +75,8 @@ pub fn build_rfc003_siren_entity<S: StateStore>(
     state_store: &S,
     id: SwapId,
     metadata: Metadata,
+    hops: Hops,
+    splits: Vec<SwapId>,
     include_state: IncludeState,
 ) -> Result<siren::Entity, HttpApiProblem> {
     with_swap_types!(
@@ -121,11 +127,39 @@ pub fn build_rfc003_siren_entity<S: StateStore>(
                     "https://github.com/comit-network/RFCs/blob/master/RFC-003-SWAP-Basic.md",
                 ));
 
-            let entity = actions.into_iter().fold(entity, |acc, action| {
-                let action = action.to_siren_action(&id);
-                acc.with_action(action)
+            let entity = match hops.previous {
+                Some(previous_hop) => entity.with_link(siren::NavigationalLink::new(
+                    &["previous-hop"],
+                    swap_path(previous_hop),
+                )),
+                None => entity,
+            };
+            let entity = match hops.next {
+                Some(next_hop) => entity.with_link(siren::NavigationalLink::new(
+                    &["next-hop"],
+                    swap_path(next_hop),
+                )),
+                None => entity,
+            };
+            let entity = match metadata.split_from {
+                Some(split_from) => entity.with_link(siren::NavigationalLink::new(
+                    &["parent"],
+                    swap_path(split_from),
+                )),
+                None => entity,
+            };
+            let entity = splits.iter().fold(entity, |acc, split| {
+                acc.with_link(siren::NavigationalLink::new(&["child"], swap_path(*split)))
             });
 
+            let entity = actions
+                .into_iter()
+                .filter(|action| hops.is_ours(action))
+                .fold(entity, |acc, action| {
+                    let action = action.to_siren_action(&id);
+                    acc.with_action(action)
+                });
+
             Ok(entity)
         })
     )
@@ -25,12 +25,12 @@ macro_rules! with_swap_types {
     ($metadata:expr, $fn:tt) => {{
         use crate::swap_protocols::{
             asset::AssetKind,
-            ledger::{Bitcoin, Ethereum},
+            ledger::{Bitcoin, Ethereum, Lightning, Litecoin},
             metadata_store::Metadata,
             LedgerKind,
         };
-        use bitcoin_support::BitcoinQuantity;
-        use ethereum_support::{Erc20Token, EtherQuantity};
+        use bitcoin_support::{BitcoinQuantity, LitecoinQuantity};
+        use ethereum_support::{Erc20Token, Erc721Token, EtherQuantity};
         let metadata = $metadata;
 
         match metadata {
@@ -40,6 +40,7 @@ macro_rules! with_swap_types {
                 alpha_asset: AssetKind::Bitcoin(_),
                 beta_asset: AssetKind::Ether(_),
                 role,
+                ..
             } => {
                 #[allow(dead_code)]
                 type AL = Bitcoin;
@@ -60,6 +61,7 @@ macro_rules! with_swap_types {
                 alpha_asset: AssetKind::Bitcoin(_),
                 beta_asset: AssetKind::Erc20(_),
                 role,
+                ..
             } => {
                 #[allow(dead_code)]
                 type AL = Bitcoin;
@@ -74,12 +76,34 @@ macro_rules! with_swap_types {
 
                 _match_role!(role, $fn)
             }
+            Metadata {
+                alpha_ledger: LedgerKind::Bitcoin(_),
+                beta_ledger: LedgerKind::Ethereum(_),
+                alpha_asset: AssetKind::Bitcoin(_),
+                beta_asset: AssetKind::Erc721(_),
+                role,
+                ..
+            } => {
+                #[allow(dead_code)]
+                type AL = Bitcoin;
+                #[allow(dead_code)]
+                type BL = Ethereum;
+                #[allow(dead_code)]
+                type AA = BitcoinQuantity;
+                #[allow(dead_code)]
+                type BA = Erc721Token;
+                #[allow(dead_code)]
+                type AcceptBody = crate::http_api::routes::rfc003::accept::OnlyRefund<BL>;
+
+                _match_role!(role, $fn)
+            }
             Metadata {
                 alpha_ledger: LedgerKind::Ethereum(_),
                 beta_ledger: LedgerKind::Bitcoin(_),
                 alpha_asset: AssetKind::Ether(_),
                 beta_asset: AssetKind::Bitcoin(_),
                 role,
+                ..
             } => {
                 #[allow(dead_code)]
                 type AL = Ethereum;
@@ -100,6 +124,7 @@ macro_rules! with_swap_types {
                 alpha_asset: AssetKind::Erc20(_),
                 beta_asset: AssetKind::Bitcoin(_),
                 role,
+                ..
             } => {
                 #[allow(dead_code)]
                 type AL = Ethereum;
@@ -114,6 +139,258 @@ macro_rules! with_swap_types {
 
                 _match_role!(role, $fn)
             }
+            Metadata {
+                alpha_ledger: LedgerKind::Ethereum(_),
+                beta_ledger: LedgerKind::Bitcoin(_),
+                alpha_asset: AssetKind::Erc721(_),
+                beta_asset: AssetKind::Bitcoin(_),
+                role,
+                ..
+            } => {
+                #[allow(dead_code)]
+                type AL = Ethereum;
+                #[allow(dead_code)]
+                type BL = Bitcoin;
+                #[allow(dead_code)]
+                type AA = Erc721Token;
+                #[allow(dead_code)]
+                type BA = BitcoinQuantity;
+                #[allow(dead_code)]
+                type AcceptBody = crate::http_api::routes::rfc003::accept::OnlyRedeem<AL>;
+
+                _match_role!(role, $fn)
+            }
+            Metadata {
+                alpha_ledger: LedgerKind::Litecoin(_),
+                beta_ledger: LedgerKind::Ethereum(_),
+                alpha_asset: AssetKind::Litecoin(_),
+                beta_asset: AssetKind::Ether(_),
+                role,
+                ..
+            } => {
+                #[allow(dead_code)]
+                type AL = Litecoin;
+                #[allow(dead_code)]
+                type BL = Ethereum;
+                #[allow(dead_code)]
+                type AA = LitecoinQuantity;
+                #[allow(dead_code)]
+                type BA = EtherQuantity;
+                #[allow(dead_code)]
+                type AcceptBody = crate::http_api::routes::rfc003::accept::OnlyRefund<BL>;
+
+                _match_role!(role, $fn)
+            }
+            Metadata {
+                alpha_ledger: LedgerKind::Litecoin(_),
+                beta_ledger: LedgerKind::Ethereum(_),
+                alpha_asset: AssetKind::Litecoin(_),
+                beta_asset: AssetKind::Erc20(_),
+                role,
+                ..
+            } => {
+                #[allow(dead_code)]
+                type AL = Litecoin;
+                #[allow(dead_code)]
+                type BL = Ethereum;
+                #[allow(dead_code)]
+                type AA = LitecoinQuantity;
+                #[allow(dead_code)]
+                type BA = Erc20Token;
+                #[allow(dead_code)]
+                type AcceptBody = crate::http_api::routes::rfc003::accept::OnlyRefund<BL>;
+
+                _match_role!(role, $fn)
+            }
+            Metadata {
+                alpha_ledger: LedgerKind::Ethereum(_),
+                beta_ledger: LedgerKind::Litecoin(_),
+                alpha_asset: AssetKind::Ether(_),
+                beta_asset: AssetKind::Litecoin(_),
+                role,
+                ..
+            } => {
+                #[allow(dead_code)]
+                type AL = Ethereum;
+                #[allow(dead_code)]
+                type BL = Litecoin;
+                #[allow(dead_code)]
+                type AA = EtherQuantity;
+                #[allow(dead_code)]
+                type BA = LitecoinQuantity;
+                #[allow(dead_code)]
+                type AcceptBody = crate::http_api::routes::rfc003::accept::OnlyRedeem<AL>;
+
+                _match_role!(role, $fn)
+            }
+            Metadata {
+                alpha_ledger: LedgerKind::Ethereum(_),
+                beta_ledger: LedgerKind::Litecoin(_),
+                alpha_asset: AssetKind::Erc20(_),
+                beta_asset: AssetKind::Litecoin(_),
+                role,
+                ..
+            } => {
+                #[allow(dead_code)]
+                type AL = Ethereum;
+                #[allow(dead_code)]
+                type BL = Litecoin;
+                #[allow(dead_code)]
+                type AA = Erc20Token;
+                #[allow(dead_code)]
+                type BA = LitecoinQuantity;
+                #[allow(dead_code)]
+                type AcceptBody = crate::http_api::routes::rfc003::accept::OnlyRedeem<AL>;
+
+                _match_role!(role, $fn)
+            }
+            Metadata {
+                alpha_ledger: LedgerKind::Ethereum(_),
+                beta_ledger: LedgerKind::Ethereum(_),
+                alpha_asset: AssetKind::Ether(_),
+                beta_asset: AssetKind::Erc20(_),
+                role,
+                ..
+            } => {
+                #[allow(dead_code)]
+                type AL = Ethereum;
+                #[allow(dead_code)]
+                type BL = Ethereum;
+                #[allow(dead_code)]
+                type AA = EtherQuantity;
+                #[allow(dead_code)]
+                type BA = Erc20Token;
+                #[allow(dead_code)]
+                type AcceptBody = crate::http_api::routes::rfc003::accept::RedeemAndRefund<AL, BL>;
+
+                _match_role!(role, $fn)
+            }
+            Metadata {
+                alpha_ledger: LedgerKind::Ethereum(_),
+                beta_ledger: LedgerKind::Ethereum(_),
+                alpha_asset: AssetKind::Erc20(_),
+                beta_asset: AssetKind::Ether(_),
+                role,
+                ..
+            } => {
+                #[allow(dead_code)]
+                type AL = Ethereum;
+                #[allow(dead_code)]
+                type BL = Ethereum;
+                #[allow(dead_code)]
+                type AA = Erc20Token;
+                #[allow(dead_code)]
+                type BA = EtherQuantity;
+                #[allow(dead_code)]
+                type AcceptBody = crate::http_api::routes::rfc003::accept::RedeemAndRefund<AL, BL>;
+
+                _match_role!(role, $fn)
+            }
+            Metadata {
+                alpha_ledger: LedgerKind::Ethereum(_),
+                beta_ledger: LedgerKind::Ethereum(_),
+                alpha_asset: AssetKind::Erc20(_),
+                beta_asset: AssetKind::Erc20(_),
+                role,
+                ..
+            } => {
+                #[allow(dead_code)]
+                type AL = Ethereum;
+                #[allow(dead_code)]
+                type BL = Ethereum;
+                #[allow(dead_code)]
+                type AA = Erc20Token;
+                #[allow(dead_code)]
+                type BA = Erc20Token;
+                #[allow(dead_code)]
+                type AcceptBody = crate::http_api::routes::rfc003::accept::RedeemAndRefund<AL, BL>;
+
+                _match_role!(role, $fn)
+            }
+            Metadata {
+                alpha_ledger: LedgerKind::Ethereum(_),
+                beta_ledger: LedgerKind::Ethereum(_),
+                alpha_asset: AssetKind::Ether(_),
+                beta_asset: AssetKind::Erc721(_),
+                role,
+                ..
+            } => {
+                #[allow(dead_code)]
+                type AL = Ethereum;
+                #[allow(dead_code)]
+                type BL = Ethereum;
+                #[allow(dead_code)]
+                type AA = EtherQuantity;
+                #[allow(dead_code)]
+                type BA = Erc721Token;
+                #[allow(dead_code)]
+                type AcceptBody = crate::http_api::routes::rfc003::accept::RedeemAndRefund<AL, BL>;
+
+                _match_role!(role, $fn)
+            }
+            Metadata {
+                alpha_ledger: LedgerKind::Ethereum(_),
+                beta_ledger: LedgerKind::Ethereum(_),
+                alpha_asset: AssetKind::Erc721(_),
+                beta_asset: AssetKind::Ether(_),
+                role,
+                ..
+            } => {
+                #[allow(dead_code)]
+                type AL = Ethereum;
+                #[allow(dead_code)]
+                type BL = Ethereum;
+                #[allow(dead_code)]
+                type AA = Erc721Token;
+                #[allow(dead_code)]
+                type BA = EtherQuantity;
+                #[allow(dead_code)]
+                type AcceptBody = crate::http_api::routes::rfc003::accept::RedeemAndRefund<AL, BL>;
+
+                _match_role!(role, $fn)
+            }
+            Metadata {
+                alpha_ledger: LedgerKind::Lightning(_),
+                beta_ledger: LedgerKind::Ethereum(_),
+                alpha_asset: AssetKind::Bitcoin(_),
+                beta_asset: AssetKind::Ether(_),
+                role,
+                ..
+            } => {
+                #[allow(dead_code)]
+                type AL = Lightning;
+                #[allow(dead_code)]
+                type BL = Ethereum;
+                #[allow(dead_code)]
+                type AA = BitcoinQuantity;
+                #[allow(dead_code)]
+                type BA = EtherQuantity;
+                #[allow(dead_code)]
+                type AcceptBody = crate::http_api::routes::rfc003::accept::RedeemAndRefund<AL, BL>;
+
+                _match_role!(role, $fn)
+            }
+            Metadata {
+                alpha_ledger: LedgerKind::Lightning(_),
+                beta_ledger: LedgerKind::Ethereum(_),
+                alpha_asset: AssetKind::Bitcoin(_),
+                beta_asset: AssetKind::Erc20(_),
+                role,
+                ..
+            } => {
+                #[allow(dead_code)]
+                type AL = Lightning;
+                #[allow(dead_code)]
+                type BL = Ethereum;
+                #[allow(dead_code)]
+                type AA = BitcoinQuantity;
+                #[allow(dead_code)]
+                type BA = Erc20Token;
+                #[allow(dead_code)]
+                type AcceptBody = crate::http_api::routes::rfc003::accept::RedeemAndRefund<AL, BL>;
+
+                _match_role!(role, $fn)
+            }
             _ => unimplemented!(),
         }
     }};
@@ -0,0 +1,365 @@
+use crate::{
+    lnd::{Error, QueryLightning},
+    swap_protocols::{
+        ledger::{InvoicePayment, InvoicePaymentState},
+        rfc003::{Secret, SecretHash},
+    },
+};
+use bitcoin_support::BitcoinQuantity;
+use futures::future::{self, Either, Future};
+use reqwest::{
+    r#async::{Client, RequestBuilder},
+    StatusCode, Url,
+};
+use std::{str::FromStr, time::Duration};
+
+/// Talks to lnd through its REST interface rather than gRPC. lnd's REST
+/// gateway mirrors the gRPC API call by call and passes the macaroon on as
+/// gRPC metadata through the `Grpc-Metadata-macaroon` header, so we get the
+/// same calls without a protobuf toolchain and can mock lnd with a plain
+/// `warp` server in tests.
+#[derive(Debug, Clone)]
+pub struct LndHttpClient {
+    client: Client,
+    endpoint: Url,
+    macaroon: Option<String>,
+    poll_interval: Duration,
+}
+
+mod payloads {
+    use derivative::Derivative;
+    use serde::{de, Deserialize, Deserializer};
+
+    /// lnd encodes 64 bit integers as strings
+    fn u64_from_str<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Invoice {
+        /// base64 encoded, empty until the invoice is settled
+        #[serde(default)]
+        pub r_preimage: String,
+        #[serde(default, deserialize_with = "u64_from_str")]
+        pub amt_paid_sat: u64,
+        #[serde(default)]
+        pub state: InvoiceState,
+    }
+
+    #[derive(Debug, Deserialize, Derivative, Clone, Copy, PartialEq)]
+    #[derivative(Default)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum InvoiceState {
+        #[derivative(Default)]
+        Open,
+        Settled,
+        Canceled,
+        Accepted,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Payments {
+        #[serde(default)]
+        pub payments: Vec<Payment>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct Payment {
+        /// hex encoded
+        pub payment_hash: String,
+        #[serde(default, deserialize_with = "u64_from_str")]
+        pub value_sat: u64,
+        /// hex encoded, all zeros until the payment succeeded
+        #[serde(default)]
+        pub payment_preimage: String,
+        #[serde(default)]
+        pub status: PaymentStatus,
+    }
+
+    #[derive(Debug, Deserialize, Derivative, Clone, Copy, PartialEq)]
+    #[derivative(Default)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum PaymentStatus {
+        #[derivative(Default)]
+        Unknown,
+        InFlight,
+        Succeeded,
+        Failed,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub struct LndError {
+        pub error: String,
+    }
+}
+
+impl LndHttpClient {
+    pub fn new(endpoint: Url, macaroon: Option<String>, poll_interval: Duration) -> Self {
+        Self {
+            client: Client::new(),
+            endpoint,
+            macaroon,
+            poll_interval,
+        }
+    }
+
+    fn get(&self, path: &str) -> RequestBuilder {
+        let url = self.endpoint.join(path).expect("invalid url");
+        let request = self.client.get(url);
+
+        match &self.macaroon {
+            Some(macaroon) => request.header("Grpc-Metadata-macaroon", macaroon.as_str()),
+            None => request,
+        }
+    }
+
+    /// The hold invoice keyed on `payment_hash`, if this node is the one
+    /// receiving the payment.
+    fn lookup_invoice(
+        &self,
+        payment_hash: SecretHash,
+    ) -> impl Future<Item = Option<payloads::Invoice>, Error = Error> {
+        self.get(&format!("v1/invoice/{:x}", payment_hash))
+            .send()
+            .map_err(|e| Error::FailedRequest(format!("Failed to look up invoice: {:?}", e)))
+            .and_then(|mut response| {
+                if response.status().is_success() {
+                    return Either::A(
+                        response
+                            .json::<payloads::Invoice>()
+                            .map(Some)
+                            .map_err(|e| Error::MalformedResponse(format!("{:?}", e))),
+                    );
+                }
+
+                // Depending on its version lnd answers with 404 or a generic
+                // error if it does not know the invoice
+                let status = response.status();
+                Either::B(
+                    response
+                        .json::<payloads::LndError>()
+                        .then(move |error| match error {
+                            Ok(ref error) if error.error.contains("unable to locate invoice") => {
+                                Ok(None)
+                            }
+                            _ if status == StatusCode::NOT_FOUND => Ok(None),
+                            Ok(error) => Err(Error::FailedRequest(error.error)),
+                            Err(_) => Err(Error::FailedRequest(format!("lnd answered {}", status))),
+                        }),
+                )
+            })
+    }
+
+    /// All payments this node sent, including the ones still in flight.
+    fn list_payments(&self) -> impl Future<Item = Vec<payloads::Payment>, Error = Error> {
+        self.get("v1/payments?include_incomplete=true")
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| Error::FailedRequest(format!("Failed to list payments: {:?}", e)))
+            .and_then(|mut response| {
+                response
+                    .json::<payloads::Payments>()
+                    .map_err(|e| Error::MalformedResponse(format!("{:?}", e)))
+            })
+            .map(|payments| payments.payments)
+    }
+}
+
+impl QueryLightning for LndHttpClient {
+    fn invoice_payment(
+        &self,
+        payment_hash: SecretHash,
+    ) -> Box<dyn Future<Item = Option<InvoicePayment>, Error = Error> + Send> {
+        let client = self.clone();
+
+        Box::new(
+            self.lookup_invoice(payment_hash)
+                .and_then(move |invoice| match invoice {
+                    Some(invoice) => {
+                        Either::A(future::result(received_payment(payment_hash, invoice)))
+                    }
+                    None => Either::B(client.list_payments().and_then(move |payments| {
+                        // Failed attempts stay in the list, the latest one counts
+                        payments
+                            .into_iter()
+                            .filter(|payment| payment.payment_hash == format!("{:x}", payment_hash))
+                            .last()
+                            .map_or(Ok(None), |payment| sent_payment(payment_hash, payment))
+                    })),
+                }),
+        )
+    }
+
+    fn poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+}
+
+fn received_payment(
+    payment_hash: SecretHash,
+    invoice: payloads::Invoice,
+) -> Result<Option<InvoicePayment>, Error> {
+    use self::payloads::InvoiceState::*;
+
+    let state = match invoice.state {
+        Open => return Ok(None),
+        Accepted => InvoicePaymentState::Accepted,
+        Canceled => InvoicePaymentState::Canceled,
+        Settled => {
+            let preimage = base64::decode(&invoice.r_preimage)
+                .map_err(|e| Error::MalformedResponse(format!("{:?}", e)))?;
+            let preimage = Secret::from_vec(&preimage)
+                .map_err(|e| Error::MalformedResponse(format!("{:?}", e)))?;
+
+            InvoicePaymentState::Settled { preimage }
+        }
+    };
+
+    Ok(Some(InvoicePayment {
+        payment_hash,
+        amount: BitcoinQuantity::from_satoshi(invoice.amt_paid_sat),
+        state,
+    }))
+}
+
+fn sent_payment(
+    payment_hash: SecretHash,
+    payment: payloads::Payment,
+) -> Result<Option<InvoicePayment>, Error> {
+    use self::payloads::PaymentStatus::*;
+
+    let state = match payment.status {
+        Unknown => return Ok(None),
+        InFlight => InvoicePaymentState::Accepted,
+        Failed => InvoicePaymentState::Canceled,
+        Succeeded => {
+            let preimage = Secret::from_str(&payment.payment_preimage)
+                .map_err(|e| Error::MalformedResponse(format!("{:?}", e)))?;
+
+            InvoicePaymentState::Settled { preimage }
+        }
+    };
+
+    Ok(Some(InvoicePayment {
+        payment_hash,
+        amount: BitcoinQuantity::from_satoshi(payment.value_sat),
+        state,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::rfc003::HashFunction;
+    use serde_json::json;
+    use spectral::prelude::*;
+    use tokio::runtime::Runtime;
+    use warp::{self, http::StatusCode, Filter};
+
+    const MACAROON: &str = "0201036c6e6402cf01030a10";
+
+    fn secret() -> Secret {
+        Secret::from(*b"hello world, you are beautiful!!")
+    }
+
+    #[test]
+    fn given_the_node_receives_the_payment_returns_settled_invoice() {
+        let mut runtime = Runtime::new().unwrap();
+        let secret = secret();
+
+        let invoice = warp::path!("v1" / "invoice" / String)
+            .and(warp::header::exact("grpc-metadata-macaroon", MACAROON))
+            .map(move |_| {
+                warp::reply::json(&json!({
+                    "r_preimage": base64::encode(secret.raw_secret()),
+                    "amt_paid_sat": "100000",
+                    "state": "SETTLED",
+                }))
+            });
+        let (address, server) = warp::serve(invoice).bind_ephemeral(([127, 0, 0, 1], 0));
+        runtime.spawn(server);
+
+        let client = LndHttpClient::new(
+            Url::parse(&format!("http://{}/", address)).unwrap(),
+            Some(MACAROON.to_string()),
+            Duration::from_secs(1),
+        );
+        let invoice_payment =
+            runtime.block_on(client.invoice_payment(secret.hash(HashFunction::Sha256)));
+
+        assert_that(&invoice_payment).is_ok_containing(Some(InvoicePayment {
+            payment_hash: secret.hash(HashFunction::Sha256),
+            amount: BitcoinQuantity::from_satoshi(100_000),
+            state: InvoicePaymentState::Settled { preimage: secret },
+        }));
+    }
+
+    #[test]
+    fn given_the_node_sends_the_payment_returns_payment_in_flight() {
+        let mut runtime = Runtime::new().unwrap();
+        let payment_hash = secret().hash(HashFunction::Sha256);
+
+        let invoice = warp::path!("v1" / "invoice" / String).map(|_| {
+            warp::reply::with_status(
+                warp::reply::json(&json!({ "error": "unable to locate invoice", "code": 2 })),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            )
+        });
+        let payments = warp::path!("v1" / "payments").map(move || {
+            warp::reply::json(&json!({
+                "payments": [
+                    {
+                        "payment_hash": format!("{:x}", payment_hash),
+                        "value_sat": "100000",
+                        "payment_preimage": "0000000000000000000000000000000000000000000000000000000000000000",
+                        "status": "FAILED",
+                    },
+                    {
+                        "payment_hash": format!("{:x}", payment_hash),
+                        "value_sat": "100000",
+                        "payment_preimage": "0000000000000000000000000000000000000000000000000000000000000000",
+                        "status": "IN_FLIGHT",
+                    }
+                ]
+            }))
+        });
+        let (address, server) =
+            warp::serve(invoice.or(payments)).bind_ephemeral(([127, 0, 0, 1], 0));
+        runtime.spawn(server);
+
+        let client = LndHttpClient::new(
+            Url::parse(&format!("http://{}/", address)).unwrap(),
+            None,
+            Duration::from_secs(1),
+        );
+        let invoice_payment = runtime.block_on(client.invoice_payment(payment_hash));
+
+        assert_that(&invoice_payment).is_ok_containing(Some(InvoicePayment {
+            payment_hash,
+            amount: BitcoinQuantity::from_satoshi(100_000),
+            state: InvoicePaymentState::Accepted,
+        }));
+    }
+
+    #[test]
+    fn given_nothing_was_paid_yet_returns_none() {
+        let mut runtime = Runtime::new().unwrap();
+
+        let invoice = warp::path!("v1" / "invoice" / String)
+            .map(|_| warp::reply::json(&json!({ "state": "OPEN" })));
+        let (address, server) = warp::serve(invoice).bind_ephemeral(([127, 0, 0, 1], 0));
+        runtime.spawn(server);
+
+        let client = LndHttpClient::new(
+            Url::parse(&format!("http://{}/", address)).unwrap(),
+            None,
+            Duration::from_secs(1),
+        );
+        let invoice_payment =
+            runtime.block_on(client.invoice_payment(secret().hash(HashFunction::Sha256)));
+
+        assert_that(&invoice_payment).is_ok_containing(None);
+    }
+}
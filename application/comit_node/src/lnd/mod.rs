@@ -0,0 +1,77 @@
+pub use self::client::LndHttpClient;
+
+use crate::swap_protocols::{ledger::InvoicePayment, rfc003::SecretHash};
+use failure::Fail;
+use futures::{future, stream::Stream, Future};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::timer::Interval;
+
+mod client;
+
+#[derive(Fail, Debug, PartialEq, Clone)]
+pub enum Error {
+    #[fail(display = "The request to lnd failed to send.")]
+    FailedRequest(String),
+    #[fail(display = "The response of lnd was somehow malformed.")]
+    MalformedResponse(String),
+    #[fail(display = "comit_node is not connected to an lnd node.")]
+    NotConfigured,
+    #[fail(display = "The lnd client encountered an unrecoverable internal error.")]
+    Internal,
+}
+
+pub trait QueryLightning: Send + Sync {
+    /// Looks up the payment of the hold invoice keyed on `payment_hash`.
+    /// Depending on the side of the swap, the node either receives the
+    /// payment through the invoice or sends it. `None` if nothing was paid
+    /// yet.
+    fn invoice_payment(
+        &self,
+        payment_hash: SecretHash,
+    ) -> Box<dyn Future<Item = Option<InvoicePayment>, Error = Error> + Send>;
+
+    fn poll_interval(&self) -> Duration;
+}
+
+/// Stands in for lnd if no node is configured. Lightning swaps fail as soon
+/// as they start watching the ledger.
+#[derive(Debug, Clone, Copy)]
+pub struct NotConfigured;
+
+impl QueryLightning for NotConfigured {
+    fn invoice_payment(
+        &self,
+        _: SecretHash,
+    ) -> Box<dyn Future<Item = Option<InvoicePayment>, Error = Error> + Send> {
+        Box::new(future::err(Error::NotConfigured))
+    }
+
+    fn poll_interval(&self) -> Duration {
+        Duration::from_secs(60)
+    }
+}
+
+/// Polls the payment of the hold invoice keyed on `payment_hash` until
+/// `select` picks something from it.
+pub fn poll_invoice_payment<I, F>(
+    query_lightning: Arc<dyn QueryLightning + Send + Sync>,
+    payment_hash: SecretHash,
+    mut select: F,
+) -> Box<dyn Future<Item = I, Error = Error> + Send>
+where
+    I: Send + 'static,
+    F: FnMut(InvoicePayment) -> Option<I> + Send + 'static,
+{
+    Box::new(
+        Interval::new(Instant::now(), query_lightning.poll_interval())
+            .map_err(|_| Error::Internal)
+            .and_then(move |_| query_lightning.invoice_payment(payment_hash))
+            .filter_map(move |invoice_payment| invoice_payment.and_then(&mut select))
+            .into_future()
+            .map(|(item, _)| item.expect("Ticker shouldn't stop"))
+            .map_err(|(e, _)| e),
+    )
+}
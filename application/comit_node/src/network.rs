@@ -1,11 +1,11 @@
 use crate::{
-    bam_ext::{FromBamHeader, ToBamHeader},
+    bam_ext::{FromBamHeader, Offer, ToBamHeader},
     comit_client::SwapReject,
     libp2p_bam::{BamBehaviour, PendingIncomingRequest},
     swap_protocols::{
         asset::{Asset, AssetKind},
-        rfc003::{self, bob::BobSpawner, CreateLedgerEvents},
-        LedgerEventDependencies, LedgerKind, SwapId, SwapProtocol,
+        rfc003::{self, bob::BobSpawner, CreateLedgerEvents, HashFunction, Policy},
+        LedgerEventDependencies, LedgerKind, SwapId, SwapProtocol, Timestamp,
     },
 };
 use bam::{
@@ -30,6 +30,9 @@ use tokio::{
     runtime::TaskExecutor,
 };
 
+/// The swap protocols we offer and accept, most preferred first
+pub const SUPPORTED_PROTOCOLS: &[SwapProtocol] = &[SwapProtocol::Rfc003];
+
 #[derive(NetworkBehaviour)]
 #[allow(missing_debug_implementations)]
 pub struct Behaviour<TSubstream, B> {
@@ -39,17 +42,20 @@ pub struct Behaviour<TSubstream, B> {
     #[behaviour(ignore)]
     bob: B,
     #[behaviour(ignore)]
+    policy: Policy,
+    #[behaviour(ignore)]
     task_executor: TaskExecutor,
 }
 
 impl<TSubstream, B> Behaviour<TSubstream, B> {
-    pub fn new(bob: B, task_executor: TaskExecutor) -> Result<Self, io::Error> {
+    pub fn new(bob: B, policy: Policy, task_executor: TaskExecutor) -> Result<Self, io::Error> {
         let mut swap_headers = HashSet::new();
         swap_headers.insert("alpha_ledger".into());
         swap_headers.insert("beta_ledger".into());
         swap_headers.insert("alpha_asset".into());
         swap_headers.insert("beta_asset".into());
         swap_headers.insert("protocol".into());
+        swap_headers.insert("hash_function".into());
 
         let mut known_headers = HashMap::new();
         known_headers.insert("SWAP".into(), swap_headers);
@@ -58,6 +64,7 @@ impl<TSubstream, B> Behaviour<TSubstream, B> {
             bam: BamBehaviour::new(known_headers),
             mdns: Mdns::new()?,
             bob,
+            policy,
             task_executor,
         })
     }
@@ -97,7 +104,7 @@ impl<TSubstream, B: BobSpawner> NetworkBehaviourEventProcess<PendingIncomingRequ
     fn inject_event(&mut self, event: PendingIncomingRequest) {
         let PendingIncomingRequest { request, channel } = event;
 
-        let generated_response = handle_request(&self.bob, request);
+        let generated_response = handle_request(&self.bob, &self.policy, request);
 
         let future = generated_response
             .and_then(|response| {
@@ -134,15 +141,15 @@ impl<TSubstream, B> NetworkBehaviourEventProcess<libp2p::mdns::MdnsEvent>
 
 fn handle_request<B: BobSpawner>(
     bob: &B,
+    policy: &Policy,
     mut request: ValidatedIncomingRequest,
 ) -> Box<dyn Future<Item = Response, Error = Infallible> + Send> {
     match request.request_type() {
         "SWAP" => {
-            let protocol: SwapProtocol = bam::header!(request
-                .take_header("protocol")
-                .map(SwapProtocol::from_bam_header));
-            match protocol {
-                SwapProtocol::Rfc003 => {
+            let protocols: Offer<SwapProtocol> =
+                bam::header!(request.take_header("protocol").map(Offer::from_bam_header));
+            match protocols.negotiate(SUPPORTED_PROTOCOLS) {
+                Some(SwapProtocol::Rfc003) => {
                     let swap_id = SwapId::default();
 
                     let alpha_ledger = bam::header!(request
@@ -157,6 +164,18 @@ fn handle_request<B: BobSpawner>(
                     let beta_asset = bam::header!(request
                         .take_header("beta_asset")
                         .map(AssetKind::from_bam_header));
+                    // An unknown hash function is unsupported, not malformed
+                    let hash_function = match request
+                        .take_header("hash_function")
+                        .map(HashFunction::from_bam_header)
+                    {
+                        None => HashFunction::default(),
+                        Some(Ok(hash_function)) => hash_function,
+                        Some(Err(e)) => {
+                            log::warn!("the requested hash function is not supported: {:?}", e);
+                            return Box::new(futures::future::ok(Response::new(Status::SE(21))));
+                        }
+                    };
 
                     match (alpha_ledger, beta_ledger, alpha_asset, beta_asset) {
                         (
@@ -166,11 +185,13 @@ fn handle_request<B: BobSpawner>(
                             AssetKind::Ether(beta_asset),
                         ) => spawn(
                             bob,
+                            policy,
                             swap_id,
                             alpha_ledger,
                             beta_ledger,
                             alpha_asset,
                             beta_asset,
+                            hash_function,
                             bam::body!(request.take_body_as()),
                         ),
                         (
@@ -180,11 +201,13 @@ fn handle_request<B: BobSpawner>(
                             AssetKind::Bitcoin(beta_asset),
                         ) => spawn(
                             bob,
+                            policy,
                             swap_id,
                             alpha_ledger,
                             beta_ledger,
                             alpha_asset,
                             beta_asset,
+                            hash_function,
                             bam::body!(request.take_body_as()),
                         ),
                         (
@@ -194,11 +217,13 @@ fn handle_request<B: BobSpawner>(
                             AssetKind::Erc20(beta_asset),
                         ) => spawn(
                             bob,
+                            policy,
                             swap_id,
                             alpha_ledger,
                             beta_ledger,
                             alpha_asset,
                             beta_asset,
+                            hash_function,
                             bam::body!(request.take_body_as()),
                         ),
                         (
@@ -208,11 +233,221 @@ fn handle_request<B: BobSpawner>(
                             AssetKind::Bitcoin(beta_asset),
                         ) => spawn(
                             bob,
+                            policy,
+                            swap_id,
+                            alpha_ledger,
+                            beta_ledger,
+                            alpha_asset,
+                            beta_asset,
+                            hash_function,
+                            bam::body!(request.take_body_as()),
+                        ),
+                        (
+                            LedgerKind::Bitcoin(alpha_ledger),
+                            LedgerKind::Ethereum(beta_ledger),
+                            AssetKind::Bitcoin(alpha_asset),
+                            AssetKind::Erc721(beta_asset),
+                        ) => spawn(
+                            bob,
+                            policy,
+                            swap_id,
+                            alpha_ledger,
+                            beta_ledger,
+                            alpha_asset,
+                            beta_asset,
+                            hash_function,
+                            bam::body!(request.take_body_as()),
+                        ),
+                        (
+                            LedgerKind::Ethereum(alpha_ledger),
+                            LedgerKind::Bitcoin(beta_ledger),
+                            AssetKind::Erc721(alpha_asset),
+                            AssetKind::Bitcoin(beta_asset),
+                        ) => spawn(
+                            bob,
+                            policy,
                             swap_id,
                             alpha_ledger,
                             beta_ledger,
                             alpha_asset,
                             beta_asset,
+                            hash_function,
+                            bam::body!(request.take_body_as()),
+                        ),
+                        (
+                            LedgerKind::Litecoin(alpha_ledger),
+                            LedgerKind::Ethereum(beta_ledger),
+                            AssetKind::Litecoin(alpha_asset),
+                            AssetKind::Ether(beta_asset),
+                        ) => spawn(
+                            bob,
+                            policy,
+                            swap_id,
+                            alpha_ledger,
+                            beta_ledger,
+                            alpha_asset,
+                            beta_asset,
+                            hash_function,
+                            bam::body!(request.take_body_as()),
+                        ),
+                        (
+                            LedgerKind::Litecoin(alpha_ledger),
+                            LedgerKind::Ethereum(beta_ledger),
+                            AssetKind::Litecoin(alpha_asset),
+                            AssetKind::Erc20(beta_asset),
+                        ) => spawn(
+                            bob,
+                            policy,
+                            swap_id,
+                            alpha_ledger,
+                            beta_ledger,
+                            alpha_asset,
+                            beta_asset,
+                            hash_function,
+                            bam::body!(request.take_body_as()),
+                        ),
+                        (
+                            LedgerKind::Ethereum(alpha_ledger),
+                            LedgerKind::Litecoin(beta_ledger),
+                            AssetKind::Ether(alpha_asset),
+                            AssetKind::Litecoin(beta_asset),
+                        ) => spawn(
+                            bob,
+                            policy,
+                            swap_id,
+                            alpha_ledger,
+                            beta_ledger,
+                            alpha_asset,
+                            beta_asset,
+                            hash_function,
+                            bam::body!(request.take_body_as()),
+                        ),
+                        (
+                            LedgerKind::Ethereum(alpha_ledger),
+                            LedgerKind::Litecoin(beta_ledger),
+                            AssetKind::Erc20(alpha_asset),
+                            AssetKind::Litecoin(beta_asset),
+                        ) => spawn(
+                            bob,
+                            policy,
+                            swap_id,
+                            alpha_ledger,
+                            beta_ledger,
+                            alpha_asset,
+                            beta_asset,
+                            hash_function,
+                            bam::body!(request.take_body_as()),
+                        ),
+                        (
+                            LedgerKind::Ethereum(alpha_ledger),
+                            LedgerKind::Ethereum(beta_ledger),
+                            AssetKind::Ether(alpha_asset),
+                            AssetKind::Erc20(beta_asset),
+                        ) => spawn(
+                            bob,
+                            policy,
+                            swap_id,
+                            alpha_ledger,
+                            beta_ledger,
+                            alpha_asset,
+                            beta_asset,
+                            hash_function,
+                            bam::body!(request.take_body_as()),
+                        ),
+                        (
+                            LedgerKind::Ethereum(alpha_ledger),
+                            LedgerKind::Ethereum(beta_ledger),
+                            AssetKind::Erc20(alpha_asset),
+                            AssetKind::Ether(beta_asset),
+                        ) => spawn(
+                            bob,
+                            policy,
+                            swap_id,
+                            alpha_ledger,
+                            beta_ledger,
+                            alpha_asset,
+                            beta_asset,
+                            hash_function,
+                            bam::body!(request.take_body_as()),
+                        ),
+                        (
+                            LedgerKind::Ethereum(alpha_ledger),
+                            LedgerKind::Ethereum(beta_ledger),
+                            AssetKind::Erc20(alpha_asset),
+                            AssetKind::Erc20(beta_asset),
+                        ) => spawn(
+                            bob,
+                            policy,
+                            swap_id,
+                            alpha_ledger,
+                            beta_ledger,
+                            alpha_asset,
+                            beta_asset,
+                            hash_function,
+                            bam::body!(request.take_body_as()),
+                        ),
+                        (
+                            LedgerKind::Ethereum(alpha_ledger),
+                            LedgerKind::Ethereum(beta_ledger),
+                            AssetKind::Ether(alpha_asset),
+                            AssetKind::Erc721(beta_asset),
+                        ) => spawn(
+                            bob,
+                            policy,
+                            swap_id,
+                            alpha_ledger,
+                            beta_ledger,
+                            alpha_asset,
+                            beta_asset,
+                            hash_function,
+                            bam::body!(request.take_body_as()),
+                        ),
+                        (
+                            LedgerKind::Ethereum(alpha_ledger),
+                            LedgerKind::Ethereum(beta_ledger),
+                            AssetKind::Erc721(alpha_asset),
+                            AssetKind::Ether(beta_asset),
+                        ) => spawn(
+                            bob,
+                            policy,
+                            swap_id,
+                            alpha_ledger,
+                            beta_ledger,
+                            alpha_asset,
+                            beta_asset,
+                            hash_function,
+                            bam::body!(request.take_body_as()),
+                        ),
+                        (
+                            LedgerKind::Lightning(alpha_ledger),
+                            LedgerKind::Ethereum(beta_ledger),
+                            AssetKind::Bitcoin(alpha_asset),
+                            AssetKind::Ether(beta_asset),
+                        ) => spawn(
+                            bob,
+                            policy,
+                            swap_id,
+                            alpha_ledger,
+                            beta_ledger,
+                            alpha_asset,
+                            beta_asset,
+                            hash_function,
+                            bam::body!(request.take_body_as()),
+                        ),
+                        (
+                            LedgerKind::Lightning(alpha_ledger),
+                            LedgerKind::Ethereum(beta_ledger),
+                            AssetKind::Bitcoin(alpha_asset),
+                            AssetKind::Erc20(beta_asset),
+                        ) => spawn(
+                            bob,
+                            policy,
+                            swap_id,
+                            alpha_ledger,
+                            beta_ledger,
+                            alpha_asset,
+                            beta_asset,
+                            hash_function,
                             bam::body!(request.take_body_as()),
                         ),
                         (alpha_ledger, beta_ledger, alpha_asset, beta_asset) => {
@@ -223,9 +458,12 @@ fn handle_request<B: BobSpawner>(
                         }
                     }
                 }
-                SwapProtocol::Unknown(protocol) => {
-                    log::warn!("the swap protocol {} is currently not supported", protocol);
-                    Box::new(futures::future::ok(Response::new(Status::RE(21))))
+                _ => {
+                    log::warn!(
+                        "none of the swap protocols in {:?} are supported",
+                        protocols
+                    );
+                    Box::new(futures::future::ok(unsupported_protocols_response()))
                 }
             }
         }
@@ -237,43 +475,76 @@ fn handle_request<B: BobSpawner>(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn<AL: rfc003::Ledger, BL: rfc003::Ledger, AA: Asset, BA: Asset, B: BobSpawner>(
     bob_spawner: &B,
+    policy: &Policy,
     swap_id: SwapId,
     alpha_ledger: AL,
     beta_ledger: BL,
     alpha_asset: AA,
     beta_asset: BA,
+    hash_function: HashFunction,
     body: rfc003::messages::RequestBody<AL, BL>,
 ) -> Box<dyn Future<Item = Response, Error = Infallible> + Send + 'static>
 where
     LedgerEventDependencies: CreateLedgerEvents<AL, AA> + CreateLedgerEvents<BL, BA>,
 {
-    match bob_spawner.spawn(
-        swap_id,
-        rfc003::messages::Request::<AL, BL, AA, BA> {
-            alpha_asset,
-            beta_asset,
-            alpha_ledger,
-            beta_ledger,
-            alpha_ledger_refund_identity: body.alpha_ledger_refund_identity,
-            beta_ledger_redeem_identity: body.beta_ledger_redeem_identity,
-            alpha_expiry: body.alpha_expiry,
-            beta_expiry: body.beta_expiry,
-            secret_hash: body.secret_hash,
-        },
-    ) {
+    let request = rfc003::messages::Request::<AL, BL, AA, BA> {
+        alpha_asset,
+        beta_asset,
+        alpha_ledger,
+        beta_ledger,
+        alpha_ledger_refund_identity: body.alpha_ledger_refund_identity,
+        beta_ledger_redeem_identity: body.beta_ledger_redeem_identity,
+        alpha_expiry: body.alpha_expiry,
+        beta_expiry: body.beta_expiry,
+        secret_hash: body.secret_hash,
+        hash_function,
+    };
+
+    if !request.hash_function_is_supported() {
+        log::warn!(
+            "the HTLCs on {:?} or {:?} cannot check secrets hashed with {:?}",
+            request.alpha_ledger,
+            request.beta_ledger,
+            hash_function
+        );
+        return Box::new(futures::future::ok(Response::new(Status::SE(21))));
+    }
+
+    if !request.complies_with(policy, Timestamp::now()) {
+        log::warn!(
+            "the HTLCs on {:?} and {:?} expiring at {:?} and {:?} don't comply with {:?}",
+            request.alpha_ledger,
+            request.beta_ledger,
+            request.alpha_expiry,
+            request.beta_expiry,
+            policy
+        );
+        return Box::new(futures::future::ok(Response::new(Status::SE(21))));
+    }
+
+    match bob_spawner.spawn(swap_id, request) {
         Ok(response_future) => Box::new(response_future.then(move |result| {
             let response = match result {
                 Ok(Ok(response)) => {
                     let body = rfc003::messages::AcceptResponseBody::<AL, BL> {
                         beta_ledger_refund_identity: response.beta_ledger_refund_identity,
                         alpha_ledger_redeem_identity: response.alpha_ledger_redeem_identity,
+                        partial_fill: response.partial_fill,
                     };
-                    Response::new(Status::OK(20)).with_body(
-                        serde_json::to_value(body)
-                            .expect("body should always serialize into serde_json::Value"),
-                    )
+                    Response::new(Status::OK(20))
+                        .with_header(
+                            "protocol",
+                            SwapProtocol::Rfc003
+                                .to_bam_header()
+                                .expect("rfc003 should always serialize into a header"),
+                        )
+                        .with_body(
+                            serde_json::to_value(body)
+                                .expect("body should always serialize into serde_json::Value"),
+                        )
                 }
                 // FIXME: the called code should not be able to produce a "Rejected" here
                 // Rejected is for cases were we can automatically determine that a given swap
@@ -283,6 +554,7 @@ where
                 //
                 // As long as Alice and Bob use the same state machine, this is not possible though.
                 Ok(Err(SwapReject::Rejected)) => Response::new(Status::SE(21)),
+                Ok(Err(SwapReject::Unsupported { .. })) => unsupported_protocols_response(),
                 Ok(Err(SwapReject::Declined { reason: None })) => Response::new(Status::SE(20)),
                 Ok(Err(SwapReject::Declined {
                     reason: Some(reason),
@@ -309,3 +581,15 @@ where
         }
     }
 }
+
+/// Tells the requester that we support none of the protocols they offered,
+/// unlike `SE(21)` which rejects the swap itself
+fn unsupported_protocols_response() -> Response {
+    Response::new(Status::SE(22)).with_header(
+        "Supported-Protocols",
+        SUPPORTED_PROTOCOLS
+            .to_vec()
+            .to_bam_header()
+            .expect("supported protocols should serialize to a header"),
+    )
+}
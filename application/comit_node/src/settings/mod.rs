@@ -1,8 +1,11 @@
 mod serde_duration;
 mod serde_log;
+mod serde_urls;
 
-use crate::seed::Seed;
+use crate::{seed::Seed, swap_protocols::rfc003::Policy};
+use ::btsieve::bitcoin::Checkpoint;
 use config::{Config, ConfigError, File};
+use derivative::Derivative;
 use libp2p::Multiaddr;
 use log::LevelFilter;
 use serde::{Deserialize, Serialize};
@@ -23,6 +26,11 @@ pub struct ComitNodeSettings {
     pub http_api: HttpSocket,
     pub btsieve: Btsieve,
     pub web_gui: Option<HttpSocket>,
+    /// The lnd node Lightning swaps are watched through.
+    pub lightning: Option<Lnd>,
+    /// What swap requests from other nodes have to comply with
+    #[serde(default)]
+    pub rfc003: Policy,
     #[serde(default = "default_log_levels")]
     pub log_levels: LogLevels,
 }
@@ -47,6 +55,9 @@ impl Default for ComitNodeSettings {
             },
             btsieve: Btsieve {
                 url: btsieve_url,
+                fallback_urls: Vec::new(),
+                authentication: None,
+                embedded: None,
                 bitcoin: PollParameters {
                     poll_interval_secs: Duration::from_secs(300),
                     network: "regtest".into(),
@@ -55,11 +66,14 @@ impl Default for ComitNodeSettings {
                     poll_interval_secs: Duration::from_secs(20),
                     network: "regtest".into(),
                 },
+                litecoin: default_litecoin(),
             },
             web_gui: Some(HttpSocket {
                 address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
                 port: 8080,
             }),
+            lightning: None,
+            rfc003: Policy::default(),
             log_levels: LogLevels {
                 comit_node: LevelFilter::Debug,
             },
@@ -67,6 +81,13 @@ impl Default for ComitNodeSettings {
     }
 }
 
+fn default_litecoin() -> PollParameters {
+    PollParameters {
+        poll_interval_secs: Duration::from_secs(75),
+        network: "regtest".into(),
+    }
+}
+
 fn default_log() -> LevelFilter {
     LevelFilter::Debug
 }
@@ -103,14 +124,87 @@ pub struct HttpSocket {
 pub struct Btsieve {
     #[serde(with = "url_serde")]
     pub url: url::Url,
+    /// Other btsieve instances watching the same ledgers. If `url` fails,
+    /// comit_node re-creates its queries on the next healthy one.
+    #[serde(
+        with = "self::serde_urls",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub fallback_urls: Vec<url::Url>,
+    pub authentication: Option<BtsieveAuthentication>,
+    /// Runs btsieve inside of comit_node instead of connecting to `url`.
+    /// Only available if comit_node is built with the `embedded-btsieve`
+    /// feature.
+    pub embedded: Option<EmbeddedBtsieve>,
     pub bitcoin: PollParameters,
     pub ethereum: PollParameters,
+    #[serde(default = "default_litecoin")]
+    pub litecoin: PollParameters,
+}
+
+/// Has to match the `http_api.authentication` section of btsieve's settings.
+#[derive(Clone, Derivative, Deserialize, Serialize, PartialEq)]
+#[derivative(Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BtsieveAuthentication {
+    Bearer {
+        #[derivative(Debug = "ignore")]
+        token: String,
+    },
+    Hmac {
+        #[derivative(Debug = "ignore")]
+        secret: String,
+    },
+}
+
+/// The ledger nodes an embedded btsieve connects to. A ledger without a node
+/// cannot be queried.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct EmbeddedBtsieve {
+    pub bitcoin: Option<EmbeddedBitcoin>,
+    pub ethereum: Option<EmbeddedEthereum>,
+}
+
+#[derive(Clone, Derivative, Deserialize, Serialize, PartialEq)]
+#[derivative(Debug)]
+pub struct EmbeddedBitcoin {
+    pub zmq_endpoint: String,
+    #[serde(with = "url_serde")]
+    pub node_url: url::Url,
+    pub node_username: String,
+    #[derivative(Debug = "ignore")]
+    pub node_password: String,
+    /// See btsieve's `checkpoint` setting
+    pub checkpoint: Option<Checkpoint>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct EmbeddedEthereum {
+    #[serde(with = "url_serde")]
+    pub node_url: url::Url,
+}
+
+#[derive(Clone, Derivative, Deserialize, Serialize, PartialEq)]
+#[derivative(Debug)]
+pub struct Lnd {
+    /// lnd's REST interface
+    #[serde(with = "url_serde")]
+    pub url: url::Url,
+    /// Hex encoded, as lnd expects it in the `Grpc-Metadata-macaroon` header
+    #[derivative(Debug = "ignore")]
+    pub macaroon: Option<String>,
+    #[serde(with = "self::serde_duration")]
+    pub poll_interval_secs: Duration,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct PollParameters {
     #[serde(with = "self::serde_duration")]
     pub poll_interval_secs: Duration,
+    /// The name btsieve serves the ledger under. For Ethereum that is either
+    /// a well-known network or the name of the EVM chain in the
+    /// `ethereum.chain` section of btsieve's settings.
     pub network: String,
 }
 
@@ -208,6 +302,90 @@ mod tests {
             .is_equal_to(&Duration::from_secs(20));
     }
 
+    #[test]
+    fn given_no_litecoin_section_litecoin_defaults_to_regtest() {
+        let settings = comit_settings();
+
+        let settings = assert_that(&settings).is_ok().subject;
+        assert_that(&settings.btsieve.litecoin).is_equal_to(default_litecoin());
+    }
+
+    #[test]
+    fn given_no_rfc003_section_default_policy_is_used() {
+        let settings = comit_settings();
+
+        let settings = assert_that(&settings).is_ok().subject;
+        assert_that(&settings.rfc003).is_equal_to(Policy::default());
+    }
+
+    #[test]
+    fn can_read_rfc003_policy() {
+        let settings = ComitNodeSettings::read("./config/rfc003_policy.toml");
+
+        let settings = assert_that(&settings).is_ok().subject;
+        assert_that(&settings.rfc003).is_equal_to(Policy {
+            min_refund_after_blocks: 288,
+            ether_htlc_registry: Some(ethereum_support::Address::from([2u8; 20])),
+            erc20_htlc_factory: Some(ethereum_support::Address::from([1u8; 20])),
+            create2_htlc_factory: Some(ethereum_support::Address::from([3u8; 20])),
+        });
+    }
+
+    #[test]
+    fn can_read_btsieve_authentication() {
+        let settings = ComitNodeSettings::read("./config/btsieve_authentication.toml");
+
+        let settings = assert_that(&settings).is_ok().subject;
+        assert_that(&settings.btsieve.authentication).is_equal_to(Some(
+            BtsieveAuthentication::Bearer {
+                token: "a4f7ac1c3a3b5f3d".to_string(),
+            },
+        ));
+    }
+
+    #[test]
+    fn can_read_custom_evm_chain_name() {
+        let settings = ComitNodeSettings::read("./config/evm_chain.toml");
+
+        let settings = assert_that(&settings).is_ok().subject;
+        assert_that(&settings.btsieve.ethereum.network).is_equal_to("devchain".to_string());
+    }
+
+    #[test]
+    fn can_read_embedded_btsieve() {
+        let settings = ComitNodeSettings::read("./config/embedded_btsieve.toml");
+
+        let settings = assert_that(&settings).is_ok().subject;
+        let embedded = assert_that(&settings.btsieve.embedded).is_some().subject;
+        assert_that(&embedded.bitcoin).is_none();
+        assert_that(&embedded.ethereum).is_equal_to(Some(EmbeddedEthereum {
+            node_url: Url::parse("http://localhost:8545").unwrap(),
+        }));
+    }
+
+    #[test]
+    fn can_read_btsieve_fallback_urls() {
+        let settings = ComitNodeSettings::read("./config/btsieve_failover.toml");
+
+        let settings = assert_that(&settings).is_ok().subject;
+        assert_that(&settings.btsieve.fallback_urls).is_equal_to(vec![
+            Url::parse("http://localhost:8182").unwrap(),
+            Url::parse("http://localhost:8183").unwrap(),
+        ]);
+    }
+
+    #[test]
+    fn can_read_lnd() {
+        let settings = ComitNodeSettings::read("./config/lnd.toml");
+
+        let settings = assert_that(&settings).is_ok().subject;
+        assert_that(&settings.lightning).is_equal_to(Some(Lnd {
+            url: Url::parse("https://localhost:8080").unwrap(),
+            macaroon: Some("0201036c6e6402cf01030a10".to_string()),
+            poll_interval_secs: Duration::from_secs(5),
+        }));
+    }
+
     #[test]
     fn config_folder_does_not_exist_will_create_folder_and_config_file() {
         let tmp_dir = env::temp_dir();
@@ -0,0 +1,20 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use url::Url;
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Url>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|url| Url::parse(url).map_err(de::Error::custom))
+        .collect()
+}
+
+pub fn serialize<S: Serializer>(value: &[Url], serializer: S) -> Result<S::Ok, S::Error> {
+    value
+        .iter()
+        .map(Url::as_str)
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
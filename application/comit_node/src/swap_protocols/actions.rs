@@ -11,13 +11,13 @@ pub trait Actions {
 }
 
 pub mod bitcoin {
-    use bitcoin_support::{Address, BitcoinQuantity, Network};
+    use bitcoin_support::{Address, BitcoinQuantity, Chain, ChainAddress, Network};
     use bitcoin_witness::{PrimedInput, PrimedTransaction};
     use serde::Serialize;
 
     #[derive(Debug, Clone, PartialEq, Serialize)]
     pub struct SendToAddress {
-        pub to: Address,
+        pub to: ChainAddress,
         pub amount: BitcoinQuantity,
         pub network: Network,
     }
@@ -27,6 +27,7 @@ pub mod bitcoin {
         // Remember: One man's input is another man's output!
         pub output: PrimedInput,
         pub network: Network,
+        pub chain: Chain,
     }
 
     impl SpendOutput {
@@ -41,7 +42,7 @@ pub mod bitcoin {
 
 pub mod ethereum {
     use crate::swap_protocols::Timestamp;
-    use ethereum_support::{web3::types::U256, Address, Bytes, EtherQuantity, Network};
+    use ethereum_support::{web3::types::U256, Address, Bytes, ChainId, EtherQuantity};
     use serde::Serialize;
 
     #[derive(Debug, Clone, PartialEq, Serialize)]
@@ -49,15 +50,56 @@ pub mod ethereum {
         pub data: Bytes,
         pub amount: EtherQuantity,
         pub gas_limit: U256,
-        pub network: Network,
+        pub chain_id: ChainId,
     }
 
     #[derive(Debug, Clone, PartialEq, Serialize)]
     pub struct CallContract {
         pub to: Address,
         pub data: Bytes,
+        pub amount: EtherQuantity,
         pub gas_limit: U256,
-        pub network: Network,
+        pub chain_id: ChainId,
         pub min_block_timestamp: Option<Timestamp>,
     }
+
+    /// Gets an HTLC on chain, either by deploying it or by calling a contract
+    /// that deploys it or keeps it together with other HTLCs
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub enum DeployOrCallContract {
+        Deploy(DeployContract),
+        Call(CallContract),
+    }
+}
+
+pub mod lightning {
+    use crate::swap_protocols::rfc003::{Secret, SecretHash};
+    use bitcoin_support::{BitcoinQuantity, Network};
+    use secp256k1_support::PublicKey;
+    use serde::Serialize;
+
+    /// Pays `amount` to the node `to`, locked to `secret_hash`. Only succeeds
+    /// once the receiving node added a matching hold invoice.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct SendPayment {
+        pub to: PublicKey,
+        pub amount: BitcoinQuantity,
+        pub secret_hash: SecretHash,
+        pub final_cltv_delta: u32,
+        pub network: Network,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct AddHoldInvoice {
+        pub amount: BitcoinQuantity,
+        pub secret_hash: SecretHash,
+        pub cltv_expiry: u32,
+        pub network: Network,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct SettleInvoice {
+        pub secret: Secret,
+        pub network: Network,
+    }
 }
@@ -1,7 +1,7 @@
 use crate::http_api::asset::FromHttpAsset;
-use bitcoin_support::BitcoinQuantity;
+use bitcoin_support::{BitcoinQuantity, LitecoinQuantity};
 use derivative::Derivative;
-use ethereum_support::{Erc20Token, EtherQuantity};
+use ethereum_support::{Erc20Quantity, Erc20Token, Erc721Token, EtherQuantity, ToBigInt, U256};
 use std::{
     fmt::{Debug, Display},
     hash::Hash,
@@ -22,33 +22,136 @@ pub trait Asset:
     + Into<AssetKind>
 {
     fn equal_or_greater_value(&self, other: &Self) -> bool;
+
+    /// The quantity of this asset in its smallest unit, `None` if it cannot
+    /// be divided
+    fn base_units(&self) -> Option<U256>;
+
+    /// This asset with another quantity in its smallest unit, `None` if it
+    /// cannot be divided or the quantity doesn't fit
+    fn with_base_units(&self, base_units: U256) -> Option<Self>;
+}
+
+/// Whether trading `alpha` for `beta` gets at least as much of the beta asset
+/// per unit of the alpha asset as trading `other_alpha` for `other_beta`
+pub fn rate_is_at_least<AA: Asset, BA: Asset>(
+    (alpha, beta): (&AA, &BA),
+    (other_alpha, other_beta): (&AA, &BA),
+) -> bool {
+    match (
+        alpha.base_units(),
+        beta.base_units(),
+        other_alpha.base_units(),
+        other_beta.base_units(),
+    ) {
+        (Some(alpha), Some(beta), Some(other_alpha), Some(other_beta)) => {
+            beta.to_bigint() * other_alpha.to_bigint() >= other_beta.to_bigint() * alpha.to_bigint()
+        }
+        _ => false,
+    }
+}
+
+fn u64_base_units(base_units: U256) -> Option<u64> {
+    if base_units > U256::from(u64::max_value()) {
+        return None;
+    }
+    Some(base_units.low_u64())
 }
 
 impl Asset for BitcoinQuantity {
     fn equal_or_greater_value(&self, other: &BitcoinQuantity) -> bool {
         self >= other
     }
+
+    fn base_units(&self) -> Option<U256> {
+        Some(U256::from(self.satoshi()))
+    }
+
+    fn with_base_units(&self, base_units: U256) -> Option<Self> {
+        u64_base_units(base_units).map(BitcoinQuantity::from_satoshi)
+    }
+}
+impl Asset for LitecoinQuantity {
+    fn equal_or_greater_value(&self, other: &LitecoinQuantity) -> bool {
+        self >= other
+    }
+
+    fn base_units(&self) -> Option<U256> {
+        Some(U256::from(self.litoshi()))
+    }
+
+    fn with_base_units(&self, base_units: U256) -> Option<Self> {
+        u64_base_units(base_units).map(LitecoinQuantity::from_litoshi)
+    }
 }
 impl Asset for EtherQuantity {
     fn equal_or_greater_value(&self, other: &EtherQuantity) -> bool {
         self >= other
     }
+
+    fn base_units(&self) -> Option<U256> {
+        Some(self.wei())
+    }
+
+    fn with_base_units(&self, base_units: U256) -> Option<Self> {
+        Some(EtherQuantity::from_wei(base_units))
+    }
 }
 impl Asset for Erc20Token {
     fn equal_or_greater_value(&self, other: &Erc20Token) -> bool {
         self.token_contract == other.token_contract && self.quantity >= other.quantity
     }
+
+    fn base_units(&self) -> Option<U256> {
+        Some(self.quantity.0)
+    }
+
+    fn with_base_units(&self, base_units: U256) -> Option<Self> {
+        Some(Erc20Token {
+            token_contract: self.token_contract,
+            quantity: Erc20Quantity(base_units),
+        })
+    }
 }
+impl Asset for Erc721Token {
+    fn equal_or_greater_value(&self, other: &Erc721Token) -> bool {
+        self == other
+    }
+
+    fn base_units(&self) -> Option<U256> {
+        None
+    }
 
-#[derive(Clone, Derivative)]
+    fn with_base_units(&self, _: U256) -> Option<Self> {
+        None
+    }
+}
+
+#[derive(Clone, PartialEq, Derivative)]
 #[derivative(Debug = "transparent")]
 pub enum AssetKind {
     Bitcoin(BitcoinQuantity),
     Ether(EtherQuantity),
     Erc20(Erc20Token),
+    Erc721(Erc721Token),
+    Litecoin(LitecoinQuantity),
     Unknown(String),
 }
 
+impl AssetKind {
+    /// See `Asset::base_units`, `None` for unknown assets as well
+    pub fn base_units(&self) -> Option<U256> {
+        match self {
+            AssetKind::Bitcoin(quantity) => quantity.base_units(),
+            AssetKind::Ether(quantity) => quantity.base_units(),
+            AssetKind::Erc20(token) => token.base_units(),
+            AssetKind::Erc721(token) => token.base_units(),
+            AssetKind::Litecoin(quantity) => quantity.base_units(),
+            AssetKind::Unknown(_) => None,
+        }
+    }
+}
+
 impl From<BitcoinQuantity> for AssetKind {
     fn from(quantity: BitcoinQuantity) -> Self {
         AssetKind::Bitcoin(quantity)
@@ -66,3 +169,15 @@ impl From<Erc20Token> for AssetKind {
         AssetKind::Erc20(quantity)
     }
 }
+
+impl From<Erc721Token> for AssetKind {
+    fn from(token: Erc721Token) -> Self {
+        AssetKind::Erc721(token)
+    }
+}
+
+impl From<LitecoinQuantity> for AssetKind {
+    fn from(quantity: LitecoinQuantity) -> Self {
+        AssetKind::Litecoin(quantity)
+    }
+}
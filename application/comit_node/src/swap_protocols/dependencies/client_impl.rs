@@ -4,14 +4,14 @@
 // without passing in all the dependencies just in case we have to
 // open up a new connection and decide how to respond to requests?
 use crate::{
-    bam_ext::{FromBamHeader, ToBamHeader},
+    bam_ext::{FromBamHeader, Offer, ToBamHeader},
     comit_client::{Client, RequestError, SwapDeclineReason, SwapReject},
-    network::Behaviour,
+    network::{Behaviour, SUPPORTED_PROTOCOLS},
     node_id::NodeId,
     swap_protocols::{
         self,
         asset::Asset,
-        rfc003::{self, bob::BobSpawner},
+        rfc003::{self, bob::BobSpawner, HashFunction},
         SwapProtocol,
     },
 };
@@ -68,6 +68,27 @@ where
             Ok(mut response) => match response.status() {
                 Status::OK(_) => {
                     log::info!("{} accepted swap request: {:?}", node_id.clone(), response);
+                    // Without negotiation, RFC003 was the only protocol
+                    let protocol = response
+                        .take_header("protocol")
+                        .map_or(Ok(SwapProtocol::Rfc003), SwapProtocol::from_bam_header)
+                        .map_err(|e| {
+                            log::error!(
+                                "Could not deserialize header in response {:?}: {}",
+                                response,
+                                e,
+                            );
+                            RequestError::InvalidResponse
+                        })?;
+                    if protocol != SwapProtocol::Rfc003 {
+                        log::error!(
+                            "{} accepted an RFC003 swap request with {:?}",
+                            node_id.clone(),
+                            protocol
+                        );
+                        return Err(RequestError::InvalidResponse);
+                    }
+
                     match serde_json::from_value(response.body().clone()) {
                         Ok(response) => Ok(Ok(response)),
                         Err(_e) => Err(RequestError::InvalidResponse),
@@ -92,6 +113,28 @@ where
                         SwapReject::Declined { reason }
                     }))
                 }
+                Status::SE(22) => {
+                    log::info!(
+                        "{} does not support any of the offered protocols: {:?}",
+                        node_id.clone(),
+                        response
+                    );
+                    let supported_protocols = response
+                        .take_header("Supported-Protocols")
+                        .map_or(Ok(Vec::new()), Vec::<SwapProtocol>::from_bam_header)
+                        .map_err(|e| {
+                            log::error!(
+                                "Could not deserialize header in response {:?}: {}",
+                                response,
+                                e,
+                            );
+                            RequestError::InvalidResponse
+                        })?;
+
+                    Ok(Err(SwapReject::Unsupported {
+                        supported_protocols,
+                    }))
+                }
                 Status::SE(_) => {
                     log::info!("{} rejected swap request: {:?}", node_id.clone(), response);
                     Ok(Err(SwapReject::Rejected))
@@ -133,13 +176,25 @@ fn build_swap_request<
     let beta_expiry = request.beta_expiry;
     let secret_hash = request.secret_hash;
 
-    Ok(json::OutgoingRequest::new("SWAP")
+    let mut outgoing_request = json::OutgoingRequest::new("SWAP")
         .with_header("alpha_ledger", request.alpha_ledger.into().to_bam_header()?)
         .with_header("beta_ledger", request.beta_ledger.into().to_bam_header()?)
         .with_header("alpha_asset", request.alpha_asset.into().to_bam_header()?)
         .with_header("beta_asset", request.beta_asset.into().to_bam_header()?)
-        .with_header("protocol", SwapProtocol::Rfc003.to_bam_header()?)
-        .with_body(serde_json::to_value(rfc003::messages::RequestBody::<
+        .with_header(
+            "protocol",
+            Offer::of(SUPPORTED_PROTOCOLS)
+                .expect("we support at least one protocol")
+                .to_bam_header()?,
+        );
+
+    if request.hash_function != HashFunction::default() {
+        outgoing_request =
+            outgoing_request.with_header("hash_function", request.hash_function.to_bam_header()?)
+    }
+
+    Ok(
+        outgoing_request.with_body(serde_json::to_value(rfc003::messages::RequestBody::<
             AL,
             BL,
         > {
@@ -148,5 +203,6 @@ fn build_swap_request<
             alpha_expiry,
             beta_expiry,
             secret_hash,
-        })?))
+        })?),
+    )
 }
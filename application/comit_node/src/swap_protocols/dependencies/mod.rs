@@ -1,5 +1,6 @@
 use crate::{
     btsieve::{QueryBitcoin, QueryEthereum},
+    lnd::{self, QueryLightning},
     seed::Seed,
 };
 use std::sync::Arc;
@@ -50,14 +51,31 @@ pub mod bob {
 pub struct LedgerEventDependencies {
     pub query_bitcoin: Arc<dyn QueryBitcoin + Send + Sync + 'static>,
     pub query_ethereum: Arc<dyn QueryEthereum + Send + Sync + 'static>,
+    /// Litecoin is queried through btsieve's Bitcoin query engine, just
+    /// under a different ledger.
+    pub query_litecoin: Arc<dyn QueryBitcoin + Send + Sync + 'static>,
+    pub query_lightning: Arc<dyn QueryLightning + Send + Sync + 'static>,
 }
 
-impl<Q: QueryBitcoin + QueryEthereum + Send + Sync + 'static> From<Q> for LedgerEventDependencies {
-    fn from(querier: Q) -> Self {
+impl LedgerEventDependencies {
+    pub fn new<Q, L>(querier: Q, litecoin_querier: L) -> Self
+    where
+        Q: QueryBitcoin + QueryEthereum + Send + Sync + 'static,
+        L: QueryBitcoin + Send + Sync + 'static,
+    {
         let queries = Arc::new(querier);
         LedgerEventDependencies {
             query_bitcoin: queries.clone(),
             query_ethereum: queries.clone(),
+            query_litecoin: Arc::new(litecoin_querier),
+            query_lightning: Arc::new(lnd::NotConfigured),
+        }
+    }
+
+    pub fn with_lightning<Q: QueryLightning + 'static>(self, query_lightning: Q) -> Self {
+        LedgerEventDependencies {
+            query_lightning: Arc::new(query_lightning),
+            ..self
         }
     }
 }
@@ -2,24 +2,48 @@ use crate::swap_protocols::ledger::{Ledger, LedgerKind};
 use bitcoin_support::{
     Address, BitcoinQuantity, IntoP2wpkhAddress, Network, PubkeyHash, Transaction, TransactionId,
 };
+use derivative::Derivative;
 use secp256k1_support::PublicKey;
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Bitcoin {
     pub network: Network,
+    pub htlc: HtlcKind,
+    /// Refund the HTLCs this many blocks after they were funded instead of
+    /// at the expiry of the swap
+    pub refund_after_blocks: Option<u16>,
+}
+
+/// The kind of output the HTLCs of a swap are locked in. Both parties have
+/// to agree on it, hence it is part of the ledger Alice proposes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Derivative)]
+#[derivative(Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HtlcKind {
+    /// A single script with a branch for redeeming and one for refunding.
+    /// Peers that predate Taproot HTLCs only know this kind and don't send
+    /// the `htlc` parameter.
+    #[derivative(Default)]
+    P2wsh,
+    /// One tapleaf each for redeeming and refunding. Spends only reveal the
+    /// leaf they use, which makes them smaller and more private.
+    Taproot,
 }
 
 impl Bitcoin {
     pub fn new(network: Network) -> Self {
-        Bitcoin { network }
+        Bitcoin {
+            network,
+            htlc: HtlcKind::default(),
+            refund_after_blocks: None,
+        }
     }
 }
 
 impl Default for Bitcoin {
     fn default() -> Self {
-        Bitcoin {
-            network: Network::Regtest,
-        }
+        Bitcoin::new(Network::Regtest)
     }
 }
 
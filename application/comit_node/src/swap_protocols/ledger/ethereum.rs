@@ -1,23 +1,43 @@
 use crate::swap_protocols::ledger::{Ledger, LedgerKind};
-use ethereum_support::{Address, EtherQuantity, Network, Transaction, H256};
+use ethereum_support::{Address, ChainId, EtherQuantity, Transaction, H256};
 use secp256k1_support::PublicKey;
 
+/// Any EVM-compatible chain, told apart from the others by its EIP-155 chain
+/// id.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Ethereum {
-    pub network: Network,
+    pub chain_id: ChainId,
+    /// The factory ERC20 HTLCs are deployed and funded through in a single
+    /// transaction. Without one they are deployed and funded separately.
+    ///
+    /// The HTLC address is taken from the logs of the factory, hence Bob only
+    /// accepts the one from his `rfc003::Policy`.
+    pub erc20_htlc_factory: Option<Address>,
+    /// The registry Ether HTLCs are kept in instead of a contract of their
+    /// own, which saves deploying one for every swap.
+    pub ether_htlc_registry: Option<Address>,
+    /// The factory HTLCs are deployed through with CREATE2, which makes their
+    /// address known before they are deployed.
+    ///
+    /// The HTLC address is taken from the logs of the factory, hence Bob only
+    /// accepts the one from his `rfc003::Policy`.
+    pub create2_htlc_factory: Option<Address>,
 }
 
 impl Ethereum {
-    pub fn new(network: Network) -> Self {
-        Ethereum { network }
+    pub fn new(chain_id: ChainId) -> Self {
+        Ethereum {
+            chain_id,
+            erc20_htlc_factory: None,
+            ether_htlc_registry: None,
+            create2_htlc_factory: None,
+        }
     }
 }
 
 impl Default for Ethereum {
     fn default() -> Self {
-        Ethereum {
-            network: Network::Regtest,
-        }
+        Ethereum::new(ChainId::REGTEST)
     }
 }
 
@@ -0,0 +1,69 @@
+use crate::swap_protocols::{
+    ledger::{Ledger, LedgerKind},
+    rfc003::{Secret, SecretHash},
+};
+use bitcoin_support::{BitcoinQuantity, Network};
+use secp256k1_support::PublicKey;
+use serde::{Deserialize, Serialize};
+
+/// The Lightning Network on top of the given Bitcoin network. Identities are
+/// node public keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Lightning {
+    pub network: Network,
+}
+
+impl Lightning {
+    pub fn new(network: Network) -> Self {
+        Lightning { network }
+    }
+}
+
+impl Default for Lightning {
+    fn default() -> Self {
+        Lightning {
+            network: Network::Regtest,
+        }
+    }
+}
+
+/// The payment of a hold invoice as seen by either the paying or the
+/// receiving node. This is the closest thing to a transaction Lightning
+/// has.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InvoicePayment {
+    pub payment_hash: SecretHash,
+    pub amount: BitcoinQuantity,
+    pub state: InvoicePaymentState,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum InvoicePaymentState {
+    /// The HTLCs paying the invoice are locked in, but the invoice is held
+    /// until the receiver settles or cancels it.
+    Accepted,
+    Settled {
+        preimage: Secret,
+    },
+    Canceled,
+}
+
+impl Ledger for Lightning {
+    type Quantity = BitcoinQuantity;
+    type TxId = SecretHash;
+    type Pubkey = PublicKey;
+    type Address = PublicKey;
+    type Identity = PublicKey;
+    type Transaction = InvoicePayment;
+
+    fn address_for_identity(&self, public_key: PublicKey) -> PublicKey {
+        public_key
+    }
+}
+
+impl From<Lightning> for LedgerKind {
+    fn from(lightning: Lightning) -> Self {
+        LedgerKind::Lightning(lightning)
+    }
+}
@@ -0,0 +1,43 @@
+use crate::swap_protocols::ledger::{Ledger, LedgerKind};
+use bitcoin_support::{
+    Address, IntoP2wpkhAddress, LitecoinQuantity, Network, PubkeyHash, Transaction, TransactionId,
+};
+use secp256k1_support::PublicKey;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Litecoin {
+    pub network: Network,
+}
+
+impl Litecoin {
+    pub fn new(network: Network) -> Self {
+        Litecoin { network }
+    }
+}
+
+impl Default for Litecoin {
+    fn default() -> Self {
+        Litecoin {
+            network: Network::Regtest,
+        }
+    }
+}
+
+impl Ledger for Litecoin {
+    type Quantity = LitecoinQuantity;
+    type TxId = TransactionId;
+    type Pubkey = PublicKey;
+    type Address = Address;
+    type Identity = PubkeyHash;
+    type Transaction = Transaction;
+
+    fn address_for_identity(&self, pubkeyhash: PubkeyHash) -> Address {
+        pubkeyhash.into_p2wpkh_address(self.network)
+    }
+}
+
+impl From<Litecoin> for LedgerKind {
+    fn from(litecoin: Litecoin) -> Self {
+        LedgerKind::Litecoin(litecoin)
+    }
+}
@@ -1,7 +1,14 @@
 mod bitcoin;
 mod ethereum;
+mod lightning;
+mod litecoin;
 
-pub use self::{bitcoin::Bitcoin, ethereum::Ethereum};
+pub use self::{
+    bitcoin::{Bitcoin, HtlcKind},
+    ethereum::Ethereum,
+    lightning::{InvoicePayment, InvoicePaymentState, Lightning},
+    litecoin::Litecoin,
+};
 
 use crate::http_api::ledger::FromHttpLedger;
 use derivative::Derivative;
@@ -49,10 +56,12 @@ pub trait Ledger:
     fn address_for_identity(&self, identity: Self::Identity) -> Self::Address;
 }
 
-#[derive(Clone, Derivative)]
+#[derive(Clone, PartialEq, Derivative)]
 #[derivative(Debug = "transparent")]
 pub enum LedgerKind {
     Bitcoin(Bitcoin),
     Ethereum(Ethereum),
+    Litecoin(Litecoin),
+    Lightning(Lightning),
     Unknown(String),
 }
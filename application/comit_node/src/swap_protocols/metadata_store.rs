@@ -1,4 +1,4 @@
-use crate::swap_protocols::{asset::AssetKind, LedgerKind};
+use crate::swap_protocols::{asset::AssetKind, LedgerKind, SwapId};
 use failure::Fail;
 use std::{
     collections::HashMap,
@@ -20,6 +20,10 @@ pub struct Metadata {
     pub alpha_asset: AssetKind,
     pub beta_asset: AssetKind,
     pub role: RoleKind,
+    /// The swap we received as Bob and forwarded with this one, if any
+    pub previous_hop: Option<SwapId>,
+    /// The swap whose order we split this one off from, if any
+    pub split_from: Option<SwapId>,
 }
 
 #[derive(Debug, Fail)]
@@ -34,6 +38,31 @@ pub trait MetadataStore<K>: Send + Sync + 'static {
     fn all(&self) -> Result<Vec<(K, Metadata)>, Error>;
 }
 
+/// Finds the swap that forwarded the swap with the given id to the next hop
+pub fn next_hop<T: MetadataStore<SwapId>>(
+    metadata_store: &T,
+    id: &SwapId,
+) -> Result<Option<SwapId>, Error> {
+    Ok(metadata_store
+        .all()?
+        .into_iter()
+        .find(|(_, metadata)| metadata.previous_hop.as_ref() == Some(id))
+        .map(|(next_hop, _)| next_hop))
+}
+
+/// Finds the swaps that were split off from the swap with the given id
+pub fn splits<T: MetadataStore<SwapId>>(
+    metadata_store: &T,
+    id: &SwapId,
+) -> Result<Vec<SwapId>, Error> {
+    Ok(metadata_store
+        .all()?
+        .into_iter()
+        .filter(|(_, metadata)| metadata.split_from.as_ref() == Some(id))
+        .map(|(split, _)| split)
+        .collect())
+}
+
 #[derive(Debug, Default)]
 pub struct InMemoryMetadataStore<K: Hash + Eq> {
     metadata: Mutex<HashMap<K, Metadata>>,
@@ -15,7 +15,7 @@ pub use self::{
     timestamp::Timestamp,
 };
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SwapProtocol {
     Rfc003,
     Unknown(String),
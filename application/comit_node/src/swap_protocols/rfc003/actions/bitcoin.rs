@@ -1,70 +1,67 @@
 use crate::swap_protocols::{
     actions::bitcoin::{SendToAddress, SpendOutput},
-    ledger::Bitcoin,
     rfc003::{
         actions::{FundAction, RedeemAction, RefundAction},
-        bitcoin::Htlc,
+        bitcoin::UtxoLedger,
         secret_source::SecretSource,
         state_machine::HtlcParams,
         Secret,
     },
 };
-use bitcoin_support::{BitcoinQuantity, OutPoint};
+use bitcoin_support::OutPoint;
 use bitcoin_witness::PrimedInput;
 
-impl FundAction<Bitcoin, BitcoinQuantity> for (Bitcoin, BitcoinQuantity) {
+impl<L: UtxoLedger> FundAction<L, L::Native> for (L, L::Native) {
     type FundActionOutput = SendToAddress;
 
-    fn fund_action(htlc_params: HtlcParams<Bitcoin, BitcoinQuantity>) -> Self::FundActionOutput {
+    fn fund_action(htlc_params: HtlcParams<L, L::Native>) -> Self::FundActionOutput {
         let to = htlc_params.compute_address();
 
         SendToAddress {
             to,
-            amount: htlc_params.asset,
-            network: htlc_params.ledger.network,
+            amount: htlc_params.asset.into(),
+            network: htlc_params.ledger.network(),
         }
     }
 }
 
-impl RefundAction<Bitcoin, BitcoinQuantity> for (Bitcoin, BitcoinQuantity) {
+impl<L: UtxoLedger> RefundAction<L, L::Native> for (L, L::Native) {
     type RefundActionOutput = SpendOutput;
 
     fn refund_action(
-        htlc_params: HtlcParams<Bitcoin, BitcoinQuantity>,
+        htlc_params: HtlcParams<L, L::Native>,
         htlc_location: OutPoint,
         secret_source: &dyn SecretSource,
     ) -> Self::RefundActionOutput {
-        let htlc = Htlc::from(htlc_params.clone());
-
         SpendOutput {
             output: PrimedInput::new(
                 htlc_location,
-                htlc_params.asset,
-                htlc.unlock_after_timeout(secret_source.secp256k1_refund()),
+                htlc_params.asset.into(),
+                htlc_params.unlock_after_timeout(secret_source.secp256k1_refund()),
             ),
-            network: htlc_params.ledger.network,
+            network: htlc_params.ledger.network(),
+            chain: L::CHAIN,
         }
     }
 }
 
-impl RedeemAction<Bitcoin, BitcoinQuantity> for (Bitcoin, BitcoinQuantity) {
+impl<L: UtxoLedger> RedeemAction<L, L::Native> for (L, L::Native) {
     type RedeemActionOutput = SpendOutput;
 
     fn redeem_action(
-        htlc_params: HtlcParams<Bitcoin, BitcoinQuantity>,
+        htlc_params: HtlcParams<L, L::Native>,
         htlc_location: OutPoint,
         secret_source: &dyn SecretSource,
         secret: Secret,
     ) -> Self::RedeemActionOutput {
-        let htlc = Htlc::from(htlc_params.clone());
-
         SpendOutput {
             output: PrimedInput::new(
                 htlc_location,
-                htlc_params.asset,
-                htlc.unlock_with_secret(secret_source.secp256k1_redeem(), &secret),
+                htlc_params.asset.into(),
+                htlc_params.unlock_with_secret(secret_source.secp256k1_redeem(), &secret),
             ),
-            network: htlc_params.ledger.network,
+            network: htlc_params.ledger.network(),
+            chain: L::CHAIN,
         }
     }
 }
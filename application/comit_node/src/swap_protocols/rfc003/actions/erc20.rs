@@ -1,22 +1,72 @@
 use crate::swap_protocols::{
-    actions::ethereum::{CallContract, DeployContract},
+    actions::ethereum::{CallContract, DeployOrCallContract},
     ledger::Ethereum,
-    rfc003::{state_machine::HtlcParams, Secret},
+    rfc003::{actions::Action, state_machine::HtlcParams, LedgerState, Secret},
     Timestamp,
 };
-use blockchain_contracts::ethereum::rfc003::erc20_htlc::Erc20Htlc;
-use ethereum_support::{Bytes, Erc20Token, Network};
+use blockchain_contracts::ethereum::rfc003::{
+    erc20_htlc::Erc20Htlc, erc20_htlc_factory::Erc20HtlcFactory,
+};
+use ethereum_support::{Bytes, ChainId, Erc20Token, EtherQuantity};
+
+/// The action that gets the HTLC in `htlc_state` closer to being funded.
+/// Through a factory that is a single call which deploys and funds the HTLC
+/// at once, otherwise the HTLC is deployed first and the tokens are
+/// transferred to it afterwards.
+pub fn deploy_or_fund_action<Accept, Decline, Redeem, Refund>(
+    htlc_params: HtlcParams<Ethereum, Erc20Token>,
+    htlc_state: &LedgerState<Ethereum>,
+) -> Option<Action<Accept, Decline, DeployOrCallContract, CallContract, Redeem, Refund>> {
+    match (htlc_params.ledger.erc20_htlc_factory, htlc_state) {
+        (Some(factory), LedgerState::NotDeployed) => Some(Action::Fund(
+            fund_through_factory_action(htlc_params, factory),
+        )),
+        (None, LedgerState::NotDeployed) => Some(Action::Deploy(deploy_action(htlc_params))),
+        (None, LedgerState::Deployed { htlc_location, .. }) => {
+            let to_erc20_contract = htlc_params.asset.token_contract;
+            Some(Action::Fund(fund_action(
+                htlc_params,
+                to_erc20_contract,
+                *htlc_location,
+            )))
+        }
+        _ => None,
+    }
+}
 
-pub fn deploy_action(htlc_params: HtlcParams<Ethereum, Erc20Token>) -> DeployContract {
+pub fn deploy_action(htlc_params: HtlcParams<Ethereum, Erc20Token>) -> DeployOrCallContract {
     htlc_params.into()
 }
 
+pub fn fund_through_factory_action(
+    htlc_params: HtlcParams<Ethereum, Erc20Token>,
+    factory: ethereum_support::Address,
+) -> CallContract {
+    let chain_id = htlc_params.ledger.chain_id;
+    let token_contract = htlc_params.asset.token_contract;
+    let token_quantity = htlc_params.asset.quantity.0;
+    let gas_limit = Erc20HtlcFactory::create_htlc_tx_gas_limit();
+
+    CallContract {
+        to: factory,
+        data: Erc20HtlcFactory::create_htlc_tx_payload(
+            token_contract,
+            token_quantity,
+            htlc_params.into(),
+        ),
+        amount: EtherQuantity::zero(),
+        gas_limit,
+        chain_id,
+        min_block_timestamp: None,
+    }
+}
+
 pub fn fund_action(
     htlc_params: HtlcParams<Ethereum, Erc20Token>,
     to_erc20_contract: ethereum_support::Address,
     beta_htlc_location: ethereum_support::Address,
 ) -> CallContract {
-    let network = htlc_params.ledger.network;
+    let chain_id = htlc_params.ledger.chain_id;
     let gas_limit = Erc20Htlc::fund_tx_gas_limit();
 
     CallContract {
@@ -25,14 +75,15 @@ pub fn fund_action(
             htlc_params.asset.quantity.0,
             beta_htlc_location,
         ),
+        amount: EtherQuantity::zero(),
         gas_limit,
-        network,
+        chain_id,
         min_block_timestamp: None,
     }
 }
 
 pub fn refund_action(
-    network: Network,
+    chain_id: ChainId,
     expiry: Timestamp,
     beta_htlc_location: ethereum_support::Address,
 ) -> CallContract {
@@ -42,8 +93,9 @@ pub fn refund_action(
     CallContract {
         to: beta_htlc_location,
         data,
+        amount: EtherQuantity::zero(),
         gas_limit,
-        network,
+        chain_id,
         min_block_timestamp: Some(expiry),
     }
 }
@@ -51,7 +103,7 @@ pub fn refund_action(
 pub fn redeem_action(
     alpha_htlc_location: ethereum_support::Address,
     secret: Secret,
-    network: Network,
+    chain_id: ChainId,
 ) -> CallContract {
     let data = Bytes::from(secret.raw_secret().to_vec());
     let gas_limit = Erc20Htlc::tx_gas_limit();
@@ -59,8 +111,9 @@ pub fn redeem_action(
     CallContract {
         to: alpha_htlc_location,
         data,
+        amount: EtherQuantity::zero(),
         gas_limit,
-        network,
+        chain_id,
         min_block_timestamp: None,
     }
 }
@@ -0,0 +1,82 @@
+use crate::swap_protocols::{
+    actions::ethereum::{CallContract, DeployOrCallContract},
+    ledger::Ethereum,
+    rfc003::{actions::Action, state_machine::HtlcParams, LedgerState, Secret},
+    Timestamp,
+};
+use blockchain_contracts::ethereum::rfc003::erc721_htlc::Erc721Htlc;
+use ethereum_support::{Bytes, ChainId, Erc721Token, EtherQuantity};
+
+/// The action that gets the HTLC in `htlc_state` closer to being funded: it
+/// is deployed first and the token is transferred to it afterwards.
+pub fn deploy_or_fund_action<Accept, Decline, Redeem, Refund>(
+    htlc_params: HtlcParams<Ethereum, Erc721Token>,
+    htlc_state: &LedgerState<Ethereum>,
+) -> Option<Action<Accept, Decline, DeployOrCallContract, CallContract, Redeem, Refund>> {
+    match htlc_state {
+        LedgerState::NotDeployed => Some(Action::Deploy(htlc_params.into())),
+        LedgerState::Deployed { htlc_location, .. } => {
+            Some(Action::Fund(fund_action(htlc_params, *htlc_location)))
+        }
+        _ => None,
+    }
+}
+
+/// Transfers the token from its owner, who is who gets it back on refund, to
+/// the HTLC
+pub fn fund_action(
+    htlc_params: HtlcParams<Ethereum, Erc721Token>,
+    htlc_location: ethereum_support::Address,
+) -> CallContract {
+    let chain_id = htlc_params.ledger.chain_id;
+    let gas_limit = Erc721Htlc::fund_tx_gas_limit();
+
+    CallContract {
+        to: htlc_params.asset.token_contract,
+        data: Erc721Htlc::transfer_erc721_tx_payload(
+            htlc_params.asset.token_id,
+            htlc_params.refund_identity,
+            htlc_location,
+        ),
+        amount: EtherQuantity::zero(),
+        gas_limit,
+        chain_id,
+        min_block_timestamp: None,
+    }
+}
+
+pub fn refund_action(
+    chain_id: ChainId,
+    expiry: Timestamp,
+    htlc_location: ethereum_support::Address,
+) -> CallContract {
+    let data = Bytes::default();
+    let gas_limit = Erc721Htlc::tx_gas_limit();
+
+    CallContract {
+        to: htlc_location,
+        data,
+        amount: EtherQuantity::zero(),
+        gas_limit,
+        chain_id,
+        min_block_timestamp: Some(expiry),
+    }
+}
+
+pub fn redeem_action(
+    htlc_location: ethereum_support::Address,
+    secret: Secret,
+    chain_id: ChainId,
+) -> CallContract {
+    let data = Bytes::from(secret.raw_secret().to_vec());
+    let gas_limit = Erc721Htlc::tx_gas_limit();
+
+    CallContract {
+        to: htlc_location,
+        data,
+        amount: EtherQuantity::zero(),
+        gas_limit,
+        chain_id,
+        min_block_timestamp: None,
+    }
+}
@@ -1,5 +1,5 @@
 use crate::swap_protocols::{
-    actions::ethereum::{CallContract, DeployContract},
+    actions::ethereum::{CallContract, DeployOrCallContract},
     ledger::Ethereum,
     rfc003::{
         actions::{FundAction, RedeemAction, RefundAction},
@@ -8,14 +8,32 @@ use crate::swap_protocols::{
         Secret,
     },
 };
-use blockchain_contracts::ethereum::rfc003::ether_htlc::EtherHtlc;
+use blockchain_contracts::ethereum::rfc003::{
+    ether_htlc::EtherHtlc, ether_htlc_registry::EtherHtlcRegistry,
+};
 use ethereum_support::{Address as EthereumAddress, Bytes, EtherQuantity};
 
 impl FundAction<Ethereum, EtherQuantity> for (Ethereum, EtherQuantity) {
-    type FundActionOutput = DeployContract;
+    type FundActionOutput = DeployOrCallContract;
 
     fn fund_action(htlc_params: HtlcParams<Ethereum, EtherQuantity>) -> Self::FundActionOutput {
-        htlc_params.into()
+        let registry = match htlc_params.ledger.ether_htlc_registry {
+            Some(registry) => registry,
+            None => return htlc_params.into(),
+        };
+
+        DeployOrCallContract::Call(CallContract {
+            to: registry,
+            data: EtherHtlcRegistry::fund_tx_payload(
+                htlc_params.secret_hash.into(),
+                htlc_params.redeem_identity,
+                htlc_params.expiry.into(),
+            ),
+            amount: htlc_params.asset,
+            gas_limit: EtherHtlcRegistry::tx_gas_limit(),
+            chain_id: htlc_params.ledger.chain_id,
+            min_block_timestamp: None,
+        })
     }
 }
 impl RefundAction<Ethereum, EtherQuantity> for (Ethereum, EtherQuantity) {
@@ -26,14 +44,23 @@ impl RefundAction<Ethereum, EtherQuantity> for (Ethereum, EtherQuantity) {
         htlc_location: EthereumAddress,
         _secret_source: &dyn SecretSource,
     ) -> Self::RefundActionOutput {
-        let data = Bytes::default();
-        let gas_limit = EtherHtlc::tx_gas_limit();
+        let (data, gas_limit) = match htlc_params.ledger.ether_htlc_registry {
+            Some(_) => (
+                EtherHtlcRegistry::refund_tx_payload(
+                    htlc_params.secret_hash.into(),
+                    htlc_params.refund_identity,
+                ),
+                EtherHtlcRegistry::tx_gas_limit(),
+            ),
+            None => (Bytes::default(), EtherHtlc::tx_gas_limit()),
+        };
 
         CallContract {
             to: htlc_location,
             data,
+            amount: EtherQuantity::zero(),
             gas_limit,
-            network: htlc_params.ledger.network,
+            chain_id: htlc_params.ledger.chain_id,
             min_block_timestamp: Some(htlc_params.expiry),
         }
     }
@@ -47,14 +74,26 @@ impl RedeemAction<Ethereum, EtherQuantity> for (Ethereum, EtherQuantity) {
         _secret_source: &dyn SecretSource,
         secret: Secret,
     ) -> Self::RedeemActionOutput {
-        let data = Bytes::from(secret.raw_secret().to_vec());
-        let gas_limit = EtherHtlc::tx_gas_limit();
+        let (data, gas_limit) = match htlc_params.ledger.ether_htlc_registry {
+            Some(_) => (
+                EtherHtlcRegistry::redeem_tx_payload(
+                    *secret.raw_secret(),
+                    htlc_params.refund_identity,
+                ),
+                EtherHtlcRegistry::tx_gas_limit(),
+            ),
+            None => (
+                Bytes::from(secret.raw_secret().to_vec()),
+                EtherHtlc::tx_gas_limit(),
+            ),
+        };
 
         CallContract {
             to: htlc_location,
             data,
+            amount: EtherQuantity::zero(),
             gas_limit,
-            network: htlc_params.ledger.network,
+            chain_id: htlc_params.ledger.chain_id,
             min_block_timestamp: None,
         }
     }
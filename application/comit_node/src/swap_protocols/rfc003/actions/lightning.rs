@@ -0,0 +1,37 @@
+use crate::swap_protocols::{
+    actions::lightning::{AddHoldInvoice, SendPayment, SettleInvoice},
+    ledger::Lightning,
+    rfc003::{state_machine::HtlcParams, Secret},
+};
+use bitcoin_support::BitcoinQuantity;
+
+pub fn send_payment_action(htlc_params: HtlcParams<Lightning, BitcoinQuantity>) -> SendPayment {
+    SendPayment {
+        to: htlc_params.redeem_identity,
+        amount: htlc_params.asset,
+        secret_hash: htlc_params.secret_hash,
+        final_cltv_delta: htlc_params.cltv_delta(),
+        network: htlc_params.ledger.network,
+    }
+}
+
+pub fn add_hold_invoice_action(
+    htlc_params: HtlcParams<Lightning, BitcoinQuantity>,
+) -> AddHoldInvoice {
+    AddHoldInvoice {
+        amount: htlc_params.asset,
+        secret_hash: htlc_params.secret_hash,
+        cltv_expiry: htlc_params.cltv_delta(),
+        network: htlc_params.ledger.network,
+    }
+}
+
+pub fn settle_invoice_action(
+    htlc_params: HtlcParams<Lightning, BitcoinQuantity>,
+    secret: Secret,
+) -> SettleInvoice {
+    SettleInvoice {
+        secret,
+        network: htlc_params.ledger.network,
+    }
+}
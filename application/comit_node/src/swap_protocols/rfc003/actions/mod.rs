@@ -1,6 +1,8 @@
 pub mod bitcoin;
 pub mod erc20;
+pub mod erc721;
 pub mod ether;
+pub mod lightning;
 
 use crate::{
     comit_client::{SwapDeclineReason, SwapReject},
@@ -64,14 +66,23 @@ pub struct Accept<AL: Ledger, BL: Ledger> {
     sender: ResponseSender<AL, BL>,
     #[derivative(Debug = "ignore")]
     secret_source: Arc<dyn SecretSource>,
+    alpha_ledger: AL,
+    beta_ledger: BL,
 }
 
 impl<AL: Ledger, BL: Ledger> Accept<AL, BL> {
     #[allow(clippy::type_complexity)]
-    pub fn new(sender: ResponseSender<AL, BL>, secret_source: Arc<dyn SecretSource>) -> Self {
+    pub fn new(
+        sender: ResponseSender<AL, BL>,
+        secret_source: Arc<dyn SecretSource>,
+        alpha_ledger: AL,
+        beta_ledger: BL,
+    ) -> Self {
         Self {
             sender,
             secret_source,
+            alpha_ledger,
+            beta_ledger,
         }
     }
     pub fn accept<P: IntoAcceptResponseBody<AL, BL>>(&self, partial_response: P) -> Result<(), ()> {
@@ -80,9 +91,11 @@ impl<AL: Ledger, BL: Ledger> Accept<AL, BL> {
         match sender.take() {
             Some(sender) => {
                 sender
-                    .send(Ok(
-                        partial_response.into_accept_response_body(self.secret_source.as_ref())
-                    ))
+                    .send(Ok(partial_response.into_accept_response_body(
+                        self.alpha_ledger,
+                        self.beta_ledger,
+                        self.secret_source.as_ref(),
+                    )))
                     .expect("Action shouldn't outlive BobToAlice");
                 Ok(())
             }
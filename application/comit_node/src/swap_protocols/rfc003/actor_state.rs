@@ -16,7 +16,7 @@ pub trait ActorState: Debug + Clone + Send + Sync + 'static {
     fn set_response(
         &mut self,
         response: Result<AcceptResponseBody<Self::AL, Self::BL>, SwapReject>,
-    );
+    ) -> Result<(), rfc003::Error>;
     fn set_secret(&mut self, secret: Secret);
     fn set_error(&mut self, error: rfc003::Error);
     fn alpha_ledger_mut(&mut self) -> &mut LedgerState<Self::AL>;
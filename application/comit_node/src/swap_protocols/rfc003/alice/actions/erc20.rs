@@ -6,10 +6,10 @@ use crate::swap_protocols::{
         actions::{erc20, Accept, Action, Decline, FundAction, RedeemAction, RefundAction},
         alice::{self, SwapCommunication},
         state_machine::HtlcParams,
-        Ledger, LedgerState,
+        Ledger, LedgerState, Secret,
     },
 };
-use ethereum_support::Erc20Token;
+use ethereum_support::{ChainId, Erc20Token};
 use std::convert::Infallible;
 
 impl<BL, BA> Actions for alice::State<Ethereum, BL, Erc20Token, BA>
@@ -22,7 +22,7 @@ where
     type ActionKind = Action<
         Accept<Ethereum, BL>,
         Decline<Ethereum, BL>,
-        ethereum::DeployContract,
+        ethereum::DeployOrCallContract,
         ethereum::CallContract,
         <(BL, BA) as RedeemAction<BL, BA>>::RedeemActionOutput,
         ethereum::CallContract,
@@ -36,35 +36,24 @@ where
             } => (request, response),
             _ => return vec![],
         };
-        let alpha_state = &self.alpha_ledger_state;
         let beta_state = &self.beta_ledger_state;
 
         use self::LedgerState::*;
 
-        let mut actions = match alpha_state {
-            NotDeployed => vec![Action::Deploy(erc20::deploy_action(
-                HtlcParams::new_alpha_params(request, response),
-            ))],
-            Deployed { htlc_location, .. } => vec![Action::Fund(erc20::fund_action(
-                HtlcParams::new_alpha_params(request, response),
-                request.alpha_asset.token_contract,
-                *htlc_location,
-            ))],
-            Funded { htlc_location, .. } => vec![Action::Refund(erc20::refund_action(
-                request.alpha_ledger.network,
-                request.alpha_expiry,
-                *htlc_location,
-            ))],
-            _ => vec![],
-        };
+        let mut actions = alpha_actions(
+            HtlcParams::new_alpha_params(request, response),
+            &self.alpha_ledger_state,
+        );
 
         if let Funded { htlc_location, .. } = beta_state {
-            actions.push(Action::Redeem(<(BL, BA)>::redeem_action(
-                HtlcParams::new_beta_params(request, response),
-                htlc_location.clone(),
-                &*self.secret_source,
-                self.secret_source.secret(),
-            )));
+            if self.knows_secret() {
+                actions.push(Action::Redeem(<(BL, BA)>::redeem_action(
+                    HtlcParams::new_beta_params(request, response),
+                    htlc_location.clone(),
+                    &*self.secret_source,
+                    self.secret_source.secret(),
+                )));
+            }
         }
         actions
     }
@@ -96,7 +85,6 @@ where
             _ => return vec![],
         };
         let alpha_state = &self.alpha_ledger_state;
-        let beta_state = &self.beta_ledger_state;
 
         use self::LedgerState::*;
 
@@ -104,7 +92,11 @@ where
             NotDeployed => vec![Action::Fund(<(AL, AA)>::fund_action(
                 HtlcParams::new_alpha_params(request, response),
             ))],
-            Funded { htlc_location, .. } => vec![Action::Refund(<(AL, AA)>::refund_action(
+            Funded {
+                htlc_location,
+                refundable: true,
+                ..
+            } => vec![Action::Refund(<(AL, AA)>::refund_action(
                 HtlcParams::new_alpha_params(request, response),
                 htlc_location.clone(),
                 &*self.secret_source,
@@ -112,13 +104,269 @@ where
             _ => vec![],
         };
 
-        if let Funded { htlc_location, .. } = beta_state {
-            actions.push(Action::Redeem(erc20::redeem_action(
-                *htlc_location,
-                self.secret_source.secret(),
-                request.beta_ledger.network,
-            )));
+        if self.knows_secret() {
+            actions.extend(
+                beta_redeem_action(
+                    request.beta_ledger.chain_id,
+                    &self.beta_ledger_state,
+                    self.secret_source.secret(),
+                )
+                .map(Action::Redeem),
+            );
+        }
+        actions
+    }
+}
+
+impl Actions for alice::State<Ethereum, Ethereum, Erc20Token, Erc20Token> {
+    #[allow(clippy::type_complexity)]
+    type ActionKind = Action<
+        Accept<Ethereum, Ethereum>,
+        Decline<Ethereum, Ethereum>,
+        ethereum::DeployOrCallContract,
+        ethereum::CallContract,
+        ethereum::CallContract,
+        ethereum::CallContract,
+    >;
+
+    fn actions(&self) -> Vec<Self::ActionKind> {
+        let (request, response) = match self.swap_communication {
+            SwapCommunication::Accepted {
+                ref request,
+                ref response,
+            } => (request, response),
+            _ => return vec![],
+        };
+        let mut actions = alpha_actions(
+            HtlcParams::new_alpha_params(request, response),
+            &self.alpha_ledger_state,
+        );
+
+        if self.knows_secret() {
+            actions.extend(
+                beta_redeem_action(
+                    request.beta_ledger.chain_id,
+                    &self.beta_ledger_state,
+                    self.secret_source.secret(),
+                )
+                .map(Action::Redeem),
+            );
         }
         actions
     }
 }
+
+/// Alice refunds her ERC20 HTLC once the ledger accepts it, until then she
+/// gets it closer to being funded
+fn alpha_actions<Accept, Decline, Redeem>(
+    htlc_params: HtlcParams<Ethereum, Erc20Token>,
+    alpha_state: &LedgerState<Ethereum>,
+) -> Vec<
+    Action<
+        Accept,
+        Decline,
+        ethereum::DeployOrCallContract,
+        ethereum::CallContract,
+        Redeem,
+        ethereum::CallContract,
+    >,
+> {
+    match alpha_state {
+        LedgerState::Funded {
+            htlc_location,
+            refundable: true,
+            ..
+        } => vec![Action::Refund(erc20::refund_action(
+            htlc_params.ledger.chain_id,
+            htlc_params.expiry,
+            *htlc_location,
+        ))],
+        _ => erc20::deploy_or_fund_action(htlc_params, alpha_state)
+            .into_iter()
+            .collect(),
+    }
+}
+
+/// Alice redeems Bob's ERC20 HTLC as soon as it is funded
+fn beta_redeem_action(
+    chain_id: ChainId,
+    beta_state: &LedgerState<Ethereum>,
+    secret: Secret,
+) -> Option<ethereum::CallContract> {
+    match beta_state {
+        LedgerState::Funded { htlc_location, .. } => {
+            Some(erc20::redeem_action(*htlc_location, secret, chain_id))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        seed::Seed,
+        swap_protocols::{
+            rfc003::{
+                actions::ActionKind,
+                messages::{AcceptResponseBody, Request},
+                HashFunction, SecretSource,
+            },
+            SwapId, Timestamp,
+        },
+    };
+    use ethereum_support::{Address, Bytes, Erc20Quantity, Transaction, H256, U256};
+    use spectral::prelude::*;
+    use std::sync::Arc;
+
+    type AliceState = alice::State<Ethereum, Ethereum, Erc20Token, Erc20Token>;
+
+    fn htlc_location() -> Address {
+        "0a81e8be41b21f651a71aab1a85c6813b8bbccf8".parse().unwrap()
+    }
+
+    fn transaction() -> Transaction {
+        Transaction {
+            hash: H256::from(1),
+            nonce: U256::from(0),
+            block_hash: None,
+            block_number: None,
+            transaction_index: None,
+            from: Address::zero(),
+            to: None,
+            value: U256::from(0),
+            gas_price: U256::from(0),
+            gas: U256::from(0),
+            input: Bytes::from(vec![]),
+        }
+    }
+
+    fn funded(refundable: bool) -> LedgerState<Ethereum> {
+        LedgerState::Funded {
+            htlc_location: htlc_location(),
+            deploy_transaction: transaction(),
+            fund_transaction: transaction(),
+            refundable,
+        }
+    }
+
+    fn accepted_swap(
+        alpha_ledger: Ethereum,
+        alpha_ledger_state: LedgerState<Ethereum>,
+        beta_ledger_state: LedgerState<Ethereum>,
+    ) -> AliceState {
+        let secret_source =
+            Seed::from(*b"hello world, you are beautiful!!").swap_seed(SwapId::default());
+        let token = Erc20Token::new(
+            "b97048628db6b661d4c2aa833e95dbe1a905b280".parse().unwrap(),
+            Erc20Quantity(U256::from(1_000)),
+        );
+        let request = Request {
+            alpha_ledger,
+            beta_ledger: Ethereum::default(),
+            alpha_asset: token,
+            beta_asset: token,
+            alpha_ledger_refund_identity: "8457037fcd80a8650c4692d7fcfc1d0a96b92867"
+                .parse()
+                .unwrap(),
+            beta_ledger_redeem_identity: "8457037fcd80a8650c4692d7fcfc1d0a96b92867"
+                .parse()
+                .unwrap(),
+            alpha_expiry: Timestamp::from(2_000_000_000),
+            beta_expiry: Timestamp::from(1_900_000_000),
+            secret_hash: secret_source.secret().hash(HashFunction::Sha256),
+            hash_function: HashFunction::Sha256,
+        };
+        let response = AcceptResponseBody {
+            beta_ledger_refund_identity: "1111111111111111111111111111111111111111"
+                .parse()
+                .unwrap(),
+            alpha_ledger_redeem_identity: "2222222222222222222222222222222222222222"
+                .parse()
+                .unwrap(),
+            partial_fill: None,
+        };
+
+        AliceState {
+            swap_communication: SwapCommunication::Accepted { request, response },
+            alpha_ledger_state,
+            beta_ledger_state,
+            secret_source: Arc::new(secret_source),
+            error: None,
+        }
+    }
+
+    fn action_kinds(state: AliceState) -> Vec<ActionKind> {
+        state.actions().iter().map(ActionKind::from).collect()
+    }
+
+    #[test]
+    fn not_deployed_alpha_htlc_gets_deployed() {
+        let state = accepted_swap(
+            Ethereum::default(),
+            LedgerState::NotDeployed,
+            LedgerState::NotDeployed,
+        );
+
+        assert_that(&action_kinds(state)).is_equal_to(vec![ActionKind::Deploy]);
+    }
+
+    #[test]
+    fn not_deployed_alpha_htlc_gets_funded_through_the_factory() {
+        let state = accepted_swap(
+            Ethereum {
+                erc20_htlc_factory: Some(
+                    "3333333333333333333333333333333333333333".parse().unwrap(),
+                ),
+                ..Ethereum::default()
+            },
+            LedgerState::NotDeployed,
+            LedgerState::NotDeployed,
+        );
+
+        assert_that(&action_kinds(state)).is_equal_to(vec![ActionKind::Fund]);
+    }
+
+    #[test]
+    fn deployed_alpha_htlc_gets_funded() {
+        let state = accepted_swap(
+            Ethereum::default(),
+            LedgerState::Deployed {
+                htlc_location: htlc_location(),
+                deploy_transaction: transaction(),
+            },
+            LedgerState::NotDeployed,
+        );
+
+        assert_that(&action_kinds(state)).is_equal_to(vec![ActionKind::Fund]);
+    }
+
+    #[test]
+    fn funded_alpha_htlc_offers_nothing_until_it_is_refundable() {
+        let state = accepted_swap(Ethereum::default(), funded(false), LedgerState::NotDeployed);
+
+        assert_that(&action_kinds(state)).is_empty();
+    }
+
+    #[test]
+    fn refundable_alpha_htlc_gets_refunded() {
+        let state = accepted_swap(Ethereum::default(), funded(true), LedgerState::NotDeployed);
+
+        assert_that(&action_kinds(state)).is_equal_to(vec![ActionKind::Refund]);
+    }
+
+    #[test]
+    fn funded_beta_htlc_gets_redeemed() {
+        let state = accepted_swap(Ethereum::default(), funded(false), funded(false));
+
+        assert_that(&action_kinds(state)).is_equal_to(vec![ActionKind::Redeem]);
+    }
+
+    #[test]
+    fn funded_beta_htlc_is_not_redeemed_without_the_secret() {
+        let mut state = accepted_swap(Ethereum::default(), funded(false), funded(false));
+        state.secret_source = Arc::new(Seed::from(*b"another seed, not the swap one!!"));
+
+        assert_that(&action_kinds(state)).is_empty();
+    }
+}
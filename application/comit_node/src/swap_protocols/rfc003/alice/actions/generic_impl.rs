@@ -46,7 +46,11 @@ where
             NotDeployed => vec![Action::Fund(<(AL, AA)>::fund_action(
                 HtlcParams::new_alpha_params(request, response),
             ))],
-            Funded { htlc_location, .. } => vec![Action::Refund(<(AL, AA)>::refund_action(
+            Funded {
+                htlc_location,
+                refundable: true,
+                ..
+            } => vec![Action::Refund(<(AL, AA)>::refund_action(
                 HtlcParams::new_alpha_params(request, response),
                 htlc_location.clone(),
                 &*self.secret_source,
@@ -55,12 +59,14 @@ where
         };
 
         if let Funded { htlc_location, .. } = beta_state {
-            actions.push(Action::Redeem(<(BL, BA)>::redeem_action(
-                HtlcParams::new_beta_params(request, response),
-                htlc_location.clone(),
-                &*self.secret_source,
-                self.secret_source.secret(),
-            )));
+            if self.knows_secret() {
+                actions.push(Action::Redeem(<(BL, BA)>::redeem_action(
+                    HtlcParams::new_beta_params(request, response),
+                    htlc_location.clone(),
+                    &*self.secret_source,
+                    self.secret_source.secret(),
+                )));
+            }
         }
         actions
     }
@@ -0,0 +1,66 @@
+use crate::swap_protocols::{
+    actions::{lightning::SendPayment, Actions},
+    asset::Asset,
+    ledger::Lightning,
+    rfc003::{
+        actions::{lightning, Accept, Action, Decline, RedeemAction},
+        alice::{self, SwapCommunication},
+        state_machine::HtlcParams,
+        Ledger, LedgerState,
+    },
+};
+use bitcoin_support::BitcoinQuantity;
+use std::convert::Infallible;
+
+/// Alice pays into Bob's hold invoice. There is nothing to refund on
+/// Lightning: the payment is released back to her once Bob cancels the
+/// invoice or it expires.
+impl<BL, BA> Actions for alice::State<Lightning, BL, BitcoinQuantity, BA>
+where
+    BL: Ledger,
+    BA: Asset,
+    (BL, BA): RedeemAction<BL, BA>,
+{
+    #[allow(clippy::type_complexity)]
+    type ActionKind = Action<
+        Accept<Lightning, BL>,
+        Decline<Lightning, BL>,
+        Infallible,
+        SendPayment,
+        <(BL, BA) as RedeemAction<BL, BA>>::RedeemActionOutput,
+        Infallible,
+    >;
+
+    fn actions(&self) -> Vec<Self::ActionKind> {
+        let (request, response) = match self.swap_communication {
+            SwapCommunication::Accepted {
+                ref request,
+                ref response,
+            } => (request, response),
+            _ => return vec![],
+        };
+        let alpha_state = &self.alpha_ledger_state;
+        let beta_state = &self.beta_ledger_state;
+
+        use self::LedgerState::*;
+
+        let mut actions = match alpha_state {
+            NotDeployed => vec![Action::Fund(lightning::send_payment_action(
+                HtlcParams::new_alpha_params(request, response),
+            ))],
+            _ => vec![],
+        };
+
+        if let Funded { htlc_location, .. } = beta_state {
+            if self.knows_secret() {
+                actions.push(Action::Redeem(<(BL, BA)>::redeem_action(
+                    HtlcParams::new_beta_params(request, response),
+                    htlc_location.clone(),
+                    &*self.secret_source,
+                    self.secret_source.secret(),
+                )));
+            }
+        }
+        actions
+    }
+}
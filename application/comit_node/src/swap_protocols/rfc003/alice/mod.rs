@@ -71,6 +71,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> State<AL, BL, AA, BA> {
             alpha_expiry: swap_request.alpha_expiry,
             beta_expiry: swap_request.beta_expiry,
             secret_hash: swap_request.secret_hash,
+            hash_function: swap_request.hash_function,
         };
 
         let context = Context {
@@ -91,6 +92,21 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> State<AL, BL, AA, BA> {
         }
     }
 
+    pub fn response(&self) -> Option<AcceptResponseBody<AL, BL>> {
+        match &self.swap_communication {
+            SwapCommunication::Accepted { response, .. } => Some(response.clone()),
+            _ => None,
+        }
+    }
+
+    /// Whether our secret unlocks the HTLCs of this swap, which it doesn't if
+    /// we forward the swap for someone else
+    pub fn knows_secret(&self) -> bool {
+        let request = self.request();
+
+        self.secret_source.secret().hash(request.hash_function) == request.secret_hash
+    }
+
     pub fn new(request: Request<AL, BL, AA, BA>, secret_source: Arc<dyn SecretSource>) -> Self {
         Self {
             swap_communication: SwapCommunication::Proposed { request },
@@ -108,12 +124,17 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> ActorState for State<AL, BL,
     type AA = AA;
     type BA = BA;
 
-    fn set_response(&mut self, response: Result<AcceptResponseBody<AL, BL>, SwapReject>) {
+    fn set_response(
+        &mut self,
+        response: Result<AcceptResponseBody<AL, BL>, SwapReject>,
+    ) -> Result<(), rfc003::Error> {
         match self.swap_communication {
             SwapCommunication::Proposed { ref request } => match response {
                 Ok(response) => {
                     self.swap_communication = SwapCommunication::Accepted {
-                        request: request.clone(),
+                        request: request
+                            .fill(response.partial_fill)
+                            .ok_or(rfc003::Error::InvalidPartialFill)?,
                         response,
                     }
                 }
@@ -126,6 +147,8 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> ActorState for State<AL, BL,
             },
             _ => log::error!("Tried to set a response after it's already set"),
         }
+
+        Ok(())
     }
 
     fn set_secret(&mut self, _secret: Secret) {
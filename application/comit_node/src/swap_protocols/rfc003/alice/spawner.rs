@@ -10,19 +10,20 @@ use crate::{
             alice,
             messages::ToRequest,
             state_store::{self, StateStore},
-            CreateLedgerEvents, Ledger,
+            CreateLedgerEvents, HashFunction, Ledger,
         },
         SwapId,
     },
 };
 use futures::{sync::mpsc, Future, Stream};
-use http_api_problem::HttpApiProblem;
+use http_api_problem::{HttpApiProblem, StatusCode};
 use std::sync::Arc;
 
 #[derive(Debug)]
 pub enum Error {
     Storage(state_store::Error),
     Metadata(metadata_store::Error),
+    UnsupportedHashFunction(HashFunction),
 }
 
 impl From<Error> for HttpApiProblem {
@@ -31,6 +32,18 @@ impl From<Error> for HttpApiProblem {
         match e {
             Storage(e) => e.into(),
             Metadata(e) => e.into(),
+            UnsupportedHashFunction(hash_function) => {
+                log::error!(
+                    "{:?} is not supported by the HTLCs of this swap",
+                    hash_function
+                );
+                HttpApiProblem::new("Hash function not supported.")
+                    .set_status(StatusCode::BAD_REQUEST)
+                    .set_detail(format!(
+                        "The HTLCs on the requested ledgers cannot check a {:?} secret hash.",
+                        hash_function
+                    ))
+            }
         }
     }
 }
@@ -61,6 +74,10 @@ impl<T: MetadataStore<SwapId>, S: StateStore, C: Client> AliceSpawner
         let swap_seed = Arc::new(self.seed.swap_seed(id));
 
         let swap_request = partial_swap_request.to_request(swap_seed.as_ref());
+        if !swap_request.hash_function_is_supported() {
+            return Err(Error::UnsupportedHashFunction(swap_request.hash_function));
+        }
+
         let alice = alice::State::new(swap_request.clone(), swap_seed);
 
         self.metadata_store
@@ -72,6 +89,8 @@ impl<T: MetadataStore<SwapId>, S: StateStore, C: Client> AliceSpawner
                     alpha_asset: swap_request.alpha_asset.into(),
                     beta_asset: swap_request.beta_asset.into(),
                     role: RoleKind::Alice,
+                    previous_hop: partial_swap_request.previous_hop(),
+                    split_from: partial_swap_request.split_from(),
                 },
             )
             .map_err(Error::Metadata)?;
@@ -1,4 +1,4 @@
-use crate::swap_protocols::rfc003::secret::{Secret, SecretHash};
+use crate::swap_protocols::rfc003::secret::{HashFunction, Secret, SecretHash};
 use bitcoin_support::Transaction;
 
 pub fn extract_secret(transaction: &Transaction, secret_hash: &SecretHash) -> Option<Secret> {
@@ -6,7 +6,7 @@ pub fn extract_secret(transaction: &Transaction, secret_hash: &SecretHash) -> Op
         txin.witness
             .iter()
             .find_map(|script_item| match Secret::from_vec(&script_item) {
-                Ok(secret) if secret.hash() == *secret_hash => Some(secret),
+                Ok(secret) if secret.hash(HashFunction::Sha256) == *secret_hash => Some(secret),
                 Ok(_) => None,
                 Err(_) => None,
             })
@@ -45,9 +45,12 @@ mod test {
         let secret = Secret::from(*b"This is our favourite passphrase");
         let transaction = setup(&secret);
 
-        assert_that!(extract_secret(&transaction, &secret.hash()))
-            .is_some()
-            .is_equal_to(&secret);
+        assert_that!(extract_secret(
+            &transaction,
+            &secret.hash(HashFunction::Sha256)
+        ))
+        .is_some()
+        .is_equal_to(&secret);
     }
 
     #[test]
@@ -72,8 +75,11 @@ mod test {
                 .unwrap();
         let secret = Secret::from_vec(&hex_secret).unwrap();
 
-        assert_that!(extract_secret(&transaction, &secret.hash()))
-            .is_some()
-            .is_equal_to(&secret);
+        assert_that!(extract_secret(
+            &transaction,
+            &secret.hash(HashFunction::Sha256)
+        ))
+        .is_some()
+        .is_equal_to(&secret);
     }
 }
@@ -1,9 +1,9 @@
 use crate::swap_protocols::{
-    rfc003::{Secret, SecretHash},
+    rfc003::{HashFunction, Secret, SecretHash},
     Timestamp,
 };
 use bitcoin_support::{
-    opcodes::{all::*, OP_CLTV},
+    opcodes::{all::*, OP_CLTV, OP_CSV},
     script::Builder,
     Address, Network, PubkeyHash, Script,
 };
@@ -23,12 +23,70 @@ pub enum UnlockingError {
     },
 }
 
+/// From when on the sender can take the funds of an HTLC back
+#[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub enum Timelock {
+    /// At a point in time, enforced through `OP_CHECKLOCKTIMEVERIFY`
+    Absolute(Timestamp),
+    /// A number of blocks after the HTLC was funded, enforced through
+    /// `OP_CHECKSEQUENCEVERIFY`
+    RelativeBlocks(u16),
+}
+
+impl Timelock {
+    /// Whether a refund transaction can make it into the next block, given
+    /// the median time past of the chain tip and the number of confirmations
+    /// of the funding transaction. Nodes only accept transactions whose
+    /// absolute locktime lies before the median time past (BIP113) and whose
+    /// input has at least as many confirmations as its relative locktime
+    /// demands once mined (BIP68).
+    pub fn allows_refund(self, median_time_past: Timestamp, funding_confirmations: u32) -> bool {
+        match self {
+            Timelock::Absolute(refund_timestamp) => median_time_past > refund_timestamp,
+            Timelock::RelativeBlocks(blocks) => funding_confirmations >= u32::from(blocks),
+        }
+    }
+
+    pub(crate) fn push_to(self, builder: Builder) -> Builder {
+        match self {
+            Timelock::Absolute(refund_timestamp) => builder
+                .push_int(refund_timestamp.into())
+                .push_opcode(OP_CLTV),
+            Timelock::RelativeBlocks(blocks) => {
+                builder.push_int(i64::from(blocks)).push_opcode(OP_CSV)
+            }
+        }
+    }
+
+    pub(crate) fn sequence(self) -> u32 {
+        match self {
+            Timelock::Absolute(_) => SEQUENCE_ALLOW_NTIMELOCK_NO_RBF,
+            // The disable and type flags are unset, which makes the sequence a
+            // block count
+            Timelock::RelativeBlocks(blocks) => u32::from(blocks),
+        }
+    }
+
+    pub(crate) fn locktime(self) -> u32 {
+        match self {
+            Timelock::Absolute(refund_timestamp) => refund_timestamp.into(),
+            Timelock::RelativeBlocks(_) => 0,
+        }
+    }
+}
+
+impl From<Timestamp> for Timelock {
+    fn from(refund_timestamp: Timestamp) -> Self {
+        Timelock::Absolute(refund_timestamp)
+    }
+}
+
 #[derive(Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub struct Htlc {
     recipient_redeem_pubkey_hash: PubkeyHash,
     sender_refund_pubkey_hash: PubkeyHash,
     secret_hash: SecretHash,
-    refund_timestamp: Timestamp,
+    refund_timelock: Timelock,
     script: Script,
 }
 
@@ -36,26 +94,28 @@ impl Htlc {
     pub fn new<
         RecipientRedeemPubkeyHash: Into<PubkeyHash>,
         SenderRefundPubkeyHash: Into<PubkeyHash>,
+        RefundTimelock: Into<Timelock>,
     >(
         recipient_redeem_pubkey_hash: RecipientRedeemPubkeyHash,
         sender_refund_pubkey_hash: SenderRefundPubkeyHash,
         secret_hash: SecretHash,
-        refund_timestamp: Timestamp,
+        refund_timelock: RefundTimelock,
     ) -> Self {
         let recipient_redeem_pubkey_hash = recipient_redeem_pubkey_hash.into();
         let sender_refund_pubkey_hash = sender_refund_pubkey_hash.into();
+        let refund_timelock = refund_timelock.into();
         let script = create_htlc(
             &recipient_redeem_pubkey_hash,
             &sender_refund_pubkey_hash,
             secret_hash.raw(),
-            refund_timestamp,
+            refund_timelock,
         );
 
         Self {
             recipient_redeem_pubkey_hash,
             sender_refund_pubkey_hash,
             secret_hash,
-            refund_timestamp,
+            refund_timelock,
             script,
         }
     }
@@ -64,6 +124,10 @@ impl Htlc {
         &self.script
     }
 
+    pub fn refund_timelock(&self) -> Timelock {
+        self.refund_timelock
+    }
+
     pub fn compute_address(&self, network: Network) -> Address {
         Address::p2wsh(&self.script, network.into())
     }
@@ -74,7 +138,7 @@ impl Htlc {
         got_keypair: KeyPair,
     ) -> Result<(), UnlockingError> {
         let got_pubkey_hash: PubkeyHash = got_keypair.public_key().into();
-        let got_secret_hash = got_secret.hash();
+        let got_secret_hash = got_secret.hash(HashFunction::Sha256);
         let expected_pubkey_hash = self.recipient_redeem_pubkey_hash;
         let expected_secret_hash = &self.secret_hash;
 
@@ -121,8 +185,8 @@ impl Htlc {
                 Witness::Bool(false),
                 Witness::PrevScript,
             ],
-            sequence: SEQUENCE_ALLOW_NTIMELOCK_NO_RBF,
-            locktime: self.refund_timestamp.into(),
+            sequence: self.refund_timelock.sequence(),
+            locktime: self.refund_timelock.locktime(),
             prev_script: self.script.clone(),
         }
     }
@@ -132,9 +196,9 @@ fn create_htlc(
     recipient_pubkey_hash: &PubkeyHash,
     sender_pubkey_hash: &PubkeyHash,
     secret_hash: &[u8],
-    refund_timestamp: Timestamp,
+    refund_timelock: Timelock,
 ) -> Script {
-    Builder::new()
+    let builder = Builder::new()
         .push_opcode(OP_IF)
         .push_opcode(OP_SIZE)
         .push_int(i64::from(Secret::LENGTH_U8))
@@ -145,9 +209,10 @@ fn create_htlc(
         .push_opcode(OP_DUP)
         .push_opcode(OP_HASH160)
         .push_slice(recipient_pubkey_hash.as_ref())
-        .push_opcode(OP_ELSE)
-        .push_int(refund_timestamp.into())
-        .push_opcode(OP_CLTV)
+        .push_opcode(OP_ELSE);
+
+    refund_timelock
+        .push_to(builder)
         .push_opcode(OP_DROP)
         .push_opcode(OP_DUP)
         .push_opcode(OP_HASH160)
@@ -215,4 +280,49 @@ mod tests {
             "bcrt1qc45uezve8vj8nds7ws0da8vfkpanqfxecem3xl7wcs3cdne0358q9zx9qg"
         );
     }
+
+    #[test]
+    fn relative_timelock_refunds_through_csv_and_sequence() {
+        let recipient_pubkey_hash = PubkeyHash::try_from(
+            &hex::decode("c021f17be99c6adfbcba5d38ee0d292c0399d2f5").unwrap()[..],
+        )
+        .unwrap();
+        let sender_pubkey_hash = PubkeyHash::try_from(
+            &hex::decode("1925a274ac004373bb5429553bdb55c40e57b124").unwrap()[..],
+        )
+        .unwrap();
+        let secret_hash = "51a488e06e9c69c555b8ad5e2c4629bb3135b96accd1f23451af75e06d3aee9c";
+
+        let htlc = Htlc::new(
+            recipient_pubkey_hash,
+            sender_pubkey_hash,
+            SecretHash::from_str(secret_hash).unwrap(),
+            Timelock::RelativeBlocks(144),
+        );
+
+        assert_eq!(
+            htlc.clone().script.into_bytes(),
+            hex::decode(
+                "6382012088a82051a488e06e9c69c555b8ad5e2c4629bb3135b96accd1f23451af75e06d3aee9c8876a914c021f17be99c6adfbcba5d38ee0d292c0399d2f567029000b27576a9141925a274ac004373bb5429553bdb55c40e57b1246888ac"
+            )
+            .unwrap()
+        );
+
+        let keypair = KeyPair::from_secret_key_slice(&[1u8; 32]).unwrap();
+        let unlock_parameters = htlc.unlock_after_timeout(keypair);
+
+        assert_eq!(unlock_parameters.sequence, 144);
+        assert_eq!(unlock_parameters.locktime, 0);
+    }
+
+    #[test]
+    fn refund_is_allowed_once_timelock_passed() {
+        let absolute = Timelock::Absolute(Timestamp::from(1_000));
+        let relative = Timelock::RelativeBlocks(6);
+
+        assert!(!absolute.allows_refund(Timestamp::from(1_000), 100));
+        assert!(absolute.allows_refund(Timestamp::from(1_001), 0));
+        assert!(!relative.allows_refund(Timestamp::from(u32::max_value()), 5));
+        assert!(relative.allows_refund(Timestamp::from(0), 6));
+    }
 }
@@ -1,16 +1,17 @@
 use crate::{
     btsieve::{BitcoinQuery, QueryBitcoin},
     swap_protocols::{
-        ledger::Bitcoin,
+        ledger::HtlcKind,
         rfc003::{
             self,
-            bitcoin::extract_secret::extract_secret,
+            bitcoin::{extract_secret::extract_secret, TaprootHtlc, Timelock, UtxoLedger},
             events::{
                 Deployed, DeployedFuture, Funded, FundedFuture, HtlcEvents, Redeemed,
-                RedeemedOrRefundedFuture, Refunded,
+                RedeemedOrRefundedFuture, RefundableFuture, Refunded,
             },
             state_machine::HtlcParams,
         },
+        Timestamp,
     },
 };
 use bitcoin_support::{BitcoinQuantity, FindOutput, OutPoint};
@@ -18,20 +19,18 @@ use futures::{
     future::{self, Either},
     Future,
 };
-use std::sync::Arc;
+use std::{convert::TryFrom, sync::Arc};
 
-impl HtlcEvents<Bitcoin, BitcoinQuantity> for Arc<dyn QueryBitcoin + Send + Sync> {
-    fn htlc_deployed(
-        &self,
-        htlc_params: HtlcParams<Bitcoin, BitcoinQuantity>,
-    ) -> Box<DeployedFuture<Bitcoin>> {
+impl<L: UtxoLedger> HtlcEvents<L, L::Native> for Arc<dyn QueryBitcoin + Send + Sync> {
+    fn htlc_deployed(&self, htlc_params: HtlcParams<L, L::Native>) -> Box<DeployedFuture<L>> {
         let query_bitcoin = Arc::clone(&self);
+        let htlc_address = htlc_params.compute_address();
         let deployed_future = self
-            .create(BitcoinQuery::deploy_htlc(htlc_params.compute_address()))
+            .create(BitcoinQuery::deploy_htlc(htlc_address.clone()))
             .and_then(move |query_id| query_bitcoin.transaction_first_result(&query_id))
             .map_err(rfc003::Error::Btsieve)
             .and_then(move |tx| {
-                let (vout, _txout) = tx.find_output(&htlc_params.compute_address())
+                let (vout, _txout) = tx.find_output(htlc_address.address())
                     .ok_or_else(|| {
                         rfc003::Error::Internal(
                             "Query returned Bitcoin transaction that didn't match the requested address".into(),
@@ -52,49 +51,61 @@ impl HtlcEvents<Bitcoin, BitcoinQuantity> for Arc<dyn QueryBitcoin + Send + Sync
 
     fn htlc_funded(
         &self,
-        _htlc_params: HtlcParams<Bitcoin, BitcoinQuantity>,
-        htlc_deployment: &Deployed<Bitcoin>,
-    ) -> Box<FundedFuture<Bitcoin, BitcoinQuantity>> {
+        _htlc_params: HtlcParams<L, L::Native>,
+        htlc_deployment: &Deployed<L>,
+    ) -> Box<FundedFuture<L, L::Native>> {
         let tx = &htlc_deployment.transaction;
-        let asset =
-            BitcoinQuantity::from_satoshi(tx.output[htlc_deployment.location.vout as usize].value);
-        Box::new(future::ok(Funded {
-            transaction: tx.clone(),
-            asset,
-        }))
+        let asset = L::Native::from(BitcoinQuantity::from_satoshi(
+            tx.output[htlc_deployment.location.vout as usize].value,
+        ));
+        Box::new(future::ok(Funded::new(tx.clone(), asset)))
     }
 
     fn htlc_redeemed_or_refunded(
         &self,
-        htlc_params: HtlcParams<Bitcoin, BitcoinQuantity>,
-        htlc_deployment: &Deployed<Bitcoin>,
-        _: &Funded<Bitcoin, BitcoinQuantity>,
-    ) -> Box<RedeemedOrRefundedFuture<Bitcoin>> {
-        let refunded_future = {
-            let query_bitcoin = Arc::clone(&self);
-
-            let refunded_query = self
-                .create(BitcoinQuery::refund_htlc(htlc_deployment.location))
-                .map_err(rfc003::Error::Btsieve);
+        htlc_params: HtlcParams<L, L::Native>,
+        htlc_deployment: &Deployed<L>,
+        _: &Funded<L, L::Native>,
+    ) -> Box<RedeemedOrRefundedFuture<L>> {
+        let query_bitcoin = Arc::clone(&self);
+        let htlc_location = htlc_deployment.location;
 
-            refunded_query
-                .and_then(move |query_id| {
-                    query_bitcoin
-                        .transaction_first_result(&query_id)
-                        .map_err(rfc003::Error::Btsieve)
-                })
-                .map(Refunded::<Bitcoin>::new)
+        let queries = match htlc_params.ledger.htlc_kind() {
+            HtlcKind::P2wsh => vec![
+                BitcoinQuery::redeem_htlc(htlc_location),
+                BitcoinQuery::refund_htlc(htlc_location),
+            ],
+            HtlcKind::Taproot => {
+                let htlc = TaprootHtlc::from(htlc_params.clone());
+                vec![
+                    BitcoinQuery::spend_htlc_through_leaf(htlc_location, htlc.redeem_leaf()),
+                    BitcoinQuery::spend_htlc_through_leaf(htlc_location, htlc.refund_leaf()),
+                ]
+            }
         };
 
-        let redeemed_future = {
-            let query_bitcoin = Arc::clone(&self);
-            let redeemed_query = self
-                .create(BitcoinQuery::redeem_htlc(htlc_deployment.location))
-                .map_err(rfc003::Error::Btsieve);
+        let redeemed_or_refunded_future = self
+            .create_batch(queries)
+            .map_err(rfc003::Error::Btsieve)
+            .and_then(move |query_ids| {
+                let (redeemed_query, refunded_query) = match query_ids.as_slice() {
+                    [redeemed_query, refunded_query] => {
+                        (redeemed_query.clone(), refunded_query.clone())
+                    }
+                    _ => {
+                        return Either::A(future::err(rfc003::Error::Internal(
+                            "btsieve did not create both the redeem and the refund query".into(),
+                        )))
+                    }
+                };
 
-            redeemed_query.and_then(move |query_id| {
-                query_bitcoin
-                    .transaction_first_result(&query_id)
+                let refunded_future = query_bitcoin
+                    .transaction_first_result(&refunded_query)
+                    .map_err(rfc003::Error::Btsieve)
+                    .map(Refunded::<L>::new);
+
+                let redeemed_future = query_bitcoin
+                    .transaction_first_result(&redeemed_query)
                     .map_err(rfc003::Error::Btsieve)
                     .and_then(move |transaction| {
                         let secret = extract_secret(&transaction, &htlc_params.secret_hash)
@@ -111,21 +122,54 @@ impl HtlcEvents<Bitcoin, BitcoinQuantity> for Arc<dyn QueryBitcoin + Send + Sync
                             transaction,
                             secret,
                         })
-                    })
+                    });
+
+                Either::B(
+                    redeemed_future
+                        .select2(refunded_future)
+                        .map(|tx| match tx {
+                            Either::A((tx, _)) => Either::A(tx),
+                            Either::B((tx, _)) => Either::B(tx),
+                        })
+                        .map_err(|either| match either {
+                            Either::A((error, _)) => error,
+                            Either::B((error, _)) => error,
+                        }),
+                )
+            });
+
+        Box::new(redeemed_or_refunded_future)
+    }
+
+    fn htlc_refundable(
+        &self,
+        htlc_params: HtlcParams<L, L::Native>,
+        _htlc_deployment: &Deployed<L>,
+        _htlc_funding: &Funded<L, L::Native>,
+    ) -> Box<RefundableFuture> {
+        let refund_timelock = htlc_params.ledger.refund_timelock(htlc_params.expiry);
+        if let Timelock::Absolute(_) = refund_timelock {
+            return Box::new(future::ok(()));
+        }
+
+        let query_bitcoin = Arc::clone(&self);
+        let refundable_future = self
+            .create(BitcoinQuery::Block { min_height: None })
+            .and_then(move |query_id| {
+                // The funding transaction was seen in a block already, every
+                // block after it adds a confirmation.
+                query_bitcoin.block_count_reaching(
+                    &query_id,
+                    Arc::new(move |blocks_since_funding| {
+                        let funding_confirmations =
+                            u32::try_from(blocks_since_funding + 1).unwrap_or(u32::max_value());
+                        refund_timelock.allows_refund(Timestamp::now(), funding_confirmations)
+                    }),
+                )
             })
-        };
+            .map(|_| ())
+            .map_err(rfc003::Error::Btsieve);
 
-        Box::new(
-            redeemed_future
-                .select2(refunded_future)
-                .map(|tx| match tx {
-                    Either::A((tx, _)) => Either::A(tx),
-                    Either::B((tx, _)) => Either::B(tx),
-                })
-                .map_err(|either| match either {
-                    Either::A((error, _)) => error,
-                    Either::B((error, _)) => error,
-                }),
-        )
+        Box::new(refundable_future)
     }
 }
@@ -1,35 +1,169 @@
 use crate::swap_protocols::{
-    ledger::Bitcoin,
-    rfc003::{state_machine::HtlcParams, Ledger},
+    asset::Asset,
+    ledger::{self, Bitcoin, HtlcKind, Litecoin},
+    rfc003::{state_machine::HtlcParams, Ledger, Policy, Secret},
+    Timestamp,
 };
-use bitcoin_support::{Address, BitcoinQuantity, OutPoint};
+use bitcoin_support::{
+    Address, BitcoinQuantity, Chain, ChainAddress, LitecoinQuantity, Network, OutPoint, PubkeyHash,
+    Transaction, TransactionId,
+};
+use bitcoin_witness::UnlockParameters;
+use secp256k1_support::KeyPair;
 
 mod extract_secret;
 mod htlc;
 mod htlc_events;
+mod taproot_htlc;
 
 pub use self::{
-    htlc::{Htlc, UnlockingError},
+    htlc::{Htlc, Timelock, UnlockingError},
     htlc_events::*,
+    taproot_htlc::TaprootHtlc,
 };
 
+/// Bitcoin aims for a block every ten minutes but at times finds them a lot
+/// faster. Relative timelocks are assumed to run out at twice the pace.
+const FASTEST_BLOCK_INTERVAL_SECS: u32 = 300;
+
+/// Bitcoin and its forks, which all use the same HTLC script, transaction
+/// format and btsieve queries. They only differ in their native asset and the
+/// way their addresses are encoded.
+pub trait UtxoLedger:
+    ledger::Ledger<
+        Identity = PubkeyHash,
+        Address = Address,
+        Transaction = Transaction,
+        TxId = TransactionId,
+    > + Ledger<HtlcLocation = OutPoint>
+{
+    type Native: Asset + From<BitcoinQuantity> + Into<BitcoinQuantity>;
+
+    const CHAIN: Chain;
+
+    fn network(&self) -> Network;
+
+    fn htlc_kind(&self) -> HtlcKind {
+        HtlcKind::P2wsh
+    }
+
+    /// The number of blocks after funding from when on HTLCs on this ledger
+    /// can be refunded, if they don't expire at the expiry of the swap
+    fn refund_after_blocks(&self) -> Option<u16> {
+        None
+    }
+
+    /// The timelock of the refund branch of HTLCs expiring at `expiry`
+    fn refund_timelock(&self, expiry: Timestamp) -> Timelock {
+        match self.refund_after_blocks() {
+            Some(blocks) => Timelock::RelativeBlocks(blocks),
+            None => Timelock::Absolute(expiry),
+        }
+    }
+
+    /// The identity the HTLCs of this ledger lock to for the given key
+    fn identity(&self, keypair: KeyPair) -> PubkeyHash {
+        match self.htlc_kind() {
+            HtlcKind::P2wsh => keypair.into(),
+            HtlcKind::Taproot => PubkeyHash::from_x_only(keypair.public_key()),
+        }
+    }
+}
+
 impl Ledger for Bitcoin {
     type HtlcLocation = OutPoint;
+
+    fn complies_with(&self, policy: &Policy) -> bool {
+        self.refund_after_blocks
+            .map_or(true, |blocks| blocks >= policy.min_refund_after_blocks)
+    }
+
+    fn earliest_refund(&self, funded_at: Timestamp, expiry: Timestamp) -> Timestamp {
+        match self.refund_after_blocks {
+            Some(blocks) => funded_at.plus(u32::from(blocks) * FASTEST_BLOCK_INTERVAL_SECS),
+            None => expiry,
+        }
+    }
 }
 
-impl From<HtlcParams<Bitcoin, BitcoinQuantity>> for Htlc {
-    fn from(htlc_params: HtlcParams<Bitcoin, BitcoinQuantity>) -> Self {
+impl UtxoLedger for Bitcoin {
+    type Native = BitcoinQuantity;
+
+    const CHAIN: Chain = Chain::Bitcoin;
+
+    fn network(&self) -> Network {
+        self.network
+    }
+
+    fn htlc_kind(&self) -> HtlcKind {
+        self.htlc
+    }
+
+    fn refund_after_blocks(&self) -> Option<u16> {
+        self.refund_after_blocks
+    }
+}
+
+impl Ledger for Litecoin {
+    type HtlcLocation = OutPoint;
+}
+
+impl UtxoLedger for Litecoin {
+    type Native = LitecoinQuantity;
+
+    const CHAIN: Chain = Chain::Litecoin;
+
+    fn network(&self) -> Network {
+        self.network
+    }
+}
+
+impl<L: UtxoLedger> From<HtlcParams<L, L::Native>> for Htlc {
+    fn from(htlc_params: HtlcParams<L, L::Native>) -> Self {
         Htlc::new(
             htlc_params.redeem_identity,
             htlc_params.refund_identity,
             htlc_params.secret_hash,
-            htlc_params.expiry,
+            htlc_params.ledger.refund_timelock(htlc_params.expiry),
         )
     }
 }
 
-impl HtlcParams<Bitcoin, BitcoinQuantity> {
-    pub fn compute_address(&self) -> Address {
-        Htlc::from(self.clone()).compute_address(self.ledger.network)
+impl<L: UtxoLedger> From<HtlcParams<L, L::Native>> for TaprootHtlc {
+    fn from(htlc_params: HtlcParams<L, L::Native>) -> Self {
+        TaprootHtlc::new(
+            htlc_params.redeem_identity,
+            htlc_params.refund_identity,
+            htlc_params.secret_hash,
+            htlc_params.ledger.refund_timelock(htlc_params.expiry),
+        )
+    }
+}
+
+impl<L: UtxoLedger> HtlcParams<L, L::Native> {
+    pub fn compute_address(&self) -> ChainAddress {
+        let network = self.ledger.network();
+        let address = match self.ledger.htlc_kind() {
+            HtlcKind::P2wsh => Htlc::from(self.clone()).compute_address(network),
+            HtlcKind::Taproot => TaprootHtlc::from(self.clone()).compute_address(network),
+        };
+
+        ChainAddress::new(L::CHAIN, address)
+    }
+
+    pub fn unlock_with_secret(&self, keypair: KeyPair, secret: &Secret) -> UnlockParameters {
+        match self.ledger.htlc_kind() {
+            HtlcKind::P2wsh => Htlc::from(self.clone()).unlock_with_secret(keypair, secret),
+            HtlcKind::Taproot => {
+                TaprootHtlc::from(self.clone()).unlock_with_secret(keypair, secret)
+            }
+        }
+    }
+
+    pub fn unlock_after_timeout(&self, keypair: KeyPair) -> UnlockParameters {
+        match self.ledger.htlc_kind() {
+            HtlcKind::P2wsh => Htlc::from(self.clone()).unlock_after_timeout(keypair),
+            HtlcKind::Taproot => TaprootHtlc::from(self.clone()).unlock_after_timeout(keypair),
+        }
     }
 }
@@ -0,0 +1,179 @@
+use crate::swap_protocols::rfc003::{bitcoin::Timelock, Secret, SecretHash};
+use bitcoin_support::{
+    opcodes::all::*, script::Builder, Address, Network, PubkeyHash, Script, TaprootOutput,
+};
+use bitcoin_witness::{UnlockParameters, Witness, SEQUENCE_ALLOW_NTIMELOCK_NO_RBF};
+use secp256k1_support::KeyPair;
+
+/// The HTLC as a Taproot output without key path. Redeeming and refunding
+/// each have their own leaf, so a spend never reveals the other branch.
+/// The leaves commit to the hash of the x-only public key, which is what
+/// `PubkeyHash::from_x_only` computes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaprootHtlc {
+    redeem_leaf: Script,
+    refund_leaf: Script,
+    refund_timelock: Timelock,
+    output: TaprootOutput,
+}
+
+impl TaprootHtlc {
+    pub fn new(
+        recipient_redeem_pubkey_hash: PubkeyHash,
+        sender_refund_pubkey_hash: PubkeyHash,
+        secret_hash: SecretHash,
+        refund_timelock: Timelock,
+    ) -> Self {
+        let redeem_leaf = create_redeem_leaf(&recipient_redeem_pubkey_hash, secret_hash.raw());
+        let refund_leaf = create_refund_leaf(&sender_refund_pubkey_hash, refund_timelock);
+        let output = TaprootOutput::script_only(redeem_leaf.clone(), refund_leaf.clone());
+
+        Self {
+            redeem_leaf,
+            refund_leaf,
+            refund_timelock,
+            output,
+        }
+    }
+
+    pub fn redeem_leaf(&self) -> &Script {
+        &self.redeem_leaf
+    }
+
+    pub fn refund_leaf(&self) -> &Script {
+        &self.refund_leaf
+    }
+
+    pub fn compute_address(&self, network: Network) -> Address {
+        self.output.address(network)
+    }
+
+    pub fn unlock_with_secret(&self, keypair: KeyPair, secret: &Secret) -> UnlockParameters {
+        UnlockParameters {
+            witness: vec![
+                Witness::SchnorrSignature(keypair),
+                Witness::XOnlyPublicKey(keypair.public_key()),
+                Witness::Data(secret.raw_secret().to_vec()),
+                Witness::PrevScript,
+                Witness::ControlBlock(self.control_block(&self.redeem_leaf)),
+            ],
+            sequence: SEQUENCE_ALLOW_NTIMELOCK_NO_RBF,
+            locktime: 0,
+            prev_script: self.redeem_leaf.clone(),
+        }
+    }
+
+    pub fn unlock_after_timeout(&self, keypair: KeyPair) -> UnlockParameters {
+        UnlockParameters {
+            witness: vec![
+                Witness::SchnorrSignature(keypair),
+                Witness::XOnlyPublicKey(keypair.public_key()),
+                Witness::PrevScript,
+                Witness::ControlBlock(self.control_block(&self.refund_leaf)),
+            ],
+            sequence: self.refund_timelock.sequence(),
+            locktime: self.refund_timelock.locktime(),
+            prev_script: self.refund_leaf.clone(),
+        }
+    }
+
+    fn control_block(&self, leaf: &Script) -> bitcoin_support::ControlBlock {
+        self.output
+            .control_block(leaf)
+            .expect("both leaves are part of the output")
+    }
+}
+
+fn create_redeem_leaf(recipient_pubkey_hash: &PubkeyHash, secret_hash: &[u8]) -> Script {
+    Builder::new()
+        .push_opcode(OP_SIZE)
+        .push_int(i64::from(Secret::LENGTH_U8))
+        .push_opcode(OP_EQUALVERIFY)
+        .push_opcode(OP_SHA256)
+        .push_slice(secret_hash)
+        .push_opcode(OP_EQUALVERIFY)
+        .push_opcode(OP_DUP)
+        .push_opcode(OP_HASH160)
+        .push_slice(recipient_pubkey_hash.as_ref())
+        .push_opcode(OP_EQUALVERIFY)
+        .push_opcode(OP_CHECKSIG)
+        .into_script()
+}
+
+fn create_refund_leaf(sender_pubkey_hash: &PubkeyHash, refund_timelock: Timelock) -> Script {
+    refund_timelock
+        .push_to(Builder::new())
+        .push_opcode(OP_DROP)
+        .push_opcode(OP_DUP)
+        .push_opcode(OP_HASH160)
+        .push_slice(sender_pubkey_hash.as_ref())
+        .push_opcode(OP_EQUALVERIFY)
+        .push_opcode(OP_CHECKSIG)
+        .into_script()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::Timestamp;
+    use spectral::prelude::*;
+    use std::{convert::TryFrom, str::FromStr};
+
+    fn htlc() -> TaprootHtlc {
+        let recipient_pubkey_hash = PubkeyHash::try_from(
+            &hex::decode("c021f17be99c6adfbcba5d38ee0d292c0399d2f5").unwrap()[..],
+        )
+        .unwrap();
+        let sender_pubkey_hash = PubkeyHash::try_from(
+            &hex::decode("1925a274ac004373bb5429553bdb55c40e57b124").unwrap()[..],
+        )
+        .unwrap();
+
+        TaprootHtlc::new(
+            recipient_pubkey_hash,
+            sender_pubkey_hash,
+            SecretHash::from_str(
+                "51a488e06e9c69c555b8ad5e2c4629bb3135b96accd1f23451af75e06d3aee9c",
+            )
+            .unwrap(),
+            Timelock::Absolute(Timestamp::from(123456789)),
+        )
+    }
+
+    #[test]
+    fn leaves_produce_correct_bytecode() {
+        let htlc = htlc();
+
+        assert_that(&htlc.redeem_leaf().clone().into_bytes()).is_equal_to(
+            hex::decode(
+                "82012088a82051a488e06e9c69c555b8ad5e2c4629bb3135b96accd1f23451af75e06d3aee9c8876a914c021f17be99c6adfbcba5d38ee0d292c0399d2f588ac"
+            )
+            .unwrap(),
+        );
+        assert_that(&htlc.refund_leaf().clone().into_bytes()).is_equal_to(
+            hex::decode("0415cd5b07b17576a9141925a274ac004373bb5429553bdb55c40e57b12488ac")
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    fn address_is_version_1_witness_program() {
+        let address = htlc().compute_address(Network::Regtest);
+
+        assert_that(&address.script_pubkey().into_bytes()[0]).is_equal_to(0x51);
+    }
+
+    #[test]
+    fn redeem_and_refund_spend_different_leaves() {
+        let htlc = htlc();
+        let keypair = KeyPair::from_secret_key_slice(&[1u8; 32]).unwrap();
+        let secret = Secret::from(*b"hello world, you are beautiful!!");
+
+        let redeem = htlc.unlock_with_secret(keypair, &secret);
+        let refund = htlc.unlock_after_timeout(keypair);
+
+        assert_that(&redeem.prev_script).is_equal_to(htlc.redeem_leaf().clone());
+        assert_that(&refund.prev_script).is_equal_to(htlc.refund_leaf().clone());
+        assert_that(&refund.locktime).is_equal_to(123456789);
+    }
+}
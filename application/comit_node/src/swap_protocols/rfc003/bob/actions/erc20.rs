@@ -22,7 +22,7 @@ where
     type ActionKind = Action<
         Accept<AL, Ethereum>,
         Decline<AL, Ethereum>,
-        ethereum::DeployContract,
+        ethereum::DeployOrCallContract,
         ethereum::CallContract,
         <(AL, AA) as RedeemAction<AL, AA>>::RedeemActionOutput,
         ethereum::CallContract,
@@ -31,12 +31,15 @@ where
     fn actions(&self) -> Vec<Self::ActionKind> {
         let (request, response) = match &self.swap_communication {
             SwapCommunication::Proposed {
-                pending_response, ..
+                request,
+                pending_response,
             } => {
                 return vec![
                     Action::Accept(Accept::new(
                         pending_response.sender.clone(),
                         Arc::clone(&self.secret_source),
+                        request.alpha_ledger,
+                        request.beta_ledger,
                     )),
                     Action::Decline(Decline::new(pending_response.sender.clone())),
                 ];
@@ -62,22 +65,23 @@ where
                     secret,
                 ))]
             }
-            (Funded { .. }, NotDeployed, _) => vec![Action::Deploy(erc20::deploy_action(
+            (Funded { .. }, ..) => erc20::deploy_or_fund_action(
                 HtlcParams::new_beta_params(request, response),
-            ))],
-            (Funded { .. }, Deployed { htlc_location, .. }, _) => {
-                vec![Action::Fund(erc20::fund_action(
-                    HtlcParams::new_beta_params(request, response),
-                    request.beta_asset.token_contract,
-                    *htlc_location,
-                ))]
-            }
+                beta_state,
+            )
+            .into_iter()
+            .collect(),
             _ => vec![],
         };
 
-        if let Funded { htlc_location, .. } = beta_state {
+        if let Funded {
+            htlc_location,
+            refundable: true,
+            ..
+        } = beta_state
+        {
             actions.push(Action::Refund(erc20::refund_action(
-                request.beta_ledger.network,
+                request.beta_ledger.chain_id,
                 request.beta_expiry,
                 *htlc_location,
             )));
@@ -106,12 +110,15 @@ where
     fn actions(&self) -> Vec<Self::ActionKind> {
         let (request, response) = match &self.swap_communication {
             SwapCommunication::Proposed {
-                pending_response, ..
+                request,
+                pending_response,
             } => {
                 return vec![
                     Action::Accept(Accept::new(
                         pending_response.sender.clone(),
                         Arc::clone(&self.secret_source),
+                        request.alpha_ledger,
+                        request.beta_ledger,
                     )),
                     Action::Decline(Decline::new(pending_response.sender.clone())),
                 ];
@@ -129,7 +136,7 @@ where
         use self::LedgerState::*;
         let mut actions = match (alpha_state, beta_state, self.secret) {
             (Funded { htlc_location, .. }, _, Some(secret)) => vec![Action::Redeem(
-                erc20::redeem_action(*htlc_location, secret, request.alpha_ledger.network),
+                erc20::redeem_action(*htlc_location, secret, request.alpha_ledger.chain_id),
             )],
             (Funded { .. }, NotDeployed, _) => vec![Action::Fund(<(BL, BA)>::fund_action(
                 HtlcParams::new_beta_params(request, response),
@@ -137,7 +144,12 @@ where
             _ => vec![],
         };
 
-        if let Funded { htlc_location, .. } = beta_state {
+        if let Funded {
+            htlc_location,
+            refundable: true,
+            ..
+        } = beta_state
+        {
             actions.push(Action::Refund(<(BL, BA)>::refund_action(
                 HtlcParams::new_beta_params(request, response),
                 htlc_location.clone(),
@@ -147,3 +159,71 @@ where
         actions
     }
 }
+
+impl Actions for bob::State<Ethereum, Ethereum, Erc20Token, Erc20Token> {
+    #[allow(clippy::type_complexity)]
+    type ActionKind = Action<
+        Accept<Ethereum, Ethereum>,
+        Decline<Ethereum, Ethereum>,
+        ethereum::DeployOrCallContract,
+        ethereum::CallContract,
+        ethereum::CallContract,
+        ethereum::CallContract,
+    >;
+
+    fn actions(&self) -> Vec<Self::ActionKind> {
+        let (request, response) = match &self.swap_communication {
+            SwapCommunication::Proposed {
+                request,
+                pending_response,
+            } => {
+                return vec![
+                    Action::Accept(Accept::new(
+                        pending_response.sender.clone(),
+                        Arc::clone(&self.secret_source),
+                        request.alpha_ledger,
+                        request.beta_ledger,
+                    )),
+                    Action::Decline(Decline::new(pending_response.sender.clone())),
+                ];
+            }
+            SwapCommunication::Accepted {
+                ref request,
+                ref response,
+            } => (request, response),
+            _ => return vec![],
+        };
+
+        let alpha_state = &self.alpha_ledger_state;
+        let beta_state = &self.beta_ledger_state;
+
+        use self::LedgerState::*;
+
+        let mut actions = match (alpha_state, beta_state, self.secret) {
+            (Funded { htlc_location, .. }, _, Some(secret)) => vec![Action::Redeem(
+                erc20::redeem_action(*htlc_location, secret, request.alpha_ledger.chain_id),
+            )],
+            (Funded { .. }, ..) => erc20::deploy_or_fund_action(
+                HtlcParams::new_beta_params(request, response),
+                beta_state,
+            )
+            .into_iter()
+            .collect(),
+            _ => vec![],
+        };
+
+        if let Funded {
+            htlc_location,
+            refundable: true,
+            ..
+        } = beta_state
+        {
+            actions.push(Action::Refund(erc20::refund_action(
+                request.beta_ledger.chain_id,
+                request.beta_expiry,
+                *htlc_location,
+            )));
+        }
+        actions
+    }
+}
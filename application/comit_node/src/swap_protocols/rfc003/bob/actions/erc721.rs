@@ -0,0 +1,161 @@
+use crate::swap_protocols::{
+    actions::{ethereum, Actions},
+    asset::Asset,
+    ledger::Ethereum,
+    rfc003::{
+        actions::{erc721, Accept, Action, Decline, FundAction, RedeemAction, RefundAction},
+        bob::{self, SwapCommunication},
+        state_machine::HtlcParams,
+        Ledger, LedgerState,
+    },
+};
+use ethereum_support::Erc721Token;
+use std::{convert::Infallible, sync::Arc};
+
+impl<AL, AA> Actions for bob::State<AL, Ethereum, AA, Erc721Token>
+where
+    AL: Ledger,
+    AA: Asset,
+    (AL, AA): RedeemAction<AL, AA>,
+{
+    #[allow(clippy::type_complexity)]
+    type ActionKind = Action<
+        Accept<AL, Ethereum>,
+        Decline<AL, Ethereum>,
+        ethereum::DeployOrCallContract,
+        ethereum::CallContract,
+        <(AL, AA) as RedeemAction<AL, AA>>::RedeemActionOutput,
+        ethereum::CallContract,
+    >;
+
+    fn actions(&self) -> Vec<Self::ActionKind> {
+        let (request, response) = match &self.swap_communication {
+            SwapCommunication::Proposed {
+                request,
+                pending_response,
+            } => {
+                return vec![
+                    Action::Accept(Accept::new(
+                        pending_response.sender.clone(),
+                        Arc::clone(&self.secret_source),
+                        request.alpha_ledger,
+                        request.beta_ledger,
+                    )),
+                    Action::Decline(Decline::new(pending_response.sender.clone())),
+                ];
+            }
+            SwapCommunication::Accepted {
+                ref request,
+                ref response,
+            } => (request, response),
+            _ => return vec![],
+        };
+
+        let alpha_state = &self.alpha_ledger_state;
+        let beta_state = &self.beta_ledger_state;
+
+        use self::LedgerState::*;
+
+        let mut actions = match (alpha_state, beta_state, self.secret) {
+            (Funded { htlc_location, .. }, _, Some(secret)) => {
+                vec![Action::Redeem(<(AL, AA)>::redeem_action(
+                    HtlcParams::new_alpha_params(request, response),
+                    htlc_location.clone(),
+                    &*self.secret_source,
+                    secret,
+                ))]
+            }
+            (Funded { .. }, ..) => erc721::deploy_or_fund_action(
+                HtlcParams::new_beta_params(request, response),
+                beta_state,
+            )
+            .into_iter()
+            .collect(),
+            _ => vec![],
+        };
+
+        if let Funded {
+            htlc_location,
+            refundable: true,
+            ..
+        } = beta_state
+        {
+            actions.push(Action::Refund(erc721::refund_action(
+                request.beta_ledger.chain_id,
+                request.beta_expiry,
+                *htlc_location,
+            )));
+        }
+        actions
+    }
+}
+
+impl<BL, BA> Actions for bob::State<Ethereum, BL, Erc721Token, BA>
+where
+    BL: Ledger,
+    BA: Asset,
+    (BL, BA): FundAction<BL, BA>,
+    (BL, BA): RefundAction<BL, BA>,
+{
+    #[allow(clippy::type_complexity)]
+    type ActionKind = Action<
+        Accept<Ethereum, BL>,
+        Decline<Ethereum, BL>,
+        Infallible,
+        <(BL, BA) as FundAction<BL, BA>>::FundActionOutput,
+        ethereum::CallContract,
+        <(BL, BA) as RefundAction<BL, BA>>::RefundActionOutput,
+    >;
+
+    fn actions(&self) -> Vec<Self::ActionKind> {
+        let (request, response) = match &self.swap_communication {
+            SwapCommunication::Proposed {
+                request,
+                pending_response,
+            } => {
+                return vec![
+                    Action::Accept(Accept::new(
+                        pending_response.sender.clone(),
+                        Arc::clone(&self.secret_source),
+                        request.alpha_ledger,
+                        request.beta_ledger,
+                    )),
+                    Action::Decline(Decline::new(pending_response.sender.clone())),
+                ];
+            }
+            SwapCommunication::Accepted {
+                ref request,
+                ref response,
+            } => (request, response),
+            _ => return vec![],
+        };
+
+        let alpha_state = &self.alpha_ledger_state;
+        let beta_state = &self.beta_ledger_state;
+
+        use self::LedgerState::*;
+        let mut actions = match (alpha_state, beta_state, self.secret) {
+            (Funded { htlc_location, .. }, _, Some(secret)) => vec![Action::Redeem(
+                erc721::redeem_action(*htlc_location, secret, request.alpha_ledger.chain_id),
+            )],
+            (Funded { .. }, NotDeployed, _) => vec![Action::Fund(<(BL, BA)>::fund_action(
+                HtlcParams::new_beta_params(request, response),
+            ))],
+            _ => vec![],
+        };
+
+        if let Funded {
+            htlc_location,
+            refundable: true,
+            ..
+        } = beta_state
+        {
+            actions.push(Action::Refund(<(BL, BA)>::refund_action(
+                HtlcParams::new_beta_params(request, response),
+                htlc_location.clone(),
+                &*self.secret_source,
+            )))
+        }
+        actions
+    }
+}
@@ -33,12 +33,15 @@ where
     fn actions(&self) -> Vec<Self::ActionKind> {
         let (request, response) = match &self.swap_communication {
             SwapCommunication::Proposed {
-                pending_response, ..
+                request,
+                pending_response,
             } => {
                 return vec![
                     Action::Accept(Accept::new(
                         pending_response.sender.clone(),
                         Arc::clone(&self.secret_source),
+                        request.alpha_ledger,
+                        request.beta_ledger,
                     )),
                     Action::Decline(Decline::new(pending_response.sender.clone())),
                 ];
@@ -69,7 +72,12 @@ where
             _ => vec![],
         };
 
-        if let Funded { htlc_location, .. } = beta_state {
+        if let Funded {
+            htlc_location,
+            refundable: true,
+            ..
+        } = beta_state
+        {
             actions.push(Action::Refund(<(BL, BA)>::refund_action(
                 HtlcParams::new_beta_params(request, response),
                 htlc_location.clone(),
@@ -0,0 +1,96 @@
+use crate::swap_protocols::{
+    actions::{
+        lightning::{AddHoldInvoice, SettleInvoice},
+        Actions,
+    },
+    asset::Asset,
+    ledger::Lightning,
+    rfc003::{
+        actions::{lightning, Accept, Action, Decline, FundAction, RefundAction},
+        bob::{self, SwapCommunication},
+        state_machine::HtlcParams,
+        Ledger, LedgerState,
+    },
+};
+use bitcoin_support::BitcoinQuantity;
+use std::sync::Arc;
+
+/// Bob receives the alpha asset through a hold invoice he has to add to his
+/// node before Alice can pay. Settling it with the secret Alice revealed on
+/// the beta ledger is what redeems it.
+impl<BL, BA> Actions for bob::State<Lightning, BL, BitcoinQuantity, BA>
+where
+    BL: Ledger,
+    BA: Asset,
+    (BL, BA): FundAction<BL, BA>,
+    (BL, BA): RefundAction<BL, BA>,
+{
+    #[allow(clippy::type_complexity)]
+    type ActionKind = Action<
+        Accept<Lightning, BL>,
+        Decline<Lightning, BL>,
+        AddHoldInvoice,
+        <(BL, BA) as FundAction<BL, BA>>::FundActionOutput,
+        SettleInvoice,
+        <(BL, BA) as RefundAction<BL, BA>>::RefundActionOutput,
+    >;
+
+    fn actions(&self) -> Vec<Self::ActionKind> {
+        let (request, response) = match &self.swap_communication {
+            SwapCommunication::Proposed {
+                request,
+                pending_response,
+            } => {
+                return vec![
+                    Action::Accept(Accept::new(
+                        pending_response.sender.clone(),
+                        Arc::clone(&self.secret_source),
+                        request.alpha_ledger,
+                        request.beta_ledger,
+                    )),
+                    Action::Decline(Decline::new(pending_response.sender.clone())),
+                ];
+            }
+            SwapCommunication::Accepted {
+                ref request,
+                ref response,
+            } => (request, response),
+            _ => return vec![],
+        };
+
+        let alpha_state = &self.alpha_ledger_state;
+        let beta_state = &self.beta_ledger_state;
+
+        use self::LedgerState::*;
+        let mut actions = match (alpha_state, beta_state, self.secret) {
+            (NotDeployed, ..) => vec![Action::Deploy(lightning::add_hold_invoice_action(
+                HtlcParams::new_alpha_params(request, response),
+            ))],
+            (Funded { .. }, _, Some(secret)) => {
+                vec![Action::Redeem(lightning::settle_invoice_action(
+                    HtlcParams::new_alpha_params(request, response),
+                    secret,
+                ))]
+            }
+            (Funded { .. }, NotDeployed, _) => vec![Action::Fund(<(BL, BA)>::fund_action(
+                HtlcParams::new_beta_params(request, response),
+            ))],
+            _ => vec![],
+        };
+
+        if let Funded {
+            htlc_location,
+            refundable: true,
+            ..
+        } = beta_state
+        {
+            actions.push(Action::Refund(<(BL, BA)>::refund_action(
+                HtlcParams::new_beta_params(request, response),
+                htlc_location.clone(),
+                &*self.secret_source,
+            )))
+        }
+
+        actions
+    }
+}
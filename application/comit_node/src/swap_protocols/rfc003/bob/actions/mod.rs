@@ -1,2 +1,4 @@
 mod erc20;
+mod erc721;
 mod generic_impl;
+mod lightning;
@@ -75,8 +75,18 @@ impl<AL: Ledger, BL: Ledger> PendingResponse<AL, BL> {
         }))
     }
 
-    pub fn accept_action(&self, secret_source: Arc<dyn SecretSource>) -> Accept<AL, BL> {
-        Accept::new(self.sender.clone(), secret_source)
+    pub fn accept_action(
+        &self,
+        secret_source: Arc<dyn SecretSource>,
+        alpha_ledger: AL,
+        beta_ledger: BL,
+    ) -> Accept<AL, BL> {
+        Accept::new(
+            self.sender.clone(),
+            secret_source,
+            alpha_ledger,
+            beta_ledger,
+        )
     }
 
     pub fn decline_action(&self) -> Decline<AL, BL> {
@@ -108,6 +118,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> State<AL, BL, AA, BA> {
             alpha_expiry: swap_request.alpha_expiry,
             beta_expiry: swap_request.beta_expiry,
             secret_hash: swap_request.secret_hash,
+            hash_function: swap_request.hash_function,
         };
 
         let context = Context {
@@ -151,6 +162,13 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> State<AL, BL, AA, BA> {
         }
     }
 
+    pub fn response(&self) -> Option<AcceptResponseBody<AL, BL>> {
+        match &self.swap_communication {
+            SwapCommunication::Accepted { response, .. } => Some(response.clone()),
+            _ => None,
+        }
+    }
+
     pub fn response_future(&self) -> Option<Box<ResponseFuture<AL, BL>>> {
         match &self.swap_communication {
             SwapCommunication::Proposed {
@@ -170,12 +188,17 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> ActorState for State<AL, BL,
     type AA = AA;
     type BA = BA;
 
-    fn set_response(&mut self, response: Result<AcceptResponseBody<AL, BL>, SwapReject>) {
+    fn set_response(
+        &mut self,
+        response: Result<AcceptResponseBody<AL, BL>, SwapReject>,
+    ) -> Result<(), rfc003::Error> {
         match self.swap_communication {
             SwapCommunication::Proposed { ref request, .. } => match response {
                 Ok(response) => {
                     self.swap_communication = SwapCommunication::Accepted {
-                        request: request.clone(),
+                        request: request
+                            .fill(response.partial_fill)
+                            .ok_or(rfc003::Error::InvalidPartialFill)?,
                         response,
                     }
                 }
@@ -188,6 +211,8 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> ActorState for State<AL, BL,
             },
             _ => log::error!("Tried to set a response after it's already set"),
         }
+
+        Ok(())
     }
 
     fn set_secret(&mut self, secret: Secret) {
@@ -70,6 +70,8 @@ impl<T: MetadataStore<SwapId>, S: StateStore> BobSpawner
                     alpha_asset: swap_request.alpha_asset.into(),
                     beta_asset: swap_request.beta_asset.into(),
                     role: RoleKind::Bob,
+                    previous_hop: None,
+                    split_from: None,
                 },
             )
             .map_err(Error::Metadata)?;
@@ -1,14 +1,14 @@
 use crate::swap_protocols::{
     asset::Asset,
     dependencies::LedgerEventDependencies,
-    ledger::{Bitcoin, Ethereum},
+    ledger::{Bitcoin, Ethereum, Lightning, Litecoin},
     rfc003::{
         events::{LedgerEventFutures, LedgerEvents},
         Ledger,
     },
 };
-use bitcoin_support::BitcoinQuantity;
-use ethereum_support::{Erc20Token, EtherQuantity};
+use bitcoin_support::{BitcoinQuantity, LitecoinQuantity};
+use ethereum_support::{Erc20Token, Erc721Token, EtherQuantity};
 
 pub trait CreateLedgerEvents<L: Ledger, A: Asset> {
     fn create_ledger_events(&self) -> Box<dyn LedgerEvents<L, A>>;
@@ -37,3 +37,27 @@ impl CreateLedgerEvents<Ethereum, Erc20Token> for LedgerEventDependencies {
         )))
     }
 }
+
+impl CreateLedgerEvents<Ethereum, Erc721Token> for LedgerEventDependencies {
+    fn create_ledger_events(&self) -> Box<dyn LedgerEvents<Ethereum, Erc721Token>> {
+        Box::new(LedgerEventFutures::new(Box::new(
+            self.query_ethereum.clone(),
+        )))
+    }
+}
+
+impl CreateLedgerEvents<Litecoin, LitecoinQuantity> for LedgerEventDependencies {
+    fn create_ledger_events(&self) -> Box<dyn LedgerEvents<Litecoin, LitecoinQuantity>> {
+        Box::new(LedgerEventFutures::new(Box::new(
+            self.query_litecoin.clone(),
+        )))
+    }
+}
+
+impl CreateLedgerEvents<Lightning, BitcoinQuantity> for LedgerEventDependencies {
+    fn create_ledger_events(&self) -> Box<dyn LedgerEvents<Lightning, BitcoinQuantity>> {
+        Box::new(LedgerEventFutures::new(Box::new(
+            self.query_lightning.clone(),
+        )))
+    }
+}
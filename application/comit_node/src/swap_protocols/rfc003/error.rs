@@ -1,10 +1,13 @@
-use crate::{btsieve, comit_client};
+use crate::{btsieve, comit_client, lnd};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Error {
     SwapResponse(comit_client::RequestError),
     Btsieve(btsieve::Error),
+    Lnd(lnd::Error),
     TimerError,
     InsufficientFunding,
+    /// Bob accepted with quantities Alice did not ask for
+    InvalidPartialFill,
     Internal(String),
 }
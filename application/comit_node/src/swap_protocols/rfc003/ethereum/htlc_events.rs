@@ -1,7 +1,8 @@
 use crate::{
-    btsieve::{EthereumQuery, EventMatcher, QueryEthereum, Topic},
+    btsieve::{
+        DecodedEvent, EthereumQuery, EventMatcher, QueryEthereum, Topic, TransactionAndDecodedLogs,
+    },
     swap_protocols::{
-        asset::Asset,
         ledger::Ethereum,
         rfc003::{
             self,
@@ -14,9 +15,13 @@ use crate::{
         },
     },
 };
+use blockchain_contracts::ethereum::rfc003::{
+    erc20_htlc_factory::HTLC_CREATED_LOG_MSG,
+    ether_htlc_registry::{SWAP_REDEEMED_LOG_MSG, SWAP_REFUNDED_LOG_MSG},
+};
 use ethereum_support::{
-    web3::types::Address, CalculateContractAddress, Erc20Token, EtherQuantity, Transaction,
-    TransactionAndReceipt,
+    web3::types::{Address, H256},
+    CalculateContractAddress, Erc20Token, Erc721Token, EtherQuantity, Transaction,
 };
 use futures::{
     future::{self, Either},
@@ -36,6 +41,24 @@ impl HtlcEvents<Ethereum, EtherQuantity> for Arc<dyn QueryEthereum + Send + Sync
         &self,
         htlc_params: HtlcParams<Ethereum, EtherQuantity>,
     ) -> Box<DeployedFuture<Ethereum>> {
+        if let Some(registry) = htlc_params.ledger.ether_htlc_registry {
+            return Box::new(
+                ether_registry::swap_funded(Arc::clone(&self), registry, htlc_params).map(
+                    move |(transaction, _)| Deployed {
+                        location: registry,
+                        transaction,
+                    },
+                ),
+            );
+        }
+        if let Some(factory) = htlc_params.ledger.create2_htlc_factory {
+            return htlc_created_by_factory(
+                Arc::clone(&self),
+                factory,
+                htlc_params.init_code_hash(),
+            );
+        }
+
         let query_ethereum = Arc::clone(&self);
         let deployed_future = query_ethereum
             .create(EthereumQuery::contract_deployment(htlc_params.bytecode()))
@@ -51,27 +74,37 @@ impl HtlcEvents<Ethereum, EtherQuantity> for Arc<dyn QueryEthereum + Send + Sync
 
     fn htlc_funded(
         &self,
-        _htlc_params: HtlcParams<Ethereum, EtherQuantity>,
+        htlc_params: HtlcParams<Ethereum, EtherQuantity>,
         deploy_transaction: &Deployed<Ethereum>,
     ) -> Box<FundedFuture<Ethereum, EtherQuantity>> {
-        Box::new(future::ok(Funded {
-            transaction: deploy_transaction.transaction.clone(),
-            asset: EtherQuantity::from_wei(deploy_transaction.transaction.value),
-        }))
+        // The value of the transaction is only what the registry got if it
+        // was called directly, hence we go by its log instead
+        if let Some(registry) = htlc_params.ledger.ether_htlc_registry {
+            return Box::new(
+                ether_registry::swap_funded(Arc::clone(&self), registry, htlc_params).map(
+                    |(transaction, value)| Funded::new(transaction, EtherQuantity::from_wei(value)),
+                ),
+            );
+        }
+
+        Box::new(future::ok(Funded::new(
+            deploy_transaction.transaction.clone(),
+            EtherQuantity::from_wei(deploy_transaction.transaction.value),
+        )))
     }
 
     fn htlc_redeemed_or_refunded(
         &self,
         htlc_params: HtlcParams<Ethereum, EtherQuantity>,
         htlc_deployment: &Deployed<Ethereum>,
-        htlc_funding: &Funded<Ethereum, EtherQuantity>,
+        _htlc_funding: &Funded<Ethereum, EtherQuantity>,
     ) -> Box<RedeemedOrRefundedFuture<Ethereum>> {
-        htlc_redeemed_or_refunded(
-            Arc::clone(&self),
-            htlc_params,
-            htlc_deployment,
-            htlc_funding,
-        )
+        let swap_id = htlc_params
+            .ledger
+            .ether_htlc_registry
+            .map(|_| htlc_params.swap_id());
+
+        htlc_redeemed_or_refunded(Arc::clone(&self), htlc_deployment.location, swap_id)
     }
 }
 
@@ -79,77 +112,162 @@ fn calcualte_contract_address_from_deployment_transaction(tx: &Transaction) -> A
     tx.from.calculate_contract_address(&tx.nonce)
 }
 
-fn htlc_redeemed_or_refunded<A: Asset>(
+/// Both HTLC factories announce the HTLCs they deploy in an `HtlcCreated`
+/// log, which the address of the HTLC is taken from.
+fn htlc_created_by_factory(
+    query_ethereum: Arc<dyn QueryEthereum + Send + Sync + 'static>,
+    factory: Address,
+    init_code_hash: H256,
+) -> Box<DeployedFuture<Ethereum>> {
+    let deployed_future = query_ethereum
+        .create(EthereumQuery::Event {
+            event_matchers: vec![EventMatcher {
+                address: Some(factory),
+                data: None,
+                topics: vec![
+                    Some(Topic(HTLC_CREATED_LOG_MSG.into())),
+                    Some(Topic(init_code_hash)),
+                ],
+            }],
+        })
+        .and_then(move |query_id| {
+            query_ethereum.transaction_and_decoded_logs_first_result(&query_id)
+        })
+        .map_err(rfc003::Error::Btsieve)
+        .and_then(move |TransactionAndDecodedLogs { transaction, logs }| {
+            logs.into_iter()
+                .filter(|log| log.address == factory)
+                .find_map(|log| match log.event {
+                    DecodedEvent::HtlcCreated {
+                        init_code_hash: created,
+                        htlc,
+                    } if created == init_code_hash => Some(htlc),
+                    _ => None,
+                })
+                .ok_or_else(|| {
+                    rfc003::Error::Internal(format!(
+                        "transaction {:?} did not contain an HtlcCreated log",
+                        transaction.hash
+                    ))
+                })
+                .map(|location| Deployed {
+                    location,
+                    transaction,
+                })
+        });
+
+    Box::new(deployed_future)
+}
+
+/// Watches the HTLC at `htlc_location` for being redeemed or refunded. HTLCs
+/// in the Ether HTLC registry are told apart by their `swap_id`.
+fn htlc_redeemed_or_refunded(
     query_ethereum: Arc<dyn QueryEthereum + Send + Sync + 'static>,
-    _: HtlcParams<Ethereum, A>,
-    htlc_deployment: &Deployed<Ethereum>,
-    _: &Funded<Ethereum, A>,
+    htlc_location: Address,
+    swap_id: Option<H256>,
 ) -> Box<RedeemedOrRefundedFuture<Ethereum>> {
-    let refunded_future = {
-        let query_ethereum = Arc::clone(&query_ethereum);
-        query_ethereum
-            .create(EthereumQuery::Event {
-                event_matchers: vec![EventMatcher {
-                    address: Some(htlc_deployment.location),
-                    data: None,
-                    topics: vec![Some(Topic(REFUND_LOG_MSG.into()))],
-                }],
-            })
-            .and_then(move |query_id| query_ethereum.transaction_first_result(&query_id))
-            .map_err(rfc003::Error::Btsieve)
-            .map(Refunded::<Ethereum>::new)
+    let event_query = |topics: Vec<Option<Topic>>| EthereumQuery::Event {
+        event_matchers: vec![EventMatcher {
+            address: Some(htlc_location),
+            data: None,
+            topics,
+        }],
+    };
+    let (redeem_topics, refund_topics) = match swap_id {
+        Some(swap_id) => (
+            vec![
+                Some(Topic(SWAP_REDEEMED_LOG_MSG.into())),
+                Some(Topic(swap_id)),
+            ],
+            vec![
+                Some(Topic(SWAP_REFUNDED_LOG_MSG.into())),
+                Some(Topic(swap_id)),
+            ],
+        ),
+        None => (
+            vec![Some(Topic(REDEEM_LOG_MSG.into()))],
+            vec![Some(Topic(REFUND_LOG_MSG.into()))],
+        ),
     };
 
-    let redeemed_future = {
-        let query_ethereum = Arc::clone(&query_ethereum);
-        query_ethereum
-            .create(EthereumQuery::Event {
-                event_matchers: vec![EventMatcher {
-                    address: Some(htlc_deployment.location),
-                    data: None,
-                    topics: vec![Some(Topic(REDEEM_LOG_MSG.into()))],
-                }],
-            })
-            .and_then(move |query_id| query_ethereum.transaction_and_receipt_first_result(&query_id))
-            .map_err(rfc003::Error::Btsieve)
-            .and_then(move |TransactionAndReceipt {transaction, receipt}| {
-                receipt
-                    .logs
-                    .into_iter()
-                    .find(|log| log.topics.contains(&REDEEM_LOG_MSG.into()))
-                    .ok_or_else(|| {
-                        rfc003::Error::Internal(format!("transaction receipt {:?} did not contain a REDEEM log", transaction.hash))
-                    }).and_then(|log| {
+    let redeemed_or_refunded_future = query_ethereum
+        .create_batch(vec![event_query(redeem_topics), event_query(refund_topics)])
+        .map_err(rfc003::Error::Btsieve)
+        .and_then(move |query_ids| {
+            let (redeemed_query, refunded_query) = match query_ids.as_slice() {
+                [redeemed_query, refunded_query] => {
+                    (redeemed_query.clone(), refunded_query.clone())
+                }
+                _ => {
+                    return Either::A(future::err(rfc003::Error::Internal(
+                        "btsieve did not create both the redeem and the refund query".into(),
+                    )))
+                }
+            };
+
+            let refunded_future = query_ethereum
+                .transaction_first_result(&refunded_query)
+                .map_err(rfc003::Error::Btsieve)
+                .map(Refunded::<Ethereum>::new);
 
-                    let log_data = log.data.0.as_ref();
-                    let secret = Secret::from_vec(log_data)
-                        .map_err(|e| rfc003::Error::Internal(format!("failed to construct secret from data in transaction receipt {:?}: {:?}", transaction.hash, e)))?;
+            let redeemed_future = query_ethereum
+                .transaction_and_decoded_logs_first_result(&redeemed_query)
+                .map_err(rfc003::Error::Btsieve)
+                .and_then(move |TransactionAndDecodedLogs { transaction, logs }| {
+                    let secret = logs
+                        .into_iter()
+                        .filter(|log| log.address == htlc_location)
+                        .find_map(|log| match (log.event, swap_id) {
+                            (DecodedEvent::Redeemed { secret }, None) => Some(secret),
+                            (
+                                DecodedEvent::SwapRedeemed {
+                                    swap_id: redeemed,
+                                    secret,
+                                },
+                                Some(swap_id),
+                            ) if redeemed == swap_id => Some(secret),
+                            _ => None,
+                        })
+                        .ok_or_else(|| {
+                            rfc003::Error::Internal(format!(
+                                "transaction {:?} did not contain a REDEEM log",
+                                transaction.hash
+                            ))
+                        })?;
+
+                    let secret = Secret::from_vec(&secret[..]).map_err(|e| {
+                        rfc003::Error::Internal(format!(
+                            "failed to construct secret from REDEEM log in transaction {:?}: {:?}",
+                            transaction.hash, e
+                        ))
+                    })?;
 
                     Ok(Redeemed {
-                            transaction,
-                            secret,
+                        transaction,
+                        secret,
                     })
-                })
-            })
-    };
+                });
 
-    Box::new(
-        redeemed_future
-            .select2(refunded_future)
-            .map(|tx| match tx {
-                Either::A((tx, _)) => Either::A(tx),
-                Either::B((tx, _)) => Either::B(tx),
-            })
-            .map_err(|either| match either {
-                Either::A((error, _)) => error,
-                Either::B((error, _)) => error,
-            }),
-    )
+            Either::B(
+                redeemed_future
+                    .select2(refunded_future)
+                    .map(|tx| match tx {
+                        Either::A((tx, _)) => Either::A(tx),
+                        Either::B((tx, _)) => Either::B(tx),
+                    })
+                    .map_err(|either| match either {
+                        Either::A((error, _)) => error,
+                        Either::B((error, _)) => error,
+                    }),
+            )
+        });
+
+    Box::new(redeemed_or_refunded_future)
 }
 
 mod erc20 {
     use super::*;
-    use ethereum_support::{Erc20Quantity, U256};
+    use ethereum_support::Erc20Quantity;
 
     // keccak('Transfer(address,address,uint256)')
     const TRANSFER_LOG_MSG: &str =
@@ -160,6 +278,23 @@ mod erc20 {
             &self,
             htlc_params: HtlcParams<Ethereum, Erc20Token>,
         ) -> Box<DeployedFuture<Ethereum>> {
+            if let Some(factory) = htlc_params.ledger.erc20_htlc_factory {
+                // The factory funds the HTLC in the same transaction, which
+                // `htlc_funded` finds the transfer of the tokens in
+                return htlc_created_by_factory(
+                    Arc::clone(&self),
+                    factory,
+                    htlc_params.init_code_hash(),
+                );
+            }
+            if let Some(factory) = htlc_params.ledger.create2_htlc_factory {
+                return htlc_created_by_factory(
+                    Arc::clone(&self),
+                    factory,
+                    htlc_params.init_code_hash(),
+                );
+            }
+
             let query_ethereum = Arc::clone(&self);
             let deployed_future = query_ethereum
                 .create(EthereumQuery::contract_deployment(htlc_params.bytecode()))
@@ -179,6 +314,7 @@ mod erc20 {
             deployment: &Deployed<Ethereum>,
         ) -> Box<FundedFuture<Ethereum, Erc20Token>> {
             let query_ethereum = Arc::clone(&self);
+            let htlc_location = deployment.location;
             let funded_future = self
                 .create(EthereumQuery::Event {
                     event_matchers: vec![EventMatcher {
@@ -192,41 +328,194 @@ mod erc20 {
                     }],
                 })
                 .and_then(move |query_id| {
-                    query_ethereum.transaction_and_receipt_first_result(&query_id)
+                    query_ethereum.transaction_and_decoded_logs_first_result(&query_id)
                 })
                 .map_err(rfc003::Error::Btsieve)
-                .and_then(
-                    |TransactionAndReceipt {
-                              transaction,
-                              receipt,
-                          }| {
-                        receipt.logs.into_iter().find(|log| log.topics.contains(&TRANSFER_LOG_MSG.into())).ok_or_else(|| {
-                            log::warn!("receipt for transaction {:?} did not contain any Transfer events", transaction.hash);
+                .and_then(move |TransactionAndDecodedLogs { transaction, logs }| {
+                    logs.into_iter()
+                        .find_map(|log| match log.event {
+                            DecodedEvent::Transfer { to, value, .. } if to == htlc_location => {
+                                Some(Erc20Token::new(log.address, Erc20Quantity(value)))
+                            }
+                            _ => None,
+                        })
+                        .ok_or_else(|| {
+                            log::warn!(
+                                "transaction {:?} did not contain any Transfer events to the HTLC",
+                                transaction.hash
+                            );
                             rfc003::Error::InsufficientFunding
-                        }).map(|log| {
-                            let quantity = Erc20Quantity(U256::from_big_endian(log.data.0.as_ref()));
-                            let asset = Erc20Token::new(log.address, quantity);
-
-                            Funded { transaction, asset }
                         })
-                    },
+                        .map(|asset| Funded::new(transaction, asset))
+                });
+
+            Box::new(funded_future)
+        }
+
+        fn htlc_redeemed_or_refunded(
+            &self,
+            _htlc_params: HtlcParams<Ethereum, Erc20Token>,
+            htlc_deployment: &Deployed<Ethereum>,
+            _htlc_funding: &Funded<Ethereum, Erc20Token>,
+        ) -> Box<RedeemedOrRefundedFuture<Ethereum>> {
+            htlc_redeemed_or_refunded(Arc::clone(&self), htlc_deployment.location, None)
+        }
+    }
+}
+
+mod erc721 {
+    use super::*;
+
+    // keccak('Transfer(address,address,uint256)'), like for ERC20
+    const TRANSFER_LOG_MSG: &str =
+        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+    impl HtlcEvents<Ethereum, Erc721Token> for Arc<dyn QueryEthereum + Send + Sync + 'static> {
+        fn htlc_deployed(
+            &self,
+            htlc_params: HtlcParams<Ethereum, Erc721Token>,
+        ) -> Box<DeployedFuture<Ethereum>> {
+            if let Some(factory) = htlc_params.ledger.create2_htlc_factory {
+                return htlc_created_by_factory(
+                    Arc::clone(&self),
+                    factory,
+                    htlc_params.init_code_hash(),
                 );
+            }
+
+            let query_ethereum = Arc::clone(&self);
+            let deployed_future = query_ethereum
+                .create(EthereumQuery::contract_deployment(htlc_params.bytecode()))
+                .and_then(move |query_id| query_ethereum.transaction_first_result(&query_id))
+                .map_err(rfc003::Error::Btsieve)
+                .map(|tx| Deployed {
+                    location: calcualte_contract_address_from_deployment_transaction(&tx),
+                    transaction: tx,
+                });
+
+            Box::new(deployed_future)
+        }
+
+        fn htlc_funded(
+            &self,
+            htlc_params: HtlcParams<Ethereum, Erc721Token>,
+            deployment: &Deployed<Ethereum>,
+        ) -> Box<FundedFuture<Ethereum, Erc721Token>> {
+            let query_ethereum = Arc::clone(&self);
+            let htlc_location = deployment.location;
+            let funded_future = self
+                .create(EthereumQuery::Event {
+                    event_matchers: vec![EventMatcher {
+                        address: Some(htlc_params.asset.token_contract),
+                        data: None,
+                        topics: vec![
+                            Some(Topic(TRANSFER_LOG_MSG.into())),
+                            None,
+                            Some(Topic(deployment.location.into())),
+                        ],
+                    }],
+                })
+                .and_then(move |query_id| {
+                    query_ethereum.transaction_and_decoded_logs_first_result(&query_id)
+                })
+                .map_err(rfc003::Error::Btsieve)
+                .and_then(move |TransactionAndDecodedLogs { transaction, logs }| {
+                    logs.into_iter()
+                        .find_map(|log| match log.event {
+                            DecodedEvent::Erc721Transfer { to, token_id, .. }
+                                if to == htlc_location =>
+                            {
+                                Some(Erc721Token::new(log.address, token_id))
+                            }
+                            _ => None,
+                        })
+                        .ok_or_else(|| {
+                            log::warn!(
+                                "transaction {:?} did not transfer any ERC721 token to the HTLC",
+                                transaction.hash
+                            );
+                            rfc003::Error::InsufficientFunding
+                        })
+                        .map(|asset| Funded::new(transaction, asset))
+                });
 
             Box::new(funded_future)
         }
 
         fn htlc_redeemed_or_refunded(
             &self,
-            htlc_params: HtlcParams<Ethereum, Erc20Token>,
+            _htlc_params: HtlcParams<Ethereum, Erc721Token>,
             htlc_deployment: &Deployed<Ethereum>,
-            htlc_funding: &Funded<Ethereum, Erc20Token>,
+            _htlc_funding: &Funded<Ethereum, Erc721Token>,
         ) -> Box<RedeemedOrRefundedFuture<Ethereum>> {
-            htlc_redeemed_or_refunded(
-                Arc::clone(&self),
-                htlc_params,
-                htlc_deployment,
-                htlc_funding,
-            )
+            htlc_redeemed_or_refunded(Arc::clone(&self), htlc_deployment.location, None)
         }
     }
 }
+
+mod ether_registry {
+    use super::*;
+    use blockchain_contracts::ethereum::rfc003::ether_htlc_registry::SWAP_FUNDED_LOG_MSG;
+    use ethereum_support::U256;
+
+    /// Finds the transaction that funded the swap of `htlc_params` in the
+    /// registry, together with the value the registry logged for it.
+    ///
+    /// Only the refund address can fund under the id of the swap, but it can
+    /// do so with any redeem address and expiry, which is why those have to
+    /// be checked.
+    pub fn swap_funded(
+        query_ethereum: Arc<dyn QueryEthereum + Send + Sync + 'static>,
+        registry: Address,
+        htlc_params: HtlcParams<Ethereum, EtherQuantity>,
+    ) -> impl Future<Item = (Transaction, U256), Error = rfc003::Error> {
+        let swap_id = htlc_params.swap_id();
+        let redeem_identity = htlc_params.redeem_identity;
+        let expiry = U256::from(u32::from(htlc_params.expiry));
+
+        query_ethereum
+            .create(EthereumQuery::Event {
+                event_matchers: vec![EventMatcher {
+                    address: Some(registry),
+                    data: None,
+                    topics: vec![
+                        Some(Topic(SWAP_FUNDED_LOG_MSG.into())),
+                        Some(Topic(swap_id)),
+                    ],
+                }],
+            })
+            .and_then(move |query_id| {
+                query_ethereum.transaction_and_decoded_logs_first_result(&query_id)
+            })
+            .map_err(rfc003::Error::Btsieve)
+            .and_then(move |TransactionAndDecodedLogs { transaction, logs }| {
+                let (redeem_address, refund_timestamp, value) = logs
+                    .into_iter()
+                    .filter(|log| log.address == registry)
+                    .find_map(|log| match log.event {
+                        DecodedEvent::SwapFunded {
+                            swap_id: funded,
+                            redeem_address,
+                            refund_timestamp,
+                            value,
+                        } if funded == swap_id => Some((redeem_address, refund_timestamp, value)),
+                        _ => None,
+                    })
+                    .ok_or_else(|| {
+                        rfc003::Error::Internal(format!(
+                            "transaction {:?} did not contain a SwapFunded log",
+                            transaction.hash
+                        ))
+                    })?;
+
+                if redeem_address != redeem_identity || refund_timestamp != expiry {
+                    return Err(rfc003::Error::Internal(format!(
+                        "transaction {:?} funded the swap with a different redeem address or expiry",
+                        transaction.hash
+                    )));
+                }
+
+                Ok((transaction, value))
+            })
+    }
+}
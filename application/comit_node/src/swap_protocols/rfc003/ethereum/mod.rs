@@ -1,12 +1,16 @@
 pub mod htlc_events;
 
 use crate::swap_protocols::{
-    actions::ethereum::DeployContract,
+    actions::ethereum::{CallContract, DeployContract, DeployOrCallContract},
     ledger::Ethereum,
-    rfc003::{state_machine::HtlcParams, Ledger},
+    rfc003::{state_machine::HtlcParams, HashFunction, Ledger, Policy, SecretHash},
 };
-use blockchain_contracts::ethereum::rfc003::{erc20_htlc::Erc20Htlc, ether_htlc::EtherHtlc};
-use ethereum_support::{web3::types::Address, Bytes, Erc20Token, EtherQuantity};
+use blockchain_contracts::ethereum::rfc003::{
+    create2_htlc_factory::Create2HtlcFactory, erc20_htlc::Erc20Htlc,
+    erc20_htlc_factory::Erc20HtlcFactory, erc721_htlc::Erc721Htlc, ether_htlc::EtherHtlc,
+    ether_htlc_registry::EtherHtlcRegistry,
+};
+use ethereum_support::{web3::types::Address, Bytes, Erc20Token, Erc721Token, EtherQuantity, H256};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
@@ -40,6 +44,33 @@ impl From<Seconds> for Duration {
 
 impl Ledger for Ethereum {
     type HtlcLocation = Address;
+
+    /// The Ether HTLC registry hashes the secret with SHA-256 itself
+    fn supports_hash_function(&self, hash_function: HashFunction) -> bool {
+        match hash_function {
+            HashFunction::Sha256 => true,
+            HashFunction::Keccak256 => self.ether_htlc_registry.is_none(),
+        }
+    }
+
+    fn complies_with(&self, policy: &Policy) -> bool {
+        let is_ours = |address: Option<Address>, ours: Option<Address>| {
+            address.map_or(true, |address| Some(address) == ours)
+        };
+
+        is_ours(self.ether_htlc_registry, policy.ether_htlc_registry)
+            && is_ours(self.erc20_htlc_factory, policy.erc20_htlc_factory)
+            && is_ours(self.create2_htlc_factory, policy.create2_htlc_factory)
+    }
+}
+
+impl From<HashFunction> for blockchain_contracts::ethereum::HashFunction {
+    fn from(hash_function: HashFunction) -> Self {
+        match hash_function {
+            HashFunction::Sha256 => blockchain_contracts::ethereum::HashFunction::Sha256,
+            HashFunction::Keccak256 => blockchain_contracts::ethereum::HashFunction::Keccak256,
+        }
+    }
 }
 
 impl From<HtlcParams<Ethereum, EtherQuantity>> for EtherHtlc {
@@ -49,6 +80,7 @@ impl From<HtlcParams<Ethereum, EtherQuantity>> for EtherHtlc {
             htlc_params.refund_identity,
             htlc_params.redeem_identity,
             htlc_params.secret_hash.into(),
+            htlc_params.hash_function.into(),
         )
     }
 }
@@ -57,6 +89,16 @@ impl HtlcParams<Ethereum, EtherQuantity> {
     pub fn bytecode(&self) -> Bytes {
         EtherHtlc::from(self.clone()).into()
     }
+
+    /// What the CREATE2 HTLC factory logs the HTLC with
+    pub fn init_code_hash(&self) -> H256 {
+        Create2HtlcFactory::init_code_hash(&self.bytecode().0)
+    }
+
+    /// What the Ether HTLC registry identifies the swap with
+    pub fn swap_id(&self) -> H256 {
+        EtherHtlcRegistry::swap_id(self.secret_hash.into(), self.refund_identity)
+    }
 }
 
 impl From<HtlcParams<Ethereum, Erc20Token>> for Erc20Htlc {
@@ -66,6 +108,7 @@ impl From<HtlcParams<Ethereum, Erc20Token>> for Erc20Htlc {
             htlc_params.refund_identity,
             htlc_params.redeem_identity,
             htlc_params.secret_hash.into(),
+            htlc_params.hash_function.into(),
             htlc_params.asset.token_contract,
             htlc_params.asset.quantity.0,
         )
@@ -76,6 +119,36 @@ impl HtlcParams<Ethereum, Erc20Token> {
     pub fn bytecode(&self) -> Bytes {
         Erc20Htlc::from(self.clone()).into()
     }
+
+    /// What the ERC20 and the CREATE2 HTLC factory log the HTLC with
+    pub fn init_code_hash(&self) -> H256 {
+        Erc20HtlcFactory::init_code_hash(Erc20Htlc::from(self.clone()))
+    }
+}
+
+impl From<HtlcParams<Ethereum, Erc721Token>> for Erc721Htlc {
+    fn from(htlc_params: HtlcParams<Ethereum, Erc721Token>) -> Self {
+        Erc721Htlc::new(
+            htlc_params.expiry.into(),
+            htlc_params.refund_identity,
+            htlc_params.redeem_identity,
+            htlc_params.secret_hash.into(),
+            htlc_params.hash_function.into(),
+            htlc_params.asset.token_contract,
+            htlc_params.asset.token_id,
+        )
+    }
+}
+
+impl HtlcParams<Ethereum, Erc721Token> {
+    pub fn bytecode(&self) -> Bytes {
+        Erc721Htlc::from(self.clone()).into()
+    }
+
+    /// What the CREATE2 HTLC factory logs the HTLC with
+    pub fn init_code_hash(&self) -> H256 {
+        Create2HtlcFactory::init_code_hash(&self.bytecode().0)
+    }
 }
 
 impl From<HtlcParams<Ethereum, EtherQuantity>> for DeployContract {
@@ -87,7 +160,7 @@ impl From<HtlcParams<Ethereum, EtherQuantity>> for DeployContract {
             data: htlc.into(),
             amount: htlc_params.asset,
             gas_limit,
-            network: htlc_params.ledger.network,
+            chain_id: htlc_params.ledger.chain_id,
         }
     }
 }
@@ -101,7 +174,69 @@ impl From<HtlcParams<Ethereum, Erc20Token>> for DeployContract {
             data: htlc.into(),
             amount: EtherQuantity::zero(),
             gas_limit,
-            network: htlc_params.ledger.network,
+            chain_id: htlc_params.ledger.chain_id,
         }
     }
 }
+
+impl From<HtlcParams<Ethereum, Erc721Token>> for DeployContract {
+    fn from(htlc_params: HtlcParams<Ethereum, Erc721Token>) -> Self {
+        let htlc = Erc721Htlc::from(htlc_params.clone());
+        let gas_limit = htlc.deployment_gas_limit();
+
+        DeployContract {
+            data: htlc.into(),
+            amount: EtherQuantity::zero(),
+            gas_limit,
+            chain_id: htlc_params.ledger.chain_id,
+        }
+    }
+}
+
+impl From<HtlcParams<Ethereum, EtherQuantity>> for DeployOrCallContract {
+    fn from(htlc_params: HtlcParams<Ethereum, EtherQuantity>) -> Self {
+        let secret_hash = htlc_params.secret_hash;
+        let create2_htlc_factory = htlc_params.ledger.create2_htlc_factory;
+
+        deploy_htlc(htlc_params.into(), secret_hash, create2_htlc_factory)
+    }
+}
+
+impl From<HtlcParams<Ethereum, Erc20Token>> for DeployOrCallContract {
+    fn from(htlc_params: HtlcParams<Ethereum, Erc20Token>) -> Self {
+        let secret_hash = htlc_params.secret_hash;
+        let create2_htlc_factory = htlc_params.ledger.create2_htlc_factory;
+
+        deploy_htlc(htlc_params.into(), secret_hash, create2_htlc_factory)
+    }
+}
+
+impl From<HtlcParams<Ethereum, Erc721Token>> for DeployOrCallContract {
+    fn from(htlc_params: HtlcParams<Ethereum, Erc721Token>) -> Self {
+        let secret_hash = htlc_params.secret_hash;
+        let create2_htlc_factory = htlc_params.ledger.create2_htlc_factory;
+
+        deploy_htlc(htlc_params.into(), secret_hash, create2_htlc_factory)
+    }
+}
+
+/// Deploys the HTLC through the CREATE2 HTLC factory if there is one
+fn deploy_htlc(
+    deployment: DeployContract,
+    secret_hash: SecretHash,
+    create2_htlc_factory: Option<Address>,
+) -> DeployOrCallContract {
+    let factory = match create2_htlc_factory {
+        Some(factory) => factory,
+        None => return DeployOrCallContract::Deploy(deployment),
+    };
+
+    DeployOrCallContract::Call(CallContract {
+        to: factory,
+        data: Create2HtlcFactory::deploy_htlc_tx_payload(secret_hash.into(), deployment.data.0),
+        amount: deployment.amount,
+        gas_limit: Create2HtlcFactory::deploy_htlc_tx_gas_limit(deployment.gas_limit),
+        chain_id: deployment.chain_id,
+        min_block_timestamp: None,
+    })
+}
@@ -3,7 +3,7 @@ use crate::swap_protocols::{
     rfc003::{
         events::{
             Deployed, DeployedFuture, Funded, FundedFuture, HtlcEvents, LedgerEvents,
-            RedeemedOrRefundedFuture,
+            RedeemedOrRefundedFuture, RefundableFuture,
         },
         state_machine::HtlcParams,
         Ledger,
@@ -21,6 +21,7 @@ pub struct LedgerEventFutures<L: Ledger, A: Asset> {
     htlc_deployed: Option<Box<DeployedFuture<L>>>,
     htlc_funded: Option<Box<FundedFuture<L, A>>>,
     htlc_redeemed_or_refunded: Option<Box<RedeemedOrRefundedFuture<L>>>,
+    htlc_refundable: Option<Box<RefundableFuture>>,
 }
 
 impl<L: Ledger, A: Asset> LedgerEventFutures<L, A> {
@@ -30,6 +31,7 @@ impl<L: Ledger, A: Asset> LedgerEventFutures<L, A> {
             htlc_deployed: None,
             htlc_funded: None,
             htlc_redeemed_or_refunded: None,
+            htlc_refundable: None,
         }
     }
 }
@@ -62,4 +64,16 @@ impl<L: Ledger, A: Asset> LedgerEvents<L, A> for LedgerEventFutures<L, A> {
             htlc_events.htlc_redeemed_or_refunded(htlc_params, htlc_deployment, htlc_funding)
         })
     }
+
+    fn htlc_refundable(
+        &mut self,
+        htlc_params: HtlcParams<L, A>,
+        htlc_deployment: &Deployed<L>,
+        htlc_funding: &Funded<L, A>,
+    ) -> &mut RefundableFuture {
+        let htlc_events = &self.htlc_events;
+        self.htlc_refundable.get_or_insert_with(move || {
+            htlc_events.htlc_refundable(htlc_params, htlc_deployment, htlc_funding)
+        })
+    }
 }
@@ -27,6 +27,18 @@ pub type ResponseFuture<AL, BL> = Future<Result<AcceptResponseBody<AL, BL>, Swap
 pub struct Funded<L: Ledger, A: Asset> {
     pub transaction: L::Transaction,
     pub asset: A,
+    /// Whether the ledger would accept a refund of the HTLC by now
+    pub refundable: bool,
+}
+
+impl<L: Ledger, A: Asset> Funded<L, A> {
+    pub fn new(transaction: L::Transaction, asset: A) -> Self {
+        Self {
+            transaction,
+            asset,
+            refundable: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -55,6 +67,7 @@ impl<L: Ledger> Refunded<L> {
 pub type DeployedFuture<L: Ledger> = Future<Deployed<L>>;
 pub type FundedFuture<L: Ledger, A: Asset> = Future<Funded<L, A>>;
 pub type RedeemedOrRefundedFuture<L: Ledger> = Future<Either<Redeemed<L>, Refunded<L>>>;
+pub type RefundableFuture = Future<()>;
 
 pub trait LedgerEvents<L: Ledger, A: Asset>: Send {
     fn htlc_deployed(&mut self, htlc_params: HtlcParams<L, A>) -> &mut DeployedFuture<L>;
@@ -71,6 +84,13 @@ pub trait LedgerEvents<L: Ledger, A: Asset>: Send {
         htlc_deployment: &Deployed<L>,
         htlc_funding: &Funded<L, A>,
     ) -> &mut RedeemedOrRefundedFuture<L>;
+
+    fn htlc_refundable(
+        &mut self,
+        htlc_params: HtlcParams<L, A>,
+        htlc_deployment: &Deployed<L>,
+        htlc_funding: &Funded<L, A>,
+    ) -> &mut RefundableFuture;
 }
 
 pub trait CommunicationEvents<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset>: Send {
@@ -93,4 +113,16 @@ pub trait HtlcEvents<L: Ledger, A: Asset>: Send + Sync + 'static {
         htlc_deployment: &Deployed<L>,
         htlc_funding: &Funded<L, A>,
     ) -> Box<RedeemedOrRefundedFuture<L>>;
+
+    /// Resolves once the ledger accepts a refund of the HTLC. HTLCs that
+    /// expire at the expiry of the swap leave waiting for it to whoever
+    /// executes the refund action.
+    fn htlc_refundable(
+        &self,
+        _htlc_params: HtlcParams<L, A>,
+        _htlc_deployment: &Deployed<L>,
+        _htlc_funding: &Funded<L, A>,
+    ) -> Box<RefundableFuture> {
+        Box::new(tokio::prelude::future::ok(()))
+    }
 }
@@ -1,7 +1,29 @@
-use crate::swap_protocols;
+use crate::swap_protocols::{
+    self,
+    rfc003::{HashFunction, Policy},
+    Timestamp,
+};
 use serde::{de::DeserializeOwned, Serialize};
 use std::fmt::Debug;
 
 pub trait Ledger: swap_protocols::Ledger {
     type HtlcLocation: PartialEq + Debug + Clone + DeserializeOwned + Serialize + Send + Sync;
+
+    /// Whether the HTLCs on this ledger can check a secret against a hash
+    /// made with `hash_function`. The Bitcoin HTLC scripts only check
+    /// SHA-256 and Lightning payment hashes are always SHA-256.
+    fn supports_hash_function(&self, hash_function: HashFunction) -> bool {
+        hash_function == HashFunction::Sha256
+    }
+
+    /// Whether Bob can rely on the HTLCs Alice asks for on this ledger
+    fn complies_with(&self, _policy: &Policy) -> bool {
+        true
+    }
+
+    /// The earliest point in time HTLCs on this ledger that are funded at
+    /// `funded_at` can be refunded, if the swap expires at `expiry` on it
+    fn earliest_refund(&self, _funded_at: Timestamp, expiry: Timestamp) -> Timestamp {
+        expiry
+    }
 }
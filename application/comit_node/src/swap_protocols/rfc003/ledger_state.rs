@@ -17,6 +17,8 @@ pub enum LedgerState<L: Ledger> {
         htlc_location: L::HtlcLocation,
         deploy_transaction: L::Transaction,
         fund_transaction: L::Transaction,
+        /// Whether the ledger would accept a refund by now
+        refundable: bool,
     },
     Redeemed {
         htlc_location: L::HtlcLocation,
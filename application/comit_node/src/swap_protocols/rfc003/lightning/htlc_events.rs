@@ -0,0 +1,220 @@
+use crate::{
+    lnd::{self, QueryLightning},
+    swap_protocols::{
+        ledger::{InvoicePaymentState, Lightning},
+        rfc003::{
+            self,
+            events::{
+                Deployed, DeployedFuture, Funded, FundedFuture, HtlcEvents, Redeemed,
+                RedeemedOrRefundedFuture, Refunded,
+            },
+            state_machine::HtlcParams,
+        },
+    },
+};
+use bitcoin_support::BitcoinQuantity;
+use futures::{
+    future::{self, Either},
+    Future,
+};
+use std::sync::Arc;
+
+impl HtlcEvents<Lightning, BitcoinQuantity> for Arc<dyn QueryLightning + Send + Sync + 'static> {
+    /// The hold invoice counts as deployed as soon as the payment is locked
+    /// in on every hop of the route.
+    fn htlc_deployed(
+        &self,
+        htlc_params: HtlcParams<Lightning, BitcoinQuantity>,
+    ) -> Box<DeployedFuture<Lightning>> {
+        let payment_hash = htlc_params.secret_hash;
+        let deployed_future =
+            lnd::poll_invoice_payment(Arc::clone(&self), payment_hash, |invoice_payment| {
+                match invoice_payment.state {
+                    InvoicePaymentState::Accepted | InvoicePaymentState::Settled { .. } => {
+                        Some(invoice_payment)
+                    }
+                    InvoicePaymentState::Canceled => None,
+                }
+            })
+            .map_err(rfc003::Error::Lnd)
+            .map(move |invoice_payment| Deployed {
+                location: payment_hash,
+                transaction: invoice_payment,
+            });
+
+        Box::new(deployed_future)
+    }
+
+    fn htlc_funded(
+        &self,
+        _htlc_params: HtlcParams<Lightning, BitcoinQuantity>,
+        htlc_deployment: &Deployed<Lightning>,
+    ) -> Box<FundedFuture<Lightning, BitcoinQuantity>> {
+        Box::new(future::ok(Funded::new(
+            htlc_deployment.transaction.clone(),
+            htlc_deployment.transaction.amount,
+        )))
+    }
+
+    fn htlc_redeemed_or_refunded(
+        &self,
+        _htlc_params: HtlcParams<Lightning, BitcoinQuantity>,
+        htlc_deployment: &Deployed<Lightning>,
+        _htlc_funding: &Funded<Lightning, BitcoinQuantity>,
+    ) -> Box<RedeemedOrRefundedFuture<Lightning>> {
+        let redeemed_or_refunded_future = lnd::poll_invoice_payment(
+            Arc::clone(&self),
+            htlc_deployment.location,
+            |invoice_payment| match invoice_payment.state {
+                InvoicePaymentState::Accepted => None,
+                InvoicePaymentState::Settled { preimage } => Some(Either::A(Redeemed {
+                    transaction: invoice_payment,
+                    secret: preimage,
+                })),
+                InvoicePaymentState::Canceled => Some(Either::B(Refunded::new(invoice_payment))),
+            },
+        )
+        .map_err(rfc003::Error::Lnd);
+
+        Box::new(redeemed_or_refunded_future)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::{
+        ledger::InvoicePayment,
+        rfc003::{HashFunction, Secret},
+        Timestamp,
+    };
+    use secp256k1_support::KeyPair;
+    use spectral::prelude::*;
+    use std::{sync::Mutex, time::Duration};
+    use tokio::runtime::Runtime;
+
+    /// Hands out one state per poll, sticking to the last one.
+    struct FakeLnd {
+        states: Mutex<Vec<Option<InvoicePaymentState>>>,
+    }
+
+    impl QueryLightning for FakeLnd {
+        fn invoice_payment(
+            &self,
+            payment_hash: rfc003::SecretHash,
+        ) -> Box<dyn Future<Item = Option<InvoicePayment>, Error = lnd::Error> + Send> {
+            let mut states = self.states.lock().unwrap();
+            let state = if states.len() > 1 {
+                states.remove(0)
+            } else {
+                states[0]
+            };
+
+            Box::new(future::ok(state.map(|state| InvoicePayment {
+                payment_hash,
+                amount: BitcoinQuantity::from_satoshi(100_000),
+                state,
+            })))
+        }
+
+        fn poll_interval(&self) -> Duration {
+            Duration::from_millis(10)
+        }
+    }
+
+    fn query_lightning(
+        states: Vec<Option<InvoicePaymentState>>,
+    ) -> Arc<dyn QueryLightning + Send + Sync + 'static> {
+        Arc::new(FakeLnd {
+            states: Mutex::new(states),
+        })
+    }
+
+    fn htlc_params(secret: Secret) -> HtlcParams<Lightning, BitcoinQuantity> {
+        let identity = KeyPair::from_secret_key_slice(&[1u8; 32])
+            .unwrap()
+            .public_key();
+
+        HtlcParams {
+            asset: BitcoinQuantity::from_satoshi(100_000),
+            ledger: Lightning::default(),
+            redeem_identity: identity,
+            refund_identity: identity,
+            expiry: Timestamp::now().plus(3600),
+            secret_hash: secret.hash(HashFunction::Sha256),
+            hash_function: HashFunction::Sha256,
+        }
+    }
+
+    #[test]
+    fn given_payment_is_locked_in_htlc_is_deployed_and_funded() {
+        let mut runtime = Runtime::new().unwrap();
+        let secret = Secret::from(*b"hello world, you are beautiful!!");
+        let query_lightning = query_lightning(vec![None, Some(InvoicePaymentState::Accepted)]);
+
+        let deployed = runtime
+            .block_on(query_lightning.htlc_deployed(htlc_params(secret)))
+            .unwrap();
+        assert_that(&deployed.location).is_equal_to(secret.hash(HashFunction::Sha256));
+
+        let funded = runtime
+            .block_on(query_lightning.htlc_funded(htlc_params(secret), &deployed))
+            .unwrap();
+        assert_that(&funded.asset).is_equal_to(BitcoinQuantity::from_satoshi(100_000));
+    }
+
+    #[test]
+    fn given_invoice_is_settled_htlc_is_redeemed_with_preimage() {
+        let mut runtime = Runtime::new().unwrap();
+        let secret = Secret::from(*b"hello world, you are beautiful!!");
+        let query_lightning = query_lightning(vec![
+            Some(InvoicePaymentState::Accepted),
+            Some(InvoicePaymentState::Accepted),
+            Some(InvoicePaymentState::Settled { preimage: secret }),
+        ]);
+
+        let deployed = runtime
+            .block_on(query_lightning.htlc_deployed(htlc_params(secret)))
+            .unwrap();
+        let funded = runtime
+            .block_on(query_lightning.htlc_funded(htlc_params(secret), &deployed))
+            .unwrap();
+        let redeemed_or_refunded = runtime.block_on(query_lightning.htlc_redeemed_or_refunded(
+            htlc_params(secret),
+            &deployed,
+            &funded,
+        ));
+
+        match redeemed_or_refunded {
+            Ok(Either::A(redeemed)) => assert_that(&redeemed.secret).is_equal_to(secret),
+            _ => panic!("expected the invoice to be redeemed"),
+        }
+    }
+
+    #[test]
+    fn given_invoice_is_canceled_htlc_is_refunded() {
+        let mut runtime = Runtime::new().unwrap();
+        let secret = Secret::from(*b"hello world, you are beautiful!!");
+        let query_lightning = query_lightning(vec![
+            Some(InvoicePaymentState::Accepted),
+            Some(InvoicePaymentState::Canceled),
+        ]);
+
+        let deployed = runtime
+            .block_on(query_lightning.htlc_deployed(htlc_params(secret)))
+            .unwrap();
+        let funded = runtime
+            .block_on(query_lightning.htlc_funded(htlc_params(secret), &deployed))
+            .unwrap();
+        let redeemed_or_refunded = runtime.block_on(query_lightning.htlc_redeemed_or_refunded(
+            htlc_params(secret),
+            &deployed,
+            &funded,
+        ));
+
+        match redeemed_or_refunded {
+            Ok(Either::B(_)) => {}
+            _ => panic!("expected the invoice to be refunded"),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+mod htlc_events;
+
+use crate::swap_protocols::{
+    ledger::Lightning,
+    rfc003::{state_machine::HtlcParams, Ledger, SecretHash},
+    Timestamp,
+};
+use bitcoin_support::BitcoinQuantity;
+
+/// Average time between two Bitcoin blocks
+const SECONDS_PER_BLOCK: u32 = 600;
+
+/// There is no HTLC to locate on Lightning, the hold invoice it is locked
+/// into is identified by its payment hash.
+impl Ledger for Lightning {
+    type HtlcLocation = SecretHash;
+}
+
+impl HtlcParams<Lightning, BitcoinQuantity> {
+    /// Lightning expresses expiries as a number of blocks relative to the
+    /// current block height, rather than as a point in time.
+    pub fn cltv_delta(&self) -> u32 {
+        cltv_delta(Timestamp::now(), self.expiry)
+    }
+}
+
+fn cltv_delta(now: Timestamp, expiry: Timestamp) -> u32 {
+    u32::from(expiry).saturating_sub(u32::from(now)) / SECONDS_PER_BLOCK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral::prelude::*;
+
+    #[test]
+    fn cltv_delta_rounds_down_to_full_blocks() {
+        let now = Timestamp::from(1_000_000);
+
+        assert_that(&cltv_delta(now, now.plus(6 * 3600 + 599))).is_equal_to(36);
+        assert_that(&cltv_delta(now, now.plus(599))).is_equal_to(0);
+    }
+
+    #[test]
+    fn given_expiry_in_the_past_cltv_delta_is_zero() {
+        let now = Timestamp::from(1_000_000);
+
+        assert_that(&cltv_delta(now, Timestamp::from(999_000))).is_equal_to(0);
+    }
+}
@@ -1,8 +1,9 @@
 use crate::swap_protocols::{
-    asset::Asset,
-    rfc003::{Ledger, SecretHash, SecretSource},
-    Timestamp,
+    asset::{self, Asset},
+    rfc003::{HashFunction, Ledger, Policy, SecretHash, SecretSource},
+    SwapId, Timestamp,
 };
+use ethereum_support::U256;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -16,12 +17,92 @@ pub struct Request<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> {
     pub alpha_expiry: Timestamp,
     pub beta_expiry: Timestamp,
     pub secret_hash: SecretHash,
+    pub hash_function: HashFunction,
+}
+
+impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> Request<AL, BL, AA, BA> {
+    /// Whether the HTLCs on both ledgers can check the secret against the
+    /// requested hash function
+    pub fn hash_function_is_supported(&self) -> bool {
+        self.alpha_ledger.supports_hash_function(self.hash_function)
+            && self.beta_ledger.supports_hash_function(self.hash_function)
+    }
+
+    /// Whether Bob can safely take part in the swap under `policy`, if Alice
+    /// funds no earlier than `now`. Bob has to be able to redeem on the alpha
+    /// ledger after Alice redeemed on the beta ledger, which she can until
+    /// `beta_expiry`.
+    pub fn complies_with(&self, policy: &Policy, now: Timestamp) -> bool {
+        self.alpha_ledger.complies_with(policy)
+            && self.beta_ledger.complies_with(policy)
+            && self.alpha_ledger.earliest_refund(now, self.alpha_expiry) > self.beta_expiry
+    }
+
+    /// The request with the quantities Bob filled it with. `None` if he
+    /// asks for more than requested or for a rate that is worse for Alice.
+    pub fn fill(&self, partial_fill: Option<PartialFill>) -> Option<Self> {
+        let partial_fill = match partial_fill {
+            Some(partial_fill) => partial_fill,
+            None => return Some(self.clone()),
+        };
+        if partial_fill.alpha_quantity.is_zero() {
+            return None;
+        }
+
+        let alpha_asset = self
+            .alpha_asset
+            .with_base_units(partial_fill.alpha_quantity)?;
+        let beta_asset = self
+            .beta_asset
+            .with_base_units(partial_fill.beta_quantity)?;
+
+        if !self.alpha_asset.equal_or_greater_value(&alpha_asset)
+            || !self.beta_asset.equal_or_greater_value(&beta_asset)
+            || !asset::rate_is_at_least(
+                (&alpha_asset, &beta_asset),
+                (&self.alpha_asset, &self.beta_asset),
+            )
+        {
+            return None;
+        }
+
+        Some(Request {
+            alpha_asset,
+            beta_asset,
+            ..self.clone()
+        })
+    }
+}
+
+/// Bob accepting less of both assets than Alice requested
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialFill {
+    #[serde(with = "serde_base_units")]
+    pub alpha_quantity: U256,
+    #[serde(with = "serde_base_units")]
+    pub beta_quantity: U256,
+}
+
+mod serde_base_units {
+    use ethereum_support::{FromDecimalStr, ToBigInt, U256};
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(base_units: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base_units.to_bigint().to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+        let base_units = String::deserialize(deserializer)?;
+        U256::from_decimal_str(&base_units).map_err(de::Error::custom)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct AcceptResponseBody<AL: Ledger, BL: Ledger> {
     pub beta_ledger_refund_identity: BL::Identity,
     pub alpha_ledger_redeem_identity: AL::Identity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partial_fill: Option<PartialFill>,
 }
 
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
@@ -35,11 +116,228 @@ pub struct RequestBody<AL: Ledger, BL: Ledger> {
 
 pub trait ToRequest<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> {
     fn to_request(&self, secret_source: &dyn SecretSource) -> Request<AL, BL, AA, BA>;
+
+    /// The swap we received as Bob that this request forwards, if any
+    fn previous_hop(&self) -> Option<SwapId> {
+        None
+    }
+
+    /// The swap whose order this request takes a share of, if any
+    fn split_from(&self) -> Option<SwapId> {
+        None
+    }
 }
 
 pub trait IntoAcceptResponseBody<AL: Ledger, BL: Ledger> {
     fn into_accept_response_body(
         self,
+        alpha_ledger: AL,
+        beta_ledger: BL,
         secret_source: &dyn SecretSource,
     ) -> AcceptResponseBody<AL, BL>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::ledger::{Bitcoin, Ethereum};
+    use bitcoin_support::{BitcoinQuantity, FromHex, PubkeyHash};
+    use ethereum_support::EtherQuantity;
+    use spectral::prelude::*;
+
+    fn request() -> Request<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity> {
+        Request {
+            alpha_ledger: Bitcoin::default(),
+            beta_ledger: Ethereum::default(),
+            alpha_asset: BitcoinQuantity::from_satoshi(100_000_000),
+            beta_asset: EtherQuantity::from_wei(U256::from(10_000)),
+            alpha_ledger_refund_identity: PubkeyHash::from_hex(
+                "1925a274ac004373bb5429553bdb55c40e57b124",
+            )
+            .unwrap(),
+            beta_ledger_redeem_identity: ethereum_support::Address::zero(),
+            alpha_expiry: Timestamp::from(2000000000),
+            beta_expiry: Timestamp::from(2000000000),
+            secret_hash: SecretHash::from([0u8; SecretHash::LENGTH]),
+            hash_function: HashFunction::Sha256,
+        }
+    }
+
+    fn partial_fill(alpha_quantity: u64, beta_quantity: u64) -> Option<PartialFill> {
+        Some(PartialFill {
+            alpha_quantity: U256::from(alpha_quantity),
+            beta_quantity: U256::from(beta_quantity),
+        })
+    }
+
+    fn bitcoin_refunding_after(blocks: u16) -> Bitcoin {
+        Bitcoin {
+            refund_after_blocks: Some(blocks),
+            ..Bitcoin::default()
+        }
+    }
+
+    #[test]
+    fn request_expiring_on_alpha_ledger_after_beta_ledger_complies() {
+        let request = Request {
+            beta_expiry: Timestamp::from(1_999_990_000),
+            ..request()
+        };
+
+        assert_that(&request.complies_with(&Policy::default(), Timestamp::from(1_900_000_000)))
+            .is_true();
+    }
+
+    #[test]
+    fn request_expiring_on_alpha_ledger_before_beta_ledger_is_refused() {
+        assert_that(&request().complies_with(&Policy::default(), Timestamp::from(1_900_000_000)))
+            .is_false();
+    }
+
+    #[test]
+    fn refund_after_fewer_blocks_than_the_policy_demands_is_refused() {
+        let request = Request {
+            alpha_ledger: bitcoin_refunding_after(6),
+            beta_expiry: Timestamp::from(1_900_000_000),
+            ..request()
+        };
+        let policy = Policy {
+            min_refund_after_blocks: 144,
+            ..Policy::default()
+        };
+
+        assert_that(&request.complies_with(&policy, Timestamp::from(1_800_000_000))).is_false();
+    }
+
+    #[test]
+    fn relative_refund_has_to_leave_room_for_beta_expiry() {
+        let now = Timestamp::from(1_900_000_000);
+        let with_beta_expiry = |beta_expiry| Request {
+            alpha_ledger: bitcoin_refunding_after(144),
+            beta_expiry,
+            ..request()
+        };
+        let policy = Policy {
+            min_refund_after_blocks: 144,
+            ..Policy::default()
+        };
+
+        // 144 blocks might be found in as little as 12 hours
+        assert_that(&with_beta_expiry(now.plus(11 * 60 * 60)).complies_with(&policy, now))
+            .is_true();
+        assert_that(&with_beta_expiry(now.plus(13 * 60 * 60)).complies_with(&policy, now))
+            .is_false();
+    }
+
+    #[test]
+    fn ether_htlc_registry_other_than_ours_is_refused() {
+        let registry = ethereum_support::Address::from([2u8; 20]);
+        let request = Request {
+            beta_ledger: Ethereum {
+                ether_htlc_registry: Some(registry),
+                ..Ethereum::default()
+            },
+            beta_expiry: Timestamp::from(1_999_990_000),
+            ..request()
+        };
+        let now = Timestamp::from(1_900_000_000);
+
+        assert_that(&request.complies_with(&Policy::default(), now)).is_false();
+        assert_that(&request.complies_with(
+            &Policy {
+                ether_htlc_registry: Some(ethereum_support::Address::from([3u8; 20])),
+                ..Policy::default()
+            },
+            now,
+        ))
+        .is_false();
+        assert_that(&request.complies_with(
+            &Policy {
+                ether_htlc_registry: Some(registry),
+                ..Policy::default()
+            },
+            now,
+        ))
+        .is_true();
+    }
+
+    #[test]
+    fn erc20_htlc_factory_other_than_ours_is_refused() {
+        let factory = ethereum_support::Address::from([1u8; 20]);
+        let request = Request {
+            beta_ledger: Ethereum {
+                erc20_htlc_factory: Some(factory),
+                ..Ethereum::default()
+            },
+            beta_expiry: Timestamp::from(1_999_990_000),
+            ..request()
+        };
+        let now = Timestamp::from(1_900_000_000);
+
+        assert_that(&request.complies_with(&Policy::default(), now)).is_false();
+        assert_that(&request.complies_with(
+            &Policy {
+                erc20_htlc_factory: Some(factory),
+                ..Policy::default()
+            },
+            now,
+        ))
+        .is_true();
+    }
+
+    #[test]
+    fn create2_htlc_factory_other_than_ours_is_refused() {
+        let factory = ethereum_support::Address::from([3u8; 20]);
+        let request = Request {
+            beta_ledger: Ethereum {
+                create2_htlc_factory: Some(factory),
+                ..Ethereum::default()
+            },
+            beta_expiry: Timestamp::from(1_999_990_000),
+            ..request()
+        };
+        let now = Timestamp::from(1_900_000_000);
+
+        assert_that(&request.complies_with(&Policy::default(), now)).is_false();
+        assert_that(&request.complies_with(
+            &Policy {
+                create2_htlc_factory: Some(factory),
+                ..Policy::default()
+            },
+            now,
+        ))
+        .is_true();
+    }
+
+    #[test]
+    fn fill_at_same_rate_adjusts_quantities() {
+        let filled = request().fill(partial_fill(25_000_000, 2_500));
+
+        assert_that(&filled.map(|request| (request.alpha_asset, request.beta_asset)))
+            .is_some()
+            .is_equal_to((
+                BitcoinQuantity::from_satoshi(25_000_000),
+                EtherQuantity::from_wei(U256::from(2_500)),
+            ));
+    }
+
+    #[test]
+    fn fill_without_partial_fill_keeps_request() {
+        assert_that(&request().fill(None)).is_equal_to(Some(request()));
+    }
+
+    #[test]
+    fn fill_at_worse_rate_for_alice_is_refused() {
+        assert_that(&request().fill(partial_fill(25_000_000, 2_499))).is_none();
+    }
+
+    #[test]
+    fn fill_for_more_than_requested_is_refused() {
+        assert_that(&request().fill(partial_fill(200_000_000, 20_000))).is_none();
+    }
+
+    #[test]
+    fn fill_for_nothing_is_refused() {
+        assert_that(&request().fill(partial_fill(0, 0))).is_none();
+    }
+}
@@ -7,7 +7,9 @@ pub mod bob;
 pub mod ethereum;
 pub mod events;
 pub mod ledger_state;
+pub mod lightning;
 pub mod messages;
+pub mod policy;
 pub mod state_machine;
 pub mod state_store;
 
@@ -27,7 +29,8 @@ pub use self::{
     error::Error,
     ledger::Ledger,
     ledger_state::{HtlcState, LedgerState},
+    policy::Policy,
     save_state::SaveState,
-    secret::{FromErr, Secret, SecretHash},
+    secret::{FromErr, HashFunction, Secret, SecretHash},
     secret_source::*,
 };
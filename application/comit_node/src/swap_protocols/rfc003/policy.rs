@@ -0,0 +1,41 @@
+use ethereum_support::Address;
+use serde::{Deserialize, Serialize};
+
+/// What Bob requires of the ledgers of a swap before he considers it. Alice
+/// picks them, hence nothing in them can be taken on trust.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Policy {
+    /// The fewest blocks HTLCs have to stay locked after they were funded, if
+    /// they are refunded relative to their funding instead of at the expiry
+    /// of the swap
+    #[serde(default = "default_min_refund_after_blocks")]
+    pub min_refund_after_blocks: u16,
+    /// Our deployment of the Ether HTLC registry. HTLCs in any other one are
+    /// refused, because the registry decides who gets the funds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ether_htlc_registry: Option<Address>,
+    /// Our deployment of the ERC20 HTLC factory. The HTLC address is taken
+    /// from the logs of the factory, hence any other one is refused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub erc20_htlc_factory: Option<Address>,
+    /// Our deployment of the CREATE2 HTLC factory. The HTLC address is taken
+    /// from the logs of the factory, hence any other one is refused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub create2_htlc_factory: Option<Address>,
+}
+
+/// A day worth of Bitcoin blocks
+fn default_min_refund_after_blocks() -> u16 {
+    144
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            min_refund_after_blocks: default_min_refund_after_blocks(),
+            ether_htlc_registry: None,
+            erc20_htlc_factory: None,
+            create2_htlc_factory: None,
+        }
+    }
+}
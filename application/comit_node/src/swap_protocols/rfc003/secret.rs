@@ -1,4 +1,4 @@
-use crypto::{digest::Digest, sha2::Sha256};
+use crypto::{digest::Digest, sha2::Sha256, sha3::Sha3};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     fmt::{self, Debug},
@@ -11,6 +11,25 @@ pub enum FromErr {
     FromHex(hex::FromHexError),
 }
 
+/// The hash function that turns the secret into the secret hash both HTLCs
+/// are locked with. Both ledgers have to be able to check it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HashFunction {
+    #[serde(rename = "SHA-256")]
+    Sha256,
+    #[serde(rename = "KECCAK-256")]
+    Keccak256,
+}
+
+/// SHA-256 was the only hash function before Alice could pick one. Peers
+/// that predate that neither send nor accept the `hash_function` header, hence
+/// it is only sent for other hash functions.
+impl Default for HashFunction {
+    fn default() -> Self {
+        HashFunction::Sha256
+    }
+}
+
 #[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub struct SecretHash([u8; Self::LENGTH]);
 
@@ -116,12 +135,6 @@ impl From<[u8; Self::LENGTH]> for Secret {
     }
 }
 
-impl From<Secret> for SecretHash {
-    fn from(secret: Secret) -> Self {
-        secret.hash()
-    }
-}
-
 impl Secret {
     // Both values need to stay the same!
     pub const LENGTH: usize = 32;
@@ -140,12 +153,15 @@ impl Secret {
         Ok(Secret(data))
     }
 
-    pub fn hash(&self) -> SecretHash {
-        let mut sha = Sha256::new();
-        sha.input(&self.0);
+    pub fn hash(&self, hash_function: HashFunction) -> SecretHash {
+        let mut hasher: Box<dyn Digest> = match hash_function {
+            HashFunction::Sha256 => Box::new(Sha256::new()),
+            HashFunction::Keccak256 => Box::new(Sha3::keccak256()),
+        };
+        hasher.input(&self.0);
 
         let mut result: [u8; SecretHash::LENGTH] = [0; SecretHash::LENGTH];
-        sha.result(&mut result);
+        hasher.result(&mut result);
         SecretHash::from(result)
     }
 
@@ -227,17 +243,39 @@ mod tests {
         let bytes = b"hello world, you are beautiful!!";
         let secret = Secret::from(*bytes);
         assert_eq!(
-            secret.hash().to_string(),
+            secret.hash(HashFunction::Sha256).to_string(),
             "68d627971643a6f97f27c58957826fcba853ec2077fd10ec6b93d8e61deb4cec"
         );
     }
 
+    #[test]
+    fn new_keccak256_secret_hash_as_hex() {
+        let bytes = b"hello world, you are beautiful!!";
+        let secret = Secret::from(*bytes);
+        assert_eq!(
+            secret.hash(HashFunction::Keccak256).to_string(),
+            "e96cb429b7adbbfd242cf8faa243ba5c5ede8ec527d23bc4464efd1e230e3a4f"
+        );
+    }
+
+    #[test]
+    fn hash_function_serializes_to_its_name() {
+        assert_eq!(
+            serde_json::to_string(&HashFunction::Keccak256).unwrap(),
+            r#""KECCAK-256""#
+        );
+        assert_eq!(
+            serde_json::from_str::<HashFunction>(r#""SHA-256""#).unwrap(),
+            HashFunction::Sha256
+        );
+    }
+
     #[test]
     fn secret_hash_should_be_displayed_as_hex() {
         let bytes = b"hello world, you are beautiful!!";
         let secret = Secret::from(*bytes);
 
-        let hash = secret.hash();
+        let hash = secret.hash(HashFunction::Sha256);
 
         let formatted_hash = format!("{}", hash);
 
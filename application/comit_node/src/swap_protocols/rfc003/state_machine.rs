@@ -9,8 +9,8 @@ use crate::{
             self,
             events::{self, Deployed, Funded, Redeemed, Refunded},
             ledger::Ledger,
-            messages::AcceptResponseBody,
-            SaveState, SecretHash,
+            messages::{AcceptResponseBody, PartialFill},
+            HashFunction, SaveState, SecretHash,
         },
         Timestamp,
     },
@@ -28,6 +28,7 @@ pub struct HtlcParams<L: Ledger, A: Asset> {
     pub refund_identity: L::Identity,
     pub expiry: Timestamp,
     pub secret_hash: SecretHash,
+    pub hash_function: HashFunction,
 }
 
 impl<L: Ledger, A: Asset> HtlcParams<L, A> {
@@ -42,6 +43,7 @@ impl<L: Ledger, A: Asset> HtlcParams<L, A> {
             refund_identity: request.alpha_ledger_refund_identity,
             expiry: request.alpha_expiry,
             secret_hash: request.secret_hash,
+            hash_function: request.hash_function,
         }
     }
 
@@ -56,6 +58,7 @@ impl<L: Ledger, A: Asset> HtlcParams<L, A> {
             refund_identity: accept_response.beta_ledger_refund_identity,
             expiry: request.beta_expiry,
             secret_hash: request.secret_hash,
+            hash_function: request.hash_function,
         }
     }
 }
@@ -73,6 +76,8 @@ pub struct OngoingSwap<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> {
     pub alpha_expiry: Timestamp,
     pub beta_expiry: Timestamp,
     pub secret_hash: SecretHash,
+    pub hash_function: HashFunction,
+    pub partial_fill: Option<PartialFill>,
 }
 
 impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> OngoingSwap<AL, BL, AA, BA> {
@@ -89,6 +94,8 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> OngoingSwap<AL, BL, AA, BA> {
             alpha_expiry: start.alpha_expiry,
             beta_expiry: start.beta_expiry,
             secret_hash: start.secret_hash,
+            hash_function: start.hash_function,
+            partial_fill: response.partial_fill,
         }
     }
 
@@ -100,6 +107,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> OngoingSwap<AL, BL, AA, BA> {
             refund_identity: self.alpha_ledger_refund_identity,
             expiry: self.alpha_expiry,
             secret_hash: self.secret_hash,
+            hash_function: self.hash_function,
         }
     }
 
@@ -111,6 +119,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> OngoingSwap<AL, BL, AA, BA> {
             refund_identity: self.beta_ledger_refund_identity,
             expiry: self.beta_expiry,
             secret_hash: self.secret_hash,
+            hash_function: self.hash_function,
         }
     }
 }
@@ -179,6 +188,73 @@ pub struct Context<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> {
     pub communication_events: Box<dyn events::CommunicationEvents<AL, BL, AA, BA>>,
 }
 
+/// Notes in `funded` once the ledger accepts a refund of its HTLC and
+/// returns whether that happened just now.
+fn poll_refundable<L: Ledger, A: Asset>(
+    ledger_events: &mut dyn events::LedgerEvents<L, A>,
+    htlc_params: HtlcParams<L, A>,
+    deployed: &Deployed<L>,
+    funded: &mut Funded<L, A>,
+) -> Result<bool, rfc003::Error> {
+    if funded.refundable {
+        return Ok(false);
+    }
+
+    match ledger_events
+        .htlc_refundable(htlc_params, deployed, funded)
+        .poll()?
+    {
+        Async::Ready(()) => {
+            funded.refundable = true;
+            Ok(true)
+        }
+        Async::NotReady => Ok(false),
+    }
+}
+
+/// Saves the current state again once an HTLC in it became refundable, so
+/// that the refund action is only offered from then on.
+macro_rules! save_once_refundable {
+    ($state:ident, $context:ident,alpha) => {
+        save_once_refundable!(
+            $state,
+            $context,
+            alpha_ledger_events,
+            alpha_htlc_params,
+            alpha_deployed,
+            alpha_funded
+        )
+    };
+    ($state:ident, $context:ident,beta) => {
+        save_once_refundable!(
+            $state,
+            $context,
+            beta_ledger_events,
+            beta_htlc_params,
+            beta_deployed,
+            beta_funded
+        )
+    };
+    (
+        $state:ident,
+        $context:ident,
+        $ledger_events:ident,
+        $htlc_params:ident,
+        $deployed:ident,
+        $funded:ident
+    ) => {{
+        let state = &mut **$state;
+        if poll_refundable(
+            &mut *$context.$ledger_events,
+            state.swap.$htlc_params(),
+            &state.$deployed,
+            &mut state.$funded,
+        )? {
+            $context.state_repo.save(state.clone().into());
+        }
+    }};
+}
+
 #[derive(StateMachineFuture)]
 #[state_machine_future(context = "Context", derive(Clone, Debug, PartialEq))]
 #[allow(missing_debug_implementations)]
@@ -194,6 +270,7 @@ pub enum Swap<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> {
         alpha_expiry: Timestamp,
         beta_expiry: Timestamp,
         secret_hash: SecretHash,
+        hash_function: HashFunction,
     },
 
     #[state_machine_future(transitions(AlphaDeployed))]
@@ -298,6 +375,7 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> PollSwap<AL, BL, AA, BA>
             alpha_expiry: state.alpha_expiry,
             beta_expiry: state.beta_expiry,
             secret_hash: state.secret_hash,
+            hash_function: state.hash_function,
         };
 
         let response = try_ready!(context
@@ -308,12 +386,31 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> PollSwap<AL, BL, AA, BA>
         let state = state.take();
 
         match response {
-            Ok(swap_accepted) => transition_save!(
-                context.state_repo,
-                Accepted {
-                    swap: OngoingSwap::new(state, swap_accepted),
+            Ok(swap_accepted) => match request.fill(swap_accepted.partial_fill) {
+                Some(filled_request) => transition_save!(
+                    context.state_repo,
+                    Accepted {
+                        swap: OngoingSwap {
+                            alpha_asset: filled_request.alpha_asset,
+                            beta_asset: filled_request.beta_asset,
+                            ..OngoingSwap::new(state, swap_accepted)
+                        },
+                    }
+                ),
+                None => {
+                    log::warn!(
+                        "Swap request was filled with {:?}, which is more than requested or at a worse rate",
+                        swap_accepted.partial_fill
+                    );
+                    transition_save!(
+                        context.state_repo,
+                        Final(SwapOutcome::Rejected {
+                            start: state,
+                            rejection_type: SwapReject::Rejected,
+                        })
+                    )
                 }
-            ),
+            },
             Err(rejection_type) => transition_save!(
                 context.state_repo,
                 Final(SwapOutcome::Rejected {
@@ -373,6 +470,8 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> PollSwap<AL, BL, AA, BA>
         state: &'s mut RentToOwn<'s, AlphaFunded<AL, BL, AA, BA>>,
         context: &'c mut RentToOwn<'c, Context<AL, BL, AA, BA>>,
     ) -> Result<Async<AfterAlphaFunded<AL, BL, AA, BA>>, rfc003::Error> {
+        save_once_refundable!(state, context, alpha);
+
         if let Async::Ready(alpha_redeemed_or_refunded) = context
             .alpha_ledger_events
             .htlc_redeemed_or_refunded(
@@ -425,6 +524,8 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> PollSwap<AL, BL, AA, BA>
         state: &'s mut RentToOwn<'s, AlphaFundedBetaDeployed<AL, BL, AA, BA>>,
         context: &'c mut RentToOwn<'c, Context<AL, BL, AA, BA>>,
     ) -> Result<Async<AfterAlphaFundedBetaDeployed<AL, BL, AA, BA>>, rfc003::Error> {
+        save_once_refundable!(state, context, alpha);
+
         if let Async::Ready(alpha_redeemed_or_refunded) = context
             .alpha_ledger_events
             .htlc_redeemed_or_refunded(
@@ -486,6 +587,9 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> PollSwap<AL, BL, AA, BA>
         state: &'s mut RentToOwn<'s, BothFunded<AL, BL, AA, BA>>,
         context: &'c mut RentToOwn<'c, Context<AL, BL, AA, BA>>,
     ) -> Result<Async<AfterBothFunded<AL, BL, AA, BA>>, rfc003::Error> {
+        save_once_refundable!(state, context, alpha);
+        save_once_refundable!(state, context, beta);
+
         if let Async::Ready(redeemed_or_refunded) = context
             .beta_ledger_events
             .htlc_redeemed_or_refunded(
@@ -566,6 +670,8 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> PollSwap<AL, BL, AA, BA>
         state: &'s mut RentToOwn<'s, AlphaFundedBetaRefunded<AL, BL, AA, BA>>,
         context: &'c mut RentToOwn<'c, Context<AL, BL, AA, BA>>,
     ) -> Result<Async<AfterAlphaFundedBetaRefunded<AL, BL, AA, BA>>, rfc003::Error> {
+        save_once_refundable!(state, context, alpha);
+
         match try_ready!(context
             .alpha_ledger_events
             .htlc_redeemed_or_refunded(
@@ -612,6 +718,8 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> PollSwap<AL, BL, AA, BA>
         state: &'s mut RentToOwn<'s, AlphaRefundedBetaFunded<AL, BL, AA, BA>>,
         context: &'c mut RentToOwn<'c, Context<AL, BL, AA, BA>>,
     ) -> Result<Async<AfterAlphaRefundedBetaFunded<AL, BL, AA, BA>>, rfc003::Error> {
+        save_once_refundable!(state, context, beta);
+
         match try_ready!(context
             .beta_ledger_events
             .htlc_redeemed_or_refunded(
@@ -658,6 +766,8 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> PollSwap<AL, BL, AA, BA>
         state: &'s mut RentToOwn<'s, AlphaRedeemedBetaFunded<AL, BL, AA, BA>>,
         context: &'c mut RentToOwn<'c, Context<AL, BL, AA, BA>>,
     ) -> Result<Async<AfterAlphaRedeemedBetaFunded<AL, BL, AA, BA>>, rfc003::Error> {
+        save_once_refundable!(state, context, beta);
+
         match try_ready!(context
             .beta_ledger_events
             .htlc_redeemed_or_refunded(
@@ -703,6 +813,8 @@ impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> PollSwap<AL, BL, AA, BA>
         state: &'s mut RentToOwn<'s, AlphaFundedBetaRedeemed<AL, BL, AA, BA>>,
         context: &'c mut RentToOwn<'c, Context<AL, BL, AA, BA>>,
     ) -> Result<Async<AfterAlphaFundedBetaRedeemed<AL, BL, AA, BA>>, rfc003::Error> {
+        save_once_refundable!(state, context, alpha);
+
         match try_ready!(context
             .alpha_ledger_events
             .htlc_redeemed_or_refunded(
@@ -767,3 +879,209 @@ impl_display!(AlphaRefundedBetaFunded);
 impl_display!(AlphaFundedBetaRedeemed);
 impl_display!(AlphaRedeemedBetaFunded);
 impl_display!(Final);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        seed::Seed,
+        swap_protocols::{
+            actions::Actions,
+            ledger::{Bitcoin, Ethereum},
+            rfc003::{
+                actions::Action,
+                alice,
+                events::{
+                    CommunicationEvents, DeployedFuture, FundedFuture, LedgerEvents,
+                    RedeemedOrRefundedFuture, RefundableFuture, ResponseFuture,
+                },
+                messages::Request,
+                state_store::{InMemoryStateStore, StateStore},
+                Secret,
+            },
+            SwapId,
+        },
+    };
+    use bitcoin_support::{BitcoinQuantity, OutPoint, Transaction};
+    use ethereum_support::EtherQuantity;
+    use futures::{stream::Stream, sync::mpsc};
+    use spectral::prelude::*;
+
+    struct FakeLedgerEvents<L: Ledger, A: Asset> {
+        htlc_deployed: Box<DeployedFuture<L>>,
+        htlc_funded: Box<FundedFuture<L, A>>,
+        htlc_redeemed_or_refunded: Box<RedeemedOrRefundedFuture<L>>,
+        htlc_refundable: Box<RefundableFuture>,
+    }
+
+    impl<L: Ledger, A: Asset> FakeLedgerEvents<L, A> {
+        fn pending() -> Self {
+            FakeLedgerEvents {
+                htlc_deployed: Box::new(future::empty()),
+                htlc_funded: Box::new(future::empty()),
+                htlc_redeemed_or_refunded: Box::new(future::empty()),
+                htlc_refundable: Box::new(future::empty()),
+            }
+        }
+    }
+
+    impl<L: Ledger, A: Asset> LedgerEvents<L, A> for FakeLedgerEvents<L, A> {
+        fn htlc_deployed(&mut self, _: HtlcParams<L, A>) -> &mut DeployedFuture<L> {
+            &mut *self.htlc_deployed
+        }
+
+        fn htlc_funded(&mut self, _: HtlcParams<L, A>, _: &Deployed<L>) -> &mut FundedFuture<L, A> {
+            &mut *self.htlc_funded
+        }
+
+        fn htlc_redeemed_or_refunded(
+            &mut self,
+            _: HtlcParams<L, A>,
+            _: &Deployed<L>,
+            _: &Funded<L, A>,
+        ) -> &mut RedeemedOrRefundedFuture<L> {
+            &mut *self.htlc_redeemed_or_refunded
+        }
+
+        fn htlc_refundable(
+            &mut self,
+            _: HtlcParams<L, A>,
+            _: &Deployed<L>,
+            _: &Funded<L, A>,
+        ) -> &mut RefundableFuture {
+            &mut *self.htlc_refundable
+        }
+    }
+
+    struct Accepting<AL: Ledger, BL: Ledger>(Box<ResponseFuture<AL, BL>>);
+
+    impl<AL: Ledger, BL: Ledger, AA: Asset, BA: Asset> CommunicationEvents<AL, BL, AA, BA>
+        for Accepting<AL, BL>
+    {
+        fn request_responded(
+            &mut self,
+            _: &Request<AL, BL, AA, BA>,
+        ) -> &mut ResponseFuture<AL, BL> {
+            &mut *self.0
+        }
+    }
+
+    type AliceState = alice::State<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>;
+
+    fn request() -> Request<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity> {
+        Request {
+            alpha_ledger: Bitcoin::default(),
+            beta_ledger: Ethereum::default(),
+            alpha_asset: BitcoinQuantity::from_bitcoin(1.0),
+            beta_asset: EtherQuantity::from_eth(10.0),
+            alpha_ledger_refund_identity: secp256k1_support::KeyPair::from_secret_key_slice(
+                &hex::decode("18e14a7b6a307f426a94f8114701e7c8e774e7f9a47e2c2035db29a206321725")
+                    .unwrap(),
+            )
+            .unwrap()
+            .into(),
+            beta_ledger_redeem_identity: "8457037fcd80a8650c4692d7fcfc1d0a96b92867"
+                .parse()
+                .unwrap(),
+            alpha_expiry: Timestamp::from(2_000_000_000),
+            beta_expiry: Timestamp::from(1_900_000_000),
+            secret_hash: Secret::from(*b"hello world, you are beautiful!!")
+                .hash(HashFunction::Sha256),
+            hash_function: HashFunction::Sha256,
+        }
+    }
+
+    /// Runs Alice's side of a swap in which the alpha HTLC gets funded and
+    /// nothing happens afterwards, returning the actions she is offered.
+    fn alice_actions_once_alpha_is_funded(
+        refundable: Box<RefundableFuture>,
+    ) -> Vec<<AliceState as Actions>::ActionKind> {
+        let request = request();
+        let funding_transaction = Transaction {
+            version: 1,
+            lock_time: 0,
+            input: vec![],
+            output: vec![],
+        };
+        let alpha_deployed = Deployed {
+            location: OutPoint {
+                txid: funding_transaction.txid(),
+                vout: 0,
+            },
+            transaction: funding_transaction.clone(),
+        };
+        let alpha_funded = Funded::new(funding_transaction, request.alpha_asset);
+
+        let (sender, receiver) = mpsc::unbounded();
+        let context = Context {
+            alpha_ledger_events: Box::new(FakeLedgerEvents {
+                htlc_deployed: Box::new(future::ok(alpha_deployed)),
+                htlc_funded: Box::new(future::ok(alpha_funded)),
+                htlc_refundable: refundable,
+                ..FakeLedgerEvents::pending()
+            }),
+            beta_ledger_events: Box::new(FakeLedgerEvents::pending()),
+            state_repo: Arc::new(sender),
+            communication_events: Box::new(Accepting(Box::new(future::ok(Ok(
+                AcceptResponseBody {
+                    beta_ledger_refund_identity: "1111111111111111111111111111111111111111"
+                        .parse()
+                        .unwrap(),
+                    alpha_ledger_redeem_identity: request.alpha_ledger_refund_identity,
+                    partial_fill: None,
+                },
+            ))))),
+        };
+        let start = Start {
+            alpha_ledger_refund_identity: request.alpha_ledger_refund_identity,
+            beta_ledger_redeem_identity: request.beta_ledger_redeem_identity,
+            alpha_ledger: request.alpha_ledger,
+            beta_ledger: request.beta_ledger,
+            alpha_asset: request.alpha_asset,
+            beta_asset: request.beta_asset,
+            alpha_expiry: request.alpha_expiry,
+            beta_expiry: request.beta_expiry,
+            secret_hash: request.secret_hash,
+            hash_function: request.hash_function,
+        };
+
+        let mut swap = Swap::start_in(start, context);
+        let poll = future::lazy(move || Ok::<_, ()>(swap.poll())).wait();
+        assert_that(&poll)
+            .is_ok()
+            .is_ok()
+            .is_equal_to(Async::NotReady);
+
+        let id = SwapId::default();
+        let seed = Seed::from(*b"hello world, you are beautiful!!");
+        let state_store = InMemoryStateStore::default();
+        state_store.insert(id, AliceState::new(request, Arc::new(seed.swap_seed(id))));
+        for state in receiver.collect().wait().unwrap() {
+            state_store.update::<AliceState>(&id, state);
+        }
+
+        state_store
+            .get::<AliceState>(&id)
+            .unwrap()
+            .unwrap()
+            .actions()
+    }
+
+    #[test]
+    fn refund_is_offered_once_the_ledger_accepts_it() {
+        let actions = alice_actions_once_alpha_is_funded(Box::new(future::ok(())));
+
+        assert_that(&actions.iter().any(|action| match action {
+            Action::Refund(_) => true,
+            _ => false,
+        }))
+        .is_true();
+    }
+
+    #[test]
+    fn refund_is_not_offered_before_the_ledger_accepts_it() {
+        let actions = alice_actions_once_alpha_is_funded(Box::new(future::empty()));
+
+        assert_that(&actions).is_empty();
+    }
+}
@@ -1,5 +1,8 @@
 use crate::swap_protocols::{
+    asset::Asset,
     rfc003::{
+        events,
+        ledger::Ledger,
         ledger_state::LedgerState,
         messages::AcceptResponseBody,
         state_machine::{
@@ -62,17 +65,57 @@ impl StateStore for InMemoryStateStore<SwapId> {
             }
         };
 
+        // One HTLC being done with doesn't stop the other one from becoming
+        // refundable, hence the ledger that is still funded is kept up to date.
+        match &update {
+            SS::AlphaFundedBetaRefunded(AlphaFundedBetaRefunded {
+                alpha_deployed,
+                alpha_funded,
+                ..
+            })
+            | SS::AlphaFundedBetaRedeemed(AlphaFundedBetaRedeemed {
+                alpha_deployed,
+                alpha_funded,
+                ..
+            }) => {
+                *actor_state.alpha_ledger_mut() =
+                    funded(alpha_deployed.clone(), alpha_funded.clone())
+            }
+            SS::AlphaRefundedBetaFunded(AlphaRefundedBetaFunded {
+                beta_deployed,
+                beta_funded,
+                ..
+            })
+            | SS::AlphaRedeemedBetaFunded(AlphaRedeemedBetaFunded {
+                beta_deployed,
+                beta_funded,
+                ..
+            }) => {
+                *actor_state.beta_ledger_mut() = funded(beta_deployed.clone(), beta_funded.clone())
+            }
+            _ => {}
+        }
+
         match update {
             SS::Start(_) => {
                 log::warn!("Attempted to save Start state for key {}", key);
                 return;
             }
-            SS::Accepted(Accepted { swap }) => actor_state.set_response(Ok(AcceptResponseBody {
-                alpha_ledger_redeem_identity: swap.alpha_ledger_redeem_identity,
-                beta_ledger_refund_identity: swap.beta_ledger_refund_identity,
-            })),
+            SS::Accepted(Accepted { swap }) => {
+                let response = Ok(AcceptResponseBody {
+                    alpha_ledger_redeem_identity: swap.alpha_ledger_redeem_identity,
+                    beta_ledger_refund_identity: swap.beta_ledger_refund_identity,
+                    partial_fill: swap.partial_fill,
+                });
+
+                if let Err(e) = actor_state.set_response(response) {
+                    actor_state.set_error(e)
+                }
+            }
             SS::Final(Final(SwapOutcome::Rejected { rejection_type, .. })) => {
-                actor_state.set_response(Err(rejection_type))
+                if let Err(e) = actor_state.set_response(Err(rejection_type)) {
+                    actor_state.set_error(e)
+                }
             }
             SS::AlphaDeployed(AlphaDeployed { alpha_deployed, .. }) => {
                 *actor_state.alpha_ledger_mut() = Deployed {
@@ -84,24 +127,14 @@ impl StateStore for InMemoryStateStore<SwapId> {
                 alpha_deployed,
                 alpha_funded,
                 ..
-            }) => {
-                *actor_state.alpha_ledger_mut() = Funded {
-                    htlc_location: alpha_deployed.location,
-                    deploy_transaction: alpha_deployed.transaction,
-                    fund_transaction: alpha_funded.transaction,
-                }
-            }
+            }) => *actor_state.alpha_ledger_mut() = funded(alpha_deployed, alpha_funded),
             SS::AlphaFundedBetaDeployed(AlphaFundedBetaDeployed {
                 alpha_deployed,
                 alpha_funded,
                 beta_deployed,
                 ..
             }) => {
-                *actor_state.alpha_ledger_mut() = Funded {
-                    htlc_location: alpha_deployed.location,
-                    deploy_transaction: alpha_deployed.transaction,
-                    fund_transaction: alpha_funded.transaction,
-                };
+                *actor_state.alpha_ledger_mut() = funded(alpha_deployed, alpha_funded);
                 *actor_state.beta_ledger_mut() = Deployed {
                     htlc_location: beta_deployed.location,
                     deploy_transaction: beta_deployed.transaction,
@@ -114,16 +147,8 @@ impl StateStore for InMemoryStateStore<SwapId> {
                 beta_funded,
                 ..
             }) => {
-                *actor_state.alpha_ledger_mut() = Funded {
-                    htlc_location: alpha_deployed.location,
-                    deploy_transaction: alpha_deployed.transaction,
-                    fund_transaction: alpha_funded.transaction,
-                };
-                *actor_state.beta_ledger_mut() = Funded {
-                    htlc_location: beta_deployed.location,
-                    deploy_transaction: beta_deployed.transaction,
-                    fund_transaction: beta_funded.transaction,
-                };
+                *actor_state.alpha_ledger_mut() = funded(alpha_deployed, alpha_funded);
+                *actor_state.beta_ledger_mut() = funded(beta_deployed, beta_funded);
             }
             SS::AlphaFundedBetaRefunded(AlphaFundedBetaRefunded {
                 beta_deployed,
@@ -249,6 +274,18 @@ impl StateStore for InMemoryStateStore<SwapId> {
     }
 }
 
+fn funded<L: Ledger, A: Asset>(
+    deployed: events::Deployed<L>,
+    funded: events::Funded<L, A>,
+) -> LedgerState<L> {
+    LedgerState::Funded {
+        htlc_location: deployed.location,
+        deploy_transaction: deployed.transaction,
+        fund_transaction: funded.transaction,
+        refundable: funded.refundable,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -256,19 +293,21 @@ mod tests {
         seed::Seed,
         swap_protocols::{
             ledger::{Bitcoin, Ethereum},
-            rfc003::{alice, messages::Request, Secret},
+            rfc003::{
+                self, alice,
+                messages::{PartialFill, Request},
+                HashFunction, Secret,
+            },
             Timestamp,
         },
     };
     use bitcoin_support::BitcoinQuantity;
-    use ethereum_support::EtherQuantity;
+    use ethereum_support::{EtherQuantity, U256};
     use spectral::prelude::*;
     use std::sync::Arc;
 
-    #[test]
-    fn insert_and_get_state() {
-        let state_store = InMemoryStateStore::default();
-        let request = Request {
+    fn request() -> Request<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity> {
+        Request {
             alpha_ledger: Bitcoin::default(),
             beta_ledger: Ethereum::default(),
             alpha_asset: BitcoinQuantity::from_bitcoin(1.0),
@@ -284,12 +323,19 @@ mod tests {
                 .unwrap(),
             alpha_expiry: Timestamp::from(2000000000),
             beta_expiry: Timestamp::from(2000000000),
-            secret_hash: Secret::from(*b"hello world, you are beautiful!!").hash(),
-        };
+            secret_hash: Secret::from(*b"hello world, you are beautiful!!")
+                .hash(HashFunction::Sha256),
+            hash_function: HashFunction::Sha256,
+        }
+    }
+
+    #[test]
+    fn insert_and_get_state() {
+        let state_store = InMemoryStateStore::default();
         let id = SwapId::default();
         let seed = Seed::from(*b"hello world, you are beautiful!!");
         let secret_source = Arc::new(seed.swap_seed(id));
-        let state = alice::State::new(request, secret_source);
+        let state = alice::State::new(request(), secret_source);
 
         state_store.insert::<alice::State<Bitcoin, Ethereum, BitcoinQuantity, EtherQuantity>>(
             id,
@@ -301,4 +347,22 @@ mod tests {
             .unwrap();
         assert_that(&res).contains_value(state);
     }
+
+    #[test]
+    fn accepting_with_more_than_requested_is_an_error() {
+        let seed = Seed::from(*b"hello world, you are beautiful!!");
+        let mut state = alice::State::new(request(), Arc::new(seed.swap_seed(SwapId::default())));
+
+        let result = state.set_response(Ok(AcceptResponseBody {
+            alpha_ledger_redeem_identity: request().alpha_ledger_refund_identity,
+            beta_ledger_refund_identity: request().beta_ledger_redeem_identity,
+            partial_fill: Some(PartialFill {
+                alpha_quantity: U256::from(200_000_000),
+                beta_quantity: U256::from(20u64) * U256::exp10(18),
+            }),
+        }));
+
+        assert_that(&result).is_err_containing(rfc003::Error::InvalidPartialFill);
+        assert_that(&state.response()).is_none();
+    }
 }
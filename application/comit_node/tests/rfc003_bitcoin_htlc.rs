@@ -13,7 +13,7 @@ use bitcoin_witness::{
     PrimedInput, PrimedTransaction, UnlockParameters, Witness, SEQUENCE_ALLOW_NTIMELOCK_NO_RBF,
 };
 use comit_node::swap_protocols::{
-    rfc003::{bitcoin::Htlc, Secret, SecretHash},
+    rfc003::{bitcoin::Htlc, HashFunction, Secret, SecretHash},
     Timestamp,
 };
 use secp256k1_support::KeyPair;
@@ -101,7 +101,8 @@ fn redeem_htlc_with_secret() {
     client.generate(432).unwrap().unwrap();
 
     let secret = Secret::from(*b"hello world, you are beautiful!!");
-    let (txid, vout, input_amount, htlc, _, keypair, _) = fund_htlc(&client, secret.hash());
+    let (txid, vout, input_amount, htlc, _, keypair, _) =
+        fund_htlc(&client, secret.hash(HashFunction::Sha256));
 
     assert!(
         htlc.can_be_unlocked_with(secret, keypair).is_ok(),
@@ -149,7 +150,7 @@ fn redeem_refund_htlc() {
 
     let secret = Secret::from(*b"hello world, you are beautiful!!");
     let (txid, vout, input_amount, htlc, refund_timestamp, _, keypair) =
-        fund_htlc(&client, secret.hash());
+        fund_htlc(&client, secret.hash(HashFunction::Sha256));
 
     let alice_addr: Address = client.get_new_address().unwrap().unwrap().into();
     let fee = BitcoinQuantity::from_satoshi(1000);
@@ -201,7 +202,8 @@ fn redeem_htlc_with_long_secret() -> Result<(), failure::Error> {
     let secret = CustomSizeSecret::from_str("Grandmother, what big secret you have!")?;
     assert_eq!(secret.0.len(), 38);
 
-    let (txid, vout, input_amount, htlc, _, keypair, _) = fund_htlc(&client, secret.hash());
+    let (txid, vout, input_amount, htlc, _, keypair, _) =
+        fund_htlc(&client, secret.hash(HashFunction::Sha256));
 
     let alice_addr: Address = client.get_new_address().unwrap().unwrap().into();
 
@@ -245,7 +247,8 @@ fn redeem_htlc_with_short_secret() -> Result<(), failure::Error> {
     let secret = CustomSizeSecret::from_str("teeny-weeny-bunny")?;
     assert_eq!(secret.0.len(), 17);
 
-    let (txid, vout, input_amount, htlc, _, keypair, _) = fund_htlc(&client, secret.hash());
+    let (txid, vout, input_amount, htlc, _, keypair, _) =
+        fund_htlc(&client, secret.hash(HashFunction::Sha256));
 
     let alice_addr: Address = client.get_new_address().unwrap().unwrap().into();
 